@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, Runtime};
 use uuid::Uuid;
 use tauri_plugin_machine_uid::MachineUidExt;
 
@@ -30,7 +30,7 @@ fn get_api_access_key() -> Result<String, String> {
 }
 
 // Secure storage functions using Tauri's app data directory
-fn get_secure_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
+fn get_secure_storage_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -48,6 +48,8 @@ struct SecureStorage {
     license_key: Option<String>,
     instance_id: Option<String>,
     selected_pluely_model: Option<String>,
+    // Left by earlier versions, moved to the credential store by migrate_plaintext_keys
+    stt_api_key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,6 +82,9 @@ pub async fn secure_storage_save(app: AppHandle, items: Vec<StorageItem>) -> Res
             "pluely_license_key" => storage.license_key = Some(item.value),
             "pluely_instance_id" => storage.instance_id = Some(item.value),
             "selected_pluely_model" => storage.selected_pluely_model = Some(item.value),
+            "stt_api_key" => {
+                return Err("The speech-to-text key is kept in the credential store, save it with set_api_key".to_string())
+            }
             _ => return Err(format!("Invalid storage key: {}", item.key)),
         }
     }
@@ -137,6 +142,7 @@ pub async fn secure_storage_remove(app: AppHandle, keys: Vec<String>) -> Result<
             "pluely_license_key" => storage.license_key = None,
             "pluely_instance_id" => storage.instance_id = None,
             "selected_pluely_model" => storage.selected_pluely_model = None,
+            "stt_api_key" => storage.stt_api_key = None,
             _ => return Err(format!("Invalid storage key: {}", key)),
         }
     }
@@ -150,6 +156,40 @@ pub async fn secure_storage_remove(app: AppHandle, keys: Vec<String>) -> Result<
     Ok(())
 }
 
+/// Speech-to-text API key earlier versions kept in plaintext, removed from the
+/// file once it was read so it only lives in the credential store afterwards.
+/// The caller stores it first, a failed store keeps the plaintext copy.
+pub fn take_plaintext_stt_api_key<R: Runtime>(
+    app: &AppHandle<R>,
+    store: impl FnOnce(&str) -> Result<(), String>,
+) -> Result<bool, String> {
+    let storage_path = get_secure_storage_path(app)?;
+    if !storage_path.exists() {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(&storage_path)
+        .map_err(|e| format!("Failed to read storage file: {}", e))?;
+
+    let mut storage: SecureStorage = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse storage file: {}", e))?;
+
+    let Some(key) = storage.stt_api_key.take() else {
+        return Ok(false);
+    };
+    if !key.is_empty() {
+        store(&key)?;
+    }
+
+    let content = serde_json::to_string(&storage)
+        .map_err(|e| format!("Failed to serialize storage: {}", e))?;
+
+    fs::write(&storage_path, content)
+        .map_err(|e| format!("Failed to write storage file: {}", e))?;
+
+    Ok(!key.is_empty())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ActivationRequest {
     license_key: String,
@@ -0,0 +1,675 @@
+// Tracking of the application that was active before Pluely took focus
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+// How long a frontmost-app query is reused before asking the OS again
+const FRONTMOST_CACHE_TTL: Duration = Duration::from_millis(200);
+
+// Identity of the frontmost application: bundle id on macOS,
+// exe name on Windows and WM_CLASS on Linux
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrontmostApp {
+    pub id: String,
+    pub name: Option<String>,
+    pub pid: Option<u32>,
+}
+
+// Frontmost window in physical screen pixels, decorations included but not shadows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub title: String,
+}
+
+impl WindowBounds {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width as i32 && y >= self.y && y < self.y + self.height as i32
+    }
+}
+
+// Top-level window under a point with the name of its process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PointedWindow {
+    #[serde(flatten)]
+    pub bounds: WindowBounds,
+    pub process: String,
+}
+
+// Last frontmost-app query, shared by all shortcut handlers
+#[derive(Default)]
+pub struct FrontmostCache {
+    last: Mutex<Option<(Instant, Option<FrontmostApp>)>>,
+}
+
+// Platform handle of a previously active application
+#[derive(Debug, Clone, Copy)]
+pub struct PreviousApp {
+    #[cfg(target_os = "macos")]
+    pid: i32,
+    #[cfg(target_os = "windows")]
+    hwnd: isize,
+}
+
+// State for restoring focus when the window is hidden
+pub struct FocusRestore {
+    pub enabled: Mutex<bool>,
+    pub previous: Mutex<Option<PreviousApp>>,
+}
+
+impl Default for FocusRestore {
+    fn default() -> Self {
+        FocusRestore {
+            enabled: Mutex::new(true),
+            previous: Mutex::new(None),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn capture_frontmost() -> Option<PreviousApp> {
+    use cidre::ns;
+
+    let app = ns::Workspace::shared().frontmost_app()?;
+    let pid = app.pid();
+
+    // Pluely itself being frontmost means there is nothing to go back to
+    if pid as u32 == std::process::id() {
+        return None;
+    }
+    Some(PreviousApp { pid })
+}
+
+#[cfg(target_os = "macos")]
+fn activate(previous: PreviousApp) {
+    use cidre::ns;
+
+    // The app may have quit in the meantime, in which case there is nothing to do
+    if let Some(app) = ns::RunningApp::with_pid(previous.pid) {
+        if !app.is_terminated() {
+            app.activate_with_opts(ns::AppActivationOpts::default());
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn capture_frontmost() -> Option<PreviousApp> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        return None;
+    }
+
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32)) };
+    if pid == std::process::id() {
+        return None;
+    }
+    Some(PreviousApp {
+        hwnd: hwnd.0 as isize,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn activate(previous: PreviousApp) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{IsWindow, SetForegroundWindow};
+
+    let hwnd = HWND(previous.hwnd as *mut core::ffi::c_void);
+    unsafe {
+        // The window may be gone if its app quit in the meantime
+        if IsWindow(hwnd).as_bool() {
+            let _ = SetForegroundWindow(hwnd);
+        }
+    }
+}
+
+// Linux window managers already return focus to the previous window on hide
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_frontmost() -> Option<PreviousApp> {
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn activate(_previous: PreviousApp) {}
+
+#[cfg(target_os = "macos")]
+fn query_frontmost_app() -> Option<FrontmostApp> {
+    use cidre::ns;
+
+    let app = ns::Workspace::shared().frontmost_app()?;
+    Some(FrontmostApp {
+        id: app.bundle_id()?.to_string(),
+        name: app.localized_name().map(|name| name.to_string()),
+        pid: Some(app.pid() as u32),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn query_frontmost_app() -> Option<FrontmostApp> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        return None;
+    }
+
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32)) };
+    if pid == 0 {
+        return None;
+    }
+
+    Some(FrontmostApp {
+        id: process_exe_name(pid)?,
+        name: None,
+        pid: Some(pid),
+    })
+}
+
+// X11 only, via xprop; Wayland does not expose the focused window to clients
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn query_frontmost_app() -> Option<FrontmostApp> {
+    use std::process::Command;
+
+    let xprop = |args: &[&str]| -> Option<String> {
+        let output = Command::new("xprop").args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    // "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007"
+    let active = xprop(&["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window_id = active.split_whitespace().last()?.to_string();
+    if window_id == "0x0" {
+        return None;
+    }
+
+    // "WM_CLASS(STRING) = "navigator", "firefox"", the class is the last entry
+    let wm_class = xprop(&["-id", &window_id, "WM_CLASS"])?;
+    let class = wm_class
+        .split('=')
+        .nth(1)?
+        .split(',')
+        .next_back()?
+        .trim()
+        .trim_matches('"')
+        .to_string();
+    if class.is_empty() {
+        return None;
+    }
+
+    let pid = xprop(&["-id", &window_id, "_NET_WM_PID"])
+        .and_then(|out| out.split('=').nth(1).and_then(|pid| pid.trim().parse().ok()));
+
+    Some(FrontmostApp {
+        id: class,
+        name: None,
+        pid,
+    })
+}
+
+// On-screen window as listed by CGWindowList
+#[cfg(target_os = "macos")]
+struct ListedWindow {
+    pid: i64,
+    layer: i64,
+    owner: String,
+    bounds: WindowBounds,
+}
+
+/// On-screen windows front to back, bounds scaled to physical pixels
+#[cfg(target_os = "macos")]
+fn list_windows() -> Vec<ListedWindow> {
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::CFString;
+    use core_graphics::display::CGDisplay;
+    use core_graphics::geometry::CGRect;
+    use core_graphics::window::{
+        copy_window_info, kCGNullWindowID, kCGWindowBounds, kCGWindowLayer, kCGWindowListExcludeDesktopElements,
+        kCGWindowListOptionOnScreenOnly, kCGWindowName, kCGWindowOwnerName, kCGWindowOwnerPID,
+    };
+
+    let Some(windows) = copy_window_info(
+        kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements,
+        kCGNullWindowID,
+    ) else {
+        return Vec::new();
+    };
+
+    let mut listed = Vec::new();
+    for item in windows.iter() {
+        let info: CFDictionary<CFString, CFType> =
+            unsafe { CFDictionary::wrap_under_get_rule(*item as CFDictionaryRef) };
+        let number = |key| {
+            info.find(unsafe { CFString::wrap_under_get_rule(key) })
+                .and_then(|value| value.downcast::<CFNumber>())
+                .and_then(|number| number.to_i64())
+        };
+        let string = |key| {
+            info.find(unsafe { CFString::wrap_under_get_rule(key) })
+                .and_then(|value| value.downcast::<CFString>())
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let (Some(pid), Some(layer)) = (number(kCGWindowOwnerPID), number(kCGWindowLayer)) else {
+            continue;
+        };
+        let Some(bounds) = info
+            .find(unsafe { CFString::wrap_under_get_rule(kCGWindowBounds) })
+            .and_then(|value| value.downcast::<CFDictionary>())
+            .and_then(|dict| CGRect::from_dict_representation(&dict))
+        else {
+            continue;
+        };
+
+        // Bounds are in points, scale by the display the window is on
+        let scale = CGDisplay::displays_with_rect(bounds, 1)
+            .ok()
+            .and_then(|(displays, _)| displays.first().copied())
+            .map(|id| {
+                let display = CGDisplay::new(id);
+                display.pixels_wide() as f64 / display.bounds().size.width
+            })
+            .unwrap_or(1.0);
+
+        listed.push(ListedWindow {
+            pid,
+            layer,
+            owner: string(kCGWindowOwnerName),
+            bounds: WindowBounds {
+                x: (bounds.origin.x * scale) as i32,
+                y: (bounds.origin.y * scale) as i32,
+                width: (bounds.size.width * scale) as u32,
+                height: (bounds.size.height * scale) as u32,
+                title: string(kCGWindowName),
+            },
+        });
+    }
+    listed
+}
+
+#[cfg(target_os = "macos")]
+fn query_window_bounds(previous: Option<PreviousApp>) -> Option<WindowBounds> {
+    use cidre::ns;
+
+    // Our own overlay being frontmost means the user wants the window behind it
+    let pid = match ns::Workspace::shared().frontmost_app().map(|app| app.pid()) {
+        Some(pid) if pid as u32 != std::process::id() => pid,
+        _ => previous?.pid,
+    };
+
+    // Window list is front to back, the first normal-layer window of the app is its front window
+    list_windows()
+        .into_iter()
+        .find(|window| window.pid == pid as i64 && window.layer == 0)
+        .map(|window| window.bounds)
+}
+
+#[cfg(target_os = "macos")]
+fn query_window_at(x: i32, y: i32) -> Option<PointedWindow> {
+    let own_pid = std::process::id() as i64;
+    list_windows()
+        .into_iter()
+        .find(|window| window.pid != own_pid && window.layer == 0 && window.bounds.contains(x, y))
+        .map(|window| PointedWindow {
+            bounds: window.bounds,
+            process: window.owner,
+        })
+}
+
+/// Executable name of a process
+#[cfg(target_os = "windows")]
+pub fn process_exe_name(pid: u32) -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let path = unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+        String::from_utf16_lossy(&buffer[..len as usize])
+    };
+
+    Some(std::path::Path::new(&path).file_name()?.to_string_lossy().to_string())
+}
+
+/// Frame of a top-level window with its title
+#[cfg(target_os = "windows")]
+fn window_frame(hwnd: windows::Win32::Foundation::HWND) -> Option<WindowBounds> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS};
+    use windows::Win32::UI::WindowsAndMessaging::{GetWindowTextLengthW, GetWindowTextW};
+
+    // The extended frame excludes the invisible resize borders and shadow
+    let mut rect = RECT::default();
+    unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut core::ffi::c_void,
+            std::mem::size_of::<RECT>() as u32,
+        )
+        .ok()?;
+    }
+
+    let len = unsafe { GetWindowTextLengthW(hwnd) };
+    let mut title = vec![0u16; len as usize + 1];
+    let copied = unsafe { GetWindowTextW(hwnd, &mut title) };
+
+    Some(WindowBounds {
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left).max(0) as u32,
+        height: (rect.bottom - rect.top).max(0) as u32,
+        title: String::from_utf16_lossy(&title[..copied.max(0) as usize]),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn query_window_bounds(previous: Option<PreviousApp>) -> Option<WindowBounds> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId, IsWindow};
+
+    // Our own overlay being frontmost means the user wants the window behind it
+    let mut hwnd = unsafe { GetForegroundWindow() };
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32)) };
+    if hwnd.0.is_null() || pid == std::process::id() {
+        hwnd = HWND(previous?.hwnd as *mut core::ffi::c_void);
+        if !unsafe { IsWindow(hwnd) }.as_bool() {
+            return None;
+        }
+    }
+
+    window_frame(hwnd)
+}
+
+#[cfg(target_os = "windows")]
+fn query_window_at(x: i32, y: i32) -> Option<PointedWindow> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetTopWindow, GetWindow, GetWindowTextLengthW, GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+        GW_HWNDNEXT,
+    };
+
+    // Top-level windows in z-order, the first visible one under the point that isn't ours
+    let mut next = unsafe { GetTopWindow(HWND::default()) }.ok();
+    while let Some(hwnd) = next.filter(|hwnd| !hwnd.0.is_null()) {
+        next = unsafe { GetWindow(hwnd, GW_HWNDNEXT) }.ok();
+
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32)) };
+        if pid == std::process::id()
+            || !unsafe { IsWindowVisible(hwnd) }.as_bool()
+            || unsafe { IsIconic(hwnd) }.as_bool()
+            || unsafe { GetWindowTextLengthW(hwnd) } == 0
+        {
+            continue;
+        }
+
+        // Windows on other virtual desktops and suspended UWP apps are cloaked
+        let mut cloaked = 0u32;
+        let _ = unsafe {
+            DwmGetWindowAttribute(
+                hwnd,
+                DWMWA_CLOAKED,
+                &mut cloaked as *mut u32 as *mut core::ffi::c_void,
+                std::mem::size_of::<u32>() as u32,
+            )
+        };
+        if cloaked != 0 {
+            continue;
+        }
+
+        let Some(bounds) = window_frame(hwnd).filter(|bounds| bounds.contains(x, y)) else {
+            continue;
+        };
+        return Some(PointedWindow {
+            bounds,
+            process: process_exe_name(pid).unwrap_or_default(),
+        });
+    }
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn run_x11_tool(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn x11_window_pid(window_id: &str) -> Option<u32> {
+    run_x11_tool("xprop", &["-id", window_id, "_NET_WM_PID"])
+        .and_then(|out| out.split('=').nth(1).and_then(|pid| pid.trim().parse::<u32>().ok()))
+}
+
+/// Frame of an X11 window with its title, None when it isn't mapped
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn x11_window_frame(window_id: &str) -> Option<WindowBounds> {
+    // xwininfo reports the client area, "Absolute upper-left X:  100"
+    let info = run_x11_tool("xwininfo", &["-id", window_id])?;
+    if !info.contains("IsViewable") {
+        return None;
+    }
+    let field = |name: &str| -> Option<i32> {
+        info.lines()
+            .find_map(|line| line.trim().strip_prefix(name))
+            .and_then(|value| value.trim().parse().ok())
+    };
+    let (x, y) = (field("Absolute upper-left X:")?, field("Absolute upper-left Y:")?);
+    let (width, height) = (field("Width:")?, field("Height:")?);
+
+    // "_NET_FRAME_EXTENTS(CARDINAL) = 0, 0, 37, 0" is left, right, top, bottom
+    let extents: Vec<i32> = run_x11_tool("xprop", &["-id", window_id, "_NET_FRAME_EXTENTS"])
+        .and_then(|out| out.split('=').nth(1).map(|values| values.to_string()))
+        .map(|values| values.split(',').filter_map(|value| value.trim().parse().ok()).collect())
+        .unwrap_or_default();
+    let (left, right, top, bottom) = match extents.as_slice() {
+        [left, right, top, bottom] => (*left, *right, *top, *bottom),
+        _ => (0, 0, 0, 0),
+    };
+
+    let title = run_x11_tool("xprop", &["-id", window_id, "_NET_WM_NAME"])
+        .and_then(|out| out.split_once('=').map(|(_, title)| title.trim().trim_matches('"').to_string()))
+        .unwrap_or_default();
+
+    Some(WindowBounds {
+        x: x - left,
+        y: y - top,
+        width: (width + left + right).max(0) as u32,
+        height: (height + top + bottom).max(0) as u32,
+        title,
+    })
+}
+
+// X11 only, via xprop and xwininfo like query_frontmost_app
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn query_window_bounds(_previous: Option<PreviousApp>) -> Option<WindowBounds> {
+    let active = run_x11_tool("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window_id = active.split_whitespace().last()?.to_string();
+    if window_id == "0x0" {
+        return None;
+    }
+
+    if x11_window_pid(&window_id) == Some(std::process::id()) {
+        return None;
+    }
+    x11_window_frame(&window_id)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn query_window_at(x: i32, y: i32) -> Option<PointedWindow> {
+    // "_NET_CLIENT_LIST_STACKING(WINDOW): window id # 0x1a00003, 0x3a00007" is bottom to top
+    let stacking = run_x11_tool("xprop", &["-root", "_NET_CLIENT_LIST_STACKING"])?;
+    let window_ids: Vec<String> = stacking
+        .split('#')
+        .nth(1)?
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    for window_id in window_ids.iter().rev() {
+        let pid = x11_window_pid(window_id);
+        if pid == Some(std::process::id()) {
+            continue;
+        }
+        let Some(bounds) = x11_window_frame(window_id).filter(|bounds| bounds.contains(x, y)) else {
+            continue;
+        };
+
+        // Process name from /proc, WM_CLASS when the window has no pid
+        let process = pid
+            .and_then(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+            .map(|comm| comm.trim().to_string())
+            .or_else(|| {
+                run_x11_tool("xprop", &["-id", window_id, "WM_CLASS"]).and_then(|out| {
+                    out.split('=')
+                        .nth(1)?
+                        .split(',')
+                        .next_back()
+                        .map(|class| class.trim().trim_matches('"').to_string())
+                })
+            })
+            .unwrap_or_default();
+        return Some(PointedWindow { bounds, process });
+    }
+    None
+}
+
+/// Bounds of the frontmost window, or of the window that was active before
+/// Pluely took focus
+pub fn frontmost_window_bounds<R: Runtime>(app: &AppHandle<R>) -> Option<WindowBounds> {
+    let state = app.state::<FocusRestore>();
+    let previous = match state.previous.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    query_window_bounds(previous)
+}
+
+/// Whether the frontmost window other than Pluely's covers a whole monitor, as
+/// games, fullscreen video and macOS fullscreen Spaces do
+pub fn is_foreground_fullscreen<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(bounds) = query_window_bounds(None) else {
+        return false;
+    };
+    let Ok(monitors) = app.available_monitors() else {
+        return false;
+    };
+    monitors.iter().any(|monitor| {
+        let (position, size) = (monitor.position(), monitor.size());
+        bounds.x <= position.x
+            && bounds.y <= position.y
+            && bounds.x + bounds.width as i32 >= position.x + size.width as i32
+            && bounds.y + bounds.height as i32 >= position.y + size.height as i32
+    })
+}
+
+/// Top-level window under a point in physical screen pixels, skipping Pluely's own
+pub fn window_at(x: i32, y: i32) -> Option<PointedWindow> {
+    query_window_at(x, y)
+}
+
+/// Frontmost application, cached briefly so several handlers share one OS query
+pub fn frontmost_app<R: Runtime>(app: &AppHandle<R>) -> Option<FrontmostApp> {
+    let state = app.state::<FrontmostCache>();
+    let mut last = match state.last.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    if let Some((queried_at, cached)) = last.as_ref() {
+        if queried_at.elapsed() < FRONTMOST_CACHE_TTL {
+            return cached.clone();
+        }
+    }
+
+    let current = query_frontmost_app();
+    *last = Some((Instant::now(), current.clone()));
+    current
+}
+
+fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<FocusRestore>();
+    let enabled = match state.enabled.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    enabled
+}
+
+/// Remember the frontmost application, call before showing the window
+pub fn remember_previous_app<R: Runtime>(app: &AppHandle<R>) {
+    if !is_enabled(app) {
+        return;
+    }
+
+    let state = app.state::<FocusRestore>();
+    let mut previous = match state.previous.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *previous = capture_frontmost();
+}
+
+/// Reactivate the application remembered before the window was shown
+pub fn restore_previous_app<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<FocusRestore>();
+    let previous = match state.previous.lock() {
+        Ok(mut guard) => guard.take(),
+        Err(poisoned) => poisoned.into_inner().take(),
+    };
+
+    if !is_enabled(app) {
+        return;
+    }
+
+    if let Some(previous) = previous {
+        activate(previous);
+    }
+}
+
+/// Tauri command to get the frontmost application
+#[tauri::command]
+pub fn get_frontmost_app<R: Runtime>(app: AppHandle<R>) -> Result<Option<FrontmostApp>, String> {
+    Ok(frontmost_app(&app))
+}
+
+/// Tauri command to enable or disable focus restoring on hide
+#[tauri::command]
+pub fn set_restore_focus<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let state = app.state::<FocusRestore>();
+    *state
+        .enabled
+        .lock()
+        .map_err(|e| format!("Failed to update focus restore setting: {}", e))? = enabled;
+    Ok(())
+}
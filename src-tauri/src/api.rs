@@ -3,8 +3,17 @@ use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
-use tauri::{AppHandle, Emitter, Manager};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 use tauri_plugin_machine_uid::MachineUidExt;
+use tokio::sync::oneshot;
+
+// State for the in-flight AI request
+#[derive(Default)]
+pub struct ActiveRequestState {
+    pub frontend_active: Mutex<bool>, // reported by the frontend via set_request_active
+    cancel: Mutex<Option<oneshot::Sender<()>>>, // set while chat_stream is running
+}
 
 fn get_app_endpoint() -> Result<String, String> {
     if let Ok(endpoint) = env::var("APP_ENDPOINT") {
@@ -211,6 +220,38 @@ pub async fn chat_stream(
     system_prompt: Option<String>,
     image_base64: Option<serde_json::Value>,
     history: Option<String>,
+) -> Result<String, String> {
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    {
+        let state = app.state::<ActiveRequestState>();
+        let mut cancel = state
+            .cancel
+            .lock()
+            .map_err(|e| format!("Failed to acquire request lock: {}", e))?;
+        *cancel = Some(cancel_tx);
+    }
+
+    let result = tokio::select! {
+        result = run_chat_stream(&app, user_message, system_prompt, image_base64, history) => result,
+        _ = cancel_rx => {
+            let _ = app.emit("chat_stream_cancelled", ());
+            Err("Request cancelled".to_string())
+        }
+    };
+
+    if let Ok(mut cancel) = app.state::<ActiveRequestState>().cancel.lock() {
+        *cancel = None;
+    }
+
+    result
+}
+
+async fn run_chat_stream(
+    app: &AppHandle,
+    user_message: String,
+    system_prompt: Option<String>,
+    image_base64: Option<serde_json::Value>,
+    history: Option<String>,
 ) -> Result<String, String> {
     // Get environment variables
     let app_endpoint = get_app_endpoint()?;
@@ -346,6 +387,43 @@ pub async fn chat_stream(
     Ok(full_response)
 }
 
+/// Whether an AI request is running, either reported by the frontend or streaming in Rust
+pub fn is_request_active<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<ActiveRequestState>();
+    let frontend_active = match state.frontend_active.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let streaming = match state.cancel.lock() {
+        Ok(guard) => guard.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    };
+    frontend_active || streaming
+}
+
+/// Abort the chat_stream request running in Rust, if any
+pub fn cancel_active_request<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<ActiveRequestState>();
+    let cancel = match state.cancel.lock() {
+        Ok(mut guard) => guard.take(),
+        Err(poisoned) => poisoned.into_inner().take(),
+    };
+    if let Some(cancel) = cancel {
+        let _ = cancel.send(());
+    }
+}
+
+// Lets the frontend report requests it runs itself
+#[tauri::command]
+pub fn set_request_active(app: AppHandle, active: bool) -> Result<(), String> {
+    let state = app.state::<ActiveRequestState>();
+    *state
+        .frontend_active
+        .lock()
+        .map_err(|e| format!("Failed to update request state: {}", e))? = active;
+    Ok(())
+}
+
 // Models API Command
 #[tauri::command]
 pub async fn fetch_models() -> Result<Vec<Model>, String> {
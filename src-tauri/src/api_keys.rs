@@ -0,0 +1,203 @@
+// Provider API keys kept in the platform credential store: the Keychain on
+// macOS, Credential Manager on Windows and the Secret Service on Linux. Keys
+// never reach the settings file or the logs, only the provider names are kept
+// in the settings so they can be listed.
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use tauri::{AppHandle, Runtime};
+
+// Settings key holding the providers that have a key stored
+const PROVIDERS_SETTING: &str = "api_key_providers";
+
+// Provider name of the key streaming transcription sends as {stt_api_key}
+pub const STT_PROVIDER: &str = "stt";
+
+// Failures the frontend tells apart, serialized as { kind, message }. Locked
+// and unavailable mean it should fall back to asking for the key each time.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum KeyringError {
+    // The store exists but is locked or access was refused
+    Locked { message: String },
+    // No credential store, e.g. no Secret Service running
+    Unavailable { message: String },
+    Invalid { message: String },
+    Failed { message: String },
+}
+
+impl KeyringError {
+    fn failed(message: String) -> Self {
+        KeyringError::Failed { message }
+    }
+}
+
+impl std::fmt::Display for KeyringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyringError::Locked { message }
+            | KeyringError::Unavailable { message }
+            | KeyringError::Invalid { message }
+            | KeyringError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+// The messages leave out anything the store returned, which may hold the key
+impl From<keyring::Error> for KeyringError {
+    fn from(error: keyring::Error) -> Self {
+        match error {
+            keyring::Error::NoStorageAccess(e) => KeyringError::Locked {
+                message: format!("The credential store is locked or access was denied: {}", e),
+            },
+            keyring::Error::PlatformFailure(e) => KeyringError::Unavailable {
+                message: format!("The credential store is unavailable: {}", e),
+            },
+            keyring::Error::BadEncoding(_) => KeyringError::Invalid {
+                message: "The stored key is not valid UTF-8".to_string(),
+            },
+            keyring::Error::TooLong(name, limit) => KeyringError::Invalid {
+                message: format!("{} is longer than the credential store allows ({})", name, limit),
+            },
+            keyring::Error::Invalid(name, reason) => KeyringError::Invalid {
+                message: format!("Invalid {}: {}", name, reason),
+            },
+            keyring::Error::NoEntry => KeyringError::failed("No key stored for this provider".to_string()),
+            other => KeyringError::failed(format!("Credential store error: {}", other)),
+        }
+    }
+}
+
+fn validate_provider(provider: &str) -> Result<(), KeyringError> {
+    if provider.trim().is_empty() {
+        return Err(KeyringError::Invalid {
+            message: "Provider cannot be empty".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn entry<R: Runtime>(app: &AppHandle<R>, provider: &str) -> Result<keyring::Entry, KeyringError> {
+    validate_provider(provider)?;
+    Ok(keyring::Entry::new(&app.config().identifier, provider)?)
+}
+
+fn stored_providers<R: Runtime>(app: &AppHandle<R>) -> BTreeSet<String> {
+    crate::settings::get(app, PROVIDERS_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn update_providers<R: Runtime>(
+    app: &AppHandle<R>,
+    change: impl FnOnce(&mut BTreeSet<String>),
+) -> Result<(), KeyringError> {
+    let mut providers = stored_providers(app);
+    change(&mut providers);
+    crate::settings::set(app, PROVIDERS_SETTING, serde_json::json!(providers)).map_err(KeyringError::failed)
+}
+
+fn store_key<R: Runtime>(app: &AppHandle<R>, provider: &str, key: &str) -> Result<(), KeyringError> {
+    if key.is_empty() {
+        return Err(KeyringError::Invalid {
+            message: "API key cannot be empty".to_string(),
+        });
+    }
+    entry(app, provider)?.set_password(key)?;
+    update_providers(app, |providers| {
+        providers.insert(provider.to_string());
+    })
+}
+
+fn read_key<R: Runtime>(app: &AppHandle<R>, provider: &str) -> Result<Option<String>, KeyringError> {
+    match entry(app, provider)?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Run a credential store call off the async runtime, the Secret Service and
+/// the Keychain can block on an unlock prompt
+async fn blocking<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, KeyringError> + Send + 'static,
+) -> Result<T, KeyringError> {
+    tauri::async_runtime::spawn_blocking(call)
+        .await
+        .map_err(|e| KeyringError::failed(format!("Credential store task failed: {}", e)))?
+}
+
+/// The provider list follows the credential store, set_setting can't change it
+pub fn validate_setting(key: &str, _value: &serde_json::Value) -> Result<(), String> {
+    if key == PROVIDERS_SETTING {
+        return Err(format!("{} follows the stored API keys, use set_api_key or delete_api_key", key));
+    }
+    Ok(())
+}
+
+/// Tauri command to store a provider's API key, replacing the previous one
+#[tauri::command]
+pub async fn set_api_key<R: Runtime>(app: AppHandle<R>, provider: String, key: String) -> Result<(), KeyringError> {
+    blocking(move || store_key(&app, &provider, &key)).await
+}
+
+/// Tauri command to read a provider's API key, null when none is stored
+#[tauri::command]
+pub async fn get_api_key<R: Runtime>(app: AppHandle<R>, provider: String) -> Result<Option<String>, KeyringError> {
+    blocking(move || read_key(&app, &provider)).await
+}
+
+/// A provider's API key for use in Rust, it never goes back to the webview
+pub async fn stored_key<R: Runtime>(app: &AppHandle<R>, provider: &str) -> Result<Option<String>, KeyringError> {
+    let (app, provider) = (app.clone(), provider.to_string());
+    blocking(move || read_key(&app, &provider)).await
+}
+
+/// Tauri command to remove a provider's API key, fine when there is none
+#[tauri::command]
+pub async fn delete_api_key<R: Runtime>(app: AppHandle<R>, provider: String) -> Result<(), KeyringError> {
+    blocking(move || {
+        match entry(&app, &provider)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(e.into()),
+        }
+        update_providers(&app, |providers| {
+            providers.remove(&provider);
+        })
+    })
+    .await
+}
+
+/// Tauri command to list the providers with a stored API key
+#[tauri::command]
+pub fn list_api_key_providers<R: Runtime>(app: AppHandle<R>) -> Vec<String> {
+    stored_providers(&app).into_iter().collect()
+}
+
+/// Tauri command to move the keys the frontend kept in plaintext into the
+/// credential store, provider -> key. Empty keys are skipped. The speech-to-text
+/// key earlier versions kept in secure_storage.json moves along as stt. Returns
+/// the providers moved, the frontend should only wipe its copies on success.
+#[tauri::command]
+pub async fn migrate_plaintext_keys<R: Runtime>(
+    app: AppHandle<R>,
+    keys: HashMap<String, String>,
+) -> Result<Vec<String>, KeyringError> {
+    blocking(move || {
+        let mut migrated = Vec::new();
+        for (provider, key) in keys.iter().filter(|(_, key)| !key.is_empty()) {
+            store_key(&app, provider, key)?;
+            migrated.push(provider.clone());
+        }
+        let moved = crate::activate::take_plaintext_stt_api_key(&app, |key| {
+            store_key(&app, STT_PROVIDER, key).map_err(|e| e.to_string())
+        })
+        .map_err(KeyringError::failed)?;
+        if moved {
+            migrated.push(STT_PROVIDER.to_string());
+        }
+        migrated.sort();
+        migrated.dedup();
+        Ok(migrated)
+    })
+    .await
+}
@@ -0,0 +1,86 @@
+// Draws the user back to a hidden or unfocused overlay when a response finishes
+// or fails: a dock bounce on macOS, a flashing taskbar button on Windows, the
+// urgency hint on Linux, and optionally a notification. Focus is never taken.
+// Desktop notifications can't report clicks, the tray or dock icon shows the
+// window instead.
+use crate::window_settings::{self, QuietHours};
+use chrono::Timelike;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime, UserAttentionType};
+use tauri_plugin_notification::NotificationExt;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttentionKind {
+    ResponseReady,
+    Error,
+}
+
+/// Whether local time falls in the quiet hours, which may span midnight
+fn in_quiet_hours(quiet_hours: &QuietHours) -> bool {
+    let now = chrono::Local::now();
+    let minute = (now.hour() * 60 + now.minute()) as u16;
+    if quiet_hours.start <= quiet_hours.end {
+        (quiet_hours.start..quiet_hours.end).contains(&minute)
+    } else {
+        minute >= quiet_hours.start || minute < quiet_hours.end
+    }
+}
+
+/// Tauri command to get the user's attention after a response finished or
+/// failed, only while the window is hidden or unfocused and outside quiet
+/// hours. Returns whether attention was requested.
+#[tauri::command]
+pub fn request_attention<R: Runtime>(app: AppHandle<R>, kind: AttentionKind) -> Result<bool, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    let visible = crate::shortcuts::is_main_window_visible(&app);
+    if visible && window.is_focused().unwrap_or(false) {
+        return Ok(false);
+    }
+    let settings = window_settings::get(&app);
+    if settings.quiet_hours.as_ref().is_some_and(in_quiet_hours) {
+        return Ok(false);
+    }
+
+    let attention = match kind {
+        AttentionKind::ResponseReady => UserAttentionType::Informational,
+        AttentionKind::Error => UserAttentionType::Critical,
+    };
+    window
+        .request_user_attention(Some(attention))
+        .map_err(|e| format!("Failed to request attention: {}", e))?;
+
+    if settings.attention_notifications {
+        let body = match kind {
+            AttentionKind::ResponseReady => "Response ready",
+            AttentionKind::Error => "Response failed",
+        };
+        if let Err(e) = app.notification().builder().title("Pluely").body(body).show() {
+            eprintln!("Failed to show attention notification: {}", e);
+        }
+    }
+    Ok(true)
+}
+
+/// Tauri command to choose whether request_attention also posts a notification
+/// and when it stays silent. Quiet hours are minutes after local midnight and
+/// may wrap past it, null turns them off.
+#[tauri::command]
+pub fn set_attention_settings<R: Runtime>(
+    app: AppHandle<R>,
+    notifications: bool,
+    quiet_hours: Option<QuietHours>,
+) -> Result<(), String> {
+    if let Some(quiet_hours) = &quiet_hours {
+        if quiet_hours.start >= 24 * 60 || quiet_hours.end >= 24 * 60 {
+            return Err("Quiet hours must be minutes between 0 and 1439".to_string());
+        }
+    }
+    window_settings::update(&app, |settings| {
+        settings.attention_notifications = notifications;
+        settings.quiet_hours = quiet_hours;
+    })?;
+    Ok(())
+}
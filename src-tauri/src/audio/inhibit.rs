@@ -0,0 +1,182 @@
+// Keeps the machine awake while anything captures, a Mac that goes to sleep ends
+// the recording. A dedicated thread holds the platform inhibitor because Windows
+// ties the execution state to the thread that set it.
+use super::{AudioCaptureState, AudioSource};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tauri::{AppHandle, Manager, Runtime};
+
+// Shown by the OS in its list of sleep blockers
+const REASON: &str = "Pluely is capturing audio";
+
+// The thread holding the inhibitor, which releases it once the sender drops
+struct Inhibitor {
+    release: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+pub struct InhibitState {
+    enabled: Mutex<bool>,
+    held: Mutex<Option<Inhibitor>>,
+    // Set by the thread once the OS accepted the inhibitor
+    active: Arc<AtomicBool>,
+}
+
+impl Default for InhibitState {
+    fn default() -> Self {
+        InhibitState {
+            enabled: Mutex::new(true),
+            held: Mutex::new(None),
+            active: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn hold(active: &AtomicBool, release: Receiver<()>) -> Result<(), String> {
+    use core_foundation::base::TCFType;
+    use core_foundation::string::{CFString, CFStringRef};
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(kind: CFStringRef, level: u32, name: CFStringRef, id: *mut u32) -> i32;
+        fn IOPMAssertionRelease(id: u32) -> i32;
+    }
+    // kIOPMAssertionLevelOn
+    const LEVEL_ON: u32 = 255;
+
+    // Keeping the display awake also keeps the system out of idle sleep
+    let kind = CFString::new("PreventUserIdleDisplaySleep");
+    let name = CFString::new(REASON);
+    let mut id = 0u32;
+    let result =
+        unsafe { IOPMAssertionCreateWithName(kind.as_concrete_TypeRef(), LEVEL_ON, name.as_concrete_TypeRef(), &mut id) };
+    if result != 0 {
+        return Err(format!("Failed to create power assertion: {}", result));
+    }
+    active.store(true, Ordering::Release);
+    let _ = release.recv();
+    unsafe { IOPMAssertionRelease(id) };
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn hold(active: &AtomicBool, release: Receiver<()>) -> Result<(), String> {
+    use windows::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+    };
+
+    let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED) };
+    if previous.0 == 0 {
+        return Err("Failed to set the thread execution state".to_string());
+    }
+    active.store(true, Ordering::Release);
+    let _ = release.recv();
+    unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn hold(active: &AtomicBool, release: Receiver<()>) -> Result<(), String> {
+    use ashpd::desktop::inhibit::{InhibitFlags, InhibitProxy};
+
+    // The portal goes to logind or the screensaver, whichever the desktop uses
+    tauri::async_runtime::block_on(async {
+        let proxy = InhibitProxy::new()
+            .await
+            .map_err(|e| format!("Failed to reach the inhibit portal: {}", e))?;
+        let request = proxy
+            .inhibit(None, InhibitFlags::Idle | InhibitFlags::Suspend, REASON)
+            .await
+            .map_err(|e| format!("Failed to inhibit sleep: {}", e))?;
+        active.store(true, Ordering::Release);
+        let _ = release.recv();
+        if let Err(e) = request.close().await {
+            eprintln!("Failed to release sleep inhibitor: {}", e);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn hold(_active: &AtomicBool, _release: Receiver<()>) -> Result<(), String> {
+    Err("Sleep inhibition is not supported on this platform".to_string())
+}
+
+fn is_anything_capturing<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let speech_capture = {
+        let state = app.state::<crate::AudioState>();
+        let running = match state.stream_task.lock() {
+            Ok(guard) => guard.is_some(),
+            Err(poisoned) => poisoned.into_inner().is_some(),
+        };
+        running
+    };
+    speech_capture
+        || super::is_capturing(app, AudioSource::Mic)
+        || super::is_capturing(app, AudioSource::System)
+        || super::is_rolling_buffer_running(app)
+}
+
+/// Hold or release the inhibitor to match the setting and running captures,
+/// call after any capture starts or ends
+pub fn update<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<AudioCaptureState>();
+    let enabled = match state.inhibit.enabled.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let wanted = enabled && is_anything_capturing(app);
+
+    let mut held = match state.inhibit.held.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if wanted && held.is_none() {
+        let (release, receiver) = mpsc::channel();
+        let active = state.inhibit.active.clone();
+        match std::thread::Builder::new()
+            .name("pluely-sleep-inhibit".to_string())
+            .spawn(move || {
+                if let Err(e) = hold(&active, receiver) {
+                    eprintln!("{}", e);
+                }
+                active.store(false, Ordering::Release);
+            }) {
+            Ok(thread) => *held = Some(Inhibitor { release, thread }),
+            Err(e) => eprintln!("Failed to start sleep inhibitor thread: {}", e),
+        }
+    } else if !wanted {
+        if let Some(inhibitor) = held.take() {
+            drop(inhibitor.release);
+            drop(held);
+            if inhibitor.thread.join().is_err() {
+                eprintln!("Sleep inhibitor thread panicked");
+            }
+        }
+    }
+}
+
+/// Tauri command to keep the machine awake while capturing, on by default
+#[tauri::command]
+pub fn set_sleep_inhibit_enabled<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    {
+        let state = app.state::<AudioCaptureState>();
+        *state
+            .inhibit
+            .enabled
+            .lock()
+            .map_err(|e| format!("Failed to update audio setting: {}", e))? = enabled;
+    }
+    update(&app);
+    Ok(())
+}
+
+/// Tauri command to tell whether sleep is being held off right now
+#[tauri::command]
+pub fn get_sleep_inhibited<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    let state = app.state::<AudioCaptureState>();
+    Ok(state.inhibit.active.load(Ordering::Acquire))
+}
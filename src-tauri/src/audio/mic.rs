@@ -0,0 +1,412 @@
+// Microphone capture through cpal
+use super::denoise::{self, NoiseSuppressor};
+use super::vad::SilenceDetector;
+use super::{
+    begin_session, emit_audio_error, emit_device_switched, end_session, stop_session, vad_config,
+    AudioSource, ChunkEmitter, FinishReason, StreamResampler,
+};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::Serialize;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime};
+
+// Settings store key of the chosen microphone, by name
+pub(super) const INPUT_DEVICE_SETTING: &str = "audio_input_device";
+
+// Buffers queued between the stream callback and the capture thread
+const CALLBACK_QUEUE: usize = 64;
+
+// How often the capture thread checks the stop flag
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Some backends stop calling back instead of reporting an unplugged device
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Least time between checks that the device is still plugged in
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+// How long to keep trying the default device after the active one went away,
+// the OS can take a moment to pick a new default
+const REOPEN_TIMEOUT: Duration = Duration::from_secs(2);
+const REOPEN_INTERVAL: Duration = Duration::from_millis(250);
+
+// Rates offered to the frontend when a device supports a range of them
+const COMMON_SAMPLE_RATES: [u32; 8] = [8_000, 16_000, 22_050, 24_000, 32_000, 44_100, 48_000, 96_000];
+
+// From the stream callbacks to the capture thread
+enum StreamMessage {
+    Samples(Vec<f32>),
+    Error(String),
+}
+
+// An open input stream, only usable on the thread that opened it
+struct OpenedStream {
+    // Held so the stream keeps running until the capture ends
+    _stream: cpal::Stream,
+    device: String,
+    sample_rate: u32,
+    receiver: Receiver<StreamMessage>,
+}
+
+// cpal has no persistent device ids, the name is what stays the same across runs
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioInputDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub sample_rates: Vec<u32>,
+}
+
+fn supported_sample_rates(device: &cpal::Device) -> Vec<u32> {
+    let Ok(configs) = device.supported_input_configs() else {
+        return Vec::new();
+    };
+    let mut rates: Vec<u32> = configs
+        .flat_map(|range| {
+            let (min, max) = (range.min_sample_rate().0, range.max_sample_rate().0);
+            COMMON_SAMPLE_RATES.into_iter().filter(move |rate| (min..=max).contains(rate))
+        })
+        .collect();
+    rates.sort_unstable();
+    rates.dedup();
+    rates
+}
+
+/// Input devices as the host sees them now, so devices plugged in later show up
+pub fn list_input_devices() -> Result<Vec<AudioInputDevice>, String> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|device| device.name().ok());
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {}", e))?
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            Some(AudioInputDevice {
+                id: name.clone(),
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                sample_rates: supported_sample_rates(&device),
+                name,
+            })
+        })
+        .collect();
+    Ok(devices)
+}
+
+/// Input device by name, or the default one. A named device that is gone falls
+/// back to the default, returned as the second value so it can be reported.
+fn find_input_device(device_id: Option<&str>) -> Result<(cpal::Device, bool), String> {
+    let host = cpal::default_host();
+    if let Some(device_id) = device_id {
+        let found = host
+            .input_devices()
+            .map_err(|e| format!("Failed to list input devices: {}", e))?
+            .find(|device| device.name().is_ok_and(|name| name == device_id));
+        if let Some(device) = found {
+            return Ok((device, false));
+        }
+    }
+    let device = host.default_input_device().ok_or("No input device available".to_string())?;
+    Ok((device, device_id.is_some()))
+}
+
+fn selected_input_device<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    Some(crate::settings::string(app, INPUT_DEVICE_SETTING, "")).filter(|id| !id.is_empty())
+}
+
+/// Config at the requested rate when the device supports it, otherwise its default
+fn input_config(device: &cpal::Device, sample_rate: Option<u32>) -> Result<cpal::SupportedStreamConfig, String> {
+    if let Some(rate) = sample_rate {
+        let supported = device
+            .supported_input_configs()
+            .map_err(|e| format!("Failed to read input configs: {}", e))?
+            .find(|range| range.min_sample_rate().0 <= rate && range.max_sample_rate().0 >= rate);
+        match supported {
+            Some(range) => return Ok(range.with_sample_rate(cpal::SampleRate(rate))),
+            None => eprintln!("Input device doesn't support {} Hz, using its default rate", rate),
+        }
+    }
+    device
+        .default_input_config()
+        .map_err(|e| format!("Failed to read default input config: {}", e))
+}
+
+/// Stream that downmixes to mono f32. The callbacks only try_send, so a slow
+/// consumer drops buffers instead of blocking or panicking the audio thread.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sender: SyncSender<StreamMessage>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    let channels = (config.channels as usize).max(1);
+    let error_sender = sender.clone();
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mono = data
+                .chunks(channels)
+                .map(|frame| frame.iter().map(|sample| sample.to_sample::<f32>()).sum::<f32>() / channels as f32)
+                .collect();
+            let _ = sender.try_send(StreamMessage::Samples(mono));
+        },
+        move |e| {
+            let _ = error_sender.try_send(StreamMessage::Error(e.to_string()));
+        },
+        None,
+    )
+}
+
+fn open_stream<R: Runtime>(
+    app: &AppHandle<R>,
+    device_id: Option<&str>,
+    sample_rate: Option<u32>,
+) -> Result<OpenedStream, String> {
+    let (device, fell_back) = find_input_device(device_id)?;
+    let name = device.name().unwrap_or_default();
+    if fell_back {
+        let used = name.clone();
+        eprintln!("Input device {:?} not found, using {}", device_id, used);
+        let _ = app.emit("audio-device-fallback", json!({ "requested": device_id, "used": used }));
+    }
+    let supported = input_config(&device, sample_rate)?;
+    let config = supported.config();
+    let (sender, receiver) = mpsc::sync_channel(CALLBACK_QUEUE);
+
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, sender),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, sender),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, sender),
+        cpal::SampleFormat::I32 => build_stream::<i32>(&device, &config, sender),
+        format => return Err(format!("Unsupported input sample format {:?}", format)),
+    }
+    .map_err(|e| format!("Failed to open input stream: {}", e))?;
+    stream.play().map_err(|e| format!("Failed to start input stream: {}", e))?;
+    Ok(OpenedStream {
+        _stream: stream,
+        device: name,
+        sample_rate: config.sample_rate.0,
+        receiver,
+    })
+}
+
+fn is_device_present(name: &str) -> bool {
+    match cpal::default_host().input_devices() {
+        Ok(mut devices) => devices.any(|device| device.name().is_ok_and(|device| device == name)),
+        // Can't tell, keep the stream rather than dropping a working device
+        Err(_) => true,
+    }
+}
+
+/// Open the default device in place of one that went away, None when no input
+/// device is left
+fn reopen_default<R: Runtime>(
+    app: &AppHandle<R>,
+    sample_rate: Option<u32>,
+    stop: &AtomicBool,
+) -> Result<Option<OpenedStream>, String> {
+    let started = Instant::now();
+    loop {
+        match open_stream(app, None, sample_rate) {
+            Ok(opened) => return Ok(Some(opened)),
+            Err(e) if started.elapsed() >= REOPEN_TIMEOUT || stop.load(Ordering::Acquire) => {
+                if cpal::default_host().default_input_device().is_none() {
+                    return Ok(None);
+                }
+                return Err(e);
+            }
+            Err(_) => std::thread::sleep(REOPEN_INTERVAL),
+        }
+    }
+}
+
+/// Forward samples until stopped or the speaker goes quiet. A device that fails or
+/// is unplugged is replaced by the default one, with silence for the gap.
+fn run_capture<R: Runtime>(
+    app: &AppHandle<R>,
+    session_id: u64,
+    opened: OpenedStream,
+    requested_rate: Option<u32>,
+    stop: &AtomicBool,
+) {
+    let sample_rate = opened.sample_rate;
+    let mut opened = opened;
+    let mut emitter = ChunkEmitter::new(app.clone(), AudioSource::Mic, session_id, sample_rate);
+    // Set when the replacement device runs at another rate than the recording
+    let mut resampler: Option<StreamResampler> = None;
+    let mut suppressor: Option<NoiseSuppressor> = None;
+    let mut last_samples = Instant::now();
+    let mut last_check = Instant::now();
+    let mut reason = FinishReason::Stopped;
+
+    // Releasing the key ends a push-to-talk recording, silence must not
+    let vad = vad_config(app);
+    let mut silence = (vad.enabled && !crate::shortcuts::is_push_to_talk_enabled(app))
+        .then(|| SilenceDetector::new(vad, sample_rate));
+
+    while !stop.load(Ordering::Acquire) {
+        if emitter.limit_reached() {
+            reason = FinishReason::MaxDuration;
+            break;
+        }
+        let lost = match opened.receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(StreamMessage::Samples(samples)) => {
+                last_samples = Instant::now();
+                let samples = match resampler.as_mut() {
+                    Some(resampler) => {
+                        resampler.push(&samples);
+                        resampler.take_output()
+                    }
+                    None => samples,
+                };
+                let samples = denoise::apply(app, &mut suppressor, sample_rate, samples);
+                // Paused audio mustn't count as silence either
+                let kept = emitter.push(&samples);
+                if kept && silence.as_mut().is_some_and(|detector| detector.push(&samples)) {
+                    reason = FinishReason::Vad;
+                    break;
+                }
+                None
+            }
+            Ok(StreamMessage::Error(e)) => Some(format!("Microphone stream failed: {}", e)),
+            Err(RecvTimeoutError::Timeout) => {
+                if last_samples.elapsed() > STALL_TIMEOUT {
+                    Some("The microphone stopped delivering audio".to_string())
+                } else if last_check.elapsed() >= DEVICE_CHECK_INTERVAL {
+                    // Only enumerate devices once audio stopped, it is slow on some hosts
+                    last_check = Instant::now();
+                    (!is_device_present(&opened.device)).then(|| format!("{} was disconnected", opened.device))
+                } else {
+                    None
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let Some(lost) = lost else {
+            continue;
+        };
+
+        eprintln!("{}, switching to the default microphone", lost);
+        match reopen_default(app, requested_rate, stop) {
+            Ok(Some(replacement)) => {
+                emit_device_switched(app, AudioSource::Mic, &opened.device, &replacement.device);
+                emitter.push_silence(last_samples.elapsed());
+                resampler = if replacement.sample_rate == sample_rate {
+                    None
+                } else {
+                    match StreamResampler::new(replacement.sample_rate, sample_rate) {
+                        Ok(resampler) => Some(resampler),
+                        Err(e) => {
+                            emit_audio_error(app, AudioSource::Mic, &e);
+                            reason = FinishReason::Error;
+                            break;
+                        }
+                    }
+                };
+                opened = replacement;
+                last_samples = Instant::now();
+            }
+            Ok(None) => {
+                eprintln!("No microphone left, finishing the recording");
+                reason = FinishReason::DeviceLost;
+                break;
+            }
+            Err(e) => {
+                emit_audio_error(app, AudioSource::Mic, &format!("{}: {}", lost, e));
+                reason = FinishReason::Error;
+                break;
+            }
+        }
+    }
+
+    // Samples that arrived before the stop still belong to the recording
+    if reason == FinishReason::Stopped {
+        while let Ok(StreamMessage::Samples(samples)) = opened.receiver.try_recv() {
+            match resampler.as_mut() {
+                Some(resampler) => {
+                    resampler.push(&samples);
+                    emitter.push(&resampler.take_output());
+                }
+                None => {
+                    emitter.push(&samples);
+                }
+            }
+        }
+    }
+    drop(opened);
+    emitter.finish(reason);
+}
+
+/// Start capturing a microphone, by name or the selected one, else the default
+pub fn start_mic<R: Runtime>(app: &AppHandle<R>, device_id: Option<String>, sample_rate: Option<u32>) -> Result<(), String> {
+    let device_id = device_id.or_else(|| selected_input_device(app));
+    begin_session(app, AudioSource::Mic, |id, stop| {
+        let (ready_sender, ready_receiver) = mpsc::channel();
+        let handle = app.clone();
+
+        // cpal streams can't move between threads, this one owns it until the end
+        let thread = std::thread::Builder::new()
+            .name("pluely-mic".to_string())
+            .spawn(move || {
+                let opened = match open_stream(&handle, device_id.as_deref(), sample_rate) {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        let _ = ready_sender.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_sender.send(Ok(()));
+
+                crate::tray::set_recording_state(&handle, crate::tray::RecordingKind::Mic);
+                run_capture(&handle, id, opened, sample_rate, &stop);
+                crate::tray::clear_recording_state(&handle, crate::tray::RecordingKind::Mic);
+                end_session(&handle, AudioSource::Mic, id);
+            })
+            .map_err(|e| format!("Failed to start microphone thread: {}", e))?;
+
+        match ready_receiver.recv() {
+            Ok(Ok(())) => Ok(thread),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err("Microphone thread exited during setup".to_string()),
+        }
+    })
+}
+
+/// Tauri command to start native microphone capture, streamed as audio-chunk events
+#[tauri::command]
+pub async fn start_mic_capture<R: Runtime>(
+    app: AppHandle<R>,
+    device_id: Option<String>,
+    sample_rate: Option<u32>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || start_mic(&app, device_id, sample_rate))
+        .await
+        .map_err(|e| format!("Failed to start microphone: {}", e))?
+}
+
+/// Tauri command to stop microphone capture, emits audio-finished once flushed
+#[tauri::command]
+pub async fn stop_mic_capture<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || stop_session(&app, AudioSource::Mic))
+        .await
+        .map_err(|e| format!("Failed to stop microphone: {}", e))?;
+    Ok(())
+}
+
+/// Tauri command to list microphones with their supported sample rates
+#[tauri::command]
+pub fn list_audio_input_devices() -> Result<Vec<AudioInputDevice>, String> {
+    list_input_devices()
+}
+
+/// Tauri command to choose the microphone native capture uses, None for the default
+#[tauri::command]
+pub fn set_audio_input_device<R: Runtime>(app: AppHandle<R>, id: Option<String>) -> Result<(), String> {
+    let id = id.filter(|id| !id.is_empty());
+    crate::settings::set(&app, INPUT_DEVICE_SETTING, json!(id))
+}
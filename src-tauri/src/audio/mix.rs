@@ -0,0 +1,218 @@
+// Mic and system audio summed into one "mixed" chunk stream for meetings. The
+// sources keep capturing on their own threads and hand samples to a mixer thread
+// while both run; when either stops they go back to their own chunks.
+use super::{AudioCaptureState, AudioSource, ChunkEmitter, FinishReason, StreamResampler};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+// Common rate both sources are resampled to
+const MIX_RATE: u32 = 48_000;
+
+// Buffers queued from the capture threads
+const MIX_QUEUE: usize = 128;
+
+// How often the mixer checks for the end of mixing
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// A source further ahead than this is mixed against silence, which covers a
+// slower clock on the other side as well as a source that went quiet
+const MAX_LAG_SAMPLES: usize = (MIX_RATE / 5) as usize;
+
+// Mixed levels above this are compressed instead of clipped
+const LIMIT_KNEE: f32 = 0.8;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MixConfig {
+    pub enabled: bool,
+    pub mic_gain: f32,
+    pub system_gain: f32,
+}
+
+impl Default for MixConfig {
+    fn default() -> Self {
+        MixConfig {
+            enabled: false,
+            mic_gain: 1.0,
+            system_gain: 1.0,
+        }
+    }
+}
+
+struct MixSamples {
+    source: AudioSource,
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+// The running mixer thread, ended by dropping the sender
+struct Mixer {
+    sender: SyncSender<MixSamples>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct MixState {
+    config: Mutex<MixConfig>,
+    running: Mutex<Option<Mixer>>,
+}
+
+fn mix_config<R: Runtime>(app: &AppHandle<R>) -> MixConfig {
+    let state = app.state::<AudioCaptureState>();
+    let config = match state.mix.config.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    config
+}
+
+/// Hand samples to the mixer, false when not mixing so the caller emits them
+pub fn forward<R: Runtime>(app: &AppHandle<R>, source: AudioSource, sample_rate: u32, samples: &[f32]) -> bool {
+    let state = app.state::<AudioCaptureState>();
+    let sender = match state.mix.running.lock() {
+        Ok(guard) => guard.as_ref().map(|mixer| mixer.sender.clone()),
+        Err(poisoned) => poisoned.into_inner().as_ref().map(|mixer| mixer.sender.clone()),
+    };
+    let Some(sender) = sender else {
+        return false;
+    };
+    let samples = MixSamples {
+        source,
+        sample_rate,
+        samples: samples.to_vec(),
+    };
+    if sender.try_send(samples).is_err() {
+        eprintln!("Audio mixer is behind, dropping {:?} samples", source);
+    }
+    true
+}
+
+/// Soft limiter, linear up to the knee and approaching 1.0 above it
+pub fn limit(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= LIMIT_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - LIMIT_KNEE;
+    sample.signum() * (LIMIT_KNEE + headroom * ((magnitude - LIMIT_KNEE) / headroom).tanh())
+}
+
+fn run_mixer<R: Runtime>(app: AppHandle<R>, session_id: u64, receiver: Receiver<MixSamples>) {
+    let mut emitter = ChunkEmitter::new(app.clone(), AudioSource::Mixed, session_id, MIX_RATE);
+    let mut resamplers: [Option<(u32, StreamResampler)>; 2] = [None, None];
+    let mut queues: [VecDeque<f32>; 2] = [VecDeque::new(), VecDeque::new()];
+    let mut mixed = Vec::new();
+
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(message) => {
+                let index = usize::from(message.source != AudioSource::Mic);
+                // A device switch can change the rate, start over with a new resampler
+                if resamplers[index].as_ref().is_none_or(|(rate, _)| *rate != message.sample_rate) {
+                    match StreamResampler::new(message.sample_rate, MIX_RATE) {
+                        Ok(resampler) => resamplers[index] = Some((message.sample_rate, resampler)),
+                        Err(e) => {
+                            eprintln!("Failed to resample {:?} for mixing: {}", message.source, e);
+                            continue;
+                        }
+                    }
+                }
+                if let Some((_, resampler)) = resamplers[index].as_mut() {
+                    resampler.push(&message.samples);
+                    queues[index].extend(resampler.take_output());
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let config = mix_config(&app);
+        let [mic, system] = &mut queues;
+        let count = mic.len().min(system.len()).max(mic.len().max(system.len()).saturating_sub(MAX_LAG_SAMPLES));
+        mixed.clear();
+        for _ in 0..count {
+            let mic_sample = mic.pop_front().unwrap_or(0.0);
+            let system_sample = system.pop_front().unwrap_or(0.0);
+            mixed.push(limit(mic_sample * config.mic_gain + system_sample * config.system_gain));
+        }
+        emitter.push(&mixed);
+    }
+
+    emitter.finish(FinishReason::Stopped);
+}
+
+/// Start or stop the mixer to match the setting and which sources capture
+pub fn update<R: Runtime>(app: &AppHandle<R>) {
+    let both = super::is_capturing(app, AudioSource::Mic) && super::is_capturing(app, AudioSource::System);
+    let should_mix = both && mix_config(app).enabled;
+
+    let state = app.state::<AudioCaptureState>();
+    let mut running = match state.mix.running.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    if should_mix && running.is_none() {
+        let (sender, receiver) = mpsc::sync_channel(MIX_QUEUE);
+        let session_id = state.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let handle = app.clone();
+        match std::thread::Builder::new()
+            .name("pluely-audio-mix".to_string())
+            .spawn(move || run_mixer(handle, session_id, receiver))
+        {
+            Ok(thread) => {
+                *running = Some(Mixer { sender, thread });
+                let _ = app.emit("audio-mix-changed", json!({ "mixed": true, "session_id": session_id }));
+            }
+            Err(e) => eprintln!("Failed to start audio mixer: {}", e),
+        }
+        return;
+    }
+
+    if !should_mix {
+        // Join outside the lock, the mixer's last push mustn't wait on it
+        let Some(mixer) = running.take() else {
+            return;
+        };
+        drop(running);
+        drop(mixer.sender);
+        if mixer.thread.join().is_err() {
+            eprintln!("Audio mixer thread panicked");
+        }
+
+        let mut sources = Vec::new();
+        for source in [AudioSource::Mic, AudioSource::System] {
+            if super::is_capturing(app, source) {
+                sources.push(source);
+            }
+        }
+        let _ = app.emit("audio-mix-changed", json!({ "mixed": false, "sources": sources }));
+    }
+}
+
+/// Tauri command to mix mic and system audio into one stream while both capture,
+/// with a gain per source
+#[tauri::command]
+pub fn set_mixed_mode<R: Runtime>(app: AppHandle<R>, config: MixConfig) -> Result<(), String> {
+    for gain in [config.mic_gain, config.system_gain] {
+        if !(0.0..=4.0).contains(&gain) {
+            return Err(format!("Mix gain must be between 0 and 4, got {}", gain));
+        }
+    }
+    {
+        let state = app.state::<AudioCaptureState>();
+        *state
+            .mix
+            .config
+            .lock()
+            .map_err(|e| format!("Failed to update audio setting: {}", e))? = config;
+    }
+    tauri::async_runtime::spawn_blocking(move || update(&app));
+    Ok(())
+}
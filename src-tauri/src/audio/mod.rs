@@ -0,0 +1,844 @@
+// Native audio capture, streamed to the frontend as 16-bit mono PCM chunks so the
+// webview doesn't need getUserMedia. Each source runs on its own thread that owns
+// the device stream; chunks go out as audio-chunk events.
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+mod denoise;
+mod inhibit;
+mod mic;
+mod mix;
+mod opus;
+#[cfg(target_os = "linux")]
+mod pulse;
+mod recordings;
+mod rolling;
+#[cfg(target_os = "macos")]
+mod screen_capture_kit;
+mod stt;
+mod system;
+mod vad;
+mod wav;
+mod whisper;
+
+pub use denoise::*;
+pub use inhibit::{update as update_sleep_inhibit, *};
+pub use mic::*;
+pub use mix::*;
+pub use opus::*;
+pub use recordings::*;
+pub use rolling::*;
+pub use stt::*;
+pub use system::*;
+pub use vad::VadConfig;
+pub use wav::*;
+pub use whisper::*;
+
+// Length of one audio-chunk event
+const CHUNK_MS: u32 = 100;
+
+// Audio measured per audio-level event, which is also the fastest they go out
+const LEVEL_WINDOW_MS: u32 = 50;
+const LEVEL_MIN_INTERVAL: Duration = Duration::from_millis(LEVEL_WINDOW_MS as u64);
+
+// Reported for digital silence instead of -inf
+const LEVEL_FLOOR_DB: f32 = -100.0;
+
+// Recordings stop on their own after this long
+pub const DEFAULT_MAX_DURATION_MS: u64 = 10 * 60 * 1000;
+
+// Share of the maximum duration at which audio-duration-warning goes out
+const DURATION_WARNING_PERCENT: u64 = 80;
+
+// Longest gap a device switch fills with silence
+const MAX_SILENCE_GAP: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioSource {
+    Mic,
+    System,
+    Mixed,
+}
+
+impl AudioSource {
+    fn name(self) -> &'static str {
+        match self {
+            AudioSource::Mic => "mic",
+            AudioSource::System => "system",
+            AudioSource::Mixed => "mixed",
+        }
+    }
+}
+
+// File format of recordings written on stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    Wav,
+    Ogg,
+}
+
+// Why a capture ended, sent with audio-finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    Stopped,
+    Vad,
+    MaxDuration,
+    Error,
+    DeviceLost,
+}
+
+// Gain and mute of one source, kept across recordings
+#[derive(Debug, Clone, Copy, Default)]
+struct SourceControl {
+    gain_db: f32,
+    muted: bool, // the stream keeps running, samples are replaced by silence
+}
+
+// A running capture, stopped by setting the flag. While paused the device keeps
+// delivering but the samples are dropped.
+struct CaptureSession {
+    id: u64,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    paused_since: Option<Instant>,
+    paused_ms: u64,
+}
+
+// Running native captures and whether the audio shortcut uses them
+pub struct AudioCaptureState {
+    pub native: Mutex<bool>,
+    max_duration_ms: Mutex<u64>,
+    level_meter: Mutex<bool>,
+    vad: Mutex<VadConfig>,
+    // Target rate for a WAV written on stop, None when off
+    auto_wav: Mutex<Option<u32>>,
+    // WAVs written on stop by session id, for encode_wav
+    wav_files: Mutex<HashMap<u64, PathBuf>>,
+    recording_format: Mutex<RecordingFormat>,
+    opus_bitrate: Mutex<u32>,
+    mix: mix::MixState,
+    rolling: rolling::RollingState,
+    stt: stt::SttState,
+    inhibit: inhibit::InhibitState,
+    // Linux sink to capture, None follows the default output
+    system_sink: Mutex<Option<String>>,
+    mic_control: Mutex<SourceControl>,
+    system_control: Mutex<SourceControl>,
+    mic: Mutex<Option<CaptureSession>>,
+    system: Mutex<Option<CaptureSession>>,
+    next_id: AtomicU64,
+}
+
+impl Default for AudioCaptureState {
+    fn default() -> Self {
+        AudioCaptureState {
+            native: Mutex::new(false),
+            max_duration_ms: Mutex::new(DEFAULT_MAX_DURATION_MS),
+            level_meter: Mutex::new(true),
+            vad: Mutex::new(VadConfig::default()),
+            auto_wav: Mutex::new(None),
+            wav_files: Mutex::new(HashMap::new()),
+            recording_format: Mutex::new(RecordingFormat::Wav),
+            opus_bitrate: Mutex::new(opus::DEFAULT_BITRATE),
+            mix: mix::MixState::default(),
+            rolling: rolling::RollingState::default(),
+            stt: stt::SttState::default(),
+            inhibit: inhibit::InhibitState::default(),
+            system_sink: Mutex::new(None),
+            mic: Mutex::new(None),
+            mic_control: Mutex::new(SourceControl::default()),
+            system_control: Mutex::new(SourceControl::default()),
+            system: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+impl AudioCaptureState {
+    // Mixed audio comes from the other two, it has no capture of its own
+    fn slot(&self, source: AudioSource) -> Option<&Mutex<Option<CaptureSession>>> {
+        match source {
+            AudioSource::Mic => Some(&self.mic),
+            AudioSource::System => Some(&self.system),
+            AudioSource::Mixed => None,
+        }
+    }
+
+    // Mixed audio takes its gains from the mix config
+    fn control(&self, source: AudioSource) -> Option<&Mutex<SourceControl>> {
+        match source {
+            AudioSource::Mic => Some(&self.mic_control),
+            AudioSource::System => Some(&self.system_control),
+            AudioSource::Mixed => None,
+        }
+    }
+}
+
+// RMS and peak over one level window
+struct LevelMeter {
+    window_samples: usize,
+    count: usize,
+    sum_squares: f32,
+    peak: f32,
+    last_emit: Option<Instant>,
+}
+
+impl LevelMeter {
+    fn new(sample_rate: u32) -> Self {
+        LevelMeter {
+            window_samples: (sample_rate * LEVEL_WINDOW_MS / 1000).max(1) as usize,
+            count: 0,
+            sum_squares: 0.0,
+            peak: 0.0,
+            last_emit: None,
+        }
+    }
+
+    /// Add one sample, returns (rms_db, peak_db) when a window completes and
+    /// enough time passed since the last one, bursts after a stall are dropped
+    fn push(&mut self, sample: f32) -> Option<(f32, f32)> {
+        self.sum_squares += sample * sample;
+        self.peak = self.peak.max(sample.abs());
+        self.count += 1;
+        if self.count < self.window_samples {
+            return None;
+        }
+
+        let rms = (self.sum_squares / self.count as f32).sqrt();
+        let peak = self.peak;
+        self.count = 0;
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+
+        if self.last_emit.is_some_and(|last| last.elapsed() < LEVEL_MIN_INTERVAL) {
+            return None;
+        }
+        self.last_emit = Some(Instant::now());
+        Some((to_db(rms), to_db(peak)))
+    }
+}
+
+fn to_db(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return LEVEL_FLOOR_DB;
+    }
+    (20.0 * amplitude.log10()).max(LEVEL_FLOOR_DB)
+}
+
+/// Splits captured samples into audio-chunk events and counts the total length,
+/// emitting audio-level events along the way when the meter is on
+pub struct ChunkEmitter<R: Runtime> {
+    app: AppHandle<R>,
+    source: AudioSource,
+    session_id: u64,
+    sample_rate: u32,
+    seq: u64,
+    pending: Vec<i16>,
+    chunk_samples: usize,
+    total_samples: u64,
+    level: LevelMeter,
+    // Resampler and target rate when a WAV is written on stop
+    wav: Option<(StreamResampler, u32)>,
+    // None for mixed audio, whose sources have their own limits
+    max_samples: Option<u64>,
+    warned: bool,
+}
+
+impl<R: Runtime> ChunkEmitter<R> {
+    pub fn new(app: AppHandle<R>, source: AudioSource, session_id: u64, sample_rate: u32) -> Self {
+        let chunk_samples = (sample_rate * CHUNK_MS / 1000).max(1) as usize;
+        let max_samples = (source != AudioSource::Mixed).then(|| max_duration_ms(&app) * sample_rate as u64 / 1000);
+        let wav = auto_wav_rate(&app).and_then(|target_rate| match StreamResampler::new(sample_rate, target_rate) {
+            Ok(resampler) => Some((resampler, target_rate)),
+            Err(e) => {
+                eprintln!("Recording won't be encoded: {}", e);
+                None
+            }
+        });
+        ChunkEmitter {
+            app,
+            source,
+            session_id,
+            sample_rate,
+            seq: 0,
+            pending: Vec::with_capacity(chunk_samples),
+            chunk_samples,
+            total_samples: 0,
+            level: LevelMeter::new(sample_rate),
+            wav,
+            max_samples,
+            warned: false,
+        }
+    }
+
+    /// Add mono samples in -1.0..=1.0, emitting every full chunk. Returns false
+    /// when the capture is paused and the samples were dropped.
+    pub fn push(&mut self, samples: &[f32]) -> bool {
+        if is_paused(&self.app, self.source, self.session_id) {
+            return false;
+        }
+        // Before anything else, so the meter shows what gets uploaded
+        let control = source_control(&self.app, self.source);
+        let adjusted: Vec<f32>;
+        let samples = if control.muted {
+            adjusted = vec![0.0; samples.len()];
+            &adjusted[..]
+        } else if control.gain_db != 0.0 {
+            let gain = 10f32.powf(control.gain_db / 20.0);
+            adjusted = samples.iter().map(|sample| mix::limit(sample * gain)).collect();
+            &adjusted[..]
+        } else {
+            samples
+        };
+        let metering = is_level_meter_enabled(&self.app);
+        if let Some((resampler, _)) = &mut self.wav {
+            resampler.push(samples);
+        }
+        // While mixing, the mixer emits the chunks for both sources
+        let mixing = self.source != AudioSource::Mixed && mix::forward(&self.app, self.source, self.sample_rate, samples);
+        for sample in samples {
+            let sample = sample.clamp(-1.0, 1.0);
+            if !mixing {
+                self.pending.push((sample * i16::MAX as f32) as i16);
+                if self.pending.len() >= self.chunk_samples {
+                    self.emit_pending();
+                }
+            }
+            if let Some((rms_db, peak_db)) = self.level.push(sample) {
+                if metering {
+                    let _ = self.app.emit(
+                        "audio-level",
+                        json!({ "source": self.source, "rms_db": rms_db, "peak_db": peak_db }),
+                    );
+                }
+            }
+        }
+        self.total_samples += samples.len() as u64;
+
+        if let Some(max_samples) = self.max_samples {
+            if !self.warned && self.total_samples * 100 >= max_samples * DURATION_WARNING_PERCENT {
+                self.warned = true;
+                let _ = self.app.emit(
+                    "audio-duration-warning",
+                    json!({
+                        "source": self.source,
+                        "session_id": self.session_id,
+                        "elapsed_ms": self.total_ms(),
+                        "max_duration_ms": max_samples * 1000 / self.sample_rate.max(1) as u64,
+                    }),
+                );
+            }
+        }
+        true
+    }
+
+    /// Fill a gap left by a device switch with silence, so later chunks keep their timestamps
+    pub fn push_silence(&mut self, gap: Duration) {
+        let gap = gap.min(MAX_SILENCE_GAP);
+        let samples = vec![0.0; (gap.as_millis() as u64 * self.sample_rate as u64 / 1000) as usize];
+        self.push(&samples);
+    }
+
+    /// True once the recording reached the maximum duration
+    pub fn limit_reached(&self) -> bool {
+        self.max_samples.is_some_and(|max_samples| self.total_samples >= max_samples)
+    }
+
+    fn emit_pending(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        stt::forward(&self.app, self.source, self.sample_rate, &self.pending);
+        let bytes: Vec<u8> = self.pending.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        let _ = self.app.emit(
+            "audio-chunk",
+            json!({
+                "source": self.source,
+                "session_id": self.session_id,
+                "seq": self.seq,
+                "sample_rate": self.sample_rate,
+                "data_base64": B64.encode(bytes),
+            }),
+        );
+        self.seq += 1;
+        self.pending.clear();
+    }
+
+    pub fn total_ms(&self) -> u64 {
+        self.total_samples * 1000 / self.sample_rate.max(1) as u64
+    }
+
+    /// Emit the partial last chunk and audio-finished, then audio-ready once the
+    /// recording is written when encoding on stop is on
+    pub fn finish(mut self, reason: FinishReason) {
+        self.emit_pending();
+        let _ = self.app.emit(
+            "audio-finished",
+            json!({
+                "source": self.source,
+                "session_id": self.session_id,
+                "reason": reason,
+                "duration_ms": self.total_ms(),
+            }),
+        );
+
+        if let Some((resampler, target_rate)) = self.wav.take() {
+            if let Err(e) = self.write_recording(resampler, target_rate) {
+                emit_audio_error(&self.app, self.source, &e);
+            }
+        }
+    }
+
+    fn write_recording(&self, resampler: StreamResampler, target_rate: u32) -> Result<(), String> {
+        let samples = resampler.finish();
+        if samples.is_empty() {
+            return Ok(());
+        }
+        save_recording(&self.app, self.source, self.session_id, &samples, target_rate, false)
+    }
+}
+
+/// Encode a recording in the chosen format to the temp folder and emit audio-ready.
+/// Retroactive recordings come from the rolling buffer instead of a capture.
+fn save_recording<R: Runtime>(
+    app: &AppHandle<R>,
+    source: AudioSource,
+    session_id: u64,
+    samples: &[f32],
+    sample_rate: u32,
+    retroactive: bool,
+) -> Result<(), String> {
+    let (format, bitrate) = recording_format(app);
+
+    // WAV stays the fallback, some STT endpoints reject Opus anyway
+    let encoded = match format {
+        RecordingFormat::Ogg => match opus::ogg_bytes(samples, sample_rate, bitrate) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                eprintln!("Falling back to WAV: {}", e);
+                None
+            }
+        },
+        RecordingFormat::Wav => None,
+    };
+    let (format, bytes) = match encoded {
+        Some(bytes) => (RecordingFormat::Ogg, bytes),
+        None => (RecordingFormat::Wav, wav_bytes(samples, sample_rate)?),
+    };
+    let extension = match format {
+        RecordingFormat::Wav => "wav",
+        RecordingFormat::Ogg => "ogg",
+    };
+    let name = format!("{}-{}.{}", source.name(), session_id, extension);
+    let path = write_temp_audio(app, &name, &bytes)?;
+    // A failed copy mustn't lose the recording, the temp file is still there
+    let recording_path = recordings::save(app, source, &bytes, extension).unwrap_or_else(|e| {
+        eprintln!("Failed to save recording: {}", e);
+        None
+    });
+
+    if format == RecordingFormat::Wav {
+        let state = app.state::<AudioCaptureState>();
+        match state.wav_files.lock() {
+            Ok(mut guard) => guard.insert(session_id, path.clone()),
+            Err(poisoned) => poisoned.into_inner().insert(session_id, path.clone()),
+        };
+    }
+    let _ = app.emit(
+        "audio-ready",
+        json!({
+            "source": source,
+            "session_id": session_id,
+            "format": format,
+            "path": path,
+            "wav_path": (format == RecordingFormat::Wav).then_some(&path),
+            "duration_ms": wav::duration_ms(samples.len(), sample_rate),
+            "bytes": bytes.len(),
+            "recording_path": recording_path,
+            "retroactive": retroactive,
+        }),
+    );
+    Ok(())
+}
+
+pub fn emit_audio_error<R: Runtime>(app: &AppHandle<R>, source: AudioSource, message: &str) {
+    eprintln!("Audio capture error ({:?}): {}", source, message);
+    let _ = app.emit("audio-error", json!({ "source": source, "message": message }));
+}
+
+/// Tell the frontend capture moved to another device and carries on
+pub fn emit_device_switched<R: Runtime>(app: &AppHandle<R>, source: AudioSource, from: &str, to: &str) {
+    eprintln!("Audio capture ({:?}) switched from {} to {}", source, from, to);
+    let _ = app.emit("audio-device-switched", json!({ "source": source, "from": from, "to": to }));
+}
+
+pub fn is_capturing<R: Runtime>(app: &AppHandle<R>, source: AudioSource) -> bool {
+    let state = app.state::<AudioCaptureState>();
+    let Some(slot) = state.slot(source) else {
+        return false;
+    };
+    let slot = match slot.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    slot.is_some()
+}
+
+fn is_paused<R: Runtime>(app: &AppHandle<R>, source: AudioSource, session_id: u64) -> bool {
+    let state = app.state::<AudioCaptureState>();
+    let Some(slot) = state.slot(source) else {
+        return false;
+    };
+    let slot = match slot.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    slot.as_ref()
+        .is_some_and(|session| session.id == session_id && session.paused_since.is_some())
+}
+
+/// Check a value set_setting would store under one of the audio keys
+pub fn validate_setting(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    match key {
+        mic::INPUT_DEVICE_SETTING => crate::settings::parse::<String>(key, value).map(drop),
+        denoise::NOISE_SUPPRESSION_SETTING => crate::settings::parse::<bool>(key, value).map(drop),
+        _ => validate_recordings_setting(key, value),
+    }
+}
+
+pub fn is_native_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<AudioCaptureState>();
+    let native = match state.native.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    native
+}
+
+pub fn max_duration_ms<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    let state = app.state::<AudioCaptureState>();
+    let max_duration_ms = match state.max_duration_ms.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    max_duration_ms
+}
+
+pub fn vad_config<R: Runtime>(app: &AppHandle<R>) -> VadConfig {
+    let state = app.state::<AudioCaptureState>();
+    let config = match state.vad.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    config
+}
+
+fn recording_format<R: Runtime>(app: &AppHandle<R>) -> (RecordingFormat, u32) {
+    let state = app.state::<AudioCaptureState>();
+    let format = match state.recording_format.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let bitrate = match state.opus_bitrate.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    (format, bitrate)
+}
+
+fn auto_wav_rate<R: Runtime>(app: &AppHandle<R>) -> Option<u32> {
+    let state = app.state::<AudioCaptureState>();
+    let rate = match state.auto_wav.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    rate
+}
+
+fn is_level_meter_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<AudioCaptureState>();
+    let enabled = match state.level_meter.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    enabled
+}
+
+fn source_control<R: Runtime>(app: &AppHandle<R>, source: AudioSource) -> SourceControl {
+    let state = app.state::<AudioCaptureState>();
+    let Some(control) = state.control(source) else {
+        return SourceControl::default();
+    };
+    let control = match control.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    control
+}
+
+/// Register a capture thread, fails when the source is already capturing
+fn begin_session<R: Runtime>(
+    app: &AppHandle<R>,
+    source: AudioSource,
+    start: impl FnOnce(u64, Arc<AtomicBool>) -> Result<JoinHandle<()>, String>,
+) -> Result<(), String> {
+    let state = app.state::<AudioCaptureState>();
+    let Some(slot) = state.slot(source) else {
+        return Err(format!("{:?} audio can't be captured directly", source));
+    };
+    let mut slot = match slot.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if slot.is_some() {
+        return Err(format!("{:?} capture already running", source));
+    }
+
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = start(id, stop.clone())?;
+    *slot = Some(CaptureSession {
+        id,
+        stop,
+        thread: Some(thread),
+        paused_since: None,
+        paused_ms: 0,
+    });
+    drop(slot);
+    mix::update(app);
+    inhibit::update(app);
+    Ok(())
+}
+
+/// Called by a capture thread when it ends on its own, e.g. after a device error
+fn end_session<R: Runtime>(app: &AppHandle<R>, source: AudioSource, id: u64) {
+    let state = app.state::<AudioCaptureState>();
+    let Some(slot) = state.slot(source) else {
+        return;
+    };
+    let mut slot = match slot.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if slot.as_ref().is_some_and(|session| session.id == id) {
+        *slot = None;
+    }
+    drop(slot);
+    mix::update(app);
+    inhibit::update(app);
+}
+
+/// Stop a capture and wait for its thread to flush the last chunk
+pub fn stop_session<R: Runtime>(app: &AppHandle<R>, source: AudioSource) -> bool {
+    let session = {
+        let state = app.state::<AudioCaptureState>();
+        let Some(slot) = state.slot(source) else {
+            return false;
+        };
+        let mut slot = match slot.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        slot.take()
+    };
+    let Some(mut session) = session else {
+        return false;
+    };
+
+    session.stop.store(true, Ordering::Release);
+    if let Some(thread) = session.thread.take() {
+        if thread.join().is_err() {
+            eprintln!("Audio capture thread panicked");
+        }
+    }
+    mix::update(app);
+    inhibit::update(app);
+    true
+}
+
+/// Emit audio-state-changed with the source's current state, gain and mute
+fn emit_state_changed<R: Runtime>(app: &AppHandle<R>, source: AudioSource, session: Option<&CaptureSession>) {
+    let control = source_control(app, source);
+    let state = match session {
+        None => "idle",
+        Some(session) if session.paused_since.is_some() => "paused",
+        Some(_) if control.muted => "muted",
+        Some(_) => "capturing",
+    };
+    let _ = app.emit(
+        "audio-state-changed",
+        json!({
+            "source": source,
+            "session_id": session.map(|session| session.id),
+            "state": state,
+            "paused_ms": session.map_or(0, |session| session.paused_ms),
+            "gain_db": control.gain_db,
+            "muted": control.muted,
+        }),
+    );
+}
+
+fn update_control<R: Runtime>(
+    app: &AppHandle<R>,
+    source: AudioSource,
+    apply: impl FnOnce(&mut SourceControl),
+) -> Result<SourceControl, String> {
+    let state = app.state::<AudioCaptureState>();
+    let control = state
+        .control(source)
+        .ok_or(format!("{:?} audio takes its gains from set_mixed_mode", source))?;
+    let updated = {
+        let mut control = control
+            .lock()
+            .map_err(|e| format!("Failed to update audio setting: {}", e))?;
+        apply(&mut control);
+        *control
+    };
+
+    if let Some(slot) = state.slot(source) {
+        let slot = match slot.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        emit_state_changed(app, source, slot.as_ref());
+    }
+    Ok(updated)
+}
+
+/// Flip the microphone mute, returns whether it is muted now
+pub fn toggle_mic_mute<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    update_control(app, AudioSource::Mic, |control| control.muted = !control.muted).map(|control| control.muted)
+}
+
+/// Pause or resume running captures, all of them when no source is given
+fn set_paused<R: Runtime>(app: &AppHandle<R>, source: Option<AudioSource>, paused: bool) -> Result<(), String> {
+    let sources = match source {
+        Some(source) => vec![source],
+        None => vec![AudioSource::Mic, AudioSource::System],
+    };
+    let state = app.state::<AudioCaptureState>();
+    let mut found = false;
+
+    for source in sources {
+        let Some(slot) = state.slot(source) else {
+            return Err(format!("{:?} audio can't be paused directly", source));
+        };
+        let mut slot = match slot.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let Some(session) = slot.as_mut() else {
+            continue;
+        };
+        if session.paused_since.is_some() == paused {
+            found = true;
+            continue;
+        }
+
+        match session.paused_since.take() {
+            Some(since) => session.paused_ms += since.elapsed().as_millis() as u64,
+            None => session.paused_since = Some(Instant::now()),
+        }
+        found = true;
+        emit_state_changed(app, source, Some(&*session));
+    }
+
+    if !found {
+        return Err("No audio capture is running".to_string());
+    }
+    Ok(())
+}
+
+/// Tauri command to pause native capture without closing the device, paused
+/// audio is left out of chunks, encoded files and duration_ms
+#[tauri::command]
+pub fn pause_audio_capture<R: Runtime>(app: AppHandle<R>, source: Option<AudioSource>) -> Result<(), String> {
+    set_paused(&app, source, true)
+}
+
+/// Tauri command to resume native capture after pause_audio_capture
+#[tauri::command]
+pub fn resume_audio_capture<R: Runtime>(app: AppHandle<R>, source: Option<AudioSource>) -> Result<(), String> {
+    set_paused(&app, source, false)
+}
+
+/// Tauri command to amplify or attenuate a source by gain_db (-30 to 30) before
+/// chunks, metering and encoding, loud audio is soft clipped. Applies mid-recording.
+#[tauri::command]
+pub fn set_capture_gain<R: Runtime>(app: AppHandle<R>, source: AudioSource, gain_db: f32) -> Result<(), String> {
+    if !(-30.0..=30.0).contains(&gain_db) {
+        return Err(format!("Gain must be between -30 and 30 dB, got {}", gain_db));
+    }
+    update_control(&app, source, |control| control.gain_db = gain_db)?;
+    Ok(())
+}
+
+/// Tauri command to mute a source, the stream keeps running and records silence
+#[tauri::command]
+pub fn set_capture_muted<R: Runtime>(app: AppHandle<R>, source: AudioSource, muted: bool) -> Result<(), String> {
+    update_control(&app, source, |control| control.muted = muted)?;
+    Ok(())
+}
+
+/// Tauri command to let the audio shortcut capture natively instead of in the webview
+#[tauri::command]
+pub fn set_native_audio_capture<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let state = app.state::<AudioCaptureState>();
+    *state
+        .native
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = enabled;
+    Ok(())
+}
+
+/// Tauri command to turn the audio-level events sent during capture on or off
+#[tauri::command]
+pub fn set_level_meter_enabled<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let state = app.state::<AudioCaptureState>();
+    *state
+        .level_meter
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = enabled;
+    Ok(())
+}
+
+/// Tauri command to configure stopping microphone recordings after silence.
+/// Applies from the next recording and never in push-to-talk mode.
+#[tauri::command]
+pub fn set_vad_config<R: Runtime>(app: AppHandle<R>, config: VadConfig) -> Result<(), String> {
+    if !(1.0..=40.0).contains(&config.threshold_db) {
+        return Err(format!("Silence threshold must be between 1 and 40 dB, got {}", config.threshold_db));
+    }
+    if !(200..=10_000).contains(&config.hangover_ms) {
+        return Err(format!("Silence duration must be between 200 and 10000 ms, got {}", config.hangover_ms));
+    }
+    let state = app.state::<AudioCaptureState>();
+    *state.vad.lock().map_err(|e| format!("Failed to update audio setting: {}", e))? = config;
+    Ok(())
+}
+
+/// Tauri command to set how long a recording may run before it stops on its own,
+/// for native capture and the frontend recorder alike
+#[tauri::command]
+pub fn set_max_recording_duration<R: Runtime>(app: AppHandle<R>, duration_ms: u64) -> Result<(), String> {
+    if !(10_000..=4 * 60 * 60 * 1000).contains(&duration_ms) {
+        return Err(format!("Maximum duration must be between 10 s and 4 h, got {} ms", duration_ms));
+    }
+    let state = app.state::<AudioCaptureState>();
+    *state
+        .max_duration_ms
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = duration_ms;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+// Ogg Opus encoding of finished recordings, a small fraction of the WAV size
+use super::{AudioCaptureState, RecordingFormat};
+use audiopus::coder::Encoder;
+use audiopus::{Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use tauri::{AppHandle, Manager, Runtime};
+
+pub const DEFAULT_BITRATE: u32 = 24_000;
+
+// Opus frames are 20 ms
+const FRAME_MS: u32 = 20;
+
+// Largest packet a single frame encodes to
+const MAX_PACKET: usize = 4000;
+
+// Ogg Opus granule positions always count 48 kHz samples
+const GRANULE_RATE: u32 = 48_000;
+
+fn opus_rate(sample_rate: u32) -> Result<SampleRate, String> {
+    match sample_rate {
+        8_000 => Ok(SampleRate::Hz8000),
+        12_000 => Ok(SampleRate::Hz12000),
+        16_000 => Ok(SampleRate::Hz16000),
+        24_000 => Ok(SampleRate::Hz24000),
+        48_000 => Ok(SampleRate::Hz48000),
+        rate => Err(format!("Opus can't encode {} Hz audio", rate)),
+    }
+}
+
+fn opus_head(pre_skip: u16, sample_rate: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(1); // mono
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // mapping family for mono/stereo
+    head
+}
+
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"pluely";
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes()); // no comments
+    tags
+}
+
+/// Mono samples at an Opus rate as an .ogg file
+pub fn ogg_bytes(samples: &[f32], sample_rate: u32, bitrate: u32) -> Result<Vec<u8>, String> {
+    let mut encoder = Encoder::new(opus_rate(sample_rate)?, Channels::Mono, Application::Voip)
+        .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(bitrate as i32))
+        .map_err(|e| format!("Failed to set Opus bitrate: {}", e))?;
+    let lookahead = encoder
+        .lookahead()
+        .map_err(|e| format!("Failed to read Opus lookahead: {}", e))? as usize;
+
+    let scale = (GRANULE_RATE / sample_rate) as u64;
+    let pre_skip = lookahead as u64 * scale;
+    let frame_samples = (sample_rate * FRAME_MS / 1000) as usize;
+
+    // The decoder drops pre_skip samples from the start, so feed that much extra
+    // silence at the end and pad the last frame
+    let mut input = samples.to_vec();
+    input.resize(samples.len() + lookahead, 0.0);
+    input.resize(input.len().div_ceil(frame_samples) * frame_samples, 0.0);
+
+    let serial = std::process::id() ^ (samples.len() as u32);
+    let mut bytes = Vec::new();
+    let mut writer = PacketWriter::new(&mut bytes);
+    writer
+        .write_packet(opus_head(pre_skip as u16, sample_rate), serial, PacketWriteEndInfo::EndPage, 0)
+        .map_err(|e| format!("Failed to write Ogg header: {}", e))?;
+    writer
+        .write_packet(opus_tags(), serial, PacketWriteEndInfo::EndPage, 0)
+        .map_err(|e| format!("Failed to write Ogg header: {}", e))?;
+
+    let frames = input.len() / frame_samples;
+    let mut packet = vec![0u8; MAX_PACKET];
+    for (index, frame) in input.chunks(frame_samples).enumerate() {
+        let len = encoder
+            .encode_float(frame, &mut packet)
+            .map_err(|e| format!("Failed to encode Opus: {}", e))?;
+        let last = index + 1 == frames;
+        // The final position ends the stream at the real length, cutting the padding
+        let granule = if last {
+            pre_skip + samples.len() as u64 * scale
+        } else {
+            (index as u64 + 1) * frame_samples as u64 * scale
+        };
+        let end = if last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer
+            .write_packet(packet[..len].to_vec(), serial, end, granule)
+            .map_err(|e| format!("Failed to write Ogg page: {}", e))?;
+    }
+    drop(writer);
+    Ok(bytes)
+}
+
+/// Tauri command to choose the format of recordings written on stop, ogg keeps
+/// the encoding rate when Opus supports it and otherwise falls back to WAV
+#[tauri::command]
+pub fn set_recording_format<R: Runtime>(
+    app: AppHandle<R>,
+    format: RecordingFormat,
+    bitrate: Option<u32>,
+) -> Result<(), String> {
+    let bitrate = bitrate.unwrap_or(DEFAULT_BITRATE);
+    if !(6_000..=128_000).contains(&bitrate) {
+        return Err(format!("Opus bitrate must be between 6000 and 128000 bps, got {}", bitrate));
+    }
+    let state = app.state::<AudioCaptureState>();
+    *state
+        .recording_format
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = format;
+    *state
+        .opus_bitrate
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = bitrate;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audiopus::coder::Decoder;
+    use ogg::reading::PacketReader;
+    use std::f32::consts::TAU;
+    use std::io::Cursor;
+
+    const TONE_HZ: f32 = 440.0;
+
+    fn sine(sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.5 * (TAU * TONE_HZ * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Rising crossings with some hysteresis so coding noise around zero isn't counted
+    fn cycles(samples: &[f32]) -> usize {
+        let mut armed = false;
+        let mut count = 0;
+        for &sample in samples {
+            if sample < -0.1 {
+                armed = true;
+            } else if sample > 0.1 && armed {
+                armed = false;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Decode the way players do: drop pre_skip samples from the start and end
+    /// at the final granule position
+    fn decode(bytes: &[u8]) -> (u32, Vec<f32>) {
+        let mut reader = PacketReader::new(Cursor::new(bytes));
+        let head = reader.read_packet_expected().unwrap().data;
+        assert_eq!(&head[..8], b"OpusHead");
+        let pre_skip = u16::from_le_bytes([head[10], head[11]]) as u64;
+        let sample_rate = u32::from_le_bytes([head[12], head[13], head[14], head[15]]);
+        let tags = reader.read_packet_expected().unwrap().data;
+        assert_eq!(&tags[..8], b"OpusTags");
+
+        let mut decoder = Decoder::new(opus_rate(sample_rate).unwrap(), Channels::Mono).unwrap();
+        let mut decoded = Vec::new();
+        let mut frame = vec![0.0f32; (sample_rate * FRAME_MS / 1000) as usize];
+        let mut end = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let len = decoder.decode_float(Some(&packet.data), &mut frame, false).unwrap();
+            decoded.extend_from_slice(&frame[..len]);
+            if packet.last_in_stream() {
+                end = packet.absgp_page();
+            }
+        }
+
+        // Both count 48 kHz samples
+        let scale = (GRANULE_RATE / sample_rate) as u64;
+        decoded.truncate((end / scale) as usize);
+        decoded.drain(..(pre_skip / scale) as usize);
+        (sample_rate, decoded)
+    }
+
+    #[test]
+    fn ogg_decodes_to_the_original_rate_and_length() {
+        // Not a whole number of 20 ms frames, so the last one is padded
+        let input = sine(16_000, 16_100);
+        let (sample_rate, decoded) = decode(&ogg_bytes(&input, 16_000, DEFAULT_BITRATE).unwrap());
+        assert_eq!(sample_rate, 16_000);
+        assert_eq!(decoded.len(), input.len());
+    }
+
+    #[test]
+    fn ogg_decodes_to_the_same_tone() {
+        let input = sine(16_000, 16_000);
+        let (_, decoded) = decode(&ogg_bytes(&input, 16_000, DEFAULT_BITRATE).unwrap());
+
+        // Lossy, so compare level and pitch rather than samples
+        let level = rms(&decoded) / rms(&input);
+        assert!((0.8..1.2).contains(&level), "level ratio {}", level);
+        let cycles = cycles(&decoded);
+        assert!((430..=450).contains(&cycles), "{} cycles in one second", cycles);
+    }
+
+    #[test]
+    fn ogg_rejects_rates_opus_cannot_encode() {
+        assert!(ogg_bytes(&sine(44_100, 441), 44_100, DEFAULT_BITRATE).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+// Linux system audio from a sink's monitor source through libpulse, which also
+// covers PipeWire through its pulse server
+use super::{emit_audio_error, emit_device_switched, AudioSink, AudioSource, ChunkEmitter, FinishReason};
+use libpulse_binding as pulse;
+use libpulse_simple_binding as psimple;
+use pulse::callbacks::ListResult;
+use pulse::context::{Context, FlagSet, State};
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::operation::{Operation, State as OperationState};
+use pulse::sample::{Format, Spec};
+use pulse::stream::Direction;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+
+pub const SAMPLE_RATE: u32 = 48_000;
+
+// 100 ms of mono 16-bit audio per read, so stop requests are noticed quickly
+const READ_BYTES: usize = (SAMPLE_RATE as usize / 10) * 2;
+
+// How often the default sink is checked while following it
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Introspection connection, only used from the thread that opened it
+struct Connection {
+    mainloop: Mainloop,
+    context: Context,
+}
+
+impl Connection {
+    fn open() -> Result<Self, String> {
+        let mut mainloop = Mainloop::new().ok_or("Failed to create PulseAudio mainloop".to_string())?;
+        let mut context =
+            Context::new(&mainloop, "pluely").ok_or("Failed to create PulseAudio context".to_string())?;
+        context
+            .connect(None, FlagSet::NOFLAGS, None)
+            .map_err(|e| format!("Failed to connect to PulseAudio: {}", e))?;
+
+        loop {
+            iterate(&mut mainloop)?;
+            match context.get_state() {
+                State::Ready => break,
+                State::Failed | State::Terminated => return Err("PulseAudio connection failed".to_string()),
+                _ => {}
+            }
+        }
+        Ok(Connection { mainloop, context })
+    }
+
+    fn wait<C: ?Sized>(&mut self, operation: Operation<C>) -> Result<(), String> {
+        while operation.get_state() == OperationState::Running {
+            iterate(&mut self.mainloop)?;
+        }
+        Ok(())
+    }
+
+    fn default_sink(&mut self) -> Result<Option<String>, String> {
+        let default = Rc::new(RefCell::new(None));
+        let result = default.clone();
+        let operation = self.context.introspect().get_server_info(move |info| {
+            *result.borrow_mut() = info.default_sink_name.as_ref().map(|name| name.to_string());
+        });
+        self.wait(operation)?;
+        let name = default.borrow_mut().take();
+        Ok(name)
+    }
+
+    fn sinks(&mut self) -> Result<Vec<AudioSink>, String> {
+        let default = self.default_sink()?;
+        let sinks = Rc::new(RefCell::new(Vec::new()));
+        let result = sinks.clone();
+        let operation = self.context.introspect().get_sink_info_list(move |item| {
+            let ListResult::Item(sink) = item else {
+                return;
+            };
+            let (Some(id), Some(monitor_source)) = (&sink.name, &sink.monitor_source_name) else {
+                return;
+            };
+            result.borrow_mut().push(AudioSink {
+                id: id.to_string(),
+                name: sink.description.as_ref().map_or(id.to_string(), |name| name.to_string()),
+                monitor_source: monitor_source.to_string(),
+                is_default: false,
+            });
+        });
+        self.wait(operation)?;
+
+        let mut sinks = std::mem::take(&mut *sinks.borrow_mut());
+        for sink in &mut sinks {
+            sink.is_default = default.as_deref() == Some(sink.id.as_str());
+        }
+        Ok(sinks)
+    }
+}
+
+fn iterate(mainloop: &mut Mainloop) -> Result<(), String> {
+    match mainloop.iterate(true) {
+        IterateResult::Success(_) => Ok(()),
+        IterateResult::Quit(_) => Err("PulseAudio mainloop quit".to_string()),
+        IterateResult::Err(e) => Err(format!("PulseAudio mainloop failed: {}", e)),
+    }
+}
+
+pub fn list_sinks() -> Result<Vec<AudioSink>, String> {
+    Connection::open()?.sinks()
+}
+
+/// The requested sink, or the default one when none is given
+fn find_sink(connection: &mut Connection, sink_id: Option<&str>) -> Result<AudioSink, String> {
+    let sinks = connection.sinks()?;
+    let found = match sink_id {
+        Some(sink_id) => sinks.into_iter().find(|sink| sink.id == sink_id),
+        None => sinks.into_iter().find(|sink| sink.is_default),
+    };
+    found.ok_or(match sink_id {
+        Some(sink_id) => format!("No audio output named {}", sink_id),
+        None => "No default audio output available".to_string(),
+    })
+}
+
+fn open_record(monitor_source: &str) -> Result<psimple::Simple, String> {
+    let spec = Spec {
+        format: Format::S16le,
+        channels: 1,
+        rate: SAMPLE_RATE,
+    };
+    psimple::Simple::new(
+        None,
+        "pluely",
+        Direction::Record,
+        Some(monitor_source),
+        "System Audio Capture",
+        &spec,
+        None,
+        None,
+    )
+    .map_err(|e| format!("Failed to record {}: {}", monitor_source, e))
+}
+
+/// Record a sink's monitor until stopped. Without a sink id the default sink is
+/// followed when it changes, a chosen sink that goes away hands over to the default
+/// one and the recording ends with device_lost when no sink is left.
+pub fn capture<R: Runtime>(app: &AppHandle<R>, session_id: u64, stop: &AtomicBool, sink_id: Option<&str>) {
+    let mut emitter = ChunkEmitter::new(app.clone(), AudioSource::System, session_id, SAMPLE_RATE);
+    let mut buffer = vec![0u8; READ_BYTES];
+    let mut reason = FinishReason::Stopped;
+    let mut sink_id = sink_id;
+    // Sink recorded before a switch and when it last delivered audio
+    let mut previous: Option<(AudioSink, Instant)> = None;
+
+    let mut connection = match Connection::open() {
+        Ok(connection) => connection,
+        Err(e) => {
+            emit_audio_error(app, AudioSource::System, &e);
+            emitter.finish(FinishReason::Error);
+            return;
+        }
+    };
+
+    'sinks: while !stop.load(Ordering::Acquire) {
+        let sink = match find_sink(&mut connection, sink_id) {
+            Ok(sink) => sink,
+            Err(e) if sink_id.is_some() && previous.is_some() => {
+                eprintln!("{}, switching to the default output", e);
+                sink_id = None;
+                continue;
+            }
+            Err(e) if previous.is_some() => {
+                eprintln!("{}, finishing the recording", e);
+                reason = FinishReason::DeviceLost;
+                break;
+            }
+            Err(e) => {
+                emit_audio_error(app, AudioSource::System, &e);
+                reason = FinishReason::Error;
+                break;
+            }
+        };
+        let record = match open_record(&sink.monitor_source) {
+            Ok(record) => record,
+            Err(e) => {
+                emit_audio_error(app, AudioSource::System, &e);
+                reason = FinishReason::Error;
+                break;
+            }
+        };
+        if let Some((from, last_read)) = previous.take() {
+            if from.id != sink.id {
+                emit_device_switched(app, AudioSource::System, &from.name, &sink.name);
+            }
+            emitter.push_silence(last_read.elapsed());
+        }
+        let mut last_check = Instant::now();
+        let mut last_read = None;
+
+        while !stop.load(Ordering::Acquire) {
+            if emitter.limit_reached() {
+                reason = FinishReason::MaxDuration;
+                break 'sinks;
+            }
+            if let Err(e) = record.read(&mut buffer) {
+                // A sink that was delivering most likely went away, look again
+                let Some(last_read) = last_read else {
+                    emit_audio_error(app, AudioSource::System, &format!("System audio stream failed: {}", e));
+                    reason = FinishReason::Error;
+                    break 'sinks;
+                };
+                eprintln!("System audio stream failed, reopening: {}", e);
+                previous = Some((sink, last_read));
+                continue 'sinks;
+            }
+            last_read = Some(Instant::now());
+            let samples: Vec<f32> = buffer
+                .chunks_exact(2)
+                .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0)
+                .collect();
+            emitter.push(&samples);
+
+            if sink_id.is_none() && last_check.elapsed() >= DEVICE_CHECK_INTERVAL {
+                last_check = Instant::now();
+                match connection.default_sink() {
+                    Ok(Some(default)) if default != sink.id => {
+                        previous = Some((sink, Instant::now()));
+                        continue 'sinks;
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to check the default audio output: {}", e),
+                }
+            }
+        }
+    }
+
+    emitter.finish(reason);
+}
+
+/// Record a sink's monitor into a callback until stopped, for the rolling buffer
+pub fn monitor(stop: &AtomicBool, sink_id: Option<&str>, mut on_samples: impl FnMut(&[f32])) -> Result<(), String> {
+    let mut connection = Connection::open()?;
+    let sink = find_sink(&mut connection, sink_id)?;
+    let record = open_record(&sink.monitor_source)?;
+    let mut buffer = vec![0u8; READ_BYTES];
+
+    while !stop.load(Ordering::Acquire) {
+        record
+            .read(&mut buffer)
+            .map_err(|e| format!("System audio stream failed: {}", e))?;
+        let samples: Vec<f32> = buffer
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0)
+            .collect();
+        on_samples(&samples);
+    }
+    Ok(())
+}
@@ -0,0 +1,257 @@
+// Finished recordings kept on disk for notes, beside the temp files that are only
+// meant for upload. The folder is rotated to the newest keep_count recordings.
+use super::AudioSource;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager, Runtime};
+
+// Settings store keys, the same names RecordingsConfig uses
+const SAVE_ENABLED_SETTING: &str = "recordings_save_enabled";
+const DIR_SETTING: &str = "recordings_dir";
+const KEEP_COUNT_SETTING: &str = "recordings_keep_count";
+
+// Folder in the app data dir used when no recordings_dir is set
+const DEFAULT_DIR_NAME: &str = "recordings";
+
+pub const DEFAULT_KEEP_COUNT: u32 = 100;
+
+// Only files named the way save names them count as recordings, for listing,
+// pruning and deleting, so a shared folder's other files are never touched
+const EXTENSIONS: [&str; 2] = ["wav", "ogg"];
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingsConfig {
+    #[serde(rename = "recordings_save_enabled")]
+    pub save_enabled: bool,
+    #[serde(rename = "recordings_dir")]
+    pub dir: Option<PathBuf>,
+    #[serde(rename = "recordings_keep_count")]
+    pub keep_count: u32,
+}
+
+impl Default for RecordingsConfig {
+    fn default() -> Self {
+        RecordingsConfig {
+            save_enabled: false,
+            dir: None,
+            keep_count: DEFAULT_KEEP_COUNT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recording {
+    pub path: PathBuf,
+    pub name: String,
+    pub source: Option<AudioSource>,
+    pub bytes: u64,
+    pub modified_ms: u64,
+}
+
+fn recordings_config<R: Runtime>(app: &AppHandle<R>) -> RecordingsConfig {
+    let dir = crate::settings::string(app, DIR_SETTING, "");
+    RecordingsConfig {
+        save_enabled: crate::settings::bool(app, SAVE_ENABLED_SETTING, false),
+        dir: (!dir.is_empty()).then(|| PathBuf::from(dir)),
+        keep_count: crate::settings::u64(app, KEEP_COUNT_SETTING, DEFAULT_KEEP_COUNT as u64) as u32,
+    }
+}
+
+fn recordings_dir<R: Runtime>(app: &AppHandle<R>, config: &RecordingsConfig) -> Result<PathBuf, String> {
+    let dir = match &config.dir {
+        Some(dir) => dir.clone(),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data directory: {}", e))?
+            .join(DEFAULT_DIR_NAME),
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Source of a file named {timestamp}[_{n}]-{source}.{ext} like save writes,
+/// None for any other name
+fn parse_recording_name(name: &str) -> Option<AudioSource> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    if !EXTENSIONS.contains(&extension) {
+        return None;
+    }
+    let (prefix, source) = stem.rsplit_once('-')?;
+    let source = match source {
+        "mic" => AudioSource::Mic,
+        "system" => AudioSource::System,
+        "mixed" => AudioSource::Mixed,
+        _ => return None,
+    };
+    let (timestamp, suffix) = prefix.split_at_checked(19)?;
+    chrono::NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    let suffix_ok = suffix.is_empty()
+        || suffix
+            .strip_prefix('_')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    suffix_ok.then_some(source)
+}
+
+fn is_recording_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(parse_recording_name)
+            .is_some()
+}
+
+fn read_recordings(dir: &Path) -> Result<Vec<Recording>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut recordings: Vec<Recording> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if !is_recording_file(&path) {
+                return None;
+            }
+            let metadata = std::fs::metadata(&path).ok()?;
+            let name = path.file_name()?.to_string_lossy().to_string();
+            let source = parse_recording_name(&name);
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |since| since.as_millis() as u64);
+            Some(Recording {
+                path,
+                name,
+                source,
+                bytes: metadata.len(),
+                modified_ms,
+            })
+        })
+        .collect();
+    // Newest first
+    recordings.sort_by(|a, b| b.modified_ms.cmp(&a.modified_ms).then_with(|| b.name.cmp(&a.name)));
+    Ok(recordings)
+}
+
+/// Keep a copy of a finished recording when saving is on, pruning the oldest
+/// beyond the keep count. Returns where it was written.
+pub fn save<R: Runtime>(
+    app: &AppHandle<R>,
+    source: AudioSource,
+    bytes: &[u8],
+    extension: &str,
+) -> Result<Option<PathBuf>, String> {
+    let config = recordings_config(app);
+    if !config.save_enabled {
+        return Ok(None);
+    }
+    let dir = recordings_dir(app, &config)?;
+
+    let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT);
+    let mut path = dir.join(format!("{}-{}.{}", timestamp, source.name(), extension));
+    // Two captures finishing within a second, mic and system stopped together
+    let mut suffix = 2;
+    while path.exists() {
+        path = dir.join(format!("{}_{}-{}.{}", timestamp, suffix, source.name(), extension));
+        suffix += 1;
+    }
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    for old in read_recordings(&dir)?.into_iter().skip(config.keep_count as usize) {
+        if let Err(e) = std::fs::remove_file(&old.path) {
+            eprintln!("Failed to remove old recording {}: {}", old.path.display(), e);
+        }
+    }
+    Ok(Some(path))
+}
+
+fn check_keep_count(keep_count: u32) -> Result<(), String> {
+    if !(1..=10_000).contains(&keep_count) {
+        return Err(format!("Recordings to keep must be between 1 and 10000, got {}", keep_count));
+    }
+    Ok(())
+}
+
+fn check_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err(format!("Recordings folder must be an absolute path, got {}", dir.display()));
+    }
+    Ok(())
+}
+
+/// Check a value set_setting would store under one of the recordings keys
+pub fn validate_recordings_setting(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    match key {
+        SAVE_ENABLED_SETTING => crate::settings::parse::<bool>(key, value).map(drop),
+        DIR_SETTING => check_dir(&crate::settings::parse::<PathBuf>(key, value)?),
+        KEEP_COUNT_SETTING => check_keep_count(crate::settings::parse(key, value)?),
+        _ => Ok(()),
+    }
+}
+
+/// Tauri command to keep finished recordings in recordings_dir (the app data
+/// folder by default), newest recordings_keep_count only. Kept in the settings store.
+#[tauri::command]
+pub fn set_recordings_config<R: Runtime>(app: AppHandle<R>, config: RecordingsConfig) -> Result<(), String> {
+    check_keep_count(config.keep_count)?;
+    if let Some(dir) = &config.dir {
+        check_dir(dir)?;
+    }
+    // Null removes a setting, bringing back the default
+    crate::settings::set(&app, DIR_SETTING, json!(config.dir))?;
+    crate::settings::set(&app, KEEP_COUNT_SETTING, json!(config.keep_count))?;
+    crate::settings::set(&app, SAVE_ENABLED_SETTING, json!(config.save_enabled))
+}
+
+/// Tauri command to list saved recordings, newest first
+#[tauri::command]
+pub fn list_recordings<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Recording>, String> {
+    let config = recordings_config(&app);
+    read_recordings(&recordings_dir(&app, &config)?)
+}
+
+/// Tauri command to delete a saved recording. Only files save wrote directly in
+/// the recordings folder can be deleted, whatever path is passed in.
+#[tauri::command]
+pub fn delete_recording<R: Runtime>(app: AppHandle<R>, path: PathBuf) -> Result<(), String> {
+    let config = recordings_config(&app);
+    let dir = recordings_dir(&app, &config)?
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve the recordings folder: {}", e))?;
+    // Resolves .. and symlinks, so only the real location is compared
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to find recording {}: {}", path.display(), e))?;
+    if path.parent() != Some(dir.as_path()) || !is_recording_file(&path) {
+        return Err(format!("{} is not a saved recording", path.display()));
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_saved_names_are_recordings() {
+        assert_eq!(parse_recording_name("2024-05-01_09-30-00-mic.wav"), Some(AudioSource::Mic));
+        assert_eq!(parse_recording_name("2024-05-01_09-30-00_2-system.ogg"), Some(AudioSource::System));
+        assert_eq!(parse_recording_name("2024-05-01_09-30-00-mixed.wav"), Some(AudioSource::Mixed));
+
+        for name in [
+            "song.wav",
+            "my-mic.wav",
+            "2024-05-01_09-30-00-mic.mp3",
+            "2024-05-01_09-30-00-guitar.wav",
+            "2024-13-01_09-30-00-mic.wav",
+            "2024-05-01_09-30-00_-mic.wav",
+            "2024-05-01_09-30-00_x-mic.wav",
+            "2024-05-01_09-30-00 copy-mic.wav",
+        ] {
+            assert_eq!(parse_recording_name(name), None, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// Rolling buffer of recent system audio, so "what did they just say" can be
+// saved after the fact. Capture runs on its own thread into a ring allocated up
+// front at 16 kHz mono, nothing leaves it until capture_recent_audio.
+use super::{save_recording, AudioCaptureState, AudioSource, StreamResampler, DEFAULT_TARGET_RATE};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+// Rate the ring keeps, 30 s of it is under 1 MB
+const RING_RATE: u32 = DEFAULT_TARGET_RATE;
+
+pub const DEFAULT_SECONDS: u32 = 30;
+
+// How often the capture thread checks the stop flag while nothing plays
+#[cfg(not(target_os = "linux"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Samples gathered before they are resampled into the ring
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const BATCH_SAMPLES: usize = 1024;
+
+// Buffers queued between the ScreenCaptureKit queue and the capture thread
+#[cfg(target_os = "macos")]
+const CALLBACK_QUEUE: usize = 64;
+
+// Wait before retrying after the capture failed, e.g. while a device switches
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Fixed size ring of 16-bit samples, the oldest are overwritten
+struct Ring {
+    samples: Vec<i16>,
+    write: usize,
+    filled: usize,
+}
+
+impl Ring {
+    fn new(seconds: u32) -> Self {
+        Ring {
+            samples: vec![0; (RING_RATE * seconds) as usize],
+            write: 0,
+            filled: 0,
+        }
+    }
+
+    fn extend(&mut self, samples: &[f32]) {
+        let len = self.samples.len();
+        for sample in samples {
+            self.samples[self.write] = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.write = (self.write + 1) % len;
+        }
+        self.filled = (self.filled + samples.len()).min(len);
+    }
+
+    /// Buffered samples, oldest first
+    fn snapshot(&self) -> Vec<f32> {
+        let len = self.samples.len();
+        let start = (self.write + len - self.filled) % len;
+        (0..self.filled)
+            .map(|offset| self.samples[(start + offset) % len] as f32 / 32768.0)
+            .collect()
+    }
+}
+
+// The running capture thread, ended through the stop flag
+struct Rolling {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct RollingState {
+    ring: Mutex<Option<Ring>>,
+    running: Mutex<Option<Rolling>>,
+}
+
+pub fn is_rolling_buffer_running<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<AudioCaptureState>();
+    let running = match state.rolling.running.lock() {
+        Ok(guard) => guard.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    };
+    running
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+async fn capture<R: Runtime>(
+    _app: &AppHandle<R>,
+    stop: &AtomicBool,
+    mut on_samples: impl FnMut(u32, &[f32]),
+) -> Result<(), String> {
+    use futures_util::StreamExt;
+
+    let input = crate::speaker::SpeakerInput::new().map_err(|e| format!("Failed to access system audio: {}", e))?;
+    let mut stream = Box::pin(input.stream());
+    let sample_rate = stream.sample_rate();
+    let mut batch = Vec::with_capacity(BATCH_SAMPLES);
+
+    while !stop.load(Ordering::Acquire) {
+        match tokio::time::timeout(POLL_INTERVAL, stream.next()).await {
+            Ok(Some(sample)) => {
+                batch.push(sample);
+                if batch.len() >= BATCH_SAMPLES {
+                    on_samples(sample_rate, &batch);
+                    batch.clear();
+                }
+            }
+            Ok(None) => return Err("System audio stream ended unexpectedly".to_string()),
+            Err(_) => {
+                on_samples(sample_rate, &batch);
+                batch.clear();
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+async fn capture<R: Runtime>(
+    _app: &AppHandle<R>,
+    stop: &AtomicBool,
+    mut on_samples: impl FnMut(u32, &[f32]),
+) -> Result<(), String> {
+    use super::screen_capture_kit::{ScreenCaptureAudio, SAMPLE_RATE};
+    use std::sync::mpsc::{self, RecvTimeoutError};
+
+    let (sender, receiver) = mpsc::sync_channel(CALLBACK_QUEUE);
+    let capture = ScreenCaptureAudio::start(sender)
+        .await
+        .map_err(|e| format!("Failed to access system audio: {}", e))?;
+
+    let mut result = Ok(());
+    while !stop.load(Ordering::Acquire) {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(samples) => on_samples(SAMPLE_RATE, &samples),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                result = Err("System audio stream ended unexpectedly".to_string());
+                break;
+            }
+        }
+    }
+    capture.stop().await;
+    result
+}
+
+#[cfg(target_os = "linux")]
+async fn capture<R: Runtime>(
+    app: &AppHandle<R>,
+    stop: &AtomicBool,
+    mut on_samples: impl FnMut(u32, &[f32]),
+) -> Result<(), String> {
+    let sink = {
+        let state = app.state::<AudioCaptureState>();
+        let sink = match state.system_sink.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        sink
+    };
+    super::pulse::monitor(stop, sink.as_deref(), |samples| {
+        on_samples(super::pulse::SAMPLE_RATE, samples)
+    })
+}
+
+fn run<R: Runtime>(app: AppHandle<R>, stop: Arc<AtomicBool>) {
+    let mut resampler: Option<(u32, StreamResampler)> = None;
+
+    while !stop.load(Ordering::Acquire) {
+        let result = tauri::async_runtime::block_on(capture(&app, &stop, |sample_rate, samples| {
+            if resampler.as_ref().is_none_or(|(rate, _)| *rate != sample_rate) {
+                match StreamResampler::new(sample_rate, RING_RATE) {
+                    Ok(created) => resampler = Some((sample_rate, created)),
+                    Err(e) => {
+                        eprintln!("Failed to resample the rolling buffer: {}", e);
+                        return;
+                    }
+                }
+            }
+            let Some((_, resampler)) = resampler.as_mut() else {
+                return;
+            };
+            resampler.push(samples);
+            let output = resampler.take_output();
+
+            let state = app.state::<AudioCaptureState>();
+            let mut ring = match state.rolling.ring.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            if let Some(ring) = ring.as_mut() {
+                ring.extend(&output);
+            }
+        }));
+
+        // Keep listening through device changes, the buffer is meant to be always on
+        if let Err(e) = result {
+            eprintln!("Rolling audio buffer capture failed, retrying: {}", e);
+            let mut waited = Duration::ZERO;
+            while waited < RETRY_DELAY && !stop.load(Ordering::Acquire) {
+                std::thread::sleep(Duration::from_millis(100));
+                waited += Duration::from_millis(100);
+            }
+        }
+    }
+}
+
+fn stop_rolling<R: Runtime>(app: &AppHandle<R>) {
+    let rolling = {
+        let state = app.state::<AudioCaptureState>();
+        let rolling = match state.rolling.running.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        rolling
+    };
+    let Some(rolling) = rolling else {
+        return;
+    };
+    rolling.stop.store(true, Ordering::Release);
+    if rolling.thread.join().is_err() {
+        eprintln!("Rolling audio buffer thread panicked");
+    }
+}
+
+fn start_rolling<R: Runtime>(app: &AppHandle<R>, seconds: u32) -> Result<(), String> {
+    let state = app.state::<AudioCaptureState>();
+    match state.rolling.ring.lock() {
+        Ok(mut guard) => *guard = Some(Ring::new(seconds)),
+        Err(poisoned) => *poisoned.into_inner() = Some(Ring::new(seconds)),
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = app.clone();
+    let flag = stop.clone();
+    let thread = std::thread::Builder::new()
+        .name("pluely-rolling-audio".to_string())
+        .spawn(move || run(handle, flag))
+        .map_err(|e| format!("Failed to start rolling audio buffer: {}", e))?;
+
+    match state.rolling.running.lock() {
+        Ok(mut guard) => *guard = Some(Rolling { stop, thread }),
+        Err(poisoned) => *poisoned.into_inner() = Some(Rolling { stop, thread }),
+    }
+    Ok(())
+}
+
+/// Encode what the rolling buffer holds and announce it with audio-ready
+pub fn capture_recent<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<AudioCaptureState>();
+    let samples = match state.rolling.ring.lock() {
+        Ok(guard) => guard.as_ref().map(Ring::snapshot),
+        Err(poisoned) => poisoned.into_inner().as_ref().map(Ring::snapshot),
+    }
+    .ok_or("The rolling audio buffer is off".to_string())?;
+    if samples.is_empty() {
+        return Err("The rolling audio buffer is still empty".to_string());
+    }
+
+    let session_id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    save_recording(app, AudioSource::System, session_id, &samples, RING_RATE, true)
+}
+
+/// Tauri command to keep the last 10 to 60 seconds (30 by default) of system
+/// audio in memory for capture_recent_audio
+#[tauri::command]
+pub async fn set_rolling_buffer<R: Runtime>(app: AppHandle<R>, enabled: bool, seconds: Option<u32>) -> Result<(), String> {
+    let seconds = seconds.unwrap_or(DEFAULT_SECONDS);
+    if !(10..=60).contains(&seconds) {
+        return Err(format!("Rolling buffer must be between 10 and 60 seconds, got {}", seconds));
+    }
+    #[cfg(target_os = "macos")]
+    if enabled {
+        super::screen_capture_kit::check_available().map_err(|e| e.to_string())?;
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        stop_rolling(&app);
+        let result = if enabled {
+            start_rolling(&app, seconds)
+        } else {
+            let state = app.state::<AudioCaptureState>();
+            match state.rolling.ring.lock() {
+                Ok(mut guard) => *guard = None,
+                Err(poisoned) => *poisoned.into_inner() = None,
+            }
+            Ok(())
+        };
+        crate::tray::refresh_idle_state(&app);
+        super::inhibit::update(&app);
+        result
+    })
+    .await
+    .map_err(|e| format!("Failed to update rolling buffer: {}", e))?
+}
+
+/// Tauri command to save the rolling buffer as a recording, emitted as audio-ready
+/// with retroactive set
+#[tauri::command]
+pub async fn capture_recent_audio<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || capture_recent(&app))
+        .await
+        .map_err(|e| format!("Failed to capture recent audio: {}", e))?
+}
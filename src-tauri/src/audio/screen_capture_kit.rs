@@ -0,0 +1,131 @@
+// macOS system audio through an audio-only ScreenCaptureKit stream (macOS 13+),
+// which needs no virtual driver but does need the screen recording permission
+use super::SystemAudioError;
+use crate::permissions::{self, PermissionKind};
+use cidre::{arc, cm, define_obj_type, dispatch, ns, objc, sc};
+use std::sync::mpsc::SyncSender;
+
+// Rate and layout asked from ScreenCaptureKit, one planar float buffer per frame
+pub const SAMPLE_RATE: u32 = 48_000;
+
+// First release with audio capture in ScreenCaptureKit
+const MIN_MACOS_MAJOR: u32 = 13;
+
+/// Major version from sw_vers, e.g. 14 for "14.5"
+fn macos_major_version() -> Option<u32> {
+    let output = std::process::Command::new("sw_vers").arg("-productVersion").output().ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().split('.').next()?.parse().ok()
+}
+
+/// Fail early with an error the frontend can explain instead of at stream start
+pub fn check_available() -> Result<(), SystemAudioError> {
+    match macos_major_version() {
+        Some(major) if major >= MIN_MACOS_MAJOR => {}
+        version => {
+            return Err(SystemAudioError::UnsupportedOsVersion {
+                message: format!(
+                    "System audio capture needs macOS {} or later{}",
+                    MIN_MACOS_MAJOR,
+                    version.map_or(String::new(), |major| format!(", this Mac runs macOS {}", major))
+                ),
+            })
+        }
+    }
+    if !permissions::is_granted(PermissionKind::Screen) {
+        return Err(SystemAudioError::PermissionDenied {
+            message: "Screen recording permission is required to capture system audio".to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub struct OutputInner {
+    sender: SyncSender<Vec<f32>>,
+}
+
+define_obj_type!(AudioOutput + sc::stream::OutputImpl, OutputInner, PLUELY_SC_AUDIO_OUTPUT);
+
+impl sc::stream::Output for AudioOutput {}
+
+#[objc::add_methods]
+impl sc::stream::OutputImpl for AudioOutput {
+    extern "C" fn impl_stream_did_output_sample_buf(
+        &mut self,
+        _cmd: Option<&objc::Sel>,
+        _stream: &sc::Stream,
+        sample_buf: &mut cm::SampleBuf,
+        kind: sc::OutputType,
+    ) {
+        if kind != sc::OutputType::Audio {
+            return;
+        }
+        let Ok(buf_list) = sample_buf.audio_buf_list::<1>() else {
+            return;
+        };
+        let buffer = &buf_list.list().buffers[0];
+        if buffer.data.is_null() || buffer.data_bytes_size == 0 {
+            return;
+        }
+        let samples = unsafe {
+            std::slice::from_raw_parts(
+                buffer.data as *const f32,
+                buffer.data_bytes_size as usize / std::mem::size_of::<f32>(),
+            )
+        };
+        // Dropped when the capture thread falls behind, never block the SCK queue
+        let _ = self.inner_mut().sender.try_send(samples.to_vec());
+    }
+}
+
+// A started stream, samples go to the sender it was started with
+pub struct ScreenCaptureAudio {
+    stream: arc::R<sc::Stream>,
+    _output: arc::R<AudioOutput>,
+    _queue: arc::R<dispatch::Queue>,
+}
+
+impl ScreenCaptureAudio {
+    /// Capture everything the Mac plays except Pluely itself, as mono f32 at SAMPLE_RATE
+    pub async fn start(sender: SyncSender<Vec<f32>>) -> Result<Self, String> {
+        let content = sc::ShareableContent::current()
+            .await
+            .map_err(|e| format!("Failed to read shareable content: {:?}", e))?;
+        let displays = content.displays();
+        let display = displays.first().ok_or("No display available for audio capture".to_string())?;
+
+        // Audio is captured system wide, the display only anchors the filter
+        let filter = sc::ContentFilter::with_display_excluding_windows(display, &ns::Array::new());
+        let mut cfg = sc::StreamCfg::new();
+        cfg.set_captures_audio(true);
+        cfg.set_excludes_current_process_audio(true);
+        cfg.set_sample_rate(SAMPLE_RATE as _);
+        cfg.set_channel_count(1);
+        // Video frames can't be turned off, keep them tiny and rare
+        cfg.set_width(2);
+        cfg.set_height(2);
+        cfg.set_minimum_frame_interval(cm::Time::new(1, 1));
+
+        let stream = sc::Stream::new(&filter, &cfg);
+        let output = AudioOutput::with(OutputInner { sender });
+        let queue = dispatch::Queue::serial_with_ar_pool();
+        stream
+            .add_stream_output(output.as_ref(), sc::OutputType::Audio, Some(&queue))
+            .map_err(|e| format!("Failed to attach audio output: {:?}", e))?;
+        stream
+            .start()
+            .await
+            .map_err(|e| format!("Failed to start ScreenCaptureKit stream: {:?}", e))?;
+
+        Ok(ScreenCaptureAudio {
+            stream,
+            _output: output,
+            _queue: queue,
+        })
+    }
+
+    pub async fn stop(self) {
+        if let Err(e) = self.stream.stop().await {
+            eprintln!("Failed to stop ScreenCaptureKit stream: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+// Live transcription over a WebSocket straight from the capture pipeline, so
+// chunks don't round-trip through the webview. Audio is sent as 16 kHz mono,
+// raw s16le or one Opus packet per message, and whatever the server sends back
+// is forwarded as stt-message. Drops reconnect with backoff while audio queues.
+use super::{AudioCaptureState, AudioSource, StreamResampler};
+use audiopus::coder::Encoder;
+use audiopus::{Application, Channels, SampleRate};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::{mpsc, watch};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+
+// Rate sent to the endpoint, what realtime STT APIs expect
+const STREAM_RATE: u32 = 16_000;
+
+// Opus packets carry 20 ms
+const OPUS_FRAME_SAMPLES: usize = (STREAM_RATE / 50) as usize;
+
+const OPUS_BITRATE: i32 = 24_000;
+
+// Chunks queued from the capture threads
+const AUDIO_QUEUE: usize = 256;
+
+pub const DEFAULT_BUFFER_SECONDS: u32 = 10;
+
+// Reconnect delays double from the first up to the last, giving up after MAX_RETRIES
+const BACKOFF_START: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(15);
+const MAX_RETRIES: u32 = 8;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Placeholder in header values replaced with the stored STT key
+const API_KEY_PLACEHOLDER: &str = "{stt_api_key}";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SttFormat {
+    #[default]
+    Pcm16,
+    Opus,
+}
+
+struct SttSamples {
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+// The running connection task, ended through the stop channel
+struct SttStream {
+    source: AudioSource,
+    sender: mpsc::Sender<SttSamples>,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct SttState {
+    running: Mutex<Option<SttStream>>,
+}
+
+/// Hand a chunk from the capture pipeline to the transcription stream, if one
+/// is running for this source
+pub fn forward<R: Runtime>(app: &AppHandle<R>, source: AudioSource, sample_rate: u32, samples: &[i16]) {
+    let state = app.state::<AudioCaptureState>();
+    let sender = match state.stt.running.lock() {
+        Ok(guard) => guard.as_ref().filter(|stream| stream.source == source).map(|stream| stream.sender.clone()),
+        Err(poisoned) => poisoned
+            .into_inner()
+            .as_ref()
+            .filter(|stream| stream.source == source)
+            .map(|stream| stream.sender.clone()),
+    };
+    let Some(sender) = sender else {
+        return;
+    };
+    let samples = SttSamples {
+        sample_rate,
+        samples: samples.iter().map(|sample| *sample as f32 / 32768.0).collect(),
+    };
+    if sender.try_send(samples).is_err() {
+        eprintln!("Streaming transcription is behind, dropping audio");
+    }
+}
+
+// Resamples to the stream rate and splits into the messages sent
+struct SttEncoder {
+    format: SttFormat,
+    resampler: Option<(u32, StreamResampler)>,
+    opus: Option<Encoder>,
+    pending: Vec<f32>,
+}
+
+impl SttEncoder {
+    fn new(format: SttFormat) -> Result<Self, String> {
+        let opus = match format {
+            SttFormat::Pcm16 => None,
+            SttFormat::Opus => {
+                let mut encoder = Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Voip)
+                    .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+                encoder
+                    .set_bitrate(audiopus::Bitrate::BitsPerSecond(OPUS_BITRATE))
+                    .map_err(|e| format!("Failed to set Opus bitrate: {}", e))?;
+                Some(encoder)
+            }
+        };
+        Ok(SttEncoder {
+            format,
+            resampler: None,
+            opus,
+            pending: Vec::new(),
+        })
+    }
+
+    fn encode(&mut self, chunk: SttSamples) -> Vec<Vec<u8>> {
+        if self.resampler.as_ref().is_none_or(|(rate, _)| *rate != chunk.sample_rate) {
+            match StreamResampler::new(chunk.sample_rate, STREAM_RATE) {
+                Ok(resampler) => self.resampler = Some((chunk.sample_rate, resampler)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return Vec::new();
+                }
+            }
+        }
+        let Some((_, resampler)) = self.resampler.as_mut() else {
+            return Vec::new();
+        };
+        resampler.push(&chunk.samples);
+        self.pending.extend(resampler.take_output());
+
+        match (self.format, self.opus.as_mut()) {
+            (SttFormat::Opus, Some(encoder)) => {
+                let mut packets = Vec::new();
+                let mut packet = vec![0u8; 4000];
+                let frames = self.pending.len() / OPUS_FRAME_SAMPLES;
+                for frame in self.pending.chunks_exact(OPUS_FRAME_SAMPLES) {
+                    match encoder.encode_float(frame, &mut packet) {
+                        Ok(len) => packets.push(packet[..len].to_vec()),
+                        Err(e) => eprintln!("Failed to encode Opus: {}", e),
+                    }
+                }
+                self.pending.drain(..frames * OPUS_FRAME_SAMPLES);
+                packets
+            }
+            _ => {
+                let bytes = self
+                    .pending
+                    .drain(..)
+                    .flat_map(|sample| ((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+                    .collect::<Vec<u8>>();
+                if bytes.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![bytes]
+                }
+            }
+        }
+    }
+}
+
+// Encoded audio waiting to be sent, oldest dropped past the limit
+struct Backlog {
+    messages: VecDeque<Vec<u8>>,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl Backlog {
+    fn new(format: SttFormat, seconds: u32) -> Self {
+        // Opus at 24 kbps is 3 kB/s, PCM at 16 kHz is 32 kB/s
+        let per_second = match format {
+            SttFormat::Pcm16 => STREAM_RATE as usize * 2,
+            SttFormat::Opus => OPUS_BITRATE as usize / 8,
+        };
+        Backlog {
+            messages: VecDeque::new(),
+            bytes: 0,
+            max_bytes: per_second * seconds as usize,
+        }
+    }
+
+    fn push(&mut self, message: Vec<u8>) {
+        self.bytes += message.len();
+        self.messages.push_back(message);
+        while self.bytes > self.max_bytes {
+            let Some(dropped) = self.messages.pop_front() else {
+                break;
+            };
+            self.bytes -= dropped.len();
+        }
+    }
+
+    fn pop(&mut self) -> Option<Vec<u8>> {
+        let message = self.messages.pop_front()?;
+        self.bytes -= message.len();
+        Some(message)
+    }
+
+    fn unpop(&mut self, message: Vec<u8>) {
+        self.bytes += message.len();
+        self.messages.push_front(message);
+    }
+}
+
+fn emit_state<R: Runtime>(app: &AppHandle<R>, state: &str, message: Option<&str>) {
+    let _ = app.emit("stt-state", json!({ "state": state, "message": message }));
+}
+
+/// Header values with the stored key filled in. Credentials have to come from
+/// the key store, a literal Authorization or key header is refused.
+async fn resolve_headers<R: Runtime>(
+    app: &AppHandle<R>,
+    headers: HashMap<String, String>,
+) -> Result<Vec<(HeaderName, HeaderValue)>, String> {
+    let api_key = if headers.values().any(|value| value.contains(API_KEY_PLACEHOLDER)) {
+        crate::api_keys::stored_key(app, crate::api_keys::STT_PROVIDER)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or(format!(
+                "No speech-to-text API key saved, store one with set_api_key for provider {} first",
+                crate::api_keys::STT_PROVIDER
+            ))?
+    } else {
+        String::new()
+    };
+
+    let mut resolved = Vec::with_capacity(headers.len());
+    for (name, value) in headers {
+        let lower = name.to_ascii_lowercase();
+        let sensitive = lower == "authorization" || lower.contains("key") || lower.contains("token");
+        if sensitive && !value.contains(API_KEY_PLACEHOLDER) {
+            return Err(format!(
+                "Header {} must use {} so the key comes from the credential store",
+                name, API_KEY_PLACEHOLDER
+            ));
+        }
+        let value = value.replace(API_KEY_PLACEHOLDER, &api_key);
+
+        let header_name =
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid header name {}", name))?;
+        let mut header_value = HeaderValue::from_str(&value).map_err(|_| format!("Invalid value for header {}", name))?;
+        header_value.set_sensitive(sensitive);
+        resolved.push((header_name, header_value));
+    }
+    Ok(resolved)
+}
+
+async fn run<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    mut encoder: SttEncoder,
+    mut backlog: Backlog,
+    mut receiver: mpsc::Receiver<SttSamples>,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut retries = 0;
+    let mut delay = BACKOFF_START;
+
+    'connect: loop {
+        let mut request = match url.as_str().into_client_request() {
+            Ok(request) => request,
+            Err(e) => {
+                emit_state(&app, "error", Some(&format!("Invalid transcription URL: {}", e)));
+                return;
+            }
+        };
+        for (name, value) in &headers {
+            request.headers_mut().insert(name.clone(), value.clone());
+        }
+
+        let connected = tokio::time::timeout(CONNECT_TIMEOUT, tokio_tungstenite::connect_async(request)).await;
+        let error = match connected {
+            Ok(Ok((socket, _))) => {
+                retries = 0;
+                delay = BACKOFF_START;
+                emit_state(&app, "connected", None);
+                let (mut write, mut read) = socket.split();
+
+                loop {
+                    // Drain what queued while connecting first
+                    while let Some(message) = backlog.pop() {
+                        if let Err(e) = write.send(Message::Binary(message.clone())).await {
+                            backlog.unpop(message);
+                            emit_state(&app, "reconnecting", Some(&e.to_string()));
+                            continue 'connect;
+                        }
+                    }
+
+                    tokio::select! {
+                        _ = stop.changed() => {
+                            // Close cleanly and give the server a moment to send its last results
+                            let _ = write.send(Message::Close(None)).await;
+                            let _ = tokio::time::timeout(CLOSE_TIMEOUT, async {
+                                while let Some(Ok(message)) = read.next().await {
+                                    if let Message::Text(text) = message {
+                                        emit_message(&app, &text);
+                                    }
+                                }
+                            })
+                            .await;
+                            emit_state(&app, "closed", None);
+                            return;
+                        }
+                        chunk = receiver.recv() => {
+                            let Some(chunk) = chunk else {
+                                emit_state(&app, "closed", None);
+                                return;
+                            };
+                            for message in encoder.encode(chunk) {
+                                backlog.push(message);
+                            }
+                        }
+                        message = read.next() => match message {
+                            Some(Ok(Message::Text(text))) => emit_message(&app, &text),
+                            Some(Ok(Message::Close(frame))) => {
+                                break frame.map_or("Server closed the connection".to_string(), |frame| {
+                                    format!("Server closed the connection: {}", frame.reason)
+                                });
+                            }
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => break e.to_string(),
+                            None => break "Connection ended".to_string(),
+                        },
+                    }
+                }
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "Timed out connecting".to_string(),
+        };
+
+        retries += 1;
+        if retries > MAX_RETRIES {
+            emit_state(&app, "error", Some(&format!("Streaming transcription failed: {}", error)));
+            return;
+        }
+        emit_state(&app, "reconnecting", Some(&error));
+
+        // Keep queueing audio during the backoff, the backlog bounds it
+        let retry_at = tokio::time::Instant::now() + delay;
+        delay = (delay * 2).min(BACKOFF_MAX);
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(retry_at) => break,
+                _ = stop.changed() => {
+                    emit_state(&app, "closed", None);
+                    return;
+                }
+                chunk = receiver.recv() => {
+                    let Some(chunk) = chunk else {
+                        return;
+                    };
+                    for message in encoder.encode(chunk) {
+                        backlog.push(message);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn emit_message<R: Runtime>(app: &AppHandle<R>, text: &str) {
+    // Servers answer in JSON, anything else is passed on as a string
+    let message = serde_json::from_str::<serde_json::Value>(text).unwrap_or_else(|_| json!(text));
+    let _ = app.emit("stt-message", message);
+}
+
+async fn stop_stream<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let stream = {
+        let state = app.state::<AudioCaptureState>();
+        let stream = match state.stt.running.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        stream
+    };
+    let Some(stream) = stream else {
+        return false;
+    };
+    let _ = stream.stop.send(true);
+    drop(stream.sender);
+    if let Err(e) = stream.task.await {
+        eprintln!("Streaming transcription task failed: {}", e);
+    }
+    true
+}
+
+/// Tauri command to stream native capture of source (mic by default) to a
+/// WebSocket STT endpoint, with server messages emitted as stt-message. Header
+/// values take the stored key through {stt_api_key}. Up to buffer_seconds of
+/// audio is kept while reconnecting.
+#[tauri::command]
+pub async fn start_streaming_transcription<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    headers: Option<HashMap<String, String>>,
+    format: Option<SttFormat>,
+    source: Option<AudioSource>,
+    buffer_seconds: Option<u32>,
+) -> Result<(), String> {
+    if !url.starts_with("wss://") && !url.starts_with("ws://") {
+        return Err("Transcription URL must start with ws:// or wss://".to_string());
+    }
+    let buffer_seconds = buffer_seconds.unwrap_or(DEFAULT_BUFFER_SECONDS);
+    if !(1..=120).contains(&buffer_seconds) {
+        return Err(format!("Buffer must be between 1 and 120 seconds, got {}", buffer_seconds));
+    }
+    let format = format.unwrap_or_default();
+    let headers = resolve_headers(&app, headers.unwrap_or_default()).await?;
+    let encoder = SttEncoder::new(format)?;
+
+    stop_stream(&app).await;
+
+    let (sender, receiver) = mpsc::channel(AUDIO_QUEUE);
+    let (stop, stop_receiver) = watch::channel(false);
+    let handle = app.clone();
+    let task = tauri::async_runtime::spawn(run(
+        handle,
+        url,
+        headers,
+        encoder,
+        Backlog::new(format, buffer_seconds),
+        receiver,
+        stop_receiver,
+    ));
+
+    let state = app.state::<AudioCaptureState>();
+    let stream = SttStream {
+        source: source.unwrap_or(AudioSource::Mic),
+        sender,
+        stop,
+        task,
+    };
+    match state.stt.running.lock() {
+        Ok(mut guard) => *guard = Some(stream),
+        Err(poisoned) => *poisoned.into_inner() = Some(stream),
+    }
+    Ok(())
+}
+
+/// Tauri command to close the transcription WebSocket, waits for the server's
+/// last messages for a moment
+#[tauri::command]
+pub async fn stop_streaming_transcription<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    if !stop_stream(&app).await {
+        return Err("No streaming transcription is running".to_string());
+    }
+    Ok(())
+}
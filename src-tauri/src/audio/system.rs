@@ -0,0 +1,269 @@
+// System audio (speaker output) capture, WASAPI loopback through the speaker
+// module on Windows, ScreenCaptureKit on macOS and a sink monitor on Linux
+use super::{begin_session, end_session, stop_session, AudioCaptureState, AudioSource};
+#[cfg(not(target_os = "linux"))]
+use super::{emit_audio_error, ChunkEmitter, FinishReason};
+use serde::Serialize;
+use std::sync::atomic::AtomicBool;
+#[cfg(not(target_os = "linux"))]
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime};
+
+// How often the capture thread checks the stop flag while nothing plays
+#[cfg(not(target_os = "linux"))]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+// Samples gathered before handing them to the chunk emitter
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const BATCH_SAMPLES: usize = 1024;
+
+// Buffers queued between the ScreenCaptureKit queue and the capture thread
+#[cfg(target_os = "macos")]
+const CALLBACK_QUEUE: usize = 64;
+
+// Start failures the frontend tells apart, serialized as { kind, message }
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SystemAudioError {
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    UnsupportedOsVersion { message: String },
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    PermissionDenied { message: String },
+    Failed { message: String },
+}
+
+impl std::fmt::Display for SystemAudioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SystemAudioError::UnsupportedOsVersion { message }
+            | SystemAudioError::PermissionDenied { message }
+            | SystemAudioError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for SystemAudioError {
+    fn from(message: String) -> Self {
+        SystemAudioError::Failed { message }
+    }
+}
+
+// An output device whose playback can be captured
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioSink {
+    pub id: String,
+    pub name: String,
+    pub monitor_source: String,
+    pub is_default: bool,
+}
+
+fn is_speaker_capture_running<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<crate::AudioState>();
+    let running = match state.stream_task.lock() {
+        Ok(guard) => guard.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    };
+    running
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+async fn run_capture<R: Runtime>(app: &AppHandle<R>, session_id: u64, stop: Arc<AtomicBool>) {
+    use futures_util::StreamExt;
+
+    let stream = match crate::speaker::SpeakerInput::new() {
+        Ok(input) => input.stream(),
+        Err(e) => {
+            emit_audio_error(app, AudioSource::System, &format!("Failed to access system audio: {}", e));
+            return;
+        }
+    };
+    let mut stream = Box::pin(stream);
+    let mut emitter = ChunkEmitter::new(app.clone(), AudioSource::System, session_id, stream.sample_rate());
+    let mut batch = Vec::with_capacity(BATCH_SAMPLES);
+    let mut reason = FinishReason::Stopped;
+    let mut last_sample = std::time::Instant::now();
+    let mut delivered = false;
+    let mut reopened = false;
+
+    while !stop.load(Ordering::Acquire) {
+        if emitter.limit_reached() {
+            reason = FinishReason::MaxDuration;
+            break;
+        }
+        match tokio::time::timeout(POLL_INTERVAL, stream.next()).await {
+            Ok(Some(sample)) => {
+                batch.push(sample);
+                last_sample = std::time::Instant::now();
+                delivered = true;
+                if batch.len() >= BATCH_SAMPLES {
+                    emitter.push(&batch);
+                    batch.clear();
+                }
+            }
+            // The stream follows default output changes itself and only ends when
+            // loopback failed, which is what an unplugged last output looks like
+            Ok(None) if !delivered && reopened => {
+                eprintln!("No audio output left, finishing the recording");
+                reason = FinishReason::DeviceLost;
+                break;
+            }
+            Ok(None) if !delivered => {
+                emit_audio_error(app, AudioSource::System, "System audio stream ended unexpectedly");
+                reason = FinishReason::Error;
+                break;
+            }
+            Ok(None) => {
+                eprintln!("System audio stream ended, reopening on the default output");
+                emitter.push(&batch);
+                batch.clear();
+                match crate::speaker::SpeakerInput::new() {
+                    Ok(input) => stream = Box::pin(input.stream()),
+                    Err(e) => {
+                        emit_audio_error(app, AudioSource::System, &format!("Failed to access system audio: {}", e));
+                        reason = FinishReason::Error;
+                        break;
+                    }
+                }
+                emitter.push_silence(last_sample.elapsed());
+                delivered = false;
+                reopened = true;
+            }
+            // Nothing playing, send what we have so chunks keep flowing
+            Err(_) => {
+                emitter.push(&batch);
+                batch.clear();
+            }
+        }
+    }
+
+    emitter.push(&batch);
+    emitter.finish(reason);
+}
+
+#[cfg(target_os = "macos")]
+async fn run_capture<R: Runtime>(app: &AppHandle<R>, session_id: u64, stop: Arc<AtomicBool>) {
+    use super::screen_capture_kit::{ScreenCaptureAudio, SAMPLE_RATE};
+    use std::sync::mpsc::{self, RecvTimeoutError};
+
+    // ScreenCaptureKit taps app audio before it reaches an output device, so
+    // plugging or unplugging outputs doesn't interrupt it
+    let (sender, receiver) = mpsc::sync_channel(CALLBACK_QUEUE);
+    let capture = match ScreenCaptureAudio::start(sender).await {
+        Ok(capture) => capture,
+        Err(e) => {
+            emit_audio_error(app, AudioSource::System, &format!("Failed to access system audio: {}", e));
+            return;
+        }
+    };
+    let mut emitter = ChunkEmitter::new(app.clone(), AudioSource::System, session_id, SAMPLE_RATE);
+    let mut reason = FinishReason::Stopped;
+
+    while !stop.load(Ordering::Acquire) {
+        if emitter.limit_reached() {
+            reason = FinishReason::MaxDuration;
+            break;
+        }
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(samples) => {
+                emitter.push(&samples);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                emit_audio_error(app, AudioSource::System, "System audio stream ended unexpectedly");
+                reason = FinishReason::Error;
+                break;
+            }
+        }
+    }
+
+    capture.stop().await;
+    while let Ok(samples) = receiver.try_recv() {
+        emitter.push(&samples);
+    }
+    emitter.finish(reason);
+}
+
+#[cfg(target_os = "linux")]
+async fn run_capture<R: Runtime>(app: &AppHandle<R>, session_id: u64, stop: Arc<AtomicBool>) {
+    let sink = selected_sink(app);
+    super::pulse::capture(app, session_id, &stop, sink.as_deref());
+}
+
+#[cfg(target_os = "linux")]
+fn selected_sink<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    let state = app.state::<AudioCaptureState>();
+    let sink = match state.system_sink.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    sink
+}
+
+/// Start capturing what the default output device plays
+pub fn start_system<R: Runtime>(app: &AppHandle<R>) -> Result<(), SystemAudioError> {
+    #[cfg(target_os = "macos")]
+    super::screen_capture_kit::check_available()?;
+
+    if is_speaker_capture_running(app) {
+        return Err("System audio is already being captured for speech detection"
+            .to_string()
+            .into());
+    }
+
+    begin_session(app, AudioSource::System, |id, stop| {
+        let handle = app.clone();
+        std::thread::Builder::new()
+            .name("pluely-system-audio".to_string())
+            .spawn(move || {
+                crate::tray::set_recording_state(&handle, crate::tray::RecordingKind::System);
+                tauri::async_runtime::block_on(run_capture(&handle, id, stop));
+                crate::tray::clear_recording_state(&handle, crate::tray::RecordingKind::System);
+                end_session(&handle, AudioSource::System, id);
+            })
+            .map_err(|e| format!("Failed to start system audio thread: {}", e))
+    })
+    .map_err(SystemAudioError::from)
+}
+
+/// Tauri command to start native system audio capture, streamed as audio-chunk
+/// events tagged "system". start_system_audio_capture is the speech-detection path.
+/// On macOS before 13 this fails with kind "unsupported_os_version".
+#[tauri::command]
+pub async fn start_system_audio_stream<R: Runtime>(app: AppHandle<R>) -> Result<(), SystemAudioError> {
+    tauri::async_runtime::spawn_blocking(move || start_system(&app))
+        .await
+        .map_err(|e| SystemAudioError::from(format!("Failed to start system audio: {}", e)))?
+}
+
+/// Tauri command to stop native system audio capture, emits audio-finished once flushed
+#[tauri::command]
+pub async fn stop_system_audio_stream<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || stop_session(&app, AudioSource::System))
+        .await
+        .map_err(|e| format!("Failed to stop system audio: {}", e))?;
+    Ok(())
+}
+
+/// Tauri command to list output devices whose playback can be captured. Only
+/// Linux can pick one, Windows and macOS capture the default output.
+#[tauri::command]
+pub fn list_audio_sinks() -> Result<Vec<AudioSink>, String> {
+    #[cfg(target_os = "linux")]
+    return super::pulse::list_sinks();
+
+    #[cfg(not(target_os = "linux"))]
+    Ok(Vec::new())
+}
+
+/// Tauri command to capture a specific sink from list_audio_sinks, None follows the
+/// default one. Takes effect on the next start.
+#[tauri::command]
+pub fn set_system_audio_sink<R: Runtime>(app: AppHandle<R>, sink: Option<String>) -> Result<(), String> {
+    let state = app.state::<AudioCaptureState>();
+    *state
+        .system_sink
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = sink.filter(|sink| !sink.is_empty());
+    Ok(())
+}
@@ -0,0 +1,99 @@
+// Energy based voice activity detection that ends a recording once the speaker
+// has been quiet for a while. The noise floor adapts so a fan or a noisy room
+// doesn't count as speech.
+use serde::{Deserialize, Serialize};
+
+// Energy is measured over frames this long
+const FRAME_MS: u32 = 20;
+
+// Pauses before the first words are common, never stop this early
+const GRACE_MS: u64 = 1000;
+
+// How fast the noise floor follows quiet frames, and drifts up under speech
+const FLOOR_FALL: f32 = 0.1;
+const FLOOR_RISE: f32 = 0.002;
+
+// Starting floor until the first frames are measured
+const INITIAL_FLOOR_DB: f32 = -60.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    pub enabled: bool,
+    // dB above the noise floor that counts as speech
+    pub threshold_db: f32,
+    // Silence after speech before the recording stops
+    pub hangover_ms: u64,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        VadConfig {
+            enabled: false,
+            threshold_db: 10.0,
+            hangover_ms: 1500,
+        }
+    }
+}
+
+pub struct SilenceDetector {
+    config: VadConfig,
+    frame_samples: usize,
+    frame_ms: u64,
+    count: usize,
+    sum_squares: f32,
+    noise_floor_db: f32,
+    elapsed_ms: u64,
+    heard_speech: bool,
+    silent_ms: u64,
+}
+
+impl SilenceDetector {
+    pub fn new(config: VadConfig, sample_rate: u32) -> Self {
+        let frame_samples = (sample_rate * FRAME_MS / 1000).max(1) as usize;
+        SilenceDetector {
+            config,
+            frame_samples,
+            frame_ms: frame_samples as u64 * 1000 / sample_rate.max(1) as u64,
+            count: 0,
+            sum_squares: 0.0,
+            noise_floor_db: INITIAL_FLOOR_DB,
+            elapsed_ms: 0,
+            heard_speech: false,
+            silent_ms: 0,
+        }
+    }
+
+    /// Feed mono samples, true once speech was followed by the hangover of silence
+    pub fn push(&mut self, samples: &[f32]) -> bool {
+        let mut triggered = false;
+        for sample in samples {
+            self.sum_squares += sample * sample;
+            self.count += 1;
+            if self.count >= self.frame_samples {
+                triggered |= self.end_frame();
+            }
+        }
+        triggered
+    }
+
+    fn end_frame(&mut self) -> bool {
+        let rms = (self.sum_squares / self.count as f32).sqrt();
+        self.count = 0;
+        self.sum_squares = 0.0;
+        self.elapsed_ms += self.frame_ms;
+
+        let energy_db = if rms > 0.0 { 20.0 * rms.log10() } else { -100.0 };
+        let speech = energy_db > self.noise_floor_db + self.config.threshold_db;
+        let rate = if speech { FLOOR_RISE } else { FLOOR_FALL };
+        self.noise_floor_db += (energy_db - self.noise_floor_db) * rate;
+
+        if speech {
+            self.heard_speech = true;
+            self.silent_ms = 0;
+            return false;
+        }
+        self.silent_ms += self.frame_ms;
+        self.heard_speech && self.elapsed_ms >= GRACE_MS && self.silent_ms >= self.config.hangover_ms
+    }
+}
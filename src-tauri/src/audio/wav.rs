@@ -0,0 +1,334 @@
+// 16 kHz mono WAV for speech-to-text endpoints. Resampling is streamed so state
+// carries over between chunks and their boundaries don't click.
+use super::AudioCaptureState;
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use rubato::{FftFixedIn, Resampler};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+// Rate Whisper style endpoints expect
+pub const DEFAULT_TARGET_RATE: u32 = 16_000;
+
+// Input frames per resampler call
+const RESAMPLE_CHUNK: usize = 1024;
+
+// Folder in the app temp dir for encoded recordings
+const WAV_DIR_NAME: &str = "pluely-audio";
+
+/// Resamples mono f32 audio fed in pieces of any size
+pub struct StreamResampler {
+    resampler: Option<FftFixedIn<f32>>,
+    input: Vec<f32>,
+    output: Vec<f32>,
+    input_len: u64,
+    from_rate: u32,
+    to_rate: u32,
+}
+
+impl StreamResampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Result<Self, String> {
+        let resampler = if from_rate == to_rate {
+            None
+        } else {
+            Some(
+                FftFixedIn::new(from_rate as usize, to_rate as usize, RESAMPLE_CHUNK, 2, 1)
+                    .map_err(|e| format!("Failed to create resampler: {}", e))?,
+            )
+        };
+        Ok(StreamResampler {
+            resampler,
+            input: Vec::with_capacity(RESAMPLE_CHUNK * 2),
+            output: Vec::new(),
+            input_len: 0,
+            from_rate,
+            to_rate,
+        })
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        self.input_len += samples.len() as u64;
+        let Some(resampler) = &mut self.resampler else {
+            self.output.extend_from_slice(samples);
+            return;
+        };
+
+        self.input.extend_from_slice(samples);
+        let mut offset = 0;
+        while self.input.len() - offset >= resampler.input_frames_next() {
+            let frames = resampler.input_frames_next();
+            match resampler.process(&[&self.input[offset..offset + frames]], None) {
+                Ok(resampled) => self.output.extend_from_slice(&resampled[0]),
+                Err(e) => eprintln!("Failed to resample audio: {}", e),
+            }
+            offset += frames;
+        }
+        self.input.drain(..offset);
+    }
+
+    /// Take what was resampled so far, for streaming use that never calls finish
+    pub fn take_output(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Flush the buffered tail, without the resampler's leading delay
+    pub fn finish(mut self) -> Vec<f32> {
+        let mut delay = 0;
+        if let Some(resampler) = &mut self.resampler {
+            delay = resampler.output_delay();
+            if !self.input.is_empty() {
+                match resampler.process_partial(Some(&[&self.input[..]]), None) {
+                    Ok(resampled) => self.output.extend_from_slice(&resampled[0]),
+                    Err(e) => eprintln!("Failed to resample audio: {}", e),
+                }
+            }
+            match resampler.process_partial::<&[f32]>(None, None) {
+                Ok(resampled) => self.output.extend_from_slice(&resampled[0]),
+                Err(e) => eprintln!("Failed to resample audio: {}", e),
+            }
+        }
+
+        let expected = (self.input_len * self.to_rate as u64 / self.from_rate.max(1) as u64) as usize;
+        let mut output = self.output;
+        output.drain(..delay.min(output.len()));
+        output.truncate(expected);
+        output
+    }
+}
+
+/// 16-bit mono WAV file contents
+pub fn wav_bytes(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, spec).map_err(|e| format!("Failed to create WAV: {}", e))?;
+    for sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(|e| format!("Failed to write WAV: {}", e))?;
+    }
+    writer.finalize().map_err(|e| format!("Failed to finish WAV: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+pub fn read_wav(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader = hound::WavReader::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let sample_rate = reader.spec().sample_rate;
+    let samples = reader
+        .samples::<i16>()
+        .map(|sample| sample.map(|sample| sample as f32 / 32768.0))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok((samples, sample_rate))
+}
+
+pub fn duration_ms(samples: usize, sample_rate: u32) -> u64 {
+    samples as u64 * 1000 / sample_rate.max(1) as u64
+}
+
+/// Write a recording to the temp folder, returning its path
+pub fn write_temp_audio<R: Runtime>(app: &AppHandle<R>, file_name: &str, bytes: &[u8]) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .temp_dir()
+        .map_err(|e| format!("Failed to get temp directory: {}", e))?
+        .join(WAV_DIR_NAME);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn session_wav<R: Runtime>(app: &AppHandle<R>, session_id: u64) -> Option<PathBuf> {
+    let state = app.state::<AudioCaptureState>();
+    let wav_files = match state.wav_files.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    wav_files.get(&session_id).cloned()
+}
+
+// Either base64 audio-chunk payloads or a finished native session
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum WavSource {
+    Session { session_id: u64 },
+    Chunks { chunks: Vec<String>, sample_rate: u32 },
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WavOutput {
+    #[default]
+    Base64,
+    File,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodedWav {
+    pub data_base64: Option<String>,
+    pub wav_path: Option<String>,
+    pub duration_ms: u64,
+    pub bytes: usize,
+}
+
+fn encode<R: Runtime>(
+    app: &AppHandle<R>,
+    source: WavSource,
+    target_rate: u32,
+    output: WavOutput,
+) -> Result<EncodedWav, String> {
+    let (resampler, name) = match &source {
+        WavSource::Session { session_id } => {
+            let path = session_wav(app, *session_id)
+                .ok_or(format!("No WAV recorded for session {}, is automatic encoding on?", session_id))?;
+            let (samples, sample_rate) = read_wav(&path)?;
+            let mut resampler = StreamResampler::new(sample_rate, target_rate)?;
+            resampler.push(&samples);
+            (resampler, format!("session-{}-{}", session_id, target_rate))
+        }
+        WavSource::Chunks { chunks, sample_rate } => {
+            let mut resampler = StreamResampler::new(*sample_rate, target_rate)?;
+            for chunk in chunks {
+                let bytes = B64.decode(chunk).map_err(|e| format!("Failed to decode audio chunk: {}", e))?;
+                let samples: Vec<f32> = bytes
+                    .chunks_exact(2)
+                    .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32768.0)
+                    .collect();
+                resampler.push(&samples);
+            }
+            (resampler, format!("chunks-{}", uuid::Uuid::new_v4()))
+        }
+    };
+
+    let samples = resampler.finish();
+    let bytes = wav_bytes(&samples, target_rate)?;
+    let duration_ms = duration_ms(samples.len(), target_rate);
+
+    Ok(match output {
+        WavOutput::Base64 => EncodedWav {
+            data_base64: Some(B64.encode(&bytes)),
+            wav_path: None,
+            duration_ms,
+            bytes: bytes.len(),
+        },
+        WavOutput::File => {
+            let path = write_temp_audio(app, &format!("{}.wav", name), &bytes)?;
+            EncodedWav {
+                data_base64: None,
+                wav_path: Some(path.to_string_lossy().to_string()),
+                duration_ms,
+                bytes: bytes.len(),
+            }
+        }
+    })
+}
+
+/// Tauri command to turn audio-chunk payloads or a native session into mono WAV
+/// at target_rate (16 kHz by default), as base64 or a temp file
+#[tauri::command]
+pub async fn encode_wav<R: Runtime>(
+    app: AppHandle<R>,
+    source: WavSource,
+    target_rate: Option<u32>,
+    output: Option<WavOutput>,
+) -> Result<EncodedWav, String> {
+    let target_rate = target_rate.unwrap_or(DEFAULT_TARGET_RATE);
+    if !(8_000..=48_000).contains(&target_rate) {
+        return Err(format!("Target rate must be between 8000 and 48000 Hz, got {}", target_rate));
+    }
+    tauri::async_runtime::spawn_blocking(move || encode(&app, source, target_rate, output.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Failed to encode WAV: {}", e))?
+}
+
+/// Tauri command to write a WAV at target_rate when native capture stops,
+/// announced with audio-ready
+#[tauri::command]
+pub fn set_auto_wav_encoding<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    target_rate: Option<u32>,
+) -> Result<(), String> {
+    let target_rate = target_rate.unwrap_or(DEFAULT_TARGET_RATE);
+    if !(8_000..=48_000).contains(&target_rate) {
+        return Err(format!("Target rate must be between 8000 and 48000 Hz, got {}", target_rate));
+    }
+    let state = app.state::<AudioCaptureState>();
+    *state
+        .auto_wav
+        .lock()
+        .map_err(|e| format!("Failed to update audio setting: {}", e))? = enabled.then_some(target_rate);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    const TONE_HZ: f32 = 440.0;
+    const TONE_LEVEL: f32 = 0.5;
+
+    fn sine(sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| TONE_LEVEL * (TAU * TONE_HZ * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    /// Push a second of 48 kHz tone in uneven pieces, the way capture callbacks
+    /// deliver it, and decode the WAV written from the 16 kHz result
+    fn round_trip() -> Vec<f32> {
+        let input = sine(48_000, 48_000);
+        let mut resampler = StreamResampler::new(48_000, 16_000).unwrap();
+        let mut rest = &input[..];
+        for size in [480, 333, 1024, 7, 2049].iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (piece, tail) = rest.split_at((*size).min(rest.len()));
+            resampler.push(piece);
+            rest = tail;
+        }
+
+        let bytes = wav_bytes(&resampler.finish(), 16_000).unwrap();
+        let mut reader = hound::WavReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16_000);
+        reader
+            .samples::<i16>()
+            .map(|sample| sample.unwrap() as f32 / 32768.0)
+            .collect()
+    }
+
+    #[test]
+    fn resampled_tone_survives_the_wav_round_trip() {
+        let output = round_trip();
+        assert_eq!(output.len(), 16_000);
+
+        // The filter rings at the very ends. In between it must follow the tone
+        // generated at 16 kHz, up to the fraction of a sample of delay left after
+        // removing the resampler's whole-sample delay.
+        let expected = sine(16_000, output.len());
+        for i in 64..output.len() - 64 {
+            let error = (output[i] - expected[i]).abs();
+            assert!(error < 0.05, "off by {} at sample {}", error, i);
+        }
+    }
+
+    #[test]
+    fn resampled_tone_has_no_jumps_at_chunk_boundaries() {
+        let output = round_trip();
+
+        // A click where two pieces meet shows up as a step the tone can't make
+        let max_step = TONE_LEVEL * TAU * TONE_HZ / 16_000.0 * 1.2;
+        for (i, pair) in output[64..output.len() - 64].windows(2).enumerate() {
+            let step = (pair[1] - pair[0]).abs();
+            assert!(step <= max_step, "step of {} at sample {}", step, i + 64);
+        }
+    }
+}
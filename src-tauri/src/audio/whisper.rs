@@ -0,0 +1,285 @@
+// Offline speech-to-text with whisper.cpp, for audio that must not leave the
+// machine. Built only with the local-stt feature, without it the commands
+// exist but fail so the frontend can hide the option.
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+#[cfg(feature = "local-stt")]
+use tauri::{Emitter, Manager};
+
+#[cfg(not(feature = "local-stt"))]
+const NOT_BUILT: &str = "Pluely was not built with local STT";
+
+// Folder in the app data dir holding ggml models
+#[cfg(feature = "local-stt")]
+const MODEL_DIR_NAME: &str = "whisper-models";
+
+// Models published by whisper.cpp, downloaded as ggml-{name}.bin
+#[cfg(feature = "local-stt")]
+const KNOWN_MODELS: &[&str] = &[
+    "tiny",
+    "tiny.en",
+    "base",
+    "base.en",
+    "small",
+    "small.en",
+    "medium",
+    "medium.en",
+    "large-v3",
+    "large-v3-turbo",
+];
+
+#[cfg(feature = "local-stt")]
+const MODEL_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+// Whisper only takes 16 kHz mono
+#[cfg(feature = "local-stt")]
+const WHISPER_RATE: u32 = 16_000;
+
+// Least time between download progress events
+#[cfg(feature = "local-stt")]
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WhisperModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+#[cfg(feature = "local-stt")]
+fn model_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join(MODEL_DIR_NAME);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+#[cfg(feature = "local-stt")]
+fn model_path<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    if !KNOWN_MODELS.contains(&name) {
+        return Err(format!("Unknown Whisper model {}, expected one of {}", name, KNOWN_MODELS.join(", ")));
+    }
+    Ok(model_dir(app)?.join(format!("ggml-{}.bin", name)))
+}
+
+#[cfg(feature = "local-stt")]
+fn installed_models<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<WhisperModel>, String> {
+    let mut models = Vec::new();
+    for name in KNOWN_MODELS {
+        let path = model_path(app, name)?;
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            models.push(WhisperModel {
+                name: name.to_string(),
+                path,
+                bytes: metadata.len(),
+            });
+        }
+    }
+    Ok(models)
+}
+
+#[cfg(feature = "local-stt")]
+async fn download<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<WhisperModel, String> {
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use tokio::io::AsyncWriteExt;
+
+    let path = model_path(app, name)?;
+    // Written under another name first so an interrupted download never looks installed
+    let partial = path.with_extension("bin.part");
+
+    let response = reqwest::get(format!("{}/ggml-{}.bin", MODEL_URL, name))
+        .await
+        .map_err(|e| format!("Failed to download Whisper model {}: {}", name, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download Whisper model {}: {}", name, response.status()));
+    }
+    let total = response.content_length();
+
+    let mut file = tokio::fs::File::create(&partial)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+    let mut stream = response.bytes_stream();
+    let mut downloaded = 0u64;
+    let mut last_progress = std::time::Instant::now();
+
+    while let Some(chunk) = stream.next().await {
+        let bytes = chunk.map_err(|e| format!("Failed to download Whisper model {}: {}", name, e))?;
+        file.write_all(&bytes)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        downloaded += bytes.len() as u64;
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = std::time::Instant::now();
+            let _ = app.emit(
+                "whisper-model-progress",
+                json!({ "name": name, "downloaded": downloaded, "total": total }),
+            );
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    drop(file);
+
+    tokio::fs::rename(&partial, &path)
+        .await
+        .map_err(|e| format!("Failed to install Whisper model {}: {}", name, e))?;
+    let _ = app.emit(
+        "whisper-model-progress",
+        json!({ "name": name, "downloaded": downloaded, "total": Some(downloaded) }),
+    );
+    Ok(WhisperModel {
+        name: name.to_string(),
+        path,
+        bytes: downloaded,
+    })
+}
+
+#[cfg(feature = "local-stt")]
+fn transcribe<R: Runtime>(
+    app: &AppHandle<R>,
+    wav_path: &std::path::Path,
+    model: &str,
+    language: Option<String>,
+) -> Result<Transcript, String> {
+    use whisper_rs::{FullParams, SamplingStrategy, SegmentCallbackData, WhisperContext, WhisperContextParameters};
+
+    let path = model_path(app, model)?;
+    if !path.exists() {
+        return Err(format!("Whisper model {} is not installed", model));
+    }
+
+    let (samples, sample_rate) = super::read_wav(wav_path)?;
+    let samples = if sample_rate == WHISPER_RATE {
+        samples
+    } else {
+        let mut resampler = super::StreamResampler::new(sample_rate, WHISPER_RATE)?;
+        resampler.push(&samples);
+        resampler.finish()
+    };
+
+    let context = WhisperContext::new_with_params(&path.to_string_lossy(), WhisperContextParameters::default())
+        .map_err(|e| format!("Failed to load Whisper model {}: {}", model, e))?;
+    let mut state = context
+        .create_state()
+        .map_err(|e| format!("Failed to create Whisper state: {}", e))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    let language = language.filter(|language| !language.is_empty());
+    params.set_language(Some(language.as_deref().unwrap_or("auto")));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+
+    // Segments arrive while decoding runs, timestamps are in 10 ms units
+    let handle = app.clone();
+    params.set_segment_callback_safe(move |segment: SegmentCallbackData| {
+        let _ = handle.emit(
+            "transcription-segment",
+            TranscriptSegment {
+                start_ms: segment.start_timestamp.max(0) as u64 * 10,
+                end_ms: segment.end_timestamp.max(0) as u64 * 10,
+                text: segment.text.trim().to_string(),
+            },
+        );
+    });
+
+    state
+        .full(params, &samples)
+        .map_err(|e| format!("Failed to transcribe {}: {}", wav_path.display(), e))?;
+
+    let count = state
+        .full_n_segments()
+        .map_err(|e| format!("Failed to read transcript: {}", e))?;
+    let mut segments = Vec::with_capacity(count.max(0) as usize);
+    for index in 0..count {
+        let text = state
+            .full_get_segment_text(index)
+            .map_err(|e| format!("Failed to read transcript: {}", e))?;
+        let start = state
+            .full_get_segment_t0(index)
+            .map_err(|e| format!("Failed to read transcript: {}", e))?;
+        let end = state
+            .full_get_segment_t1(index)
+            .map_err(|e| format!("Failed to read transcript: {}", e))?;
+        segments.push(TranscriptSegment {
+            start_ms: start.max(0) as u64 * 10,
+            end_ms: end.max(0) as u64 * 10,
+            text: text.trim().to_string(),
+        });
+    }
+
+    let text = segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(Transcript { text, segments })
+}
+
+/// Tauri command to transcribe a WAV file on this machine with an installed model,
+/// emitting transcription-segment as segments are decoded. language defaults to auto.
+#[tauri::command]
+pub async fn transcribe_local<R: Runtime>(
+    app: AppHandle<R>,
+    wav_path: PathBuf,
+    model: String,
+    language: Option<String>,
+) -> Result<Transcript, String> {
+    #[cfg(feature = "local-stt")]
+    return tauri::async_runtime::spawn_blocking(move || transcribe(&app, &wav_path, &model, language))
+        .await
+        .map_err(|e| format!("Failed to transcribe audio: {}", e))?;
+
+    #[cfg(not(feature = "local-stt"))]
+    {
+        let _ = (app, wav_path, model, language);
+        Err(NOT_BUILT.to_string())
+    }
+}
+
+/// Tauri command to fetch a ggml model into the app data dir, with
+/// whisper-model-progress events while it downloads
+#[tauri::command]
+pub async fn download_whisper_model<R: Runtime>(app: AppHandle<R>, name: String) -> Result<WhisperModel, String> {
+    #[cfg(feature = "local-stt")]
+    return download(&app, &name).await;
+
+    #[cfg(not(feature = "local-stt"))]
+    {
+        let _ = (app, name);
+        Err(NOT_BUILT.to_string())
+    }
+}
+
+/// Tauri command to list the Whisper models that are installed
+#[tauri::command]
+pub fn list_whisper_models<R: Runtime>(app: AppHandle<R>) -> Result<Vec<WhisperModel>, String> {
+    #[cfg(feature = "local-stt")]
+    return installed_models(&app);
+
+    #[cfg(not(feature = "local-stt"))]
+    {
+        let _ = app;
+        Err(NOT_BUILT.to_string())
+    }
+}
@@ -0,0 +1,348 @@
+// Launch on login: a Launch Agent on macOS, the Run registry key on Windows and
+// an XDG autostart entry on Linux. The state is always read back from the OS
+// because users can change these entries outside the app.
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+// Command line flag to start without showing the window
+pub const HIDDEN_FLAG: &str = "--hidden";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    pub start_hidden: bool,
+}
+
+/// Whether the app was launched with the hidden flag
+pub fn is_start_hidden() -> bool {
+    std::env::args().any(|arg| arg == HIDDEN_FLAG)
+}
+
+fn current_exe() -> Result<String, String> {
+    std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to locate executable: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent_path<R: Runtime>(app: &AppHandle<R>) -> Result<std::path::PathBuf, String> {
+    let home = std::env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+    Ok(std::path::PathBuf::from(home)
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", app.config().identifier)))
+}
+
+#[cfg(target_os = "macos")]
+fn write_autostart<R: Runtime>(app: &AppHandle<R>, start_hidden: bool) -> Result<(), String> {
+    let path = launch_agent_path(app)?;
+    let mut arguments = format!("        <string>{}</string>\n", xml_escape(&current_exe()?));
+    if start_hidden {
+        arguments.push_str(&format!("        <string>{}</string>\n", HIDDEN_FLAG));
+    }
+
+    let plist = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        app.config().identifier,
+        arguments
+    );
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create LaunchAgents dir: {}", e))?;
+    }
+    std::fs::write(&path, plist).map_err(|e| format!("Failed to write launch agent: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn remove_autostart<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let path = launch_agent_path(app)?;
+    match std::fs::remove_file(&path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove launch agent: {}", e)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_autostart<R: Runtime>(app: &AppHandle<R>) -> Result<AutostartStatus, String> {
+    let path = launch_agent_path(app)?;
+    let plist = match std::fs::read_to_string(&path) {
+        Ok(plist) => plist,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(AutostartStatus {
+                enabled: false,
+                start_hidden: false,
+            })
+        }
+        Err(e) => return Err(format!("Failed to read launch agent: {}", e)),
+    };
+
+    // "<key>Disabled</key><true/>" turns the agent off without removing it
+    let compact: String = plist.split_whitespace().collect();
+    Ok(AutostartStatus {
+        enabled: !compact.contains("<key>Disabled</key><true/>"),
+        start_hidden: compact.contains(&format!("<string>{}</string>", HIDDEN_FLAG)),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+// Task Manager records disabled startup entries here instead of touching the Run key
+#[cfg(target_os = "windows")]
+const STARTUP_APPROVED_KEY: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\StartupApproved\\Run";
+
+#[cfg(target_os = "windows")]
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(target_os = "windows")]
+fn registry_value_name<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.config()
+        .product_name
+        .clone()
+        .unwrap_or_else(|| "Pluely".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn read_registry_value(
+    key: &str,
+    name: &str,
+    flags: windows::Win32::System::Registry::REG_ROUTINE_FLAGS,
+) -> Option<Vec<u8>> {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER};
+
+    let key = wide(key);
+    let name = wide(name);
+    let mut size = 0u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(name.as_ptr()),
+            flags,
+            None,
+            None,
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+
+    let mut data = vec![0u8; size as usize];
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(name.as_ptr()),
+            flags,
+            None,
+            Some(data.as_mut_ptr() as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+    data.truncate(size as usize);
+    Some(data)
+}
+
+#[cfg(target_os = "windows")]
+fn write_autostart<R: Runtime>(app: &AppHandle<R>, start_hidden: bool) -> Result<(), String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Registry::{RegDeleteKeyValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let mut command = format!("\"{}\"", current_exe()?);
+    if start_hidden {
+        command.push(' ');
+        command.push_str(HIDDEN_FLAG);
+    }
+
+    let key = wide(RUN_KEY);
+    let name = wide(&registry_value_name(app));
+    let value = wide(&command);
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(name.as_ptr()),
+            REG_SZ.0,
+            Some(value.as_ptr() as *const core::ffi::c_void),
+            (value.len() * 2) as u32,
+        )
+        .ok()
+        .map_err(|e| format!("Failed to write Run registry key: {}", e))?;
+
+        // Re-enabling from the app should also lift a Task Manager disable
+        let approved = wide(STARTUP_APPROVED_KEY);
+        let _ = RegDeleteKeyValueW(HKEY_CURRENT_USER, PCWSTR(approved.as_ptr()), PCWSTR(name.as_ptr()));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn remove_autostart<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{RegDeleteKeyValueW, HKEY_CURRENT_USER};
+
+    let key = wide(RUN_KEY);
+    let name = wide(&registry_value_name(app));
+    let result = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, PCWSTR(key.as_ptr()), PCWSTR(name.as_ptr())) };
+    if result.is_err() && result != ERROR_FILE_NOT_FOUND {
+        return Err(format!("Failed to remove Run registry key: {:?}", result));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn read_autostart<R: Runtime>(app: &AppHandle<R>) -> Result<AutostartStatus, String> {
+    use windows::Win32::System::Registry::{RRF_RT_REG_BINARY, RRF_RT_REG_SZ};
+
+    let name = registry_value_name(app);
+    let Some(command) = read_registry_value(RUN_KEY, &name, RRF_RT_REG_SZ) else {
+        return Ok(AutostartStatus {
+            enabled: false,
+            start_hidden: false,
+        });
+    };
+    let command: Vec<u16> = command
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    let command = String::from_utf16_lossy(&command);
+
+    // An odd first byte in StartupApproved means disabled in Task Manager
+    let disabled = read_registry_value(STARTUP_APPROVED_KEY, &name, RRF_RT_REG_BINARY)
+        .and_then(|data| data.first().copied())
+        .is_some_and(|flag| flag & 1 == 1);
+
+    Ok(AutostartStatus {
+        enabled: !disabled,
+        start_hidden: command.split_whitespace().any(|arg| arg == HIDDEN_FLAG),
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn desktop_entry_path<R: Runtime>(app: &AppHandle<R>) -> Result<std::path::PathBuf, String> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+            std::path::PathBuf::from(home).join(".config")
+        }
+    };
+    let name = app
+        .config()
+        .product_name
+        .clone()
+        .unwrap_or_else(|| "pluely".to_string())
+        .to_lowercase();
+    Ok(config_dir.join("autostart").join(format!("{}.desktop", name)))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn write_autostart<R: Runtime>(app: &AppHandle<R>, start_hidden: bool) -> Result<(), String> {
+    let path = desktop_entry_path(app)?;
+    // AppImages run from a temporary mount, the stable path is in $APPIMAGE
+    let exe = std::env::var("APPIMAGE").or_else(|_| current_exe())?;
+    let mut exec = format!("\"{}\"", exe.replace('"', "\\\""));
+    if start_hidden {
+        exec.push(' ');
+        exec.push_str(HIDDEN_FLAG);
+    }
+
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nX-GNOME-Autostart-enabled=true\n",
+        app.config().product_name.clone().unwrap_or_else(|| "Pluely".to_string()),
+        exec
+    );
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create autostart dir: {}", e))?;
+    }
+    std::fs::write(&path, entry).map_err(|e| format!("Failed to write autostart entry: {}", e))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn remove_autostart<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let path = desktop_entry_path(app)?;
+    match std::fs::remove_file(&path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove autostart entry: {}", e)),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn read_autostart<R: Runtime>(app: &AppHandle<R>) -> Result<AutostartStatus, String> {
+    let path = desktop_entry_path(app)?;
+    let entry = match std::fs::read_to_string(&path) {
+        Ok(entry) => entry,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(AutostartStatus {
+                enabled: false,
+                start_hidden: false,
+            })
+        }
+        Err(e) => return Err(format!("Failed to read autostart entry: {}", e)),
+    };
+
+    let value = |key: &str| {
+        entry
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.trim_start().strip_prefix('='))
+            .map(|value| value.trim().to_string())
+    };
+    // Desktop environments switch entries off with either key instead of deleting them
+    let disabled = value("Hidden").as_deref() == Some("true")
+        || value("X-GNOME-Autostart-enabled").as_deref() == Some("false");
+
+    Ok(AutostartStatus {
+        enabled: !disabled,
+        start_hidden: value("Exec").is_some_and(|exec| {
+            exec.split_whitespace().any(|arg| arg == HIDDEN_FLAG)
+        }),
+    })
+}
+
+/// Tauri command to enable or disable launching on login
+#[tauri::command]
+pub fn set_autostart<R: Runtime>(app: AppHandle<R>, enabled: bool, start_hidden: bool) -> Result<AutostartStatus, String> {
+    if enabled {
+        write_autostart(&app, start_hidden)?;
+    } else {
+        remove_autostart(&app)?;
+    }
+    read_autostart(&app)
+}
+
+/// Tauri command to read the launch-on-login entry as the OS currently has it
+#[tauri::command]
+pub fn get_autostart<R: Runtime>(app: AppHandle<R>) -> Result<AutostartStatus, String> {
+    read_autostart(&app)
+}
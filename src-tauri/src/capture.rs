@@ -0,0 +1,1406 @@
+// Native screen capture, so screenshots don't depend on browser capture prompts
+use base64::Engine;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{imageops, ColorType, DynamicImage, ImageEncoder, RgbaImage};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::active_app::WindowBounds;
+use crate::permissions::{self, PermissionKind};
+use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::oneshot;
+use xcap::Monitor;
+
+// Label of the region selection window, also used by its capability
+const REGION_WINDOW_LABEL: &str = "region-select";
+
+// How long to wait for the window to actually leave the screen before capturing
+const HIDE_TIMEOUT: Duration = Duration::from_millis(250);
+
+// Interval of screenshot-countdown events during a delayed capture
+const COUNTDOWN_TICK: Duration = Duration::from_secs(1);
+
+// Settings store keys of the monitor the screenshot shortcut captures and of
+// the default image options
+const MONITOR_SETTING: &str = "screenshot_monitor";
+const OUTPUT_SETTING: &str = "screenshot_output";
+
+// Settings store keys of the screenshot shortcut behaviour
+const NATIVE_SETTING: &str = "screenshot_native";
+const EXCLUDE_WINDOW_SETTING: &str = "screenshot_exclude_window";
+const MODE_SETTING: &str = "screenshot_mode";
+const COPY_TO_CLIPBOARD_SETTING: &str = "screenshot_copy_to_clipboard";
+const SHORTCUT_DELAY_SETTING: &str = "screenshot_shortcut_delay_ms";
+
+// Differing hash bits below which two shortcut captures count as the same frame
+const DEFAULT_DUPLICATE_THRESHOLD: u32 = 8;
+
+// Settings store keys of the duplicate filter switch and threshold
+const DUPLICATE_FILTER_SETTING: &str = "duplicate_screenshot_filter";
+const DUPLICATE_THRESHOLD_SETTING: &str = "duplicate_screenshot_threshold";
+
+// Grid of the difference hash, 32x32 bits
+const FRAME_HASH_SIZE: u32 = 32;
+
+// Extra delay after hiding so the compositor has drawn two frames without the window
+const HIDE_SETTLE_DELAY: Duration = Duration::from_millis(34);
+
+// What the screenshot shortcut captures when it captures natively
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreenshotMode {
+    Full,
+    Region,
+    ActiveWindow,
+    WindowUnderCursor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Webp, // lossless only, takes no quality
+}
+
+// Quality of JPEG captures when none is given
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+// Encoding of captured images, max_dimension caps the longer side
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ImageOptions {
+    #[serde(default = "default_image_format")]
+    pub format: ImageFormat,
+    #[serde(default)]
+    pub quality: Option<u8>, // JPEG only
+    #[serde(default)]
+    pub max_dimension: Option<u32>,
+}
+
+fn default_image_format() -> ImageFormat {
+    ImageFormat::Png
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            format: default_image_format(),
+            quality: None,
+            max_dimension: None,
+        }
+    }
+}
+
+impl ImageOptions {
+    /// Reject a quality the format can't honour instead of silently ignoring it
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format == ImageFormat::Webp && self.quality.is_some() {
+            return Err("WebP captures are lossless, quality only applies to JPEG".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Native capture state that only lasts for the session
+pub struct CaptureSettings {
+    pub portal_approved: Mutex<bool>, // the Wayland screenshot dialog was accepted once
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        CaptureSettings {
+            portal_approved: Mutex::new(false),
+        }
+    }
+}
+
+// "screenshot_monitor" setting: "cursor", "primary" or a monitor id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorTarget {
+    Cursor,
+    Primary,
+    #[serde(untagged)]
+    Id(u32),
+}
+
+// Display as listed for the monitor picker, sizes in physical pixels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorDetails {
+    pub id: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f32,
+    pub is_primary: bool,
+}
+
+// Difference hash of a downscaled grayscale frame, row by row
+type FrameHash = [u64; (FRAME_HASH_SIZE * FRAME_HASH_SIZE / 64) as usize];
+
+// Previous full-screen shortcut capture, to skip sending the same frame again
+struct LastFrame {
+    hash: FrameHash,
+    monitor: u32,
+    id: String,
+}
+
+#[derive(Default)]
+pub struct DuplicateFilter {
+    last: Mutex<Option<LastFrame>>,
+}
+
+// What the screenshot shortcut produced
+enum ShortcutCapture {
+    Captured(Box<ScreenCapture>, Option<FrameHash>),
+    Duplicate(String), // history id of the matching previous capture
+}
+
+// Region selection in progress, answered by finish_region_selection
+#[derive(Default)]
+pub struct RegionSelection {
+    pending: Mutex<Option<oneshot::Sender<Option<SelectionRect>>>>,
+}
+
+// Delayed capture counting down, answered by cancel_screenshot
+#[derive(Default)]
+pub struct ScreenshotCountdown {
+    cancel: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+// Selected rectangle as fractions of the selection window
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SelectionRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenCapture {
+    pub data: String, // base64 in the requested format
+    pub format: ImageFormat,
+    pub width: u32, // final size after downscaling
+    pub height: u32,
+    pub scale_factor: f32,
+    pub monitor: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>, // screenshot history id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>, // e.g. the requested monitor was unplugged
+    #[serde(skip)]
+    pub source: Option<Arc<RgbaImage>>, // full frame when data is downscaled or lossy, for redactions
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowCapture {
+    #[serde(flatten)]
+    pub capture: ScreenCapture,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>, // owning process, for windows picked by the cursor
+}
+
+// Result of a region capture, cancelling is not an error
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum RegionCapture {
+    Captured(ScreenCapture),
+    Cancelled,
+}
+
+// Capture failures the frontend tells apart, serialized as { kind, message }
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum CaptureError {
+    PermissionDenied { message: String },
+    MonitorNotFound { message: String },
+    Cancelled { message: String },
+    Failed { message: String },
+}
+
+impl CaptureError {
+    fn failed(message: String) -> Self {
+        CaptureError::Failed { message }
+    }
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::PermissionDenied { message }
+            | CaptureError::MonitorNotFound { message }
+            | CaptureError::Cancelled { message }
+            | CaptureError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Monitor by id, or the one containing the cursor, or the primary one. An id
+/// that is no longer connected falls back to the cursor monitor.
+fn find_monitor<R: Runtime>(app: &AppHandle<R>, monitor_id: Option<u32>) -> Result<Monitor, CaptureError> {
+    let monitors = Monitor::all().map_err(|e| CaptureError::failed(format!("Failed to get monitors: {}", e)))?;
+
+    if let Some(monitor_id) = monitor_id {
+        if let Some(index) = monitors.iter().position(|monitor| monitor.id() == monitor_id) {
+            return Ok(monitors.into_iter().nth(index).unwrap());
+        }
+        eprintln!("Monitor {} is not connected, using the cursor monitor", monitor_id);
+    }
+
+    if let Ok(cursor) = app.cursor_position() {
+        let (x, y) = (cursor.x as i32, cursor.y as i32);
+        if let Some(index) = monitors.iter().position(|monitor| {
+            let (left, top, width, height) = physical_bounds(monitor);
+            x >= left && x < left + width as i32 && y >= top && y < top + height as i32
+        }) {
+            return Ok(monitors.into_iter().nth(index).unwrap());
+        }
+    }
+
+    monitors
+        .into_iter()
+        .find(|monitor| monitor.is_primary())
+        .ok_or(CaptureError::MonitorNotFound {
+            message: "No primary monitor found".to_string(),
+        })
+}
+
+/// The monitor id the screenshot shortcut should capture, None for the cursor monitor
+fn resolve_target(target: MonitorTarget) -> Option<u32> {
+    match target {
+        MonitorTarget::Cursor => None,
+        MonitorTarget::Id(id) => Some(id),
+        MonitorTarget::Primary => Monitor::all()
+            .ok()?
+            .into_iter()
+            .find(|monitor| monitor.is_primary())
+            .map(|monitor| monitor.id()),
+    }
+}
+
+// Monitor a capture came from, bounds in physical pixels
+#[derive(Debug, Clone, Copy)]
+struct MonitorInfo {
+    id: u32,
+    scale_factor: f32,
+    bounds: (i32, i32, u32, u32),
+    missing: Option<u32>, // requested monitor that was gone, this one is the fallback
+}
+
+impl From<&Monitor> for MonitorInfo {
+    fn from(monitor: &Monitor) -> Self {
+        MonitorInfo {
+            id: monitor.id(),
+            scale_factor: monitor.scale_factor(),
+            bounds: physical_bounds(monitor),
+            missing: None,
+        }
+    }
+}
+
+/// Monitor bounds in physical pixels, xcap reports points on macOS
+fn physical_bounds(monitor: &Monitor) -> (i32, i32, u32, u32) {
+    #[cfg(target_os = "macos")]
+    {
+        let scale = monitor.scale_factor();
+        (
+            (monitor.x() as f32 * scale) as i32,
+            (monitor.y() as f32 * scale) as i32,
+            (monitor.width() as f32 * scale) as u32,
+            (monitor.height() as f32 * scale) as u32,
+        )
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    (monitor.x(), monitor.y(), monitor.width(), monitor.height())
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png_buffer = Vec::new();
+    PngEncoder::new(&mut png_buffer)
+        .write_image(image.as_raw(), image.width(), image.height(), ColorType::Rgba8.into())
+        .map_err(|e| format!("Failed to encode to PNG: {}", e))?;
+    Ok(png_buffer)
+}
+
+/// Capture the raw image of a monitor, blocking
+fn grab_monitor<R: Runtime>(app: &AppHandle<R>, monitor_id: Option<u32>) -> Result<(MonitorInfo, RgbaImage), CaptureError> {
+    if !permissions::is_granted(PermissionKind::Screen) {
+        return Err(CaptureError::PermissionDenied {
+            message: "Screen recording permission is required to take screenshots".to_string(),
+        });
+    }
+
+    // Compositors don't let clients read the screen, the portal decides what is captured
+    if crate::portal_shortcuts::is_wayland_session() {
+        let desktop = grab_portal(app)?;
+        let Ok(monitor) = find_monitor(app, monitor_id) else {
+            // Nothing to crop to, the whole desktop is all that is known
+            let info = MonitorInfo {
+                id: 0,
+                scale_factor: 1.0,
+                bounds: (0, 0, desktop.width(), desktop.height()),
+                missing: monitor_id,
+            };
+            return Ok((info, desktop));
+        };
+        let mut info = MonitorInfo::from(&monitor);
+        info.missing = monitor_id.filter(|id| *id != info.id);
+
+        let layout: Vec<_> = Monitor::all()
+            .map(|monitors| monitors.iter().map(physical_bounds).collect())
+            .unwrap_or_default();
+        let mut image = crop_to_monitor(&desktop, &layout, info.bounds).ok_or_else(|| {
+            CaptureError::failed(
+                "The portal screenshot doesn't match the monitor layout, can't capture one monitor".to_string(),
+            )
+        })?;
+        crate::redaction::redact_monitor(app, info.id, &mut image);
+        return Ok((info, image));
+    }
+
+    let monitor = find_monitor(app, monitor_id)?;
+    let mut image = monitor
+        .capture_image()
+        .map_err(|e| CaptureError::failed(format!("Failed to capture image: {}", e)))?;
+    let mut info = MonitorInfo::from(&monitor);
+    info.missing = monitor_id.filter(|id| *id != info.id);
+    crate::redaction::redact_monitor(app, info.id, &mut image);
+    Ok((info, image))
+}
+
+/// Cut one monitor out of a portal screenshot, which covers the whole desktop.
+/// Only possible when the image has the size of the monitor layout, scaled
+/// compositors may return something else. A single monitor is the whole image.
+fn crop_to_monitor(desktop: &RgbaImage, layout: &[(i32, i32, u32, u32)], bounds: (i32, i32, u32, u32)) -> Option<RgbaImage> {
+    if layout.len() == 1 {
+        return Some(desktop.clone());
+    }
+
+    let left = layout.iter().map(|(x, _, _, _)| *x).min()?;
+    let top = layout.iter().map(|(_, y, _, _)| *y).min()?;
+    let right = layout.iter().map(|(x, _, width, _)| *x + *width as i32).max()?;
+    let bottom = layout.iter().map(|(_, y, _, height)| *y + *height as i32).max()?;
+    if (right - left, bottom - top) != (desktop.width() as i32, desktop.height() as i32) {
+        return None;
+    }
+
+    let (x, y, width, height) = bounds;
+    let (x, y) = (x - left, y - top);
+    if x < 0 || y < 0 || x as u32 + width > desktop.width() || y as u32 + height > desktop.height() {
+        return None;
+    }
+    Some(image::imageops::crop_imm(desktop, x as u32, y as u32, width, height).to_image())
+}
+
+/// Capture through the org.freedesktop.portal.Screenshot portal, blocking.
+/// The first capture shows the portal dialog; once it was approved later ones
+/// ask non-interactively, which portals with a permission store answer silently.
+#[cfg(target_os = "linux")]
+fn grab_portal<R: Runtime>(app: &AppHandle<R>) -> Result<RgbaImage, CaptureError> {
+    use ashpd::desktop::screenshot::Screenshot;
+
+    let state = app.state::<CaptureSettings>();
+    let approved = match state.portal_approved.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+
+    let request = |interactive: bool| {
+        tauri::async_runtime::block_on(async move {
+            let response = Screenshot::request()
+                .interactive(interactive)
+                .modal(true)
+                .send()
+                .await?
+                .response()?;
+            Ok::<_, ashpd::Error>(response.uri().clone())
+        })
+    };
+
+    let uri = match request(!approved) {
+        Ok(uri) => Ok(uri),
+        // A revoked permission needs the dialog again
+        Err(e) if approved => {
+            eprintln!("Non-interactive portal screenshot failed, asking again: {}", e);
+            request(true)
+        }
+        Err(e) => Err(e),
+    }
+    .map_err(|e| match e {
+        ashpd::Error::Response(_) => CaptureError::PermissionDenied {
+            message: format!("The screenshot was cancelled or denied: {}", e),
+        },
+        e => CaptureError::failed(format!("Screenshot portal failed: {}", e)),
+    })?;
+
+    match state.portal_approved.lock() {
+        Ok(mut guard) => *guard = true,
+        Err(poisoned) => *poisoned.into_inner() = true,
+    }
+
+    let path = uri
+        .to_file_path()
+        .map_err(|_| CaptureError::failed(format!("Unexpected screenshot location: {}", uri)))?;
+    let image = image::open(&path).map(|image| image.to_rgba8());
+    // The portal leaves the file in the user's pictures folder
+    if let Err(e) = std::fs::remove_file(&path) {
+        eprintln!("Failed to remove portal screenshot {}: {}", path.display(), e);
+    }
+    image.map_err(|e| CaptureError::failed(format!("Failed to read portal screenshot: {}", e)))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn grab_portal<R: Runtime>(_app: &AppHandle<R>) -> Result<RgbaImage, CaptureError> {
+    Err(CaptureError::failed("The screenshot portal is only available on Linux".to_string()))
+}
+
+/// Downscale so the longer side fits max_dimension, keeping the aspect ratio
+pub fn downscale(image: RgbaImage, max_dimension: Option<u32>) -> RgbaImage {
+    let Some(max_dimension) = max_dimension.filter(|max| *max > 0) else {
+        return image;
+    };
+    let longest = image.width().max(image.height());
+    if longest <= max_dimension {
+        return image;
+    }
+
+    let ratio = max_dimension as f64 / longest as f64;
+    let width = ((image.width() as f64 * ratio).round() as u32).max(1);
+    let height = ((image.height() as f64 * ratio).round() as u32).max(1);
+    imageops::resize(&image, width, height, FilterType::Lanczos3)
+}
+
+pub fn encode_image(image: &RgbaImage, options: &ImageOptions) -> Result<Vec<u8>, String> {
+    options.validate()?;
+    match options.format {
+        ImageFormat::Png => encode_png(image),
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+            let mut buffer = Vec::new();
+            JpegEncoder::new_with_quality(&mut buffer, options.quality.unwrap_or(DEFAULT_JPEG_QUALITY).clamp(1, 100))
+                .write_image(rgb.as_raw(), rgb.width(), rgb.height(), ColorType::Rgb8.into())
+                .map_err(|e| format!("Failed to encode to JPEG: {}", e))?;
+            Ok(buffer)
+        }
+        ImageFormat::Webp => {
+            let mut buffer = Vec::new();
+            WebPEncoder::new_lossless(&mut buffer)
+                .write_image(image.as_raw(), image.width(), image.height(), ColorType::Rgba8.into())
+                .map_err(|e| format!("Failed to encode to WebP: {}", e))?;
+            Ok(buffer)
+        }
+    }
+}
+
+fn to_screen_capture(monitor: &MonitorInfo, image: RgbaImage, options: &ImageOptions) -> Result<ScreenCapture, CaptureError> {
+    let scaled = match options.max_dimension {
+        Some(max) if max > 0 && image.width().max(image.height()) > max => Some(downscale(image.clone(), Some(max))),
+        _ => None,
+    };
+    let stored = scaled.as_ref().unwrap_or(&image);
+    let data = encode_image(stored, options).map_err(CaptureError::failed)?;
+    let (width, height) = stored.dimensions();
+    // Later redactions can't be painted exactly on a downscaled or lossy image
+    let source = (scaled.is_some() || options.format == ImageFormat::Jpeg).then(|| Arc::new(image));
+    Ok(ScreenCapture {
+        data: base64::engine::general_purpose::STANDARD.encode(data),
+        format: options.format,
+        width,
+        height,
+        scale_factor: monitor.scale_factor,
+        monitor: monitor.id,
+        id: None,
+        warning: monitor.missing.map(|missing| {
+            format!(
+                "Monitor {} is no longer connected, captured monitor {} under the cursor instead",
+                missing, monitor.id
+            )
+        }),
+        source,
+    })
+}
+
+/// Capture a monitor and encode it as base64 PNG, blocking
+pub fn capture_monitor<R: Runtime>(
+    app: &AppHandle<R>,
+    monitor_id: Option<u32>,
+    options: &ImageOptions,
+) -> Result<ScreenCapture, CaptureError> {
+    let (monitor, image) = grab_monitor(app, monitor_id)?;
+    to_screen_capture(&monitor, image, options)
+}
+
+/// Capture the monitor first, then let the user pick a rectangle of it in a
+/// selection window covering that monitor. Capturing up front keeps the
+/// selection window itself out of the image.
+pub async fn select_region<R: Runtime>(
+    app: &AppHandle<R>,
+    monitor_id: Option<u32>,
+    options: ImageOptions,
+) -> Result<RegionCapture, CaptureError> {
+    let handle = app.clone();
+    let (monitor, image) = tauri::async_runtime::spawn_blocking(move || {
+        with_window_excluded(&handle, || grab_monitor(&handle, monitor_id))
+    })
+    .await
+    .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))??;
+
+    let (sender, receiver) = oneshot::channel();
+    {
+        let state = app.state::<RegionSelection>();
+        let mut pending = match state.pending.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if pending.as_ref().is_some_and(|pending| !pending.is_closed()) {
+            return Err(CaptureError::failed("Region selection already in progress".to_string()));
+        }
+        *pending = Some(sender);
+    }
+
+    let (x, y, width, height) = monitor.bounds;
+    let window = WebviewWindowBuilder::new(app, REGION_WINDOW_LABEL, WebviewUrl::App("region-select.html".into()))
+        .title("Select region")
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .shadow(false)
+        .focused(true)
+        .visible(false)
+        .build()
+        .map_err(|e| CaptureError::failed(format!("Failed to open region selection: {}", e)))?;
+
+    // Closing the window any other way counts as cancelling
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            finish_selection(&handle, None);
+        }
+    });
+
+    let shown = window
+        .set_position(tauri::PhysicalPosition { x, y })
+        .and_then(|_| window.set_size(tauri::PhysicalSize { width, height }))
+        .and_then(|_| window.show())
+        .and_then(|_| window.set_focus());
+    if let Err(e) = shown {
+        eprintln!("Failed to show region selection: {}", e);
+        let _ = window.destroy();
+    }
+
+    let rect = receiver.await.ok().flatten();
+    let _ = window.destroy();
+
+    let Some(rect) = rect else {
+        return Ok(RegionCapture::Cancelled);
+    };
+
+    // crop_imm clamps the rectangle to the image
+    let (image_width, image_height) = (image.width() as f64, image.height() as f64);
+    let cropped = imageops::crop_imm(
+        &image,
+        (rect.x.clamp(0.0, 1.0) * image_width) as u32,
+        (rect.y.clamp(0.0, 1.0) * image_height) as u32,
+        ((rect.width * image_width) as u32).max(1),
+        ((rect.height * image_height) as u32).max(1),
+    )
+    .to_image();
+
+    to_screen_capture(&monitor, cropped, &options).map(RegionCapture::Captured)
+}
+
+/// Capture the frontmost window, or the one behind Pluely when it is frontmost, blocking
+pub fn capture_window<R: Runtime>(app: &AppHandle<R>, options: &ImageOptions) -> Result<WindowCapture, CaptureError> {
+    if !permissions::is_granted(PermissionKind::Screen) {
+        return Err(CaptureError::PermissionDenied {
+            message: "Screen recording permission is required to take screenshots".to_string(),
+        });
+    }
+
+    // Read the bounds before hiding the overlay changes which window is in front
+    let bounds = crate::active_app::frontmost_window_bounds(app)
+        .filter(|bounds| bounds.width > 0 && bounds.height > 0)
+        .ok_or(CaptureError::failed("No active window to capture".to_string()))?;
+    capture_bounds(app, bounds, None, options)
+}
+
+/// Capture the window under the mouse cursor, skipping Pluely's own windows, blocking
+pub fn capture_window_under_cursor<R: Runtime>(
+    app: &AppHandle<R>,
+    options: &ImageOptions,
+) -> Result<WindowCapture, CaptureError> {
+    if !permissions::is_granted(PermissionKind::Screen) {
+        return Err(CaptureError::PermissionDenied {
+            message: "Screen recording permission is required to take screenshots".to_string(),
+        });
+    }
+
+    let cursor = app
+        .cursor_position()
+        .map_err(|e| CaptureError::failed(format!("Failed to get cursor position: {}", e)))?;
+    let window = crate::active_app::window_at(cursor.x as i32, cursor.y as i32)
+        .filter(|window| window.bounds.width > 0 && window.bounds.height > 0)
+        .ok_or(CaptureError::failed("No window under the cursor to capture".to_string()))?;
+    capture_bounds(app, window.bounds, Some(window.process), options)
+}
+
+/// Capture a monitor and crop it to a window's frame
+fn capture_bounds<R: Runtime>(
+    app: &AppHandle<R>,
+    bounds: WindowBounds,
+    process: Option<String>,
+    options: &ImageOptions,
+) -> Result<WindowCapture, CaptureError> {
+    let monitors = Monitor::all().map_err(|e| CaptureError::failed(format!("Failed to get monitors: {}", e)))?;
+    let (center_x, center_y) = (bounds.x + bounds.width as i32 / 2, bounds.y + bounds.height as i32 / 2);
+    let monitor = monitors
+        .into_iter()
+        .find(|monitor| {
+            let (left, top, width, height) = physical_bounds(monitor);
+            center_x >= left && center_x < left + width as i32 && center_y >= top && center_y < top + height as i32
+        })
+        .ok_or(CaptureError::MonitorNotFound {
+            message: "The window is not on any monitor".to_string(),
+        })?;
+
+    // Through grab_monitor so Wayland goes through the portal, already redacted
+    let (info, image) = with_window_excluded(app, || grab_monitor(app, Some(monitor.id())))?;
+
+    // Windows hanging off the edge of the monitor are cut at the edge
+    let (left, top, _, _) = info.bounds;
+    let (offset_x, offset_y) = (bounds.x - left, bounds.y - top);
+    let width = bounds.width.saturating_sub((-offset_x).max(0) as u32).max(1);
+    let height = bounds.height.saturating_sub((-offset_y).max(0) as u32).max(1);
+    let cropped = imageops::crop_imm(&image, offset_x.max(0) as u32, offset_y.max(0) as u32, width, height).to_image();
+
+    Ok(WindowCapture {
+        capture: to_screen_capture(&info, cropped, options)?,
+        title: bounds.title,
+        process,
+    })
+}
+
+fn finish_selection<R: Runtime>(app: &AppHandle<R>, rect: Option<SelectionRect>) {
+    let state = app.state::<RegionSelection>();
+    let pending = match state.pending.lock() {
+        Ok(mut guard) => guard.take(),
+        Err(poisoned) => poisoned.into_inner().take(),
+    };
+    if let Some(sender) = pending {
+        let _ = sender.send(rect);
+    }
+}
+
+/// Whether a screenshot countdown or region selection still needs the overlay around
+pub fn is_screenshot_pending<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<RegionSelection>();
+    let selecting = match state.pending.lock() {
+        Ok(guard) => guard.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    };
+    selecting || is_countdown_active(app)
+}
+
+fn is_exclude_window_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    crate::settings::bool(app, EXCLUDE_WINDOW_SETTING, true)
+}
+
+/// Run capture with the main window off screen, then put it back as it was.
+/// Only visibility and focus are restored; the per-show handlers don't run since
+/// the window is gone for a few frames only. Content protection already keeps
+/// the window out of captures on macOS and Windows, this covers the rest.
+fn with_window_excluded<R: Runtime, T>(app: &AppHandle<R>, capture: impl FnOnce() -> T) -> T {
+    let window = match app.get_webview_window("main") {
+        Some(window) if is_exclude_window_enabled(app) && crate::shortcuts::is_main_window_visible(app) => window,
+        _ => return capture(),
+    };
+
+    let was_focused = matches!(window.is_focused(), Ok(true));
+    if let Err(e) = window.hide() {
+        eprintln!("Failed to hide window for capture: {}", e);
+        return capture();
+    }
+
+    let started = Instant::now();
+    while matches!(window.is_visible(), Ok(true)) && started.elapsed() < HIDE_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    std::thread::sleep(HIDE_SETTLE_DELAY);
+
+    let result = capture();
+
+    if let Err(e) = window.show() {
+        eprintln!("Failed to show window after capture: {}", e);
+    }
+    if was_focused {
+        if let Err(e) = window.set_focus() {
+            eprintln!("Failed to focus window after capture: {}", e);
+        }
+    }
+    result
+}
+
+fn is_countdown_active<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<ScreenshotCountdown>();
+    let cancel = match state.cancel.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cancel.as_ref().is_some_and(|cancel| !cancel.is_closed())
+}
+
+/// Wait delay_ms with a screenshot-countdown event every second, false when
+/// cancelled. The window is left unfocused so the user can open menus meanwhile.
+async fn countdown<R: Runtime>(app: &AppHandle<R>, delay_ms: u64) -> Result<bool, CaptureError> {
+    if delay_ms == 0 {
+        return Ok(true);
+    }
+
+    let (sender, mut cancelled) = oneshot::channel();
+    {
+        let state = app.state::<ScreenshotCountdown>();
+        let mut cancel = match state.cancel.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if cancel.as_ref().is_some_and(|cancel| !cancel.is_closed()) {
+            return Err(CaptureError::failed("A delayed screenshot is already counting down".to_string()));
+        }
+        *cancel = Some(sender);
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(delay_ms);
+    let completed = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if let Some(window) = app.get_webview_window("main") {
+            if let Err(e) = window.emit("screenshot-countdown", json!({ "remaining_ms": remaining.as_millis() as u64 })) {
+                eprintln!("Failed to emit screenshot countdown: {}", e);
+            }
+        }
+        if remaining.is_zero() {
+            break true;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(remaining.min(COUNTDOWN_TICK)) => {}
+            _ = &mut cancelled => break false,
+        }
+    };
+
+    // Only clear our own sender, a new countdown may have started after a cancel
+    drop(cancelled);
+    let state = app.state::<ScreenshotCountdown>();
+    let mut cancel = match state.cancel.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if cancel.as_ref().is_some_and(|cancel| cancel.is_closed()) {
+        *cancel = None;
+    }
+    Ok(completed)
+}
+
+fn cancelled_error() -> CaptureError {
+    CaptureError::Cancelled {
+        message: "The screenshot was cancelled".to_string(),
+    }
+}
+
+/// Difference hash: each bit tells whether a cell is brighter than its right
+/// neighbour, so noise barely changes it while moved content flips many bits
+fn frame_hash(image: &RgbaImage) -> FrameHash {
+    let small = imageops::resize(image, FRAME_HASH_SIZE + 1, FRAME_HASH_SIZE, FilterType::Triangle);
+    let small = DynamicImage::ImageRgba8(small).to_luma8();
+
+    let mut hash = [0u64; (FRAME_HASH_SIZE * FRAME_HASH_SIZE / 64) as usize];
+    for y in 0..FRAME_HASH_SIZE {
+        for x in 0..FRAME_HASH_SIZE {
+            if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+                let bit = (y * FRAME_HASH_SIZE + x) as usize;
+                hash[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+    hash
+}
+
+fn hash_distance(a: &FrameHash, b: &FrameHash) -> u32 {
+    a.iter().zip(b.iter()).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+/// History id of the previous capture when it's the same frame on the same monitor
+fn find_duplicate<R: Runtime>(app: &AppHandle<R>, monitor: &MonitorInfo, hash: &FrameHash) -> Option<String> {
+    let threshold = crate::settings::u64(app, DUPLICATE_THRESHOLD_SETTING, DEFAULT_DUPLICATE_THRESHOLD as u64);
+    let threshold = u32::try_from(threshold).unwrap_or(u32::MAX);
+    let state = app.state::<DuplicateFilter>();
+    let last = match state.last.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    last.as_ref()
+        .filter(|last| {
+            last.monitor == monitor.id
+                && hash_distance(&last.hash, hash) <= threshold
+                && crate::screenshot_history::contains(app, &last.id)
+        })
+        .map(|last| last.id.clone())
+}
+
+fn is_duplicate_filter_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    crate::settings::bool(app, DUPLICATE_FILTER_SETTING, true)
+}
+
+/// Full-screen capture for the shortcut, identical frames aren't encoded again
+fn capture_full_for_shortcut<R: Runtime>(
+    app: &AppHandle<R>,
+    monitor_id: Option<u32>,
+    options: &ImageOptions,
+) -> Result<ShortcutCapture, CaptureError> {
+    let (monitor, image) = with_window_excluded(app, || grab_monitor(app, monitor_id))?;
+
+    let hash = if is_duplicate_filter_enabled(app) {
+        let hash = frame_hash(&image);
+        if let Some(previous_id) = find_duplicate(app, &monitor, &hash) {
+            return Ok(ShortcutCapture::Duplicate(previous_id));
+        }
+        Some(hash)
+    } else {
+        None
+    };
+    Ok(ShortcutCapture::Captured(Box::new(to_screen_capture(&monitor, image, options)?), hash))
+}
+
+/// Add a capture to the screenshot history and tag it with the history id
+fn remember<R: Runtime>(app: &AppHandle<R>, mut capture: ScreenCapture, mode: ScreenshotMode) -> ScreenCapture {
+    let source = capture.source.take();
+    match crate::screenshot_history::record(app, &capture, source.as_deref(), mode) {
+        Ok(id) => capture.id = Some(id),
+        Err(e) => eprintln!("Failed to keep screenshot in history: {}", e),
+    }
+    capture
+}
+
+fn is_copy_to_clipboard_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    crate::settings::bool(app, COPY_TO_CLIPBOARD_SETTING, false)
+}
+
+/// Put a base64 PNG, JPEG or WebP on the clipboard as image data. Errors start
+/// with "invalid-image:" or "clipboard-unavailable:" so the frontend can tell them apart.
+fn copy_image<R: Runtime>(app: &AppHandle<R>, data: &str) -> Result<(), String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|e| format!("invalid-image: {}", e))?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("invalid-image: {}", e))?
+        .to_rgba8();
+
+    // The clipboard plugin converts RGBA to each platform's native image flavors
+    let (width, height) = (image.width(), image.height());
+    let image = tauri::image::Image::new_owned(image.into_raw(), width, height);
+    app.clipboard()
+        .write_image(&image)
+        .map_err(|e| format!("clipboard-unavailable: {}", e))
+}
+
+/// Options used when a command doesn't pass its own
+pub fn output_options<R: Runtime>(app: &AppHandle<R>) -> ImageOptions {
+    let mut options: ImageOptions = crate::settings::get(app, OUTPUT_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    // Earlier versions stored a quality with every format
+    if options.format == ImageFormat::Webp {
+        options.quality = None;
+    }
+    options
+}
+
+fn screenshot_mode<R: Runtime>(app: &AppHandle<R>) -> ScreenshotMode {
+    crate::settings::get(app, MODE_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or(ScreenshotMode::Full)
+}
+
+fn shortcut_delay_ms<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    crate::settings::u64(app, SHORTCUT_DELAY_SETTING, 0)
+}
+
+/// Whether the screenshot shortcut captures in Rust rather than in the frontend
+pub fn is_native_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let native = crate::settings::bool(app, NATIVE_SETTING, false);
+    let delay_ms = shortcut_delay_ms(app);
+    // Region, window and delayed captures only exist natively
+    native || delay_ms > 0 || screenshot_mode(app) != ScreenshotMode::Full
+}
+
+/// Capture in the given mode, None when a region selection was cancelled
+async fn capture_in_mode<R: Runtime>(
+    app: &AppHandle<R>,
+    mode: ScreenshotMode,
+    options: ImageOptions,
+) -> Result<Option<ShortcutCapture>, CaptureError> {
+    let target = crate::settings::get(app, MONITOR_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or(MonitorTarget::Cursor);
+    let monitor_id = resolve_target(target);
+
+    match mode {
+        ScreenshotMode::Full => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn_blocking(move || capture_full_for_shortcut(&handle, monitor_id, &options))
+                .await
+                .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))
+                .and_then(|result| result)
+                .map(Some)
+        }
+        ScreenshotMode::ActiveWindow => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn_blocking(move || capture_window(&handle, &options))
+                .await
+                .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))
+                .and_then(|result| result)
+                .map(|window| Some(ShortcutCapture::Captured(Box::new(window.capture), None)))
+        }
+        ScreenshotMode::WindowUnderCursor => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn_blocking(move || capture_window_under_cursor(&handle, &options))
+                .await
+                .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))
+                .and_then(|result| result)
+                .map(|window| Some(ShortcutCapture::Captured(Box::new(window.capture), None)))
+        }
+        ScreenshotMode::Region => select_region(app, monitor_id, options).await.map(|region| match region {
+            RegionCapture::Captured(capture) => Some(ShortcutCapture::Captured(Box::new(capture), None)),
+            RegionCapture::Cancelled => None,
+        }),
+    }
+}
+
+/// Capture in the configured mode and emit screenshot-captured, screenshot-cancelled
+/// or screenshot-failed
+pub fn capture_and_emit<R: Runtime>(app: &AppHandle<R>) {
+    // Pressing the shortcut again during a countdown must not capture twice
+    if is_countdown_active(app) {
+        eprintln!("Screenshot countdown already running, ignoring shortcut");
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let options = output_options(&app);
+        let mode = screenshot_mode(&app);
+        let delay_ms = shortcut_delay_ms(&app);
+
+        let capture = match countdown(&app, delay_ms).await {
+            Ok(true) => capture_in_mode(&app, mode, options).await,
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        let capture = capture.map(|capture| {
+            capture.map(|capture| match capture {
+                ShortcutCapture::Captured(capture, hash) => {
+                    let capture = remember(&app, *capture, mode);
+                    if let (Some(hash), Some(id)) = (hash, capture.id.clone()) {
+                        let state = app.state::<DuplicateFilter>();
+                        let frame = Some(LastFrame {
+                            hash,
+                            monitor: capture.monitor,
+                            id,
+                        });
+                        match state.last.lock() {
+                            Ok(mut guard) => *guard = frame,
+                            Err(poisoned) => *poisoned.into_inner() = frame,
+                        };
+                    }
+                    ShortcutCapture::Captured(Box::new(capture), hash)
+                }
+                duplicate => duplicate,
+            })
+        });
+
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        let result = match capture {
+            Ok(None) => window.emit("screenshot-cancelled", json!({})),
+            Ok(Some(ShortcutCapture::Duplicate(id))) => {
+                window.emit("screenshot-captured", json!({ "duplicate": true, "id": id }))
+            }
+            Ok(Some(ShortcutCapture::Captured(capture, _))) => {
+                if is_copy_to_clipboard_enabled(&app) {
+                    if let Err(e) = copy_image(&app, &capture.data) {
+                        eprintln!("Failed to copy screenshot to clipboard: {}", e);
+                    }
+                }
+                let path = match crate::screenshot_save::save(&app, &capture) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        eprintln!("Failed to save screenshot: {}", e);
+                        if let Err(e) = window.emit("screenshot-save-failed", json!({ "error": e })) {
+                            eprintln!("Failed to emit screenshot event: {}", e);
+                        }
+                        None
+                    }
+                };
+                window.emit(
+                    "screenshot-captured",
+                    json!({
+                        "duplicate": false,
+                        "id": capture.id,
+                        "data": capture.data,
+                        "format": capture.format,
+                        "monitor": capture.monitor,
+                        "width": capture.width,
+                        "height": capture.height,
+                        "scale_factor": capture.scale_factor,
+                        "warning": capture.warning,
+                        "path": path,
+                    }),
+                )
+            }
+            Err(e) => {
+                eprintln!("Native screenshot failed: {}", e);
+                window.emit("screenshot-failed", &e)
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to emit screenshot event: {}", e);
+        }
+    });
+}
+
+/// Tauri command to list connected monitors, ids stay the same while a display
+/// is connected
+#[tauri::command]
+pub fn list_monitors() -> Result<Vec<MonitorDetails>, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to get monitors: {}", e))?;
+    Ok(monitors
+        .iter()
+        .map(|monitor| {
+            let (x, y, width, height) = physical_bounds(monitor);
+            MonitorDetails {
+                id: monitor.id(),
+                name: monitor.name().to_string(),
+                width,
+                height,
+                x,
+                y,
+                scale_factor: monitor.scale_factor(),
+                is_primary: monitor.is_primary(),
+            }
+        })
+        .collect())
+}
+
+/// Tauri command to choose the monitor the screenshot shortcut captures
+#[tauri::command]
+pub fn set_screenshot_monitor<R: Runtime>(app: AppHandle<R>, monitor: MonitorTarget) -> Result<(), String> {
+    crate::settings::set(&app, MONITOR_SETTING, json!(monitor))
+}
+
+/// Tauri command to capture a monitor, by default the one under the cursor
+#[tauri::command]
+pub async fn capture_screen<R: Runtime>(
+    app: AppHandle<R>,
+    monitor: Option<u32>,
+    options: Option<ImageOptions>,
+    delay_ms: Option<u64>,
+) -> Result<ScreenCapture, CaptureError> {
+    let options = options.unwrap_or_else(|| output_options(&app));
+    if !countdown(&app, delay_ms.unwrap_or(0)).await? {
+        return Err(cancelled_error());
+    }
+    let handle = app.clone();
+    let capture = tauri::async_runtime::spawn_blocking(move || {
+        with_window_excluded(&handle, || capture_monitor(&handle, monitor, &options))
+    })
+    .await
+    .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))??;
+    Ok(remember(&app, capture, ScreenshotMode::Full))
+}
+
+/// Tauri command to switch the screenshot shortcut to native capture
+#[tauri::command]
+pub fn set_native_screenshots<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    crate::settings::set(&app, NATIVE_SETTING, json!(enabled))
+}
+
+/// Tauri command to choose whether the overlay is hidden from screenshots
+#[tauri::command]
+pub fn set_exclude_window_from_capture<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    crate::settings::set(&app, EXCLUDE_WINDOW_SETTING, json!(enabled))
+}
+
+/// Tauri command to drag-select part of a monitor, by default the one under the cursor
+#[tauri::command]
+pub async fn capture_region<R: Runtime>(
+    app: AppHandle<R>,
+    monitor: Option<u32>,
+    options: Option<ImageOptions>,
+    delay_ms: Option<u64>,
+) -> Result<RegionCapture, CaptureError> {
+    let options = options.unwrap_or_else(|| output_options(&app));
+    if !countdown(&app, delay_ms.unwrap_or(0)).await? {
+        return Ok(RegionCapture::Cancelled);
+    }
+    Ok(match select_region(&app, monitor, options).await? {
+        RegionCapture::Captured(capture) => RegionCapture::Captured(remember(&app, capture, ScreenshotMode::Region)),
+        RegionCapture::Cancelled => RegionCapture::Cancelled,
+    })
+}
+
+/// Tauri command to capture only the active window, with its title
+#[tauri::command]
+pub async fn capture_active_window<R: Runtime>(
+    app: AppHandle<R>,
+    options: Option<ImageOptions>,
+    delay_ms: Option<u64>,
+) -> Result<WindowCapture, CaptureError> {
+    let options = options.unwrap_or_else(|| output_options(&app));
+    if !countdown(&app, delay_ms.unwrap_or(0)).await? {
+        return Err(cancelled_error());
+    }
+    let handle = app.clone();
+    let mut window = tauri::async_runtime::spawn_blocking(move || capture_window(&handle, &options))
+        .await
+        .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))??;
+    window.capture = remember(&app, window.capture, ScreenshotMode::ActiveWindow);
+    Ok(window)
+}
+
+/// Tauri command to capture the window under the mouse cursor, with its title and process
+#[tauri::command]
+pub async fn capture_window_at_cursor<R: Runtime>(
+    app: AppHandle<R>,
+    options: Option<ImageOptions>,
+    delay_ms: Option<u64>,
+) -> Result<WindowCapture, CaptureError> {
+    let options = options.unwrap_or_else(|| output_options(&app));
+    if !countdown(&app, delay_ms.unwrap_or(0)).await? {
+        return Err(cancelled_error());
+    }
+    let handle = app.clone();
+    let mut window = tauri::async_runtime::spawn_blocking(move || capture_window_under_cursor(&handle, &options))
+        .await
+        .map_err(|e| CaptureError::failed(format!("Capture task failed: {}", e)))??;
+    window.capture = remember(&app, window.capture, ScreenshotMode::WindowUnderCursor);
+    Ok(window)
+}
+
+/// Tauri command called by the selection window, None when cancelled
+#[tauri::command]
+pub fn finish_region_selection<R: Runtime>(app: AppHandle<R>, rect: Option<SelectionRect>) -> Result<(), String> {
+    finish_selection(&app, rect);
+    Ok(())
+}
+
+/// Tauri command to choose what the screenshot shortcut captures
+#[tauri::command]
+pub fn set_screenshot_mode<R: Runtime>(app: AppHandle<R>, mode: ScreenshotMode) -> Result<(), String> {
+    crate::settings::set(&app, MODE_SETTING, json!(mode))
+}
+
+/// Tauri command to set the default format, quality and size cap of screenshots
+#[tauri::command]
+pub fn set_screenshot_output<R: Runtime>(app: AppHandle<R>, options: ImageOptions) -> Result<(), String> {
+    options.validate()?;
+    crate::settings::set(&app, OUTPUT_SETTING, json!(options))
+}
+
+/// Tauri command to copy a captured screenshot to the clipboard as an image
+#[tauri::command]
+pub fn copy_screenshot_to_clipboard<R: Runtime>(app: AppHandle<R>, data_base64: String) -> Result<(), String> {
+    copy_image(&app, &data_base64)
+}
+
+/// Tauri command to copy every native capture to the clipboard automatically
+#[tauri::command]
+pub fn set_copy_screenshots_to_clipboard<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    crate::settings::set(&app, COPY_TO_CLIPBOARD_SETTING, json!(enabled))
+}
+
+/// Tauri command to set the countdown before the screenshot shortcut captures
+#[tauri::command]
+pub fn set_screenshot_shortcut_delay<R: Runtime>(app: AppHandle<R>, delay_ms: u64) -> Result<(), String> {
+    crate::settings::set(&app, SHORTCUT_DELAY_SETTING, json!(delay_ms))
+}
+
+/// Tauri command to stop a delayed screenshot that is still counting down
+#[tauri::command]
+pub fn cancel_screenshot<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<ScreenshotCountdown>();
+    let cancel = match state.cancel.lock() {
+        Ok(mut guard) => guard.take(),
+        Err(poisoned) => poisoned.into_inner().take(),
+    };
+    if let Some(cancel) = cancel {
+        let _ = cancel.send(());
+    }
+    Ok(())
+}
+
+/// Check a value set_setting would store under one of the screenshot keys
+pub fn validate_setting(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    match key {
+        MONITOR_SETTING => crate::settings::parse::<MonitorTarget>(key, value).map(drop),
+        OUTPUT_SETTING => crate::settings::parse::<ImageOptions>(key, value)?.validate(),
+        MODE_SETTING => crate::settings::parse::<ScreenshotMode>(key, value).map(drop),
+        NATIVE_SETTING | EXCLUDE_WINDOW_SETTING | COPY_TO_CLIPBOARD_SETTING | DUPLICATE_FILTER_SETTING => {
+            crate::settings::parse::<bool>(key, value).map(drop)
+        }
+        SHORTCUT_DELAY_SETTING => crate::settings::parse::<u64>(key, value).map(drop),
+        DUPLICATE_THRESHOLD_SETTING => crate::settings::parse::<u32>(key, value).map(drop),
+        _ => Ok(()),
+    }
+}
+
+/// Tauri command to turn skipping of repeated identical shortcut screenshots on
+/// or off, threshold is how many of the 1024 hash bits may differ
+#[tauri::command]
+pub fn set_duplicate_screenshot_filter<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    threshold: Option<u32>,
+) -> Result<(), String> {
+    crate::settings::set(&app, DUPLICATE_FILTER_SETTING, json!(enabled))?;
+    crate::settings::set(
+        &app,
+        DUPLICATE_THRESHOLD_SETTING,
+        json!(threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD)),
+    )?;
+    if !enabled {
+        let state = app.state::<DuplicateFilter>();
+        match state.last.lock() {
+            Ok(mut guard) => *guard = None,
+            Err(poisoned) => *poisoned.into_inner() = None,
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two 1920x1080 monitors, the second to the right and 200 px lower
+    const LAYOUT: [(i32, i32, u32, u32); 2] = [(0, 0, 1920, 1080), (1920, 200, 1920, 1080)];
+
+    #[test]
+    fn portal_crop_cuts_out_the_requested_monitor() {
+        let mut desktop = RgbaImage::new(3840, 1280);
+        desktop.put_pixel(1920, 200, image::Rgba([255, 0, 0, 255]));
+        let cropped = crop_to_monitor(&desktop, &LAYOUT, LAYOUT[1]).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (1920, 1080));
+        assert_eq!(cropped.get_pixel(0, 0), &image::Rgba([255, 0, 0, 255]));
+
+        // Layouts may start left of or above the origin
+        let shifted = LAYOUT.map(|(x, y, width, height)| (x - 1920, y - 200, width, height));
+        let cropped = crop_to_monitor(&desktop, &shifted, shifted[1]).unwrap();
+        assert_eq!(cropped.get_pixel(0, 0), &image::Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn portal_crop_refuses_an_image_that_does_not_match_the_layout() {
+        // A scaled compositor returning logical pixels
+        let desktop = RgbaImage::new(1920, 640);
+        assert!(crop_to_monitor(&desktop, &LAYOUT, LAYOUT[0]).is_none());
+    }
+
+    #[test]
+    fn portal_crop_keeps_a_single_monitor_whole() {
+        let desktop = RgbaImage::new(2560, 1440);
+        let cropped = crop_to_monitor(&desktop, &[(0, 0, 1280, 720)], (0, 0, 1280, 720)).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (2560, 1440));
+    }
+
+    #[test]
+    fn webp_refuses_a_quality() {
+        let image = RgbaImage::new(4, 4);
+        let lossless = ImageOptions {
+            format: ImageFormat::Webp,
+            ..ImageOptions::default()
+        };
+        assert!(encode_image(&image, &lossless).is_ok());
+
+        let with_quality = ImageOptions {
+            quality: Some(60),
+            ..lossless
+        };
+        assert!(encode_image(&image, &with_quality).is_err());
+    }
+
+    #[test]
+    fn downscale_caps_the_longer_side_and_keeps_the_aspect_ratio() {
+        let image = RgbaImage::new(3840, 1600);
+        let scaled = downscale(image, Some(1920));
+        assert_eq!((scaled.width(), scaled.height()), (1920, 800));
+
+        let portrait = RgbaImage::new(1200, 3000);
+        let scaled = downscale(portrait, Some(1500));
+        assert_eq!((scaled.width(), scaled.height()), (600, 1500));
+    }
+
+    // A light desktop with a darker window and some text-like detail in it
+    fn desktop(window_x: u32, window_y: u32) -> RgbaImage {
+        RgbaImage::from_fn(1920, 1080, |x, y| {
+            let inside = (window_x..window_x + 800).contains(&x) && (window_y..window_y + 600).contains(&y);
+            if inside {
+                let line = (y - window_y) % 24 < 12 && (x - window_x) % 8 < 6;
+                image::Rgba(if line { [40, 40, 40, 255] } else { [90, 90, 110, 255] })
+            } else {
+                let shade = 180 + (x * 60 / 1920) as u8;
+                image::Rgba([shade, shade, 220, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn identical_frames_hash_the_same() {
+        let image = desktop(200, 150);
+        assert_eq!(hash_distance(&frame_hash(&image), &frame_hash(&image.clone())), 0);
+    }
+
+    #[test]
+    fn a_few_changed_pixels_stay_under_the_threshold() {
+        let image = desktop(200, 150);
+        let mut changed = image.clone();
+        // A blinking caret and a clock tick
+        for y in 400..418 {
+            changed.put_pixel(612, y, image::Rgba([255, 255, 255, 255]));
+        }
+        for x in 1850..1856 {
+            changed.put_pixel(x, 1060, image::Rgba([0, 0, 0, 255]));
+        }
+        let distance = hash_distance(&frame_hash(&image), &frame_hash(&changed));
+        assert!(distance <= DEFAULT_DUPLICATE_THRESHOLD, "distance {}", distance);
+    }
+
+    #[test]
+    fn a_moved_window_exceeds_the_threshold() {
+        let before = desktop(200, 150);
+        let after = desktop(900, 400);
+        let distance = hash_distance(&frame_hash(&before), &frame_hash(&after));
+        assert!(distance > DEFAULT_DUPLICATE_THRESHOLD, "distance {}", distance);
+    }
+
+    #[test]
+    fn downscale_leaves_small_images_alone() {
+        let image = RgbaImage::new(1280, 720);
+        assert_eq!(downscale(image.clone(), Some(1920)).dimensions(), (1280, 720));
+        assert_eq!(downscale(image.clone(), Some(0)).dimensions(), (1280, 720));
+        assert_eq!(downscale(image, None).dimensions(), (1280, 720));
+    }
+}
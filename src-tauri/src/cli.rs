@@ -0,0 +1,89 @@
+// Command line flags that run the same actions as the shortcuts, so scripts
+// and window managers can drive a running instance
+use crate::{autostart, deep_link, shortcuts, startup};
+use tauri::{AppHandle, Runtime};
+
+pub const USAGE: &str = "Usage: pluely [--toggle] [--screenshot] [--audio] [--system-audio] [--hidden]
+
+  --toggle        Show or hide the window
+  --screenshot    Capture a screenshot
+  --audio         Start voice input
+  --system-audio  Toggle system audio capture
+  --hidden        Start without showing the window";
+
+// Flag -> shortcut action it triggers
+const ACTION_FLAGS: &[(&str, &str)] = &[
+    ("--toggle", "toggle_window"),
+    ("--screenshot", "screenshot"),
+    ("--audio", "audio_recording"),
+    ("--system-audio", "system_audio"),
+];
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CliOptions {
+    pub hidden: bool,
+    pub deep_link: bool, // a pluely:// link was passed along with the flags
+    pub actions: Vec<&'static str>,
+}
+
+/// Parse argv including the program name, unknown flags are an error
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliOptions, String> {
+    let mut options = CliOptions::default();
+    for arg in args.into_iter().skip(1) {
+        if arg == autostart::HIDDEN_FLAG {
+            options.hidden = true;
+            continue;
+        }
+        // Windows and Linux pass pluely:// links as arguments, handled by deep_link
+        if deep_link::is_deep_link_arg(&arg) {
+            options.deep_link = true;
+            continue;
+        }
+        // Finder adds a process serial number on older macOS versions
+        if arg.starts_with("-psn_") {
+            continue;
+        }
+        match ACTION_FLAGS.iter().find(|(flag, _)| *flag == arg) {
+            Some((_, action_id)) => {
+                if !options.actions.contains(action_id) {
+                    options.actions.push(action_id);
+                }
+            }
+            None => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// Exit with usage on invalid arguments, call from main before starting the app
+pub fn validate_args_or_exit() {
+    if let Err(e) = parse_args(std::env::args()) {
+        eprintln!("{}\n\n{}", e, USAGE);
+        std::process::exit(2);
+    }
+}
+
+/// Run the actions requested on the command line, from this launch or forwarded
+/// by a second instance. A bare second launch brings the window back. The first
+/// launch's actions wait until the frontend is listening.
+pub fn run_actions<R: Runtime>(app: &AppHandle<R>, args: Vec<String>, forwarded: bool) {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Ignoring command line: {}", e);
+            return;
+        }
+    };
+
+    if options.actions.is_empty() {
+        if forwarded && !options.hidden && !options.deep_link && !shortcuts::is_main_window_visible(app) {
+            shortcuts::handle_shortcut_action(app, "toggle_window", "");
+        }
+        return;
+    }
+
+    for action_id in options.actions {
+        let handle = app.clone();
+        startup::run_when_ready(app, move || shortcuts::handle_shortcut_action(&handle, action_id, ""));
+    }
+}
@@ -17,6 +17,13 @@ pub fn migrations() -> Vec<Migration> {
             sql: include_str!("migrations/chat-history.sql"),
             kind: MigrationKind::Up,
         },
+        // Migration 3: Add the model column to messages for the Rust history commands
+        Migration {
+            version: 3,
+            description: "add_message_model",
+            sql: include_str!("migrations/message-model.sql"),
+            kind: MigrationKind::Up,
+        },
     ]
 }
 
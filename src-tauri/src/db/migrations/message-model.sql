@@ -0,0 +1,2 @@
+-- Model that produced a message, null for user messages and older rows
+ALTER TABLE messages ADD COLUMN model TEXT;
@@ -0,0 +1,113 @@
+// pluely:// links for launchers like Raycast and Alfred:
+// pluely://ask?text=..., pluely://toggle, pluely://screenshot, pluely://audio
+use crate::{shortcuts, startup};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, Runtime, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+pub const SCHEME: &str = "pluely";
+
+// Longest prompt accepted from a link, in characters
+const MAX_PREFILL_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum DeepLinkAction {
+    Ask(String),
+    Shortcut(&'static str),
+}
+
+/// Whether a command line argument is a link rather than a flag
+pub fn is_deep_link_arg(arg: &str) -> bool {
+    arg.starts_with(&format!("{}:", SCHEME))
+}
+
+fn parse_url(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported scheme: {}", url.scheme()));
+    }
+
+    // pluely://ask has "ask" as host, pluely:ask has it as path
+    let target = match url.host_str() {
+        Some(host) if url.path().trim_matches('/').is_empty() => host.to_string(),
+        Some(host) => return Err(format!("Unknown link path: {}{}", host, url.path())),
+        None => url.path().trim_matches('/').to_string(),
+    };
+
+    match target.to_lowercase().as_str() {
+        "ask" => {
+            let text = url
+                .query_pairs()
+                .find(|(key, _)| key == "text")
+                .map(|(_, value)| value.trim().to_string())
+                .unwrap_or_default();
+            if text.chars().count() > MAX_PREFILL_CHARS {
+                return Err(format!("Link text is longer than {} characters", MAX_PREFILL_CHARS));
+            }
+            Ok(DeepLinkAction::Ask(text))
+        }
+        "toggle" => Ok(DeepLinkAction::Shortcut("toggle_window")),
+        "screenshot" => Ok(DeepLinkAction::Shortcut("screenshot")),
+        "audio" => Ok(DeepLinkAction::Shortcut("audio_recording")),
+        other => Err(format!("Unknown link action: {}", other)),
+    }
+}
+
+fn handle_url<R: Runtime>(app: &AppHandle<R>, url: &Url) {
+    let action = match parse_url(url) {
+        Ok(action) => action,
+        Err(e) => {
+            eprintln!("Ignoring link {}: {}", url, e);
+            return;
+        }
+    };
+
+    match action {
+        DeepLinkAction::Shortcut(action_id) => shortcuts::handle_shortcut_action(app, action_id, ""),
+        DeepLinkAction::Ask(text) => {
+            if !shortcuts::is_main_window_visible(app) {
+                shortcuts::handle_shortcut_action(app, "toggle_window", "");
+            }
+            let Some(window) = app.get_webview_window("main") else {
+                return;
+            };
+            // Without text the link only brings up the input
+            let result = if text.is_empty() {
+                window.emit("focus-text-input", json!({}))
+            } else {
+                window.emit("prefill-and-submit", json!({ "text": text }))
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to emit link event: {}", e);
+            }
+        }
+    }
+}
+
+/// Handle links opened while running and the one that launched the app
+pub fn setup_deep_links<R: Runtime>(app: &AppHandle<R>) {
+    // Windows and Linux only know the scheme once it is registered for this executable
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("Failed to register pluely:// links: {}", e);
+    }
+
+    // macOS delivers the launch link as an event, possibly before the frontend loads
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            let app = handle.clone();
+            startup::run_when_ready(&handle, move || handle_url(&app, &url));
+        }
+    });
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            for url in urls {
+                let handle = app.clone();
+                startup::run_when_ready(app, move || handle_url(&handle, &url));
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to read launch link: {}", e),
+    }
+}
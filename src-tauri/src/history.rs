@@ -0,0 +1,402 @@
+// Conversation history in the conversations and messages tables of pluely.db,
+// the database the frontend reads through the SQL plugin. The plugin creates
+// and migrates it at startup (db::migrations), sessions here are its
+// conversations. One thread owns the connection and works through a queue:
+// writes are queued and the command returns right away, reads wait their turn
+// so they see every earlier write. Failed writes are reported with
+// history-write-failed { error }.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+// Opened by the SQL plugin from the app config dir, see tauri.conf.json preload
+const DATABASE_FILE: &str = "pluely.db";
+
+// Title the frontend gives a conversation it can't name
+const DEFAULT_TITLE: &str = "New Conversation";
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+// How long a read waits behind queued writes
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How long a statement waits for the SQL plugin's connections to let go
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+}
+
+impl Role {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        }
+    }
+
+    fn parse(value: &str) -> Role {
+        match value {
+            "assistant" => Role::Assistant,
+            "system" => Role::System,
+            _ => Role::User,
+        }
+    }
+}
+
+// What is known about a file sent with a message, never its contents. Same
+// shape as the frontend's attached files, which also carry the base64 data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub mime_type: Option<String>,
+    #[serde(rename = "size", default)]
+    pub size_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewMessage {
+    pub role: Role,
+    pub text: String,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Message {
+    pub id: String,
+    pub session_id: String,
+    pub role: Role,
+    pub text: String,
+    pub attachments: Vec<Attachment>,
+    pub model: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub message_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionWithMessages {
+    #[serde(flatten)]
+    pub session: Session,
+    pub messages: Vec<Message>,
+}
+
+enum Command {
+    CreateSession(Session),
+    AppendMessage(Message),
+    DeleteSession(String),
+    Clear,
+    ListSessions {
+        limit: u32,
+        offset: u32,
+        reply: Sender<Result<Vec<Session>, String>>,
+    },
+    GetSession {
+        id: String,
+        reply: Sender<Result<Option<SessionWithMessages>, String>>,
+    },
+}
+
+// Set by init, None when the database couldn't be opened
+#[derive(Default)]
+pub struct HistoryState {
+    sender: Mutex<Option<Sender<Command>>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Connection, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+
+    let connection = Connection::open(config_dir.join(DATABASE_FILE))
+        .map_err(|e| format!("Failed to open history database: {}", e))?;
+    connection
+        .busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to configure history database: {}", e))?;
+    connection
+        .execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA foreign_keys = ON;")
+        .map_err(|e| format!("Failed to configure history database: {}", e))?;
+
+    // The plugin has run every migration by now, the newest adds messages.model
+    connection
+        .prepare("SELECT model FROM messages LIMIT 0")
+        .map_err(|e| format!("History database isn't migrated: {}", e))?;
+    Ok(connection)
+}
+
+fn write(connection: &mut Connection, command: Command) -> Result<(), rusqlite::Error> {
+    match command {
+        Command::CreateSession(session) => {
+            connection.execute(
+                "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![session.id, session.title, session.created_at as i64, session.updated_at as i64],
+            )?;
+        }
+        Command::AppendMessage(message) => {
+            // Null rather than an empty list, like the frontend writes it
+            let attachments = if message.attachments.is_empty() {
+                None
+            } else {
+                serde_json::to_string(&message.attachments).ok()
+            };
+            // A trigger moves the conversation's updated_at along
+            connection.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files, model)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    message.id,
+                    message.session_id,
+                    message.role.as_str(),
+                    message.text,
+                    message.created_at as i64,
+                    attachments,
+                    message.model
+                ],
+            )?;
+        }
+        Command::DeleteSession(id) => {
+            connection.execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
+        }
+        Command::Clear => {
+            connection.execute_batch("DELETE FROM messages; DELETE FROM conversations;")?;
+        }
+        Command::ListSessions { .. } | Command::GetSession { .. } => {}
+    }
+    Ok(())
+}
+
+const SESSION_COLUMNS: &str = "c.id, c.title, c.created_at, c.updated_at,
+    (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id)";
+
+fn session_from_row(row: &rusqlite::Row) -> Result<Session, rusqlite::Error> {
+    Ok(Session {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get::<_, i64>(2)? as u64,
+        updated_at: row.get::<_, i64>(3)? as u64,
+        message_count: row.get::<_, i64>(4)? as u64,
+    })
+}
+
+fn list_sessions_page(connection: &Connection, limit: u32, offset: u32) -> Result<Vec<Session>, rusqlite::Error> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {} FROM conversations c ORDER BY c.updated_at DESC LIMIT ?1 OFFSET ?2",
+        SESSION_COLUMNS
+    ))?;
+    let sessions = statement
+        .query_map(params![limit, offset], session_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sessions)
+}
+
+fn read_session(connection: &Connection, id: &str) -> Result<Option<SessionWithMessages>, rusqlite::Error> {
+    let Some(session) = connection
+        .query_row(
+            &format!("SELECT {} FROM conversations c WHERE c.id = ?1", SESSION_COLUMNS),
+            params![id],
+            session_from_row,
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let mut statement = connection.prepare(
+        "SELECT id, conversation_id, role, content, attached_files, model, timestamp
+         FROM messages WHERE conversation_id = ?1 ORDER BY timestamp ASC, rowid ASC",
+    )?;
+    let messages = statement
+        .query_map(params![id], |row| {
+            let attachments: Option<String> = row.get(4)?;
+            Ok(Message {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                role: Role::parse(&row.get::<_, String>(2)?),
+                text: row.get(3)?,
+                attachments: attachments
+                    .and_then(|attachments| serde_json::from_str(&attachments).ok())
+                    .unwrap_or_default(),
+                model: row.get(5)?,
+                created_at: row.get::<_, i64>(6)? as u64,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(SessionWithMessages { session, messages }))
+}
+
+fn run<R: Runtime>(app: AppHandle<R>, mut connection: Connection, receiver: mpsc::Receiver<Command>) {
+    for command in receiver {
+        match command {
+            Command::ListSessions { limit, offset, reply } => {
+                let sessions = list_sessions_page(&connection, limit, offset)
+                    .map_err(|e| format!("Failed to list sessions: {}", e));
+                let _ = reply.send(sessions);
+            }
+            Command::GetSession { id, reply } => {
+                let session = read_session(&connection, &id).map_err(|e| format!("Failed to read session: {}", e));
+                let _ = reply.send(session);
+            }
+            command => {
+                if let Err(e) = write(&mut connection, command) {
+                    let error = format!("Failed to save history: {}", e);
+                    eprintln!("{}", error);
+                    if let Err(e) = app.emit("history-write-failed", json!({ "error": error })) {
+                        eprintln!("Failed to emit history-write-failed event: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Open the database and start the history thread, call during setup
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let connection = match open(app) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let handle = app.clone();
+    if let Err(e) = std::thread::Builder::new()
+        .name("pluely-history".to_string())
+        .spawn(move || run(handle, connection, receiver))
+    {
+        eprintln!("Failed to start history thread: {}", e);
+        return;
+    }
+
+    let state = app.state::<HistoryState>();
+    match state.sender.lock() {
+        Ok(mut guard) => *guard = Some(sender),
+        Err(poisoned) => *poisoned.into_inner() = Some(sender),
+    };
+}
+
+fn send<R: Runtime>(app: &AppHandle<R>, command: Command) -> Result<(), String> {
+    let state = app.state::<HistoryState>();
+    let sender = match state.sender.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let Some(sender) = sender.as_ref() else {
+        return Err("History is unavailable".to_string());
+    };
+    sender
+        .send(command)
+        .map_err(|_| "History thread exited".to_string())
+}
+
+/// Queue a read and wait for the reply off the async runtime
+async fn read<R: Runtime, T: Send + 'static>(
+    app: AppHandle<R>,
+    command: impl FnOnce(Sender<Result<T, String>>) -> Command + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (reply, result) = mpsc::channel();
+        send(&app, command(reply))?;
+        result
+            .recv_timeout(READ_TIMEOUT)
+            .map_err(|_| "Timed out reading history".to_string())?
+    })
+    .await
+    .map_err(|e| format!("Failed to read history: {}", e))?
+}
+
+/// Tauri command to start a new session, returned right away while it is saved
+#[tauri::command]
+pub fn create_session<R: Runtime>(app: AppHandle<R>, title: Option<String>) -> Result<Session, String> {
+    let now = now_ms();
+    let session = Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: title
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_TITLE.to_string()),
+        created_at: now,
+        updated_at: now,
+        message_count: 0,
+    };
+    send(&app, Command::CreateSession(session.clone()))?;
+    Ok(session)
+}
+
+/// Tauri command to add a message to a session, returned right away while it
+/// is saved. msg is { role, text, attachments?, model? }.
+#[tauri::command]
+pub fn append_message<R: Runtime>(app: AppHandle<R>, session_id: String, msg: NewMessage) -> Result<Message, String> {
+    let message = Message {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id,
+        role: msg.role,
+        text: msg.text,
+        attachments: msg.attachments,
+        model: msg.model,
+        created_at: now_ms(),
+    };
+    send(&app, Command::AppendMessage(message.clone()))?;
+    Ok(message)
+}
+
+/// Tauri command to list sessions, most recently active first. limit defaults
+/// to 50 and is capped at 500.
+#[tauri::command]
+pub async fn list_sessions<R: Runtime>(
+    app: AppHandle<R>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<Session>, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = offset.unwrap_or(0);
+    read(app, move |reply| Command::ListSessions { limit, offset, reply }).await
+}
+
+/// Tauri command to read a session with its messages, null when there is none
+#[tauri::command]
+pub async fn get_session<R: Runtime>(app: AppHandle<R>, id: String) -> Result<Option<SessionWithMessages>, String> {
+    read(app, move |reply| Command::GetSession { id, reply }).await
+}
+
+/// Tauri command to delete a session and its messages
+#[tauri::command]
+pub fn delete_session<R: Runtime>(app: AppHandle<R>, id: String) -> Result<(), String> {
+    send(&app, Command::DeleteSession(id))
+}
+
+/// Tauri command to delete every session
+#[tauri::command]
+pub fn clear_history<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    send(&app, Command::Clear)
+}
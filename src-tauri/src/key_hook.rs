@@ -0,0 +1,768 @@
+// Low-level keyboard hook for bindings the global shortcut plugin can't express,
+// such as a bare right Ctrl or a double-tapped Shift on Windows and X11, and
+// Globe/Fn combos through a CGEventTap on macOS.
+use crate::shortcuts;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::ShortcutState;
+
+// A modifier key on a specific side of the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HookKey {
+    LeftCtrl,
+    RightCtrl,
+    LeftShift,
+    RightShift,
+    LeftAlt,
+    RightAlt,
+    LeftMeta,
+    RightMeta,
+}
+
+impl HookKey {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "lctrl" | "lcontrol" => Some(HookKey::LeftCtrl),
+            "rctrl" | "rcontrol" => Some(HookKey::RightCtrl),
+            "lshift" => Some(HookKey::LeftShift),
+            "rshift" => Some(HookKey::RightShift),
+            "lalt" | "loption" => Some(HookKey::LeftAlt),
+            "ralt" | "roption" | "altgr" => Some(HookKey::RightAlt),
+            "lmeta" | "lsuper" | "lcmd" | "lwin" => Some(HookKey::LeftMeta),
+            "rmeta" | "rsuper" | "rcmd" | "rwin" => Some(HookKey::RightMeta),
+            _ => None,
+        }
+    }
+}
+
+// A set of side-specific modifiers held together, e.g. "rctrl" or "lctrl+rshift"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookCombo {
+    keys: Vec<HookKey>, // sorted, without duplicates
+}
+
+impl FromStr for HookCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = Vec::new();
+        for token in s.split('+').map(|token| token.trim().to_lowercase()) {
+            let key = HookKey::from_token(&token)
+                .ok_or_else(|| format!("'{}' is not a left/right modifier key", token))?;
+            keys.push(key);
+        }
+        keys.sort();
+        keys.dedup();
+
+        if keys.is_empty() {
+            return Err("Empty key hook binding".to_string());
+        }
+        Ok(HookCombo { keys })
+    }
+}
+
+// A modifier tapped twice in a row, e.g. "double:shift" or "double:rctrl".
+// The generic names accept either side of the keyboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoubleTap {
+    keys: Vec<HookKey>,
+}
+
+impl FromStr for DoubleTap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let token = s.trim().to_lowercase();
+        let keys = match token.as_str() {
+            "ctrl" | "control" => vec![HookKey::LeftCtrl, HookKey::RightCtrl],
+            "shift" => vec![HookKey::LeftShift, HookKey::RightShift],
+            "alt" | "option" => vec![HookKey::LeftAlt, HookKey::RightAlt],
+            "meta" | "super" | "cmd" | "win" => vec![HookKey::LeftMeta, HookKey::RightMeta],
+            other => vec![HookKey::from_token(other)
+                .ok_or_else(|| format!("'{}' is not a modifier key", other))?],
+        };
+        Ok(DoubleTap { keys })
+    }
+}
+
+// A combo with the macOS Globe/Fn key, e.g. "fn+space" or "fn+shift+a"
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub struct FnCombo {
+    keycode: u16, // macOS virtual key code
+    mods: FnMods,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct FnMods {
+    cmd: bool,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+}
+
+impl FromStr for FnCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut has_fn = false;
+        let mut mods = FnMods::default();
+        let mut keycode = None;
+
+        for token in s.split('+').map(|token| token.trim().to_lowercase()) {
+            match token.as_str() {
+                "fn" | "globe" => has_fn = true,
+                "cmd" | "command" | "meta" | "super" => mods.cmd = true,
+                "ctrl" | "control" => mods.ctrl = true,
+                "alt" | "option" => mods.alt = true,
+                "shift" => mods.shift = true,
+                key => {
+                    if keycode.is_some() {
+                        return Err("Fn shortcuts take a single key".to_string());
+                    }
+                    keycode = Some(mac_keycode(key).ok_or_else(|| format!("Unsupported key '{}'", key))?);
+                }
+            }
+        }
+
+        if !has_fn {
+            return Err("Not an fn shortcut".to_string());
+        }
+        let keycode = keycode.ok_or_else(|| "Fn shortcuts need a key".to_string())?;
+        Ok(FnCombo { keycode, mods })
+    }
+}
+
+// macOS virtual key codes (kVK_*) for the keys offered with fn and in capture
+const MAC_KEYCODES: &[(&str, u16)] = &[
+    ("a", 0), ("s", 1), ("d", 2), ("f", 3), ("h", 4), ("g", 5), ("z", 6), ("x", 7),
+    ("c", 8), ("v", 9), ("b", 11), ("q", 12), ("w", 13), ("e", 14), ("r", 15),
+    ("y", 16), ("t", 17), ("1", 18), ("2", 19), ("3", 20), ("4", 21), ("6", 22),
+    ("5", 23), ("9", 25), ("7", 26), ("8", 28), ("0", 29), ("o", 31), ("u", 32),
+    ("i", 34), ("p", 35), ("l", 37), ("j", 38), ("k", 40), ("n", 45), ("m", 46),
+    ("enter", 36), ("tab", 48), ("space", 49), ("backslash", 42), ("escape", 53),
+];
+
+fn mac_keycode(key: &str) -> Option<u16> {
+    let key = match key {
+        "return" => "enter",
+        "esc" => "escape",
+        key => key,
+    };
+    MAC_KEYCODES.iter().find(|(name, _)| *name == key).map(|(_, code)| *code)
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn mac_key_name(keycode: u16) -> Option<&'static str> {
+    MAC_KEYCODES.iter().find(|(_, code)| *code == keycode).map(|(name, _)| *name)
+}
+
+// Any binding delivered by the hook instead of the global shortcut plugin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookBinding {
+    Combo(HookCombo),
+    DoubleTap(DoubleTap),
+    Fn(FnCombo),
+}
+
+impl FromStr for HookBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Some(key) = trimmed.strip_prefix("double:") {
+            return key.parse().map(HookBinding::DoubleTap);
+        }
+
+        let is_fn = trimmed
+            .split('+')
+            .any(|token| matches!(token.trim().to_lowercase().as_str(), "fn" | "globe"));
+        if is_fn {
+            return trimmed.parse().map(HookBinding::Fn);
+        }
+        trimmed.parse().map(HookBinding::Combo)
+    }
+}
+
+// Default time allowed between the two taps of a double tap
+const DEFAULT_DOUBLE_TAP_WINDOW_MS: u64 = 300;
+
+// Registered bindings and the live keyboard state seen by the hook
+#[derive(Default)]
+pub struct KeyHookState {
+    inner: Mutex<HookInner>,
+}
+
+struct HookInner {
+    bindings: Vec<HookBinding>,
+    held: Vec<HookKey>,
+    active: Vec<HookBinding>, // combos that fired Pressed and wait for a release
+    interrupted: bool,        // a non-modifier key went down while modifiers were held
+    tap_candidate: Option<HookKey>, // modifier pressed alone, a tap if released cleanly
+    last_tap: Option<(HookKey, Instant)>,
+    double_tap_window: Duration,
+    capturing: bool, // the next chord goes to the shortcut capture instead of bindings
+    started: bool,
+    error: Option<String>,
+}
+
+impl Default for HookInner {
+    fn default() -> Self {
+        HookInner {
+            bindings: Vec::new(),
+            held: Vec::new(),
+            active: Vec::new(),
+            interrupted: false,
+            tap_candidate: None,
+            last_tap: None,
+            double_tap_window: Duration::from_millis(DEFAULT_DOUBLE_TAP_WINDOW_MS),
+            capturing: false,
+            started: false,
+            error: None,
+        }
+    }
+}
+
+// Fn only reaches the macOS event tap, other keys the Windows and Linux hooks
+const FN_HOOK: bool = cfg!(target_os = "macos");
+const KEY_HOOK: bool = cfg!(any(target_os = "windows", target_os = "linux"));
+
+/// Whether this platform has a key hook implementation for binding
+pub fn is_supported(binding: &HookBinding) -> bool {
+    if matches!(binding, HookBinding::Fn(_)) {
+        FN_HOOK
+    } else {
+        KEY_HOOK
+    }
+}
+
+/// Whether the app may observe keyboard events, always true outside macOS
+pub fn has_input_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        #[link(name = "ApplicationServices", kind = "framework")]
+        extern "C" {
+            fn AXIsProcessTrusted() -> bool;
+        }
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    true
+}
+
+/// Start delivering events for binding, the hook thread is started on first use
+pub fn register<R: Runtime>(app: &AppHandle<R>, binding: &HookBinding) -> Result<(), String> {
+    if !is_supported(binding) {
+        return Err("This key hook binding is not supported on this platform".to_string());
+    }
+    if !has_input_permission() {
+        return Err("Accessibility permission is required for fn shortcuts".to_string());
+    }
+
+    let state = app.state::<KeyHookState>();
+    let mut inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    // The macOS tap fails without permission, try again once it was granted
+    if cfg!(target_os = "macos") && inner.error.is_some() {
+        inner.error = None;
+        inner.started = false;
+    }
+
+    if let Some(error) = &inner.error {
+        return Err(format!("Keyboard hook unavailable: {}", error));
+    }
+    if inner.bindings.contains(binding) {
+        return Err("Key hook binding already registered".to_string());
+    }
+    inner.bindings.push(binding.clone());
+
+    if !inner.started {
+        inner.started = true;
+        start_listener(app.clone());
+    }
+    Ok(())
+}
+
+pub fn unregister<R: Runtime>(app: &AppHandle<R>, binding: &HookBinding) -> Result<(), String> {
+    let state = app.state::<KeyHookState>();
+    let mut inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    let before = inner.bindings.len();
+    inner.bindings.retain(|registered| registered != binding);
+    inner.active.retain(|active| active != binding);
+    if inner.bindings.len() == before {
+        return Err("Key hook binding is not registered".to_string());
+    }
+    Ok(())
+}
+
+pub fn is_registered<R: Runtime>(app: &AppHandle<R>, binding: &HookBinding) -> bool {
+    let state = app.state::<KeyHookState>();
+    let inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    inner.bindings.contains(binding)
+}
+
+/// Hand the next modifier+key chord to shortcuts::handle_captured_chord, for the
+/// shortcut capture in settings. Reading the keyboard beats grabbing every
+/// combination the capture could want with the OS.
+pub fn begin_capture<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if !KEY_HOOK && !FN_HOOK {
+        return Err("Shortcut capture is not supported on this platform".to_string());
+    }
+    // Wayland keeps the keyboard from other clients, the hook would never see a key
+    if crate::portal_shortcuts::is_wayland_session() {
+        return Err("Shortcut capture is not available on Wayland, type the combination instead".to_string());
+    }
+    if !has_input_permission() {
+        return Err("Accessibility permission is required to capture shortcuts".to_string());
+    }
+
+    let state = app.state::<KeyHookState>();
+    let mut inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    // Same retry as register once the macOS permission was granted
+    if cfg!(target_os = "macos") && inner.error.is_some() {
+        inner.error = None;
+        inner.started = false;
+    }
+    if let Some(error) = &inner.error {
+        return Err(format!("Keyboard hook unavailable: {}", error));
+    }
+    inner.capturing = true;
+
+    if !inner.started {
+        inner.started = true;
+        start_listener(app.clone());
+    }
+    Ok(())
+}
+
+pub fn end_capture<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<KeyHookState>();
+    match state.inner.lock() {
+        Ok(mut guard) => guard.capturing = false,
+        Err(poisoned) => poisoned.into_inner().capturing = false,
+    };
+}
+
+/// Shortcut string for a captured chord, None without a modifier since a bare
+/// key would fire while typing. Modifiers come in a fixed order.
+fn capture_chord(modifiers: &[&str], key: &str) -> Option<String> {
+    const ORDER: &[&str] = &["fn", "cmd", "ctrl", "alt", "shift", "super"];
+    let mut parts: Vec<&str> = ORDER.iter().copied().filter(|name| modifiers.contains(name)).collect();
+    if parts.is_empty() {
+        return None;
+    }
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+/// Offer a key press to a running capture, returns true when it was taken
+fn offer_capture<R: Runtime>(app: &AppHandle<R>, modifiers: &[&str], key: &str) -> bool {
+    let chord = {
+        let state = app.state::<KeyHookState>();
+        let mut inner = match state.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if !inner.capturing {
+            return false;
+        }
+        let Some(chord) = capture_chord(modifiers, key) else {
+            return false;
+        };
+        inner.capturing = false;
+        chord
+    };
+    shortcuts::handle_captured_chord(app, chord);
+    true
+}
+
+/// Set the time allowed between the two taps of a double tap
+pub fn set_double_tap_window<R: Runtime>(app: &AppHandle<R>, window: Duration) {
+    let state = app.state::<KeyHookState>();
+    let mut inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    inner.double_tap_window = window;
+}
+
+/// Feed one key event into the combo tracker, `key` is None for non-modifier keys.
+/// A combo is pressed once exactly its keys are held with nothing typed in between,
+/// and released as soon as one of them goes up. A double tap is two clean taps of
+/// the same modifier with no other key in between, like typing a capital letter.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+fn process_key<R: Runtime>(app: &AppHandle<R>, key: Option<HookKey>, pressed: bool) {
+    let state = app.state::<KeyHookState>();
+    let mut inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    let mut events = Vec::new();
+    match (key, pressed) {
+        (None, true) => {
+            if !inner.held.is_empty() {
+                inner.interrupted = true;
+            }
+            inner.tap_candidate = None;
+            inner.last_tap = None;
+        }
+        (None, false) => {}
+        (Some(key), true) => {
+            // Auto-repeat sends the press again while held
+            if inner.held.contains(&key) {
+                return;
+            }
+            inner.held.push(key);
+            inner.held.sort();
+
+            // Only a modifier pressed on its own can become a tap
+            inner.tap_candidate = if inner.held.len() == 1 { Some(key) } else { None };
+            if inner.held.len() > 1 {
+                inner.last_tap = None;
+            }
+
+            if !inner.interrupted {
+                let held = inner.held.clone();
+                let newly_active: Vec<HookBinding> = inner
+                    .bindings
+                    .iter()
+                    .filter(|binding| matches!(binding, HookBinding::Combo(combo) if combo.keys == held))
+                    .cloned()
+                    .collect();
+                for combo in newly_active {
+                    if !inner.active.contains(&combo) {
+                        inner.active.push(combo.clone());
+                        events.push((combo, ShortcutState::Pressed));
+                    }
+                }
+            }
+        }
+        (Some(key), false) => {
+            inner.held.retain(|held| *held != key);
+            if inner.held.is_empty() {
+                inner.interrupted = false;
+            }
+
+            let (released, still_active): (Vec<HookBinding>, Vec<HookBinding>) = inner
+                .active
+                .drain(..)
+                .partition(|binding| matches!(binding, HookBinding::Combo(combo) if combo.keys.contains(&key)));
+            inner.active = still_active;
+            events.extend(released.into_iter().map(|binding| (binding, ShortcutState::Released)));
+
+            if inner.tap_candidate.take() == Some(key) {
+                let now = Instant::now();
+                let window = inner.double_tap_window;
+                let is_double = matches!(
+                    inner.last_tap,
+                    Some((last_key, at)) if last_key == key && now.duration_since(at) <= window
+                );
+
+                if is_double {
+                    inner.last_tap = None;
+                    let tapped: Vec<HookBinding> = inner
+                        .bindings
+                        .iter()
+                        .filter(|binding| matches!(binding, HookBinding::DoubleTap(tap) if tap.keys.contains(&key)))
+                        .cloned()
+                        .collect();
+                    for binding in tapped {
+                        events.push((binding.clone(), ShortcutState::Pressed));
+                        events.push((binding, ShortcutState::Released));
+                    }
+                } else {
+                    inner.last_tap = Some((key, now));
+                }
+            } else {
+                inner.last_tap = None;
+            }
+        }
+    }
+    drop(inner);
+
+    // Shortcut handlers expect the main thread, like the plugin's own handler
+    for (binding, shortcut_state) in events {
+        let handle = app.clone();
+        if let Err(e) = app.run_on_main_thread(move || {
+            shortcuts::handle_hook_event(&handle, &binding, shortcut_state);
+        }) {
+            eprintln!("Failed to dispatch key hook event: {}", e);
+        }
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn start_listener<R: Runtime>(app: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let handle = app.clone();
+        let result = rdev::listen(move |event| match event.event_type {
+            rdev::EventType::KeyPress(key) => {
+                if let Some(name) = key_name(key) {
+                    offer_capture(&handle, &held_modifiers(&handle), name);
+                }
+                process_key(&handle, map_key(key), true)
+            }
+            rdev::EventType::KeyRelease(key) => process_key(&handle, map_key(key), false),
+            _ => {}
+        });
+
+        // listen only returns when the hook could not be installed, e.g. on Wayland
+        if let Err(e) = result {
+            eprintln!("Failed to start keyboard hook: {:?}", e);
+            let state = app.state::<KeyHookState>();
+            let mut inner = match state.inner.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            inner.error = Some(format!("{:?}", e));
+        }
+    });
+}
+
+/// Decide what the macOS tap does with one key event, true swallows it.
+/// Only keys of configured fn combos are swallowed, everything else passes through.
+#[cfg(target_os = "macos")]
+fn process_fn_key<R: Runtime>(
+    app: &AppHandle<R>,
+    keycode: u16,
+    mods: FnMods,
+    has_fn: bool,
+    pressed: bool,
+    is_repeat: bool,
+) -> bool {
+    let state = app.state::<KeyHookState>();
+    let mut inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    let mut events = Vec::new();
+    let swallow = if pressed {
+        let matched = inner.bindings.iter().find(|binding| {
+            matches!(binding, HookBinding::Fn(combo) if has_fn && combo.keycode == keycode && combo.mods == mods)
+        });
+        match matched.cloned() {
+            Some(binding) => {
+                if !is_repeat && !inner.active.contains(&binding) {
+                    inner.active.push(binding.clone());
+                    events.push((binding, ShortcutState::Pressed));
+                }
+                true
+            }
+            None => false,
+        }
+    } else {
+        // Fn may already be up when the key is released, so match on the key alone
+        let (released, still_active): (Vec<HookBinding>, Vec<HookBinding>) = inner
+            .active
+            .drain(..)
+            .partition(|binding| matches!(binding, HookBinding::Fn(combo) if combo.keycode == keycode));
+        inner.active = still_active;
+        let swallow = !released.is_empty();
+        events.extend(released.into_iter().map(|binding| (binding, ShortcutState::Released)));
+        swallow
+    };
+    drop(inner);
+
+    for (binding, shortcut_state) in events {
+        let handle = app.clone();
+        if let Err(e) = app.run_on_main_thread(move || {
+            shortcuts::handle_hook_event(&handle, &binding, shortcut_state);
+        }) {
+            eprintln!("Failed to dispatch key hook event: {}", e);
+        }
+    }
+    swallow
+}
+
+#[cfg(target_os = "macos")]
+fn start_listener<R: Runtime>(app: AppHandle<R>) {
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use core_graphics::event::{
+        CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+        CGEventType, EventField,
+    };
+
+    std::thread::spawn(move || {
+        let handle = app.clone();
+        let tap = CGEventTap::new(
+            CGEventTapLocation::Session,
+            CGEventTapPlacement::HeadInsertEventTap,
+            CGEventTapOptions::Default,
+            vec![CGEventType::KeyDown, CGEventType::KeyUp],
+            move |_proxy, event_type, event| {
+                let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as u16;
+                let is_repeat = event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0;
+                let flags = event.get_flags();
+                let mods = FnMods {
+                    cmd: flags.contains(CGEventFlags::CGEventFlagCommand),
+                    ctrl: flags.contains(CGEventFlags::CGEventFlagControl),
+                    alt: flags.contains(CGEventFlags::CGEventFlagAlternate),
+                    shift: flags.contains(CGEventFlags::CGEventFlagShift),
+                };
+                let has_fn = flags.contains(CGEventFlags::CGEventFlagSecondaryFn);
+                let pressed = matches!(event_type, CGEventType::KeyDown);
+
+                // A captured chord is swallowed so it doesn't also type into the app
+                if pressed && !is_repeat {
+                    if let Some(name) = mac_key_name(keycode) {
+                        let modifiers: Vec<&str> = [
+                            (has_fn, "fn"),
+                            (mods.cmd, "cmd"),
+                            (mods.ctrl, "ctrl"),
+                            (mods.alt, "alt"),
+                            (mods.shift, "shift"),
+                        ]
+                        .into_iter()
+                        .filter_map(|(held, modifier)| held.then_some(modifier))
+                        .collect();
+                        if offer_capture(&handle, &modifiers, name) {
+                            return None;
+                        }
+                    }
+                }
+
+                // Returning None drops the event, anything else is passed on untouched
+                if process_fn_key(&handle, keycode, mods, has_fn, pressed, is_repeat) {
+                    None
+                } else {
+                    Some(event.clone())
+                }
+            },
+        );
+
+        match tap {
+            Ok(tap) => unsafe {
+                let Ok(source) = tap.mach_port.create_runloop_source(0) else {
+                    eprintln!("Failed to create run loop source for the keyboard tap");
+                    return;
+                };
+                CFRunLoop::get_current().add_source(&source, kCFRunLoopCommonModes);
+                tap.enable();
+                CFRunLoop::run_current();
+            },
+            Err(()) => {
+                eprintln!("Failed to create keyboard event tap, accessibility permission missing?");
+                let state = app.state::<KeyHookState>();
+                let mut inner = match state.inner.lock() {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                inner.error = Some("Accessibility permission is required for fn shortcuts".to_string());
+            }
+        }
+    });
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn start_listener<R: Runtime>(_app: AppHandle<R>) {}
+
+/// Modifiers held right now as shortcut names, either side counts
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn held_modifiers<R: Runtime>(app: &AppHandle<R>) -> Vec<&'static str> {
+    let state = app.state::<KeyHookState>();
+    let inner = match state.inner.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let mut modifiers: Vec<&'static str> = inner
+        .held
+        .iter()
+        .map(|key| match key {
+            HookKey::LeftCtrl | HookKey::RightCtrl => "ctrl",
+            HookKey::LeftShift | HookKey::RightShift => "shift",
+            HookKey::LeftAlt | HookKey::RightAlt => "alt",
+            HookKey::LeftMeta | HookKey::RightMeta => "super",
+        })
+        .collect();
+    modifiers.dedup();
+    modifiers
+}
+
+/// Shortcut names for the non-modifier keys a capture accepts
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn key_name(key: rdev::Key) -> Option<&'static str> {
+    use rdev::Key;
+    let name = match key {
+        Key::KeyA => "a", Key::KeyB => "b", Key::KeyC => "c", Key::KeyD => "d", Key::KeyE => "e",
+        Key::KeyF => "f", Key::KeyG => "g", Key::KeyH => "h", Key::KeyI => "i", Key::KeyJ => "j",
+        Key::KeyK => "k", Key::KeyL => "l", Key::KeyM => "m", Key::KeyN => "n", Key::KeyO => "o",
+        Key::KeyP => "p", Key::KeyQ => "q", Key::KeyR => "r", Key::KeyS => "s", Key::KeyT => "t",
+        Key::KeyU => "u", Key::KeyV => "v", Key::KeyW => "w", Key::KeyX => "x", Key::KeyY => "y",
+        Key::KeyZ => "z",
+        Key::Num0 => "0", Key::Num1 => "1", Key::Num2 => "2", Key::Num3 => "3", Key::Num4 => "4",
+        Key::Num5 => "5", Key::Num6 => "6", Key::Num7 => "7", Key::Num8 => "8", Key::Num9 => "9",
+        Key::F1 => "f1", Key::F2 => "f2", Key::F3 => "f3", Key::F4 => "f4", Key::F5 => "f5",
+        Key::F6 => "f6", Key::F7 => "f7", Key::F8 => "f8", Key::F9 => "f9", Key::F10 => "f10",
+        Key::F11 => "f11", Key::F12 => "f12",
+        Key::Space => "space", Key::Return => "enter", Key::Tab => "tab", Key::Escape => "escape",
+        Key::Backspace => "backspace", Key::BackSlash => "backslash", Key::Slash => "slash",
+        Key::Comma => "comma", Key::Dot => "period", Key::SemiColon => "semicolon",
+        Key::Quote => "quote", Key::LeftBracket => "bracketleft", Key::RightBracket => "bracketright",
+        Key::Minus => "minus", Key::Equal => "equal", Key::BackQuote => "backquote",
+        Key::UpArrow => "up", Key::DownArrow => "down", Key::LeftArrow => "left", Key::RightArrow => "right",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn map_key(key: rdev::Key) -> Option<HookKey> {
+    match key {
+        rdev::Key::ControlLeft => Some(HookKey::LeftCtrl),
+        rdev::Key::ControlRight => Some(HookKey::RightCtrl),
+        rdev::Key::ShiftLeft => Some(HookKey::LeftShift),
+        rdev::Key::ShiftRight => Some(HookKey::RightShift),
+        rdev::Key::Alt => Some(HookKey::LeftAlt),
+        rdev::Key::AltGr => Some(HookKey::RightAlt),
+        rdev::Key::MetaLeft => Some(HookKey::LeftMeta),
+        rdev::Key::MetaRight => Some(HookKey::RightMeta),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    #[test]
+    fn captured_chords_need_a_modifier() {
+        assert_eq!(capture_chord(&[], "a"), None);
+        assert_eq!(capture_chord(&["shift", "ctrl"], "a").as_deref(), Some("ctrl+shift+a"));
+    }
+
+    #[test]
+    fn captured_chords_parse_as_shortcuts() {
+        for key in ["a", "0", "f12", "space", "enter", "period", "bracketleft", "backquote", "up"] {
+            let chord = capture_chord(&["ctrl", "alt"], key).unwrap();
+            assert!(chord.parse::<Shortcut>().is_ok(), "{} does not parse", chord);
+        }
+    }
+
+    #[test]
+    fn mac_key_names_round_trip() {
+        assert_eq!(mac_keycode("return"), Some(36));
+        assert_eq!(mac_key_name(36), Some("enter"));
+        assert_eq!(mac_key_name(mac_keycode("k").unwrap()), Some("k"));
+    }
+}
@@ -1,8 +1,33 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 mod activate;
+mod active_app;
 mod api;
+mod api_keys;
+mod attention;
+mod audio;
+mod autostart;
+mod capture;
+pub mod cli;
+mod deep_link;
+mod history;
+mod key_hook;
+mod ocr;
+mod permissions;
+mod portal_shortcuts;
+mod redaction;
+mod screenshot_history;
+mod screenshot_save;
+mod session_lock;
+mod settings;
 mod shortcuts;
+mod slide;
+mod startup;
+mod ticker;
+mod tray;
+mod tts;
 mod window;
+mod window_settings;
+mod window_state;
 mod db;
 use base64::Engine;
 use image::codecs::png::PngEncoder;
@@ -68,21 +93,54 @@ pub fn run() {
         .unwrap_or("")
         .to_string();
     let mut builder = tauri::Builder::default()
+        // A second launch forwards its flags to the running instance and exits
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            cli::run_actions(app, argv, true);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(
             tauri_plugin_sql::Builder::default()
                 .add_migrations("sqlite:pluely.db", db::migrations())
                 .build(),
         )
         .manage(AudioState::default())
-        .manage(shortcuts::WindowVisibility {
-            is_hidden: Mutex::new(false),
-        })
+        .manage(audio::AudioCaptureState::default())
+        .manage(window::WindowState::default())
         .manage(shortcuts::RegisteredShortcuts::default())
+        .manage(shortcuts::PushToTalkState::default())
+        .manage(shortcuts::RecordingTimer::default())
+        .manage(shortcuts::ShortcutCaptureState::default())
+        .manage(shortcuts::ShortcutDebounce::default())
+        .manage(active_app::FocusRestore::default())
+        .manage(active_app::FrontmostCache::default())
+        .manage(shortcuts::ShortcutBlocklist::default())
+        .manage(shortcuts::LastQueryState::default())
+        .manage(shortcuts::ShortcutProfiles::default())
+        .manage(shortcuts::LastResponseState::default())
+        .manage(key_hook::KeyHookState::default())
+        .manage(portal_shortcuts::PortalShortcuts::default())
+        .manage(capture::CaptureSettings::default())
+        .manage(capture::RegionSelection::default())
+        .manage(capture::ScreenshotCountdown::default())
+        .manage(capture::DuplicateFilter::default())
+        .manage(redaction::Redactions::default())
+        .manage(screenshot_history::ScreenshotHistory::default())
+        .manage(shortcuts::ClickThroughState::default())
+        .manage(api::ActiveRequestState::default())
+        .manage(tts::TtsState::default())
+        .manage(window_state::WindowStateStore::default())
+        .manage(window::SummonSettings::default())
+        .manage(settings::Settings::default())
+        .manage(slide::SlideState::default())
+        .manage(history::HistoryState::default())
+        .manage(startup::StartupQueue::default())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_keychain::init())
         .plugin(tauri_plugin_shell::init()) // Add shell plugin
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(posthog_init(PostHogConfig {
             api_key: posthog_api_key,
             options: Some(PostHogOptions {
@@ -100,14 +158,156 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             greet,
             get_app_version,
+            startup::frontend_ready,
             window::set_window_height,
+            window_state::reset_window_position,
+            window::set_summon_to_cursor_monitor,
+            window::set_window_opacity,
+            window::set_content_protection,
+            window::get_content_protection_support,
+            window::set_hide_on_blur,
+            window::set_window_preset,
+            window::set_visible_on_all_workspaces,
+            window::set_fullscreen_behavior,
+            window::set_window_anchor,
+            window::open_settings_window,
+            window::get_window_state,
+            window::set_skip_switcher,
+            window::set_float_over_fullscreen,
+            window::set_close_hides_to_tray,
+            window::set_webview_zoom,
+            window::set_position_locked,
+            settings::get_setting,
+            settings::set_setting,
+            settings::get_all_settings,
+            api_keys::set_api_key,
+            api_keys::get_api_key,
+            api_keys::delete_api_key,
+            api_keys::list_api_key_providers,
+            api_keys::migrate_plaintext_keys,
+            history::create_session,
+            history::append_message,
+            history::list_sessions,
+            history::get_session,
+            history::delete_session,
+            history::clear_history,
+            tray::quit_app,
+            attention::request_attention,
+            attention::set_attention_settings,
+            ticker::show_response_overlay,
+            ticker::hide_response_overlay,
+            slide::set_slide_animation,
             capture_to_base64,
+            capture::capture_screen,
+            capture::set_native_screenshots,
+            capture::set_exclude_window_from_capture,
+            capture::capture_region,
+            capture::capture_active_window,
+            capture::capture_window_at_cursor,
+            capture::finish_region_selection,
+            capture::set_screenshot_mode,
+            capture::set_screenshot_output,
+            capture::copy_screenshot_to_clipboard,
+            capture::set_copy_screenshots_to_clipboard,
+            capture::set_screenshot_shortcut_delay,
+            capture::cancel_screenshot,
+            capture::list_monitors,
+            capture::set_screenshot_monitor,
+            capture::set_duplicate_screenshot_filter,
+            screenshot_history::list_recent_screenshots,
+            screenshot_history::get_screenshot,
+            screenshot_history::clear_screenshot_history,
+            screenshot_history::set_screenshot_history_limit,
+            ocr::ocr_screenshot,
+            audio::start_mic_capture,
+            audio::stop_mic_capture,
+            audio::list_audio_input_devices,
+            audio::set_audio_input_device,
+            audio::start_system_audio_stream,
+            audio::stop_system_audio_stream,
+            audio::set_native_audio_capture,
+            audio::set_level_meter_enabled,
+            audio::set_vad_config,
+            audio::set_noise_suppression,
+            audio::encode_wav,
+            audio::set_auto_wav_encoding,
+            audio::set_recording_format,
+            audio::set_mixed_mode,
+            audio::set_max_recording_duration,
+            audio::pause_audio_capture,
+            audio::resume_audio_capture,
+            audio::list_audio_sinks,
+            audio::set_system_audio_sink,
+            audio::set_rolling_buffer,
+            audio::capture_recent_audio,
+            audio::set_capture_gain,
+            audio::set_capture_muted,
+            audio::set_recordings_config,
+            audio::list_recordings,
+            audio::delete_recording,
+            audio::start_streaming_transcription,
+            audio::stop_streaming_transcription,
+            audio::set_sleep_inhibit_enabled,
+            audio::get_sleep_inhibited,
+            tts::speak_text,
+            tts::stop_speaking,
+            tts::list_tts_voices,
+            audio::transcribe_local,
+            audio::download_whisper_model,
+            audio::list_whisper_models,
+            redaction::apply_redactions,
+            redaction::get_monitor_redactions,
+            redaction::set_monitor_redactions,
+            screenshot_save::set_screenshot_saving,
+            screenshot_save::reveal_in_file_manager,
+            permissions::check_permission,
+            permissions::request_permission,
             shortcuts::check_shortcuts_registered,
+            shortcuts::get_shortcut_status,
+            shortcuts::list_registered_shortcuts,
             shortcuts::get_registered_shortcuts,
             shortcuts::update_shortcuts,
+            shortcuts::set_shortcuts,
+            shortcuts::reset_shortcuts,
+            shortcuts::reset_shortcuts_to_defaults,
+            shortcuts::list_shortcut_profiles,
+            shortcuts::save_shortcut_profile,
+            shortcuts::activate_shortcut_profile,
+            shortcuts::export_shortcuts_config,
+            shortcuts::import_shortcuts_config,
+            shortcuts::set_shortcut_scope,
+            shortcuts::reregister_shortcuts,
+            shortcuts::suspend_shortcuts,
+            shortcuts::resume_shortcuts,
+            shortcuts::begin_shortcut_capture,
+            shortcuts::cancel_shortcut_capture,
             shortcuts::validate_shortcut_key,
+            shortcuts::describe_shortcut_key,
             shortcuts::set_app_icon_visibility,
             shortcuts::set_always_on_top,
+            shortcuts::get_always_on_top,
+            shortcuts::set_click_through,
+            shortcuts::get_click_through,
+            shortcuts::set_click_through_timeout,
+            shortcuts::sync_window_visibility,
+            active_app::set_restore_focus,
+            active_app::get_frontmost_app,
+            shortcuts::set_last_query_available,
+            shortcuts::set_last_response,
+            shortcuts::set_scroll_amount,
+            shortcuts::set_screenshot_prompt_template,
+            shortcuts::get_screenshot_prompt_template,
+            shortcuts::notify_provider_changed,
+            shortcuts::set_double_tap_window,
+            shortcuts::set_shortcut_blocklist,
+            shortcuts::get_shortcut_blocklist,
+            shortcuts::set_push_to_talk,
+            shortcuts::recording_stopped,
+            shortcuts::get_push_to_talk,
+            shortcuts::set_hold_tap_threshold,
+            tray::set_tray_recording_state,
+            autostart::set_autostart,
+            autostart::get_autostart,
             activate::activate_license_api,
             activate::deactivate_license_api,
             activate::validate_license_api,
@@ -118,6 +318,7 @@ pub fn run() {
             activate::secure_storage_remove,
             api::transcribe_audio,
             api::chat_stream,
+            api::set_request_active,
             api::fetch_models,
             api::create_system_prompt,
             api::check_license_status,
@@ -131,20 +332,45 @@ pub fn run() {
             speaker::get_capture_status,
             speaker::get_audio_sample_rate,
             speaker::list_system_audio_devices,
-            speaker::get_default_audio_device
+            speaker::get_default_audio_device,
+            speaker::list_audio_sessions
         ])
+        .on_window_event(|window, event| {
+            shortcuts::handle_window_event(window, event);
+            window::handle_window_event(window, event);
+            window_state::handle_window_event(window, event);
+        })
         .setup(|app| {
             // Setup main window positioning
             window::setup_main_window(app).expect("Failed to setup main window");
+            window_state::restore(app.handle());
+            settings::init(app.handle());
+            window_settings::init(app.handle());
+            history::init(app.handle());
+            // The saved position may be on a display that is gone
+            window_state::ensure_on_screen(app.handle());
+            window_state::watch_monitors(app.handle());
+            ticker::init(app.handle());
+            screenshot_history::init(app.handle());
+            redaction::init(app.handle());
+
+            // Launched at login with --hidden, the window stays hidden until toggled
+            if autostart::is_start_hidden() {
+                let state = app.state::<window::WindowState>();
+                match state.is_hidden.lock() {
+                    Ok(mut guard) => *guard = true,
+                    Err(poisoned) => *poisoned.into_inner() = true,
+                };
+            }
 
             // Initialize global shortcut plugin with centralized handler
             app.handle().plugin(
                 tauri_plugin_global_shortcut::Builder::new()
                     .with_handler(move |app, shortcut, event| {
-                        use tauri_plugin_global_shortcut::{Shortcut, ShortcutState};
-                        
-                        if event.state() == ShortcutState::Pressed {
-                            // Get registered shortcuts and find matching action
+                        use tauri_plugin_global_shortcut::Shortcut;
+
+                        // Get registered shortcuts and find matching action
+                        let action = {
                             let state = app.state::<shortcuts::RegisteredShortcuts>();
                             let registered = match state.shortcuts.lock() {
                                 Ok(guard) => guard,
@@ -153,24 +379,46 @@ pub fn run() {
                                     poisoned.into_inner()
                                 }
                             };
-                            
-                            // Find which action this shortcut maps to
-                            for (action_id, shortcut_str) in registered.iter() {
-                                if let Ok(s) = shortcut_str.parse::<Shortcut>() {
-                                    if &s == shortcut {
-                                        eprintln!("Shortcut triggered: {} ({})", action_id, shortcut_str);
-                                        shortcuts::handle_shortcut_action(&app, action_id);
-                                        break;
-                                    }
-                                }
-                            }
+
+                            registered.iter().find_map(|(action_id, keys)| {
+                                keys.iter()
+                                    .find(|key| key.parse::<Shortcut>().ok().as_ref() == Some(shortcut))
+                                    .map(|key| (action_id.clone(), key.clone()))
+                            })
+                        };
+
+                        if let Some((action_id, shortcut_str)) = action {
+                            eprintln!("Shortcut {:?}: {} ({})", event.state(), action_id, shortcut_str);
+                            shortcuts::handle_shortcut_event(app, &action_id, &shortcut_str, event.state());
                         }
                     })
                     .build(),
             ).expect("Failed to initialize global shortcut plugin");
+            portal_shortcuts::start(app.handle());
             if let Err(e) = shortcuts::setup_global_shortcuts(app.handle()) {
                 eprintln!("Failed to setup global shortcuts: {}", e);
             }
+            shortcuts::start_resume_watcher(app.handle());
+            if let Err(e) = tray::setup_tray(app.handle()) {
+                eprintln!("Failed to setup tray icon: {}", e);
+            }
+            deep_link::setup_deep_links(app.handle());
+            cli::run_actions(app.handle(), std::env::args().collect(), false);
+
+            if let Some(window) = app.get_webview_window("main") {
+                window::apply_saved_zoom(app.handle(), &window);
+            }
+
+            // Shown only now that the saved position is applied
+            if !autostart::is_start_hidden() {
+                if let Some(window) = app.get_webview_window("main") {
+                    if let Err(e) = window.show() {
+                        eprintln!("Failed to show window: {}", e);
+                    }
+                    // Not focused, so the focus event won't register the scoped shortcuts
+                    shortcuts::after_window_shown(app.handle(), &window);
+                }
+            }
 
             Ok(())
         });
@@ -181,7 +429,26 @@ pub fn run() {
         builder = builder.plugin(tauri_plugin_macos_permissions::init());
     }
 
+    // Create the main window hidden so it doesn't flash at the default spot
+    // before its saved position is restored, --hidden keeps it that way
+    let mut context = tauri::generate_context!();
+    for window in context.config_mut().app.windows.iter_mut() {
+        window.visible = false;
+    }
+
     builder
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::ExitRequested { .. } => window_state::flush(app),
+            // Clicking the dock icon, bouncing after request_attention or not,
+            // shows the hidden window the same way the toggle does
+            #[cfg(target_os = "macos")]
+            tauri::RunEvent::Reopen { .. } => {
+                if !shortcuts::is_main_window_visible(app) {
+                    shortcuts::handle_shortcut_action(app, "toggle_window", "");
+                }
+            }
+            _ => {}
+        });
 }
@@ -2,5 +2,6 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    pluely_lib::cli::validate_args_or_exit();
     pluely_lib::run()
 }
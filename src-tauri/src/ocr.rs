@@ -0,0 +1,320 @@
+// Text recognition on screenshots, so text-heavy captures can go to a cheaper
+// model as text. Windows uses the built-in Windows.Media.Ocr engine and macOS
+// the Vision framework. Linux uses tesseract, built only with the tesseract-ocr
+// feature as it needs the tesseract and leptonica libraries, without it the
+// command fails so the frontend can hide the option.
+use crate::screenshot_history;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+#[cfg(all(not(any(target_os = "windows", target_os = "macos")), not(feature = "tesseract-ocr")))]
+const NOT_BUILT: &str = "Pluely was not built with OCR support";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrLine {
+    pub text: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Boxes are in pixels of the image that was recognized
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrResult {
+    pub text: String,
+    pub lines: Vec<OcrLine>,
+    pub language: String,
+}
+
+/// BCP 47 tag of the system locale, "en-US" when it can't be read
+fn system_language() -> String {
+    sys_locale::get_locale().unwrap_or_else(|| "en-US".to_string())
+}
+
+/// Screenshot history id, or base64 image data
+fn load_image<R: Runtime>(app: &AppHandle<R>, id_or_data: &str) -> Result<image::RgbaImage, String> {
+    let bytes = match screenshot_history::load(app, id_or_data) {
+        Ok((_, bytes)) => bytes,
+        Err(_) => base64::engine::general_purpose::STANDARD
+            .decode(id_or_data.trim())
+            .map_err(|_| "Not a screenshot id or base64 image".to_string())?,
+    };
+    image::load_from_memory(&bytes)
+        .map(|image| image.to_rgba8())
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+fn recognize<R: Runtime>(_app: &AppHandle<R>, image: image::RgbaImage, language: &str) -> Result<OcrResult, String> {
+    use windows::core::HSTRING;
+    use windows::Globalization::Language;
+    use windows::Graphics::Imaging::{BitmapPixelFormat, SoftwareBitmap};
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Storage::Streams::DataWriter;
+
+    let language = Language::CreateLanguage(&HSTRING::from(language))
+        .map_err(|e| format!("Unsupported OCR language {}: {}", language, e))?;
+    let engine = OcrEngine::TryCreateFromLanguage(&language).map_err(|_| {
+        format!(
+            "No OCR language pack for {} installed",
+            language.LanguageTag().map(|tag| tag.to_string()).unwrap_or_default()
+        )
+    })?;
+
+    // The engine rejects images over its size limit, boxes are scaled back after
+    let width = image.width();
+    let max_dimension = OcrEngine::MaxImageDimension().unwrap_or(10000);
+    let image = crate::capture::downscale(image, Some(max_dimension));
+    let scale = width as f32 / image.width() as f32;
+    let (scaled_width, scaled_height) = image.dimensions();
+
+    let mut pixels = image.into_raw();
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    let writer = DataWriter::new().map_err(|e| format!("Failed to create OCR buffer: {}", e))?;
+    let bitmap = writer
+        .WriteBytes(&pixels)
+        .and_then(|_| writer.DetachBuffer())
+        .and_then(|buffer| {
+            SoftwareBitmap::CreateCopyFromBuffer(
+                &buffer,
+                BitmapPixelFormat::Bgra8,
+                scaled_width as i32,
+                scaled_height as i32,
+            )
+        })
+        .map_err(|e| format!("Failed to create OCR bitmap: {}", e))?;
+
+    let result = engine
+        .RecognizeAsync(&bitmap)
+        .and_then(|operation| operation.get())
+        .map_err(|e| format!("Text recognition failed: {}", e))?;
+
+    let mut lines = Vec::new();
+    for line in result.Lines().map_err(|e| format!("Failed to read OCR lines: {}", e))? {
+        let text = line.Text().map(|text| text.to_string()).unwrap_or_default();
+        let (mut left, mut top, mut right, mut bottom) = (f32::MAX, f32::MAX, 0f32, 0f32);
+        if let Ok(words) = line.Words() {
+            for rect in words.into_iter().filter_map(|word| word.BoundingRect().ok()) {
+                left = left.min(rect.X);
+                top = top.min(rect.Y);
+                right = right.max(rect.X + rect.Width);
+                bottom = bottom.max(rect.Y + rect.Height);
+            }
+        }
+        if text.is_empty() || left > right {
+            continue;
+        }
+        lines.push(OcrLine {
+            text,
+            x: (left * scale).round() as i32,
+            y: (top * scale).round() as i32,
+            width: ((right - left) * scale).round() as u32,
+            height: ((bottom - top) * scale).round() as u32,
+        });
+    }
+
+    Ok(OcrResult {
+        text: lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"),
+        lines,
+        language: language.LanguageTag().map(|tag| tag.to_string()).unwrap_or_default(),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn recognize<R: Runtime>(_app: &AppHandle<R>, image: image::RgbaImage, language: &str) -> Result<OcrResult, String> {
+    use cidre::{cv, ns, vn};
+    use std::ffi::c_void;
+
+    extern "C" fn release_pixels(pixels: *mut c_void, _base_address: *const *const c_void) {
+        drop(unsafe { Box::from_raw(pixels as *mut Vec<u8>) });
+    }
+
+    let (width, height) = image.dimensions();
+    let mut pixels = image.into_raw();
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    // The pixel buffer owns the bytes from here and frees them in release_pixels
+    let pixels = Box::into_raw(Box::new(pixels));
+    let buffer = cv::PixelBuf::with_bytes(
+        width as usize,
+        height as usize,
+        unsafe { (*pixels).as_mut_ptr() } as *mut c_void,
+        width as usize * 4,
+        release_pixels,
+        pixels as *mut c_void,
+        cv::PixelFormat::_32_BGRA,
+        None,
+    )
+    .map_err(|e| format!("Failed to create OCR bitmap: {}", e))?;
+
+    let mut request = vn::RecognizeTextRequest::new();
+    request.set_recognition_level(vn::RequestTextRecognitionLevel::Accurate);
+    request.set_uses_lang_correction(true);
+    let tag = ns::String::with_str(language);
+    request.set_recognition_langs(&ns::Array::<ns::String>::from_slice(&[&tag]));
+
+    let handler = vn::ImageRequestHandler::with_cv_pixel_buf(&buffer, None)
+        .ok_or_else(|| "Failed to load image for OCR".to_string())?;
+    handler
+        .perform(&ns::Array::<vn::Request>::from_slice(&[&request]))
+        .map_err(|e| format!("Text recognition failed: {}", e))?;
+
+    // Vision boxes are normalized with the origin at the bottom left
+    let mut lines = Vec::new();
+    if let Some(observations) = request.results() {
+        for observation in observations.iter() {
+            let candidates = observation.top_candidates(1);
+            let Some(candidate) = candidates.iter().next() else {
+                continue;
+            };
+            let text = candidate.string().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            let rect = observation.bounding_box();
+            lines.push(OcrLine {
+                text,
+                x: (rect.origin.x * width as f64).round() as i32,
+                y: ((1.0 - rect.origin.y - rect.size.height) * height as f64).round() as i32,
+                width: (rect.size.width * width as f64).round() as u32,
+                height: (rect.size.height * height as f64).round() as u32,
+            });
+        }
+    }
+
+    Ok(OcrResult {
+        text: lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"),
+        lines,
+        language: language.to_string(),
+    })
+}
+
+/// Tesseract language code for a BCP 47 tag, tesseract codes pass through
+#[cfg(all(not(any(target_os = "windows", target_os = "macos")), feature = "tesseract-ocr"))]
+fn tesseract_language(language: &str) -> String {
+    let tag = language.replace('_', "-").to_lowercase();
+    let mut parts = tag.split('-');
+    let primary = parts.next().unwrap_or("en");
+    let region = parts.next().unwrap_or("");
+    let code = match (primary, region) {
+        ("zh", "tw") | ("zh", "hk") | ("zh", "hant") => "chi_tra",
+        ("zh", _) => "chi_sim",
+        ("en", _) => "eng",
+        ("de", _) => "deu",
+        ("fr", _) => "fra",
+        ("es", _) => "spa",
+        ("it", _) => "ita",
+        ("pt", _) => "por",
+        ("nl", _) => "nld",
+        ("pl", _) => "pol",
+        ("ru", _) => "rus",
+        ("uk", _) => "ukr",
+        ("tr", _) => "tur",
+        ("ja", _) => "jpn",
+        ("ko", _) => "kor",
+        ("ar", _) => "ara",
+        ("hi", _) => "hin",
+        ("sv", _) => "swe",
+        ("cs", _) => "ces",
+        _ if primary.len() == 3 => return language.to_string(),
+        _ => "eng",
+    };
+    code.to_string()
+}
+
+#[cfg(all(not(any(target_os = "windows", target_os = "macos")), feature = "tesseract-ocr"))]
+fn recognize<R: Runtime>(app: &AppHandle<R>, image: image::RgbaImage, language: &str) -> Result<OcrResult, String> {
+    use leptess::{capi, LepTess};
+
+    let language = tesseract_language(language);
+    // tessdata bundled by tauri.tesseract.conf.json wins over a system install
+    let tessdata = app
+        .path()
+        .resource_dir()
+        .map(|dir| dir.join("tessdata"))
+        .ok()
+        .filter(|dir| dir.exists());
+    let mut tess = LepTess::new(tessdata.as_ref().and_then(|dir| dir.to_str()), &language)
+        .map_err(|e| format!("Failed to load OCR language {}: {}", language, e))?;
+
+    let png = crate::capture::encode_png(&image)?;
+    tess.set_image_from_mem(&png)
+        .map_err(|e| format!("Failed to load image for OCR: {}", e))?;
+    // Screenshots carry no DPI, this keeps tesseract from guessing a tiny one
+    tess.set_source_resolution(144);
+
+    let mut lines = Vec::new();
+    if let Some(boxes) = tess.get_component_boxes(capi::TessPageIteratorLevel_RIL_TEXTLINE, true) {
+        for line_box in &boxes {
+            let geometry = line_box.get_geometry();
+            tess.set_rectangle(geometry.x, geometry.y, geometry.w, geometry.h);
+            let text = tess.get_utf8_text().unwrap_or_default().trim().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            lines.push(OcrLine {
+                text,
+                x: geometry.x,
+                y: geometry.y,
+                width: geometry.w.max(0) as u32,
+                height: geometry.h.max(0) as u32,
+            });
+        }
+    }
+
+    Ok(OcrResult {
+        text: lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"),
+        lines,
+        language,
+    })
+}
+
+#[cfg(all(not(any(target_os = "windows", target_os = "macos")), not(feature = "tesseract-ocr")))]
+fn recognize<R: Runtime>(_app: &AppHandle<R>, _image: image::RgbaImage, _language: &str) -> Result<OcrResult, String> {
+    Err(NOT_BUILT.to_string())
+}
+
+/// Tauri command to recognize text in a screenshot, by history id or base64 data.
+/// Returns a job id at once, the result arrives as ocr-completed or ocr-failed.
+#[tauri::command]
+pub fn ocr_screenshot<R: Runtime>(
+    app: AppHandle<R>,
+    id_or_data: String,
+    language: Option<String>,
+) -> Result<String, String> {
+    let image = load_image(&app, &id_or_data)?;
+    let language = language.unwrap_or_else(system_language);
+    let job_id = uuid::Uuid::new_v4().to_string();
+
+    let handle = app.clone();
+    let job = job_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let recognizer = handle.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || recognize(&recognizer, image, &language))
+            .await
+            .map_err(|e| format!("OCR task failed: {}", e))
+            .and_then(|result| result);
+
+        let Some(window) = handle.get_webview_window("main") else {
+            return;
+        };
+        let emitted = match result {
+            Ok(result) => window.emit("ocr-completed", json!({ "job_id": job, "result": result })),
+            Err(e) => {
+                eprintln!("OCR failed: {}", e);
+                window.emit("ocr-failed", json!({ "job_id": job, "error": e }))
+            }
+        };
+        if let Err(e) = emitted {
+            eprintln!("Failed to emit OCR event: {}", e);
+        }
+    });
+
+    Ok(job_id)
+}
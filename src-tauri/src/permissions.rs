@@ -0,0 +1,142 @@
+// macOS privacy (TCC) permissions for screen recording, microphone and
+// accessibility. Windows and Linux don't gate these, so everything is granted.
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionKind {
+    Screen,
+    Microphone,
+    Accessibility,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    NotDetermined,
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXIsProcessTrustedWithOptions(options: core_foundation::dictionary::CFDictionaryRef) -> bool;
+    static kAXTrustedCheckOptionPrompt: core_foundation::string::CFStringRef;
+}
+
+/// Current status, screen recording and accessibility can't tell denied from
+/// never asked and report denied for both
+#[cfg(target_os = "macos")]
+pub fn check(kind: PermissionKind) -> PermissionStatus {
+    use cidre::av;
+
+    let granted = |granted: bool| {
+        if granted {
+            PermissionStatus::Granted
+        } else {
+            PermissionStatus::Denied
+        }
+    };
+
+    match kind {
+        PermissionKind::Screen => granted(unsafe { CGPreflightScreenCaptureAccess() }),
+        PermissionKind::Accessibility => granted(unsafe { AXIsProcessTrusted() }),
+        PermissionKind::Microphone => {
+            match av::CaptureDevice::authorization_status_for_media_type(av::MediaType::audio()) {
+                av::AuthorizationStatus::Authorized => PermissionStatus::Granted,
+                av::AuthorizationStatus::NotDetermined => PermissionStatus::NotDetermined,
+                _ => PermissionStatus::Denied,
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn check(_kind: PermissionKind) -> PermissionStatus {
+    PermissionStatus::Granted
+}
+
+pub fn is_granted(kind: PermissionKind) -> bool {
+    check(kind) == PermissionStatus::Granted
+}
+
+/// Show the system prompt if macOS still allows one, otherwise open the
+/// matching System Settings pane
+#[cfg(target_os = "macos")]
+fn request<R: Runtime>(app: &AppHandle<R>, kind: PermissionKind) -> Result<(), String> {
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::string::CFString;
+    use tauri_plugin_shell::ShellExt;
+
+    let prompted = match (kind, check(kind)) {
+        (_, PermissionStatus::Granted) => return Ok(()),
+        // The prompt only appears the first time, later calls just return false
+        (PermissionKind::Screen, _) => unsafe { CGRequestScreenCaptureAccess() },
+        (PermissionKind::Accessibility, _) => {
+            let prompt = unsafe { CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt) };
+            let options = CFDictionary::from_CFType_pairs(&[(prompt.as_CFType(), CFBoolean::true_value().as_CFType())]);
+            unsafe { AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef()) }
+        }
+        // Opening an input stream makes macOS ask for the microphone
+        (PermissionKind::Microphone, PermissionStatus::NotDetermined) => {
+            use cpal::traits::{DeviceTrait, HostTrait};
+            if let Some(device) = cpal::default_host().default_input_device() {
+                if let Ok(config) = device.default_input_config() {
+                    let _ = device.build_input_stream(
+                        &config.into(),
+                        |_: &[f32], _: &cpal::InputCallbackInfo| {},
+                        |e| eprintln!("Microphone permission stream error: {}", e),
+                        None,
+                    );
+                }
+            }
+            return Ok(());
+        }
+        (PermissionKind::Microphone, _) => false,
+    };
+    if prompted {
+        return Ok(());
+    }
+
+    let pane = match kind {
+        PermissionKind::Screen => "Privacy_ScreenCapture",
+        PermissionKind::Microphone => "Privacy_Microphone",
+        PermissionKind::Accessibility => "Privacy_Accessibility",
+    };
+    app.shell()
+        .command("open")
+        .args([format!("x-apple.systempreferences:com.apple.preference.security?{}", pane)])
+        .spawn()
+        .map_err(|e| format!("Failed to open system settings: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn request<R: Runtime>(_app: &AppHandle<R>, _kind: PermissionKind) -> Result<(), String> {
+    Ok(())
+}
+
+/// Tauri command to check a macOS privacy permission
+#[tauri::command]
+pub fn check_permission(kind: PermissionKind) -> Result<PermissionStatus, String> {
+    Ok(check(kind))
+}
+
+/// Tauri command to ask for a macOS privacy permission
+#[tauri::command]
+pub fn request_permission<R: Runtime>(app: AppHandle<R>, kind: PermissionKind) -> Result<PermissionStatus, String> {
+    request(&app, kind)?;
+    Ok(check(kind))
+}
@@ -0,0 +1,243 @@
+// Global shortcuts on Wayland through the org.freedesktop.portal.GlobalShortcuts portal.
+// Compositors don't allow X11-style grabs, so the portal binds on our behalf once the
+// user approves its dialog. Actions in the shared registry map to portal shortcut ids.
+use crate::shortcuts::Bindings;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::mpsc;
+
+// State of the portal session, only enabled on Wayland
+#[derive(Default)]
+pub struct PortalShortcuts {
+    enabled: Mutex<bool>,
+    approved: Mutex<Option<HashMap<String, bool>>>, // None until the dialog was answered
+    requests: Mutex<Option<mpsc::UnboundedSender<Bindings>>>,
+    requested: Mutex<Option<Bindings>>, // last set sent, so a show or hide that changes nothing skips the rebind
+}
+
+/// Whether the desktop session is Wayland
+pub fn is_wayland_session() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+    match std::env::var("XDG_SESSION_TYPE") {
+        Ok(session_type) => session_type.eq_ignore_ascii_case("wayland"),
+        Err(_) => std::env::var_os("WAYLAND_DISPLAY").is_some(),
+    }
+}
+
+/// Whether shortcuts go through the portal instead of the global shortcut plugin
+pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<PortalShortcuts>();
+    let enabled = match state.enabled.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    enabled
+}
+
+/// Whether the user approved the portal binding for action_id, None before the dialog
+pub fn is_approved<R: Runtime>(app: &AppHandle<R>, action_id: &str) -> Option<bool> {
+    let state = app.state::<PortalShortcuts>();
+    let approved = match state.approved.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    approved
+        .as_ref()
+        .map(|approved| approved.get(action_id).copied().unwrap_or(false))
+}
+
+/// Ask the portal to bind the given registry, replacing what was bound before
+pub fn request_bind<R: Runtime>(app: &AppHandle<R>, bindings: &Bindings) {
+    let state = app.state::<PortalShortcuts>();
+    {
+        let mut requested = match state.requested.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if requested.as_ref() == Some(bindings) {
+            return;
+        }
+        *requested = Some(bindings.clone());
+    }
+    let requests = match state.requests.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(sender) = requests.as_ref() {
+        if sender.send(bindings.clone()).is_err() {
+            eprintln!("Portal shortcut session is gone");
+        }
+    }
+}
+
+/// Open the portal session on Wayland, call before the shortcuts are registered
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    if !is_wayland_session() {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    {
+        let state = app.state::<PortalShortcuts>();
+        match state.enabled.lock() {
+            Ok(mut guard) => *guard = true,
+            Err(poisoned) => *poisoned.into_inner() = true,
+        }
+        match state.requests.lock() {
+            Ok(mut guard) => *guard = Some(sender),
+            Err(poisoned) => *poisoned.into_inner() = Some(sender),
+        };
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run_session(&app, receiver).await {
+            eprintln!("Global shortcuts portal failed: {}", e);
+            let state = app.state::<PortalShortcuts>();
+            match state.approved.lock() {
+                Ok(mut guard) => *guard = Some(HashMap::new()),
+                Err(poisoned) => *poisoned.into_inner() = Some(HashMap::new()),
+            };
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+async fn run_session<R: Runtime>(
+    app: &AppHandle<R>,
+    mut requests: mpsc::UnboundedReceiver<Bindings>,
+) -> Result<(), String> {
+    use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+    use futures_util::StreamExt;
+    use tauri_plugin_global_shortcut::ShortcutState;
+
+    let proxy = GlobalShortcuts::new().await.map_err(|e| e.to_string())?;
+    let session = proxy.create_session().await.map_err(|e| e.to_string())?;
+    let mut activated = proxy.receive_activated().await.map_err(|e| e.to_string())?;
+    let mut deactivated = proxy.receive_deactivated().await.map_err(|e| e.to_string())?;
+
+    loop {
+        tokio::select! {
+            Some(mut bindings) = requests.recv() => {
+                // Only the latest registry matters if several updates queued up
+                while let Ok(newer) = requests.try_recv() {
+                    bindings = newer;
+                }
+
+                let shortcuts: Vec<NewShortcut> = bindings
+                    .iter()
+                    .filter_map(|(action_id, keys)| {
+                        let trigger = keys.iter().find_map(|key| to_portal_trigger(key))?;
+                        Some(
+                            NewShortcut::new(action_id.as_str(), action_id.replace('_', " "))
+                                .preferred_trigger(trigger.as_str()),
+                        )
+                    })
+                    .collect();
+
+                let bound = match proxy.bind_shortcuts(&session, &shortcuts, None).await {
+                    Ok(request) => match request.response() {
+                        Ok(response) => response
+                            .shortcuts()
+                            .iter()
+                            .map(|shortcut| shortcut.id().to_string())
+                            .collect(),
+                        Err(e) => {
+                            eprintln!("Portal shortcut binding was not approved: {}", e);
+                            Vec::new()
+                        }
+                    },
+                    Err(e) => {
+                        eprintln!("Failed to bind portal shortcuts: {}", e);
+                        Vec::new()
+                    }
+                };
+
+                let approved: HashMap<String, bool> = bindings
+                    .keys()
+                    .map(|action_id| (action_id.clone(), bound.contains(action_id)))
+                    .collect();
+                let state = app.state::<PortalShortcuts>();
+                match state.approved.lock() {
+                    Ok(mut guard) => *guard = Some(approved),
+                    Err(poisoned) => *poisoned.into_inner() = Some(approved),
+                };
+            }
+            Some(event) = activated.next() => {
+                dispatch(app, event.shortcut_id(), ShortcutState::Pressed);
+            }
+            Some(event) = deactivated.next() => {
+                dispatch(app, event.shortcut_id(), ShortcutState::Released);
+            }
+            else => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn run_session<R: Runtime>(
+    _app: &AppHandle<R>,
+    _requests: mpsc::UnboundedReceiver<Bindings>,
+) -> Result<(), String> {
+    Ok(())
+}
+
+/// Run the handler for a portal activation on the main thread, like the plugin does
+#[cfg(target_os = "linux")]
+fn dispatch<R: Runtime>(
+    app: &AppHandle<R>,
+    action_id: &str,
+    state: tauri_plugin_global_shortcut::ShortcutState,
+) {
+    // Suspended or out of scope, the rebind that drops it may still be pending.
+    // Releases still go through so a key held across a hide lets go.
+    if matches!(state, tauri_plugin_global_shortcut::ShortcutState::Pressed)
+        && !crate::shortcuts::is_portal_action_live(app, action_id)
+    {
+        return;
+    }
+
+    let binding = crate::shortcuts::get_registered_shortcuts(app.clone())
+        .ok()
+        .and_then(|registered| registered.get(action_id).and_then(|keys| keys.first().cloned()))
+        .unwrap_or_default();
+
+    let handle = app.clone();
+    let action_id = action_id.to_string();
+    if let Err(e) = app.run_on_main_thread(move || {
+        crate::shortcuts::handle_shortcut_event(&handle, &action_id, &binding, state);
+    }) {
+        eprintln!("Failed to dispatch portal shortcut: {}", e);
+    }
+}
+
+/// Convert "ctrl+shift+a" to the portal trigger format "CTRL+SHIFT+a".
+/// Key hook bindings like "rctrl" can't be expressed and return None.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn to_portal_trigger(key: &str) -> Option<String> {
+    if key.parse::<crate::key_hook::HookBinding>().is_ok() {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    for token in key.split('+').map(|token| token.trim().to_lowercase()) {
+        let part = match token.as_str() {
+            "ctrl" | "control" => "CTRL".to_string(),
+            "alt" | "option" => "ALT".to_string(),
+            "shift" => "SHIFT".to_string(),
+            "cmd" | "command" | "super" | "meta" => "LOGO".to_string(),
+            "escape" | "esc" => "Escape".to_string(),
+            "enter" | "return" => "Return".to_string(),
+            "tab" => "Tab".to_string(),
+            "space" => "space".to_string(),
+            "" => return None,
+            other => other.to_string(),
+        };
+        parts.push(part);
+    }
+    Some(parts.join("+"))
+}
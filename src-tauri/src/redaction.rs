@@ -0,0 +1,227 @@
+// Black rectangles painted over screenshots before they leave the machine.
+// Saved redactions are per monitor and applied to the full-resolution grab,
+// before cropping or downscaling, so nothing survives resampling.
+use crate::capture::{self, ImageFormat, ImageOptions};
+use crate::screenshot_history::{self, StoredScreenshot};
+use base64::Engine;
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+
+const REDACTIONS_FILE: &str = "redactions.json";
+
+// Rectangle in image pixels, a negative x or y counts from the right or bottom
+// edge so "the top-right 400x300" is { x: -400, y: 0, width: 400, height: 300 }
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Saved redactions by monitor id
+#[derive(Default)]
+pub struct Redactions {
+    by_monitor: Mutex<HashMap<u32, Vec<RedactionRect>>>,
+}
+
+fn get_redactions_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    Ok(config_dir.join(REDACTIONS_FILE))
+}
+
+/// Load saved redactions, call during setup
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let path = match get_redactions_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    if !path.exists() {
+        return;
+    }
+
+    let saved = match fs::read_to_string(&path).map(|content| serde_json::from_str(&content)) {
+        Ok(Ok(saved)) => saved,
+        Ok(Err(e)) => {
+            eprintln!("Failed to parse redactions file: {}", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("Failed to read redactions file: {}", e);
+            return;
+        }
+    };
+
+    let state = app.state::<Redactions>();
+    match state.by_monitor.lock() {
+        Ok(mut guard) => *guard = saved,
+        Err(poisoned) => *poisoned.into_inner() = saved,
+    };
+}
+
+/// Paint the rectangles solid black, parts outside the image are ignored
+pub fn black_out(image: &mut RgbaImage, rects: &[RedactionRect]) {
+    let (width, height) = image.dimensions();
+    for rect in rects {
+        let left = if rect.x < 0 { width as i64 + rect.x as i64 } else { rect.x as i64 };
+        let top = if rect.y < 0 { height as i64 + rect.y as i64 } else { rect.y as i64 };
+        let right = (left + rect.width as i64).clamp(0, width as i64) as u32;
+        let bottom = (top + rect.height as i64).clamp(0, height as i64) as u32;
+        let (left, top) = (left.clamp(0, width as i64) as u32, top.clamp(0, height as i64) as u32);
+
+        for y in top..bottom {
+            for x in left..right {
+                image.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+    }
+}
+
+/// Map rectangles from an image of one size onto the same picture at another,
+/// rounding outward so a downscaled rectangle never covers less of the original
+fn scale_rects(rects: &[RedactionRect], from: (u32, u32), to: (u32, u32)) -> Vec<RedactionRect> {
+    let ratio_x = to.0 as f64 / from.0.max(1) as f64;
+    let ratio_y = to.1 as f64 / from.1.max(1) as f64;
+    rects
+        .iter()
+        .map(|rect| {
+            let left = (rect.x as f64 * ratio_x).floor();
+            let top = (rect.y as f64 * ratio_y).floor();
+            let right = ((rect.x as f64 + rect.width as f64) * ratio_x).ceil();
+            let bottom = ((rect.y as f64 + rect.height as f64) * ratio_y).ceil();
+            RedactionRect {
+                x: left as i32,
+                y: top as i32,
+                width: (right - left) as u32,
+                height: (bottom - top) as u32,
+            }
+        })
+        .collect()
+}
+
+/// Apply the saved redactions of a monitor to its full-resolution grab
+pub fn redact_monitor<R: Runtime>(app: &AppHandle<R>, monitor_id: u32, image: &mut RgbaImage) {
+    let state = app.state::<Redactions>();
+    let by_monitor = match state.by_monitor.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(rects) = by_monitor.get(&monitor_id) {
+        black_out(image, rects);
+    }
+}
+
+/// Tauri command to black out parts of a stored screenshot, in pixels of the
+/// stored image. When the history kept the full frame behind a downscaled or
+/// JPEG screenshot the rectangles are painted on that frame and the stored image
+/// is made from it again, otherwise on the stored image itself. The redacted
+/// image replaces the original in the history.
+#[tauri::command]
+pub fn apply_redactions<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    rects: Vec<RedactionRect>,
+) -> Result<StoredScreenshot, String> {
+    let (entry, bytes) = screenshot_history::load(&app, &id)?;
+    let output = capture::output_options(&app);
+    let options = ImageOptions {
+        format: entry.format,
+        quality: output.quality.filter(|_| entry.format == ImageFormat::Jpeg),
+        max_dimension: None,
+    };
+
+    let (image, source) = match screenshot_history::load_source(&app, &entry)? {
+        Some(mut source) => {
+            let rects = scale_rects(&rects, (entry.width, entry.height), source.dimensions());
+            black_out(&mut source, &rects);
+            let image = capture::downscale(source.clone(), Some(entry.width.max(entry.height)));
+            (image, Some(source))
+        }
+        None => {
+            let mut image = image::load_from_memory(&bytes)
+                .map_err(|e| format!("Failed to decode screenshot: {}", e))?
+                .to_rgba8();
+            black_out(&mut image, &rects);
+            (image, None)
+        }
+    };
+
+    let bytes = capture::encode_image(&image, &options)?;
+    let entry = screenshot_history::replace(&app, &id, &bytes, source.as_ref())?;
+    Ok(StoredScreenshot {
+        entry,
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    })
+}
+
+/// Tauri command to get the saved redactions by monitor id
+#[tauri::command]
+pub fn get_monitor_redactions<R: Runtime>(app: AppHandle<R>) -> Result<HashMap<u32, Vec<RedactionRect>>, String> {
+    let state = app.state::<Redactions>();
+    let by_monitor = match state.by_monitor.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    Ok(by_monitor.clone())
+}
+
+/// Tauri command to save the redactions applied to every capture of a monitor,
+/// an empty list removes them
+#[tauri::command]
+pub fn set_monitor_redactions<R: Runtime>(
+    app: AppHandle<R>,
+    monitor: u32,
+    rects: Vec<RedactionRect>,
+) -> Result<(), String> {
+    let state = app.state::<Redactions>();
+    let mut by_monitor = match state.by_monitor.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if rects.is_empty() {
+        by_monitor.remove(&monitor);
+    } else {
+        by_monitor.insert(monitor, rects);
+    }
+    let content =
+        serde_json::to_string_pretty(&*by_monitor).map_err(|e| format!("Failed to serialize redactions: {}", e))?;
+
+    // Written while still locked, through a temp file so a crash never leaves it half written
+    let path = get_redactions_path(&app)?;
+    let partial = path.with_extension("json.tmp");
+    fs::write(&partial, content).map_err(|e| format!("Failed to write redactions file: {}", e))?;
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to replace redactions file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_rects_cover_at_least_the_same_area() {
+        // A 1000x500 screenshot kept as a 400x200 image
+        let rects = [RedactionRect { x: 10, y: 10, width: 21, height: 11 }];
+        let scaled = scale_rects(&rects, (400, 200), (1000, 500));
+        assert_eq!(scaled, vec![RedactionRect { x: 25, y: 25, width: 53, height: 28 }]);
+
+        // Edge-relative rectangles stay anchored to the same edge
+        let rects = [RedactionRect { x: -100, y: 0, width: 100, height: 50 }];
+        let scaled = scale_rects(&rects, (400, 200), (1000, 500));
+        assert_eq!(scaled, vec![RedactionRect { x: -250, y: 0, width: 250, height: 125 }]);
+    }
+}
@@ -0,0 +1,305 @@
+// Recent native captures, kept as files in the app cache dir so re-asking
+// about an older screenshot doesn't hold megabytes of images in memory.
+// Only the index lives in memory, files left over from a previous run are removed.
+use crate::capture::{self, ImageFormat, ScreenCapture, ScreenshotMode};
+use base64::Engine;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
+
+// Directory in the app cache dir holding the files
+const HISTORY_DIR: &str = "screenshots";
+
+const DEFAULT_HISTORY_LIMIT: usize = 10;
+const DEFAULT_HISTORY_MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenshotEntry {
+    pub id: String,
+    pub timestamp_ms: u64,
+    pub monitor: u32,
+    pub mode: ScreenshotMode,
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u64,
+    #[serde(skip)]
+    pub source_bytes: Option<u64>, // full frame kept as PNG when the image is downscaled or lossy
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredScreenshot {
+    #[serde(flatten)]
+    pub entry: ScreenshotEntry,
+    pub data: String, // base64
+}
+
+// Oldest first, evicted by count and by total size
+pub struct ScreenshotHistory {
+    entries: Mutex<VecDeque<ScreenshotEntry>>,
+    limit: Mutex<usize>,
+    max_bytes: Mutex<u64>,
+}
+
+impl Default for ScreenshotHistory {
+    fn default() -> Self {
+        ScreenshotHistory {
+            entries: Mutex::new(VecDeque::new()),
+            limit: Mutex::new(DEFAULT_HISTORY_LIMIT),
+            max_bytes: Mutex::new(DEFAULT_HISTORY_MAX_BYTES),
+        }
+    }
+}
+
+fn history_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get cache directory: {}", e))?;
+    Ok(cache_dir.join(HISTORY_DIR))
+}
+
+fn file_path(dir: &Path, entry: &ScreenshotEntry) -> PathBuf {
+    let extension = match entry.format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Webp => "webp",
+    };
+    dir.join(format!("{}.{}", entry.id, extension))
+}
+
+fn source_path(dir: &Path, entry: &ScreenshotEntry) -> PathBuf {
+    dir.join(format!("{}.full.png", entry.id))
+}
+
+fn total_bytes(entry: &ScreenshotEntry) -> u64 {
+    entry.size_bytes + entry.source_bytes.unwrap_or(0)
+}
+
+/// Write the full frame next to the image, returning its size
+fn write_source(dir: &Path, entry: &ScreenshotEntry, source: &RgbaImage) -> Result<u64, String> {
+    let bytes = capture::encode_png(source)?;
+    fs::write(source_path(dir, entry), &bytes).map_err(|e| format!("Failed to save screenshot {}: {}", entry.id, e))?;
+    Ok(bytes.len() as u64)
+}
+
+/// Remove files from a previous run, call during setup
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    if let Ok(dir) = history_dir(app) {
+        if dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                eprintln!("Failed to clear old screenshots: {}", e);
+            }
+        }
+    }
+}
+
+/// Store a capture and return its id, evicting the oldest ones over the limits.
+/// The source is the full frame behind a downscaled or lossy capture.
+pub fn record<R: Runtime>(
+    app: &AppHandle<R>,
+    capture: &ScreenCapture,
+    source: Option<&RgbaImage>,
+    mode: ScreenshotMode,
+) -> Result<String, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&capture.data)
+        .map_err(|e| format!("Failed to decode screenshot: {}", e))?;
+
+    let mut entry = ScreenshotEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0),
+        monitor: capture.monitor,
+        mode,
+        format: capture.format,
+        width: capture.width,
+        height: capture.height,
+        size_bytes: bytes.len() as u64,
+        source_bytes: None,
+    };
+
+    let dir = history_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create screenshot directory: {}", e))?;
+    fs::write(file_path(&dir, &entry), &bytes).map_err(|e| format!("Failed to save screenshot: {}", e))?;
+    if let Some(source) = source {
+        entry.source_bytes = Some(write_source(&dir, &entry, source)?);
+    }
+
+    let state = app.state::<ScreenshotHistory>();
+    let limit = match state.limit.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let max_bytes = match state.max_bytes.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let mut entries = match state.entries.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    let id = entry.id.clone();
+    entries.push_back(entry);
+    evict(&dir, &mut entries, limit, max_bytes);
+    Ok(id)
+}
+
+/// Drop the oldest entries until both limits hold, the newest always stays
+fn evict(dir: &Path, entries: &mut VecDeque<ScreenshotEntry>, limit: usize, max_bytes: u64) {
+    let mut total: u64 = entries.iter().map(total_bytes).sum();
+    while entries.len() > 1 && (entries.len() > limit.max(1) || total > max_bytes) {
+        let Some(oldest) = entries.pop_front() else {
+            break;
+        };
+        total -= total_bytes(&oldest);
+        if let Err(e) = fs::remove_file(file_path(dir, &oldest)) {
+            eprintln!("Failed to remove screenshot {}: {}", oldest.id, e);
+        }
+        if oldest.source_bytes.is_some() {
+            if let Err(e) = fs::remove_file(source_path(dir, &oldest)) {
+                eprintln!("Failed to remove screenshot {}: {}", oldest.id, e);
+            }
+        }
+    }
+}
+
+/// Tauri command to list stored screenshots, newest first
+#[tauri::command]
+pub fn list_recent_screenshots<R: Runtime>(app: AppHandle<R>) -> Result<Vec<ScreenshotEntry>, String> {
+    let state = app.state::<ScreenshotHistory>();
+    let entries = match state.entries.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    Ok(entries.iter().rev().cloned().collect())
+}
+
+/// Whether a screenshot is still kept
+pub fn contains<R: Runtime>(app: &AppHandle<R>, id: &str) -> bool {
+    let state = app.state::<ScreenshotHistory>();
+    let entries = match state.entries.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    entries.iter().any(|entry| entry.id == id)
+}
+
+/// Read a stored screenshot's entry and encoded bytes
+pub fn load<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<(ScreenshotEntry, Vec<u8>), String> {
+    let entry = {
+        let state = app.state::<ScreenshotHistory>();
+        let entries = match state.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .cloned()
+            .ok_or(format!("No screenshot with id {}", id))?
+    };
+
+    let bytes = fs::read(file_path(&history_dir(app)?, &entry))
+        .map_err(|e| format!("Failed to read screenshot {}: {}", id, e))?;
+    Ok((entry, bytes))
+}
+
+/// Read the full frame kept behind a downscaled or lossy screenshot
+pub fn load_source<R: Runtime>(app: &AppHandle<R>, entry: &ScreenshotEntry) -> Result<Option<RgbaImage>, String> {
+    if entry.source_bytes.is_none() {
+        return Ok(None);
+    }
+    let image = image::open(source_path(&history_dir(app)?, entry))
+        .map_err(|e| format!("Failed to read screenshot {}: {}", entry.id, e))?;
+    Ok(Some(image.to_rgba8()))
+}
+
+/// Overwrite a stored screenshot with edited bytes in the same format, and its
+/// full frame when it keeps one
+pub fn replace<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    bytes: &[u8],
+    source: Option<&RgbaImage>,
+) -> Result<ScreenshotEntry, String> {
+    let state = app.state::<ScreenshotHistory>();
+    let mut entries = match state.entries.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let entry = entries
+        .iter_mut()
+        .find(|entry| entry.id == id)
+        .ok_or(format!("No screenshot with id {}", id))?;
+
+    let dir = history_dir(app)?;
+    fs::write(file_path(&dir, entry), bytes).map_err(|e| format!("Failed to save screenshot {}: {}", id, e))?;
+    entry.size_bytes = bytes.len() as u64;
+    if let Some(source) = source.filter(|_| entry.source_bytes.is_some()) {
+        entry.source_bytes = Some(write_source(&dir, entry, source)?);
+    }
+    Ok(entry.clone())
+}
+
+/// Tauri command to get a stored screenshot with its image data
+#[tauri::command]
+pub fn get_screenshot<R: Runtime>(app: AppHandle<R>, id: String) -> Result<StoredScreenshot, String> {
+    let (entry, bytes) = load(&app, &id)?;
+    Ok(StoredScreenshot {
+        entry,
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    })
+}
+
+/// Tauri command to delete all stored screenshots
+#[tauri::command]
+pub fn clear_screenshot_history<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<ScreenshotHistory>();
+    let mut entries = match state.entries.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    entries.clear();
+
+    let dir = history_dir(&app)?;
+    match fs::remove_dir_all(&dir) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear screenshots: {}", e)),
+    }
+}
+
+/// Tauri command to set how many screenshots and bytes the history keeps
+#[tauri::command]
+pub fn set_screenshot_history_limit<R: Runtime>(
+    app: AppHandle<R>,
+    count: usize,
+    max_bytes: Option<u64>,
+) -> Result<(), String> {
+    let state = app.state::<ScreenshotHistory>();
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_HISTORY_MAX_BYTES);
+    match state.limit.lock() {
+        Ok(mut guard) => *guard = count,
+        Err(poisoned) => *poisoned.into_inner() = count,
+    }
+    match state.max_bytes.lock() {
+        Ok(mut guard) => *guard = max_bytes,
+        Err(poisoned) => *poisoned.into_inner() = max_bytes,
+    }
+
+    let mut entries = match state.entries.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    evict(&history_dir(&app)?, &mut entries, count, max_bytes);
+    Ok(())
+}
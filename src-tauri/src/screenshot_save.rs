@@ -0,0 +1,176 @@
+// Archiving of shortcut screenshots to a folder next to sending them to the model.
+// A folder that can't be written to is reported but never fails the capture.
+use crate::capture::{ImageFormat, ScreenCapture};
+use base64::Engine;
+use std::fs;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+
+// Settings store keys
+const ENABLED_SETTING: &str = "screenshot_save_enabled";
+const DIR_SETTING: &str = "screenshot_dir";
+const FILENAME_PATTERN_SETTING: &str = "screenshot_filename_pattern";
+
+// Supports {date}, {time} and {monitor}
+const DEFAULT_FILENAME_PATTERN: &str = "Pluely {date} at {time}";
+
+// Folder in the user's pictures directory used when none is configured
+const DEFAULT_DIR_NAME: &str = "Pluely";
+
+fn screenshot_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = crate::settings::string(app, DIR_SETTING, "");
+    if !dir.is_empty() {
+        return Ok(PathBuf::from(dir));
+    }
+    app.path()
+        .picture_dir()
+        .map(|pictures| pictures.join(DEFAULT_DIR_NAME))
+        .map_err(|e| format!("Failed to get pictures directory: {}", e))
+}
+
+/// File name from the pattern, without characters file systems reject
+fn file_name(pattern: &str, monitor: u32, format: ImageFormat) -> String {
+    let now = chrono::Local::now();
+    let name = pattern
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H.%M.%S").to_string())
+        .replace("{monitor}", &monitor.to_string());
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c => c,
+        })
+        .collect();
+    let name = name.trim();
+    let name = if name.is_empty() { "Pluely" } else { name };
+
+    let extension = match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Webp => "webp",
+    };
+    format!("{}.{}", name, extension)
+}
+
+/// Path in dir that doesn't exist yet, numbering repeats like "name (2).png"
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let (stem, extension) = name.rsplit_once('.').unwrap_or((name, ""));
+    (2..)
+        .map(|n| dir.join(format!("{} ({}).{}", stem, n, extension)))
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
+
+/// Write a capture when saving is enabled, returning the absolute path
+pub fn save<R: Runtime>(app: &AppHandle<R>, capture: &ScreenCapture) -> Result<Option<PathBuf>, String> {
+    if !crate::settings::bool(app, ENABLED_SETTING, false) {
+        return Ok(None);
+    }
+    let pattern = crate::settings::string(app, FILENAME_PATTERN_SETTING, DEFAULT_FILENAME_PATTERN);
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&capture.data)
+        .map_err(|e| format!("Failed to decode screenshot: {}", e))?;
+    let dir = screenshot_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let path = unique_path(&dir, &file_name(&pattern, capture.monitor, capture.format));
+    fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(Some(fs::canonicalize(&path).unwrap_or(path)))
+}
+
+fn check_dir(dir: &str) -> Result<(), String> {
+    if !Path::new(dir).is_absolute() {
+        return Err(format!("Screenshot folder must be an absolute path: {}", dir));
+    }
+    Ok(())
+}
+
+/// Check a value set_setting would store under one of the saving keys
+pub fn validate_setting(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    match key {
+        ENABLED_SETTING => crate::settings::parse::<bool>(key, value).map(drop),
+        DIR_SETTING => check_dir(&crate::settings::parse::<String>(key, value)?),
+        FILENAME_PATTERN_SETTING => crate::settings::parse::<String>(key, value).map(drop),
+        _ => Ok(()),
+    }
+}
+
+/// Tauri command to configure archiving of shortcut screenshots, a missing dir
+/// or pattern keeps the default. Kept in the settings store.
+#[tauri::command]
+pub fn set_screenshot_saving<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    dir: Option<String>,
+    filename_pattern: Option<String>,
+) -> Result<(), String> {
+    let dir = dir.filter(|dir| !dir.trim().is_empty());
+    if let Some(dir) = &dir {
+        check_dir(dir)?;
+    }
+    let filename_pattern = filename_pattern.filter(|pattern| !pattern.trim().is_empty());
+
+    // Null removes a setting, bringing back the default
+    crate::settings::set(&app, DIR_SETTING, json!(dir))?;
+    crate::settings::set(&app, FILENAME_PATTERN_SETTING, json!(filename_pattern))?;
+    crate::settings::set(&app, ENABLED_SETTING, json!(enabled))
+}
+
+/// Tauri command to show a file selected in Finder, Explorer or the Linux file manager
+#[tauri::command]
+pub fn reveal_in_file_manager<R: Runtime>(app: AppHandle<R>, path: String) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    #[cfg(target_os = "macos")]
+    let result = app
+        .shell()
+        .command("open")
+        .args([std::ffi::OsStr::new("-R"), path.as_os_str()])
+        .spawn();
+
+    #[cfg(target_os = "windows")]
+    let result = app
+        .shell()
+        .command("explorer")
+        .args([format!("/select,{}", path.display())])
+        .spawn();
+
+    // FileManager1 selects the file in Nautilus, Dolphin and others, otherwise open the folder
+    #[cfg(target_os = "linux")]
+    let result = {
+        let uri = tauri::Url::from_file_path(&path)
+            .map(|uri| uri.to_string())
+            .map_err(|_| format!("Invalid path: {}", path.display()))?;
+        let selected = std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+                &format!("array:string:{}", uri),
+                "string:",
+            ])
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if selected {
+            return Ok(());
+        }
+        let folder = path.parent().unwrap_or(&path).to_path_buf();
+        app.shell().command("xdg-open").args([folder.as_os_str()]).spawn()
+    };
+
+    result.map(|_| ()).map_err(|e| format!("Failed to open file manager: {}", e))
+}
@@ -0,0 +1,192 @@
+// Session unlock notifications. Some desktops drop global shortcut grabs while the
+// lock screen is up, and a lock doesn't always sleep, so the resume watcher misses it.
+use std::sync::OnceLock;
+
+static ON_UNLOCK: OnceLock<Box<dyn Fn() + Send + Sync>> = OnceLock::new();
+
+fn unlocked() {
+    if let Some(on_unlock) = ON_UNLOCK.get() {
+        on_unlock();
+    }
+}
+
+/// Call on_unlock from a background thread whenever the user unlocks the session.
+/// Only the first call starts watching.
+pub fn watch_unlock<F: Fn() + Send + Sync + 'static>(on_unlock: F) {
+    if ON_UNLOCK.set(Box::new(on_unlock)).is_err() {
+        return;
+    }
+    if let Err(e) = std::thread::Builder::new()
+        .name("pluely-session-lock".to_string())
+        .spawn(|| {
+            if let Err(e) = listen() {
+                eprintln!("Session unlock notifications are unavailable: {}", e);
+            }
+        })
+    {
+        eprintln!("Failed to start session lock watcher: {}", e);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn listen() -> Result<(), String> {
+    use windows::core::w;
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, HMENU, HWND_MESSAGE, MSG,
+        WINDOW_EX_STYLE, WINDOW_STYLE, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_SESSION_UNLOCK,
+    };
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg == WM_WTSSESSION_CHANGE && wparam.0 as u32 == WTS_SESSION_UNLOCK {
+            unlocked();
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    // WTS only notifies windows, a message-only one is enough
+    unsafe {
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            lpszClassName: w!("PluelySessionLock"),
+            ..Default::default()
+        };
+        if RegisterClassW(&class) == 0 {
+            return Err("Failed to register the session window class".to_string());
+        }
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            w!("PluelySessionLock"),
+            w!(""),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            HMENU::default(),
+            HINSTANCE::default(),
+            None,
+        )
+        .map_err(|e| format!("Failed to create the session window: {}", e))?;
+        WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION)
+            .map_err(|e| format!("Failed to register for session notifications: {}", e))?;
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+            DispatchMessageW(&msg);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn listen() -> Result<(), String> {
+    use core_foundation::base::TCFType;
+    use core_foundation::runloop::CFRunLoop;
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::c_void;
+
+    type Callback = extern "C" fn(*const c_void, *const c_void, CFStringRef, *const c_void, *const c_void);
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFNotificationCenterGetDistributedCenter() -> *const c_void;
+        fn CFNotificationCenterAddObserver(
+            center: *const c_void,
+            observer: *const c_void,
+            callback: Callback,
+            name: CFStringRef,
+            object: *const c_void,
+            suspension_behavior: isize,
+        );
+    }
+    // CFNotificationSuspensionBehaviorDeliverImmediately
+    const DELIVER_IMMEDIATELY: isize = 4;
+
+    extern "C" fn on_notification(
+        _center: *const c_void,
+        _observer: *const c_void,
+        _name: CFStringRef,
+        _object: *const c_void,
+        _user_info: *const c_void,
+    ) {
+        unlocked();
+    }
+
+    let name = CFString::new("com.apple.screenIsUnlocked");
+    unsafe {
+        let center = CFNotificationCenterGetDistributedCenter();
+        if center.is_null() {
+            return Err("No distributed notification center".to_string());
+        }
+        CFNotificationCenterAddObserver(
+            center,
+            std::ptr::null(),
+            on_notification,
+            name.as_concrete_TypeRef(),
+            std::ptr::null(),
+            DELIVER_IMMEDIATELY,
+        );
+    }
+    CFRunLoop::run_current();
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn listen() -> Result<(), String> {
+    use ashpd::zbus::{message::Type, Connection, MatchRule, MessageStream};
+    use futures_util::stream::{self, BoxStream, StreamExt};
+
+    fn rule(interface: &'static str, member: &'static str) -> Result<MatchRule<'static>, String> {
+        Ok(MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface(interface)
+            .and_then(|builder| builder.member(member))
+            .map_err(|e| e.to_string())?
+            .build())
+    }
+
+    tauri::async_runtime::block_on(async {
+        let mut signals: Vec<BoxStream<'static, bool>> = Vec::new();
+
+        // ActiveChanged(false) when the screensaver or lock screen goes away,
+        // GNOME uses its own name for the same interface
+        let session = Connection::session().await.map_err(|e| e.to_string())?;
+        for interface in ["org.freedesktop.ScreenSaver", "org.gnome.ScreenSaver"] {
+            let stream = MessageStream::for_match_rule(rule(interface, "ActiveChanged")?, &session, None)
+                .await
+                .map_err(|e| e.to_string())?;
+            signals.push(
+                stream
+                    .map(|message| matches!(message.map(|message| message.body().deserialize::<bool>()), Ok(Ok(false))))
+                    .boxed(),
+            );
+        }
+
+        // logind tells lockers to let go, e.g. for loginctl unlock-session
+        match Connection::system().await {
+            Ok(system) => {
+                let stream = MessageStream::for_match_rule(rule("org.freedesktop.login1.Session", "Unlock")?, &system, None)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                signals.push(stream.map(|message| message.is_ok()).boxed());
+            }
+            Err(e) => eprintln!("Failed to reach logind, only screensaver unlocks are seen: {}", e),
+        }
+
+        let mut signals = stream::select_all(signals);
+        while let Some(is_unlock) = signals.next().await {
+            if is_unlock {
+                unlocked();
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn listen() -> Result<(), String> {
+    Err("Session lock notifications are not supported on this platform".to_string())
+}
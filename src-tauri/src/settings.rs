@@ -0,0 +1,238 @@
+// App settings shared by the frontend and the backend, kept in settings.json in
+// the app config dir as { version, values }. Reads come from a cache, every set
+// writes the whole file through a temp file and a rename so a crash never
+// leaves it half written, and announces the change to every window.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+// Bump with a step in migrate() when the layout of values changes
+const SCHEMA_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsFile {
+    version: u64,
+    values: Map<String, Value>,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        SettingsFile {
+            version: SCHEMA_VERSION,
+            values: Map::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Settings {
+    cache: RwLock<SettingsFile>,
+}
+
+fn get_settings_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    Ok(config_dir.join(SETTINGS_FILE))
+}
+
+/// Bring a file of any earlier version up to SCHEMA_VERSION. Returns None when
+/// it isn't a settings file at all.
+fn migrate(content: Value) -> Option<SettingsFile> {
+    let mut content = match content {
+        Value::Object(content) => content,
+        _ => return None,
+    };
+    let version = content.get("version").and_then(Value::as_u64);
+    let values = match version {
+        Some(_) => match content.remove("values") {
+            Some(Value::Object(values)) => values,
+            _ => Map::new(),
+        },
+        // Before versioning the file was a flat map of values
+        None => content,
+    };
+    Some(SettingsFile {
+        version: SCHEMA_VERSION,
+        values,
+    })
+}
+
+fn write_file(path: &Path, file: &SettingsFile) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(file).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let partial = path.with_extension("json.tmp");
+    fs::write(&partial, content).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    fs::rename(&partial, path).map_err(|e| format!("Failed to replace settings file: {}", e))
+}
+
+/// Load the settings file, creating or migrating it, call during setup
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let path = match get_settings_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let loaded = if path.exists() {
+        match fs::read_to_string(&path).map(|content| serde_json::from_str::<Value>(&content)) {
+            Ok(Ok(content)) => migrate(content),
+            Ok(Err(e)) => {
+                eprintln!("Failed to parse settings file: {}", e);
+                None
+            }
+            Err(e) => {
+                eprintln!("Failed to read settings file: {}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    // A missing file or one that couldn't be parsed starts over at v1, the
+    // unreadable one is kept aside
+    let file = match loaded {
+        Some(file) => file,
+        None => {
+            if path.exists() {
+                let backup = path.with_extension("json.bak");
+                if let Err(e) = fs::rename(&path, &backup) {
+                    eprintln!("Failed to keep unreadable settings file: {}", e);
+                }
+            }
+            SettingsFile::default()
+        }
+    };
+    if let Err(e) = write_file(&path, &file) {
+        eprintln!("{}", e);
+    }
+
+    let state = app.state::<Settings>();
+    match state.cache.write() {
+        Ok(mut guard) => *guard = file,
+        Err(poisoned) => *poisoned.into_inner() = file,
+    };
+}
+
+/// The stored value of a setting, None when it was never set
+pub fn get<R: Runtime>(app: &AppHandle<R>, key: &str) -> Option<Value> {
+    let state = app.state::<Settings>();
+    let value = match state.cache.read() {
+        Ok(guard) => guard.values.get(key).cloned(),
+        Err(poisoned) => poisoned.into_inner().values.get(key).cloned(),
+    };
+    value
+}
+
+// Typed reads for the backend, so behavior follows the user's settings
+// instead of being hard-coded
+
+/// A boolean setting, default when unset or not a boolean
+pub fn bool<R: Runtime>(app: &AppHandle<R>, key: &str, default: bool) -> bool {
+    get(app, key).and_then(|value| value.as_bool()).unwrap_or(default)
+}
+
+/// An unsigned integer setting, default when unset or not one
+pub fn u64<R: Runtime>(app: &AppHandle<R>, key: &str, default: u64) -> u64 {
+    get(app, key).and_then(|value| value.as_u64()).unwrap_or(default)
+}
+
+/// A string setting, default when unset or not a string
+pub fn string<R: Runtime>(app: &AppHandle<R>, key: &str, default: &str) -> String {
+    get(app, key)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Decode a value given to set_setting for a key a module reads as a type
+pub fn parse<T: DeserializeOwned>(key: &str, value: &Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| format!("Invalid value for setting {}: {}", key, e))
+}
+
+/// Check a value against the module that owns the key, so set_setting can't
+/// store what that module's own command would refuse. Null always passes, it
+/// brings back the default.
+fn validate(key: &str, value: &Value) -> Result<(), String> {
+    if value.is_null() {
+        return Ok(());
+    }
+    crate::window_settings::validate_setting(key, value)?;
+    crate::shortcuts::validate_setting(key, value)?;
+    crate::capture::validate_setting(key, value)?;
+    crate::screenshot_save::validate_setting(key, value)?;
+    crate::audio::validate_setting(key, value)?;
+    crate::api_keys::validate_setting(key, value)
+}
+
+/// Store a setting, write the file and emit setting-changed. A null value
+/// removes the setting.
+pub fn set<R: Runtime>(app: &AppHandle<R>, key: &str, value: Value) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("Setting key cannot be empty".to_string());
+    }
+    let path = get_settings_path(app)?;
+    {
+        let state = app.state::<Settings>();
+        // Held across the write so concurrent sets land in order
+        let mut cache = state
+            .cache
+            .write()
+            .map_err(|e| format!("Failed to update settings: {}", e))?;
+        let mut updated = cache.clone();
+        if value.is_null() {
+            updated.values.remove(key);
+        } else {
+            updated.values.insert(key.to_string(), value.clone());
+        }
+        write_file(&path, &updated)?;
+        *cache = updated;
+    }
+
+    if let Err(e) = app.emit("setting-changed", json!({ "key": key, "value": value })) {
+        eprintln!("Failed to emit setting-changed event: {}", e);
+    }
+    Ok(())
+}
+
+/// Tauri command to read one setting, null when it was never set
+#[tauri::command]
+pub fn get_setting<R: Runtime>(app: AppHandle<R>, key: String) -> Option<Value> {
+    get(&app, &key)
+}
+
+/// Tauri command to store a setting, null removes it. Every window receives
+/// setting-changed { key, value }. Keys other modules read are checked like
+/// their own commands check them; those commands also apply a change right
+/// away, set_setting only stores it.
+#[tauri::command]
+pub fn set_setting<R: Runtime>(app: AppHandle<R>, key: String, value: Value) -> Result<(), String> {
+    validate(&key, &value)?;
+    set(&app, &key, value)
+}
+
+/// Tauri command to read every stored setting
+#[tauri::command]
+pub fn get_all_settings<R: Runtime>(app: AppHandle<R>) -> Result<Map<String, Value>, String> {
+    let state = app.state::<Settings>();
+    let values = state
+        .cache
+        .read()
+        .map_err(|e| format!("Failed to read settings: {}", e))?
+        .values
+        .clone();
+    Ok(values)
+}
@@ -1,29 +1,247 @@
+use crate::key_hook::{self, HookBinding};
+use crate::permissions::{self, PermissionKind, PermissionStatus};
+use crate::window::WindowState;
+use crate::{active_app, api, audio, capture, portal_shortcuts, session_lock, tray};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager, Runtime};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tokio::sync::oneshot;
 
-// State for window visibility
-pub struct WindowVisibility {
-    #[allow(dead_code)]
-    pub is_hidden: Mutex<bool>,
+// action_id -> shortcut keys bound to it
+pub type Bindings = HashMap<String, Vec<String>>;
+
+// File in the app config dir holding the user's shortcut bindings
+const SHORTCUTS_FILE: &str = "shortcuts.json";
+
+// Version of the export_shortcuts_config format
+const SHORTCUTS_SCHEMA_VERSION: u32 = 1;
+
+// Upper limit of bindings per action
+const MAX_BINDINGS_PER_ACTION: usize = 3;
+
+// Platform default bindings (action_id, shortcut_key)
+#[cfg(target_os = "macos")]
+const DEFAULT_SHORTCUTS: &[(&str, &str)] = &[
+    ("toggle_window", "cmd+backslash"),
+    ("system_audio", "cmd+shift+m"),
+    ("audio_recording", "cmd+shift+a"),
+    ("screenshot", "cmd+shift+s"),
+    ("toggle_always_on_top", "cmd+alt+p"),
+    ("toggle_click_through", "cmd+alt+k"),
+    ("cancel_request", "cmd+escape"),
+    ("rerun_last_query", "cmd+alt+r"),
+    ("cycle_ai_provider", "cmd+alt+n"),
+    ("screenshot_and_ask", "cmd+alt+e"),
+    ("clear_conversation", "cmd+alt+x"),
+    ("copy_last_response", "cmd+alt+c"),
+    ("scroll_response_up", "cmd+alt+up"),
+    ("scroll_response_down", "cmd+alt+down"),
+    ("capture_recent_audio", "cmd+alt+b"),
+];
+
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_SHORTCUTS: &[(&str, &str)] = &[
+    ("toggle_window", "ctrl+backslash"),
+    ("system_audio", "ctrl+shift+m"),
+    ("audio_recording", "ctrl+shift+a"),
+    ("screenshot", "ctrl+shift+s"),
+    ("toggle_always_on_top", "ctrl+alt+p"),
+    ("toggle_click_through", "ctrl+alt+k"),
+    ("cancel_request", "ctrl+alt+escape"),
+    ("rerun_last_query", "ctrl+alt+r"),
+    ("cycle_ai_provider", "ctrl+alt+n"),
+    ("screenshot_and_ask", "ctrl+alt+e"),
+    ("clear_conversation", "ctrl+alt+x"),
+    ("copy_last_response", "ctrl+alt+c"),
+    ("scroll_response_up", "ctrl+alt+shift+up"),
+    ("scroll_response_down", "ctrl+alt+shift+down"),
+    ("capture_recent_audio", "ctrl+alt+b"),
+];
+
+// Actions scoped to a visible window unless the user picked another scope
+const DEFAULT_WINDOW_VISIBLE_ACTIONS: &[&str] = &["scroll_response_up", "scroll_response_down"];
+
+// Alternate bindings tried when a default shortcut is taken by another app
+#[cfg(target_os = "macos")]
+const FALLBACK_SHORTCUTS: &[(&str, &[&str])] = &[
+    ("toggle_window", &["cmd+shift+backslash", "cmd+alt+backslash"]),
+    ("system_audio", &["cmd+alt+m"]),
+    ("audio_recording", &["cmd+alt+a"]),
+    ("screenshot", &["cmd+alt+s"]),
+    ("toggle_always_on_top", &["cmd+alt+shift+p"]),
+    ("toggle_click_through", &["cmd+alt+shift+k"]),
+    ("cancel_request", &["cmd+shift+escape"]),
+    ("rerun_last_query", &["cmd+alt+shift+r"]),
+];
+
+#[cfg(not(target_os = "macos"))]
+const FALLBACK_SHORTCUTS: &[(&str, &[&str])] = &[
+    ("toggle_window", &["ctrl+shift+backslash", "ctrl+alt+backslash"]),
+    ("system_audio", &["ctrl+alt+m"]),
+    ("audio_recording", &["ctrl+alt+a"]),
+    ("screenshot", &["ctrl+alt+s"]),
+    ("toggle_always_on_top", &["ctrl+alt+shift+p"]),
+    ("toggle_click_through", &["ctrl+alt+shift+k"]),
+    ("cancel_request", &["ctrl+alt+shift+escape"]),
+    ("rerun_last_query", &["ctrl+alt+shift+r"]),
+];
+
+// Presses of the same shortcut closer together than this are ignored
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(200);
+// Longer for destructive actions so a double press doesn't also hit the next conversation
+const CLEAR_CONVERSATION_DEBOUNCE_WINDOW: Duration = Duration::from_millis(1500);
+
+// How often the resume watcher samples the wall clock, and the jump that counts as a sleep
+const RESUME_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const RESUME_JUMP_THRESHOLD: Duration = Duration::from_secs(30);
+
+const DEFAULT_SCREENSHOT_PROMPT: &str = "explain this";
+
+// Settings store keys of the scroll shortcuts' step and the screenshot-and-ask prompt
+const SCROLL_AMOUNT_SETTING: &str = "scroll_amount";
+const SCREENSHOT_PROMPT_SETTING: &str = "screenshot_prompt_template";
+
+// Largest response mirrored from the frontend for the copy shortcut
+const MAX_LAST_RESPONSE_BYTES: usize = 1024 * 1024;
+
+// Settings store key of the push-to-talk tap threshold
+const TAP_THRESHOLD_SETTING: &str = "push_to_talk_tap_threshold_ms";
+const DEFAULT_TAP_THRESHOLD_MS: u64 = 300;
+const MAX_TAP_THRESHOLD_MS: u64 = 2000;
+const DEFAULT_CAPTURE_TIMEOUT_MS: u64 = 5000;
+const MAX_CAPTURE_TIMEOUT_MS: u64 = 30000;
+
+// Safety timeout for click-through mode, the flag itself is in WindowState
+#[derive(Default)]
+pub struct ClickThroughState {
+    pub timeout_minutes: Mutex<Option<u64>>, // auto-disable after this many minutes
+    timer: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
 }
 
 // State for registered shortcuts
 pub struct RegisteredShortcuts {
-    pub shortcuts: Mutex<HashMap<String, String>>, // action_id -> shortcut_key
+    pub shortcuts: Mutex<Bindings>, // action_id -> registered shortcut keys
+    pub configured: Mutex<Bindings>, // action_id -> keys the user chose, registered or not
+    pub platform_bindings: Mutex<HashMap<String, Bindings>>, // other platform -> imported bindings, kept for export
+    pub failures: Mutex<HashMap<String, Vec<ShortcutFailure>>>, // action_id -> bindings that failed
+    pub suspended: Mutex<bool>, // bindings are kept but not registered with the OS
+    pub scopes: Mutex<HashMap<String, ShortcutScope>>, // actions without an entry are global
 }
 
 impl Default for RegisteredShortcuts {
     fn default() -> Self {
         RegisteredShortcuts {
             shortcuts: Mutex::new(HashMap::new()),
+            configured: Mutex::new(HashMap::new()),
+            platform_bindings: Mutex::new(HashMap::new()),
+            failures: Mutex::new(HashMap::new()),
+            suspended: Mutex::new(false),
+            scopes: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// State for push-to-talk on the audio shortcut
+pub struct PushToTalkState {
+    pub enabled: Mutex<bool>,
+    pub held_since: Mutex<Option<Instant>>, // set between the first press and the release
+    pub started: Mutex<Option<u64>>,        // timer generation of the recording the held press started
+    pub latched: Mutex<Option<u64>>,        // timer generation of a recording a tap left running
+}
+
+impl Default for PushToTalkState {
+    fn default() -> Self {
+        PushToTalkState {
+            enabled: Mutex::new(false),
+            held_since: Mutex::new(None),
+            started: Mutex::new(None),
+            latched: Mutex::new(None),
         }
     }
 }
 
+// Deadline for recordings the frontend runs. Each start and recording_stopped
+// bumps the generation, so timers of earlier recordings do nothing.
+#[derive(Default)]
+pub struct RecordingTimer {
+    pub generation: Mutex<u64>,
+}
+
+// Whether the frontend has a previous query the rerun shortcut can repeat
+#[derive(Default)]
+pub struct LastQueryState {
+    pub available: Mutex<bool>,
+}
+
+// Last AI response mirrored by the frontend, for copying without the window
+#[derive(Default)]
+pub struct LastResponseState {
+    pub text: Mutex<Option<String>>,
+}
+
+// How far one press of the scroll shortcuts moves the response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollAmount {
+    #[default]
+    Line,
+    Page,
+}
+
+
+// Applications in which shortcuts are ignored, matched against FrontmostApp::id
+#[derive(Default)]
+pub struct ShortcutBlocklist {
+    pub apps: Mutex<Vec<String>>,
+}
+
+// State for debouncing repeated shortcut presses
+#[derive(Default)]
+pub struct ShortcutDebounce {
+    last_handled: Mutex<HashMap<String, Instant>>, // action_id -> last accepted press
+    held: Mutex<HashSet<String>>,                  // bindings pressed and not yet released
+}
+
+// State for an in-progress shortcut capture from the settings UI
+#[derive(Default)]
+pub struct ShortcutCaptureState {
+    session: Mutex<Option<CaptureSession>>,
+}
+
+struct CaptureSession {
+    sender: Option<oneshot::Sender<Option<String>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutFailure {
+    pub binding: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutStatus {
+    pub binding: String,
+    pub registered: bool,
+    pub error: Option<String>,
+    pub approved: Option<bool>, // portal backend only, None until the user answered its dialog
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutStatusReport {
+    pub backend: String, // "plugin", or "portal" on Wayland
+    pub suspended: bool,
+    pub input_permission: bool, // macOS accessibility, needed by fn shortcuts
+    pub shortcuts: HashMap<String, Vec<ShortcutStatus>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShortcutBinding {
     pub action: String,
@@ -36,262 +254,2722 @@ pub struct ShortcutsConfig {
     pub bindings: HashMap<String, ShortcutBinding>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutUpdateResult {
+    pub binding: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// When a shortcut is registered with the OS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShortcutScope {
+    #[default]
+    Global,
+    WindowVisible, // only while the overlay is on screen
+}
+
+// Contents of the shortcuts file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedShortcuts {
+    #[serde(default)]
+    bindings: HashMap<String, BindingList>,
+    #[serde(default)]
+    platform_bindings: HashMap<String, Bindings>,
+    #[serde(default)]
+    scopes: HashMap<String, ShortcutScope>,
+    #[serde(default)]
+    profiles: HashMap<String, ShortcutProfile>,
+    #[serde(default)]
+    active_profile: Option<String>,
+}
+
+// Portable shortcut config from export_shortcuts_config
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedShortcuts {
+    schema_version: u32,
+    platform: String, // std::env::consts::OS of the exporting machine
+    #[serde(default)]
+    bindings: Bindings,
+    #[serde(default)]
+    platform_bindings: HashMap<String, Bindings>, // platform -> explicit bindings, never remapped
+    #[serde(default)]
+    scopes: HashMap<String, ShortcutScope>,
+    #[serde(default)]
+    profiles: HashMap<String, ShortcutProfile>,
+    #[serde(default)]
+    active_profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedShortcut {
+    pub action: String, // "profile:action" for profile entries
+    pub binding: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutImportReport {
+    pub applied: bool,
+    pub rejected: Vec<RejectedShortcut>,
+}
+
+// Named set of bindings layered over the defaults, action_id -> binding
+pub type ShortcutProfile = HashMap<String, ProfileBinding>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileBinding {
+    pub binding: BindingList,
+    #[serde(default = "default_profile_enabled")]
+    pub enabled: bool, // false unbinds the action while the profile is active
+}
+
+fn default_profile_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutProfileList {
+    pub active: Option<String>,
+    pub profiles: HashMap<String, ShortcutProfile>,
+}
+
+// Saved shortcut profiles and the one currently applied
+#[derive(Default)]
+pub struct ShortcutProfiles {
+    pub profiles: Mutex<HashMap<String, ShortcutProfile>>,
+    pub active: Mutex<Option<String>>,
+}
+
+// A binding string resolved to the layer that delivers it: the global shortcut
+// plugin for normal chords, the key hook for side-specific modifiers like "rctrl"
+#[derive(Debug, Clone, PartialEq)]
+enum ParsedBinding {
+    Chord(Shortcut),
+    Hook(HookBinding),
+}
+
+// A binding the app owns, as checked against the OS for debugging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredShortcutInfo {
+    pub action: String,
+    pub binding: String,            // as configured
+    pub normalized: Option<String>, // canonical form, None if it no longer parses
+    pub is_registered: bool,
+}
+
+// How a binding string would be registered, for the settings UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutKeyInfo {
+    pub valid: bool,
+    pub syntax: Option<String>, // "chord", "key-hook", "double-tap" or "fn-key"
+    pub error: Option<String>,
+}
+
+// Bindings of an action as saved or submitted, a single key is accepted as well
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BindingList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl BindingList {
+    fn into_vec(self) -> Vec<String> {
+        let keys = match self {
+            BindingList::One(key) => vec![key],
+            BindingList::Many(keys) => keys,
+        };
+        keys.into_iter().filter(|key| !key.is_empty()).collect()
+    }
+}
 
 /// Initialize global shortcuts for the application
 pub fn setup_global_shortcuts<R: Runtime>(
     app: &AppHandle<R>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Let the frontend initialize from localStorage
-    let state = app.state::<RegisteredShortcuts>();
-    let _registered = match state.shortcuts.lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => {
-            eprintln!("Mutex poisoned in setup, recovering...");
-            poisoned.into_inner()
+    let mut bindings = default_shortcuts();
+    let saved = load_saved_shortcuts(app);
+
+    {
+        let state = app.state::<RegisteredShortcuts>();
+        let mut scopes = match state.scopes.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *scopes = default_scopes();
+        scopes.extend(saved.scopes);
+        scopes.remove("toggle_window");
+
+        match state.platform_bindings.lock() {
+            Ok(mut guard) => *guard = saved.platform_bindings,
+            Err(poisoned) => *poisoned.into_inner() = saved.platform_bindings,
+        };
+    }
+
+    // The saved bindings already reflect the active profile, only the names are restored
+    {
+        let state = app.state::<ShortcutProfiles>();
+        match state.profiles.lock() {
+            Ok(mut guard) => *guard = saved.profiles,
+            Err(poisoned) => *poisoned.into_inner() = saved.profiles,
         }
-    };
-    eprintln!("Global shortcuts state initialized, waiting for frontend config");
-    
+        match state.active.lock() {
+            Ok(mut guard) => *guard = saved.active_profile,
+            Err(poisoned) => *poisoned.into_inner() = saved.active_profile,
+        };
+    }
+
+    // Saved bindings override the defaults, actions with unparsable entries keep the default
+    for (action_id, keys) in saved.bindings {
+        let keys = keys.into_vec();
+        if let Some(invalid) = keys.iter().find(|key| parse_binding(key).is_err()) {
+            eprintln!(
+                "Ignoring invalid saved shortcut '{}' for {}, using default",
+                invalid, action_id
+            );
+            continue;
+        }
+        bindings.insert(action_id, keys);
+    }
+    bindings.retain(|_, keys| !keys.is_empty());
+
+    register_bindings(app, &bindings);
+    eprintln!("Global shortcuts initialized for {} actions", bindings.len());
+
     Ok(())
 }
 
-/// Handle shortcut action based on action_id
-pub fn handle_shortcut_action<R: Runtime>(app: &AppHandle<R>, action_id: &str) {
-    match action_id {
-        "toggle_window" => handle_toggle_window(app),
-        "audio_recording" => handle_audio_shortcut(app),
-        "screenshot" => handle_screenshot_shortcut(app),
-        "system_audio" => handle_system_audio_shortcut(app),
-        custom_action => {
-            // Emit custom action event for frontend to handle
-            if let Some(window) = app.get_webview_window("main") {
-                if let Err(e) = window.emit("custom-shortcut-triggered", json!({ "action": custom_action })) {
-                    eprintln!("Failed to emit custom shortcut event: {}", e);
+/// Platform default bindings as an action_id -> shortcut keys map
+pub fn default_shortcuts() -> Bindings {
+    DEFAULT_SHORTCUTS
+        .iter()
+        .map(|(action_id, key)| (action_id.to_string(), vec![key.to_string()]))
+        .collect()
+}
+
+/// Unregister everything and register the given bindings, updating state.
+/// While suspended the bindings are only stored and get registered on resume.
+fn register_bindings<R: Runtime>(app: &AppHandle<R>, bindings: &Bindings) {
+    if let Err(e) = unregister_all_shortcuts(app) {
+        eprintln!("Failed to unregister shortcuts: {}", e);
+    }
+    set_configured_shortcuts(app, bindings.clone());
+
+    if is_suspended(app) {
+        let state = app.state::<RegisteredShortcuts>();
+        let mut registered = match state.shortcuts.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                eprintln!("Mutex poisoned in register_bindings, recovering...");
+                poisoned.into_inner()
+            }
+        };
+        *registered = bindings.clone();
+        drop(registered);
+        set_failures(app, HashMap::new());
+        return;
+    }
+
+    let mut successfully_registered = HashMap::new();
+    let mut failures = HashMap::new();
+
+    for (action_id, keys) in bindings {
+        let mut active: Vec<String> = Vec::new();
+        let mut action_failures = Vec::new();
+
+        for shortcut_str in keys.iter().take(MAX_BINDINGS_PER_ACTION) {
+            if active.contains(shortcut_str) {
+                continue;
+            }
+
+            let error = match register_shortcut_str(app, shortcut_str) {
+                Ok(()) => {
+                    eprintln!("Registered shortcut: {} -> {}", action_id, shortcut_str);
+                    active.push(shortcut_str.clone());
+                    continue;
+                }
+                Err(e) => e,
+            };
+            eprintln!("Failed to register {} shortcut: {}", action_id, error);
+
+            // Only default bindings get fallbacks, custom ones are the user's choice
+            let fallback = if is_default_binding(action_id, shortcut_str) {
+                fallback_shortcuts(action_id)
+                    .iter()
+                    .find(|fallback| register_shortcut_str(app, fallback).is_ok())
+                    .map(|fallback| fallback.to_string())
+            } else {
+                None
+            };
+
+            match &fallback {
+                Some(fallback) => {
+                    eprintln!("Registered fallback shortcut: {} -> {}", action_id, fallback);
+                    active.push(fallback.clone());
+                }
+                None => {
+                    action_failures.push(ShortcutFailure {
+                        binding: shortcut_str.clone(),
+                        error: error.clone(),
+                    });
                 }
             }
+
+            emit_registration_failed(app, action_id, shortcut_str, &error, fallback.as_deref());
+        }
+
+        if !active.is_empty() {
+            successfully_registered.insert(action_id.clone(), active);
+        }
+        if !action_failures.is_empty() {
+            failures.insert(action_id.clone(), action_failures);
         }
     }
+
+    let state = app.state::<RegisteredShortcuts>();
+    {
+        let mut registered = match state.shortcuts.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                eprintln!("Mutex poisoned in register_bindings, recovering...");
+                poisoned.into_inner()
+            }
+        };
+        registered.clear();
+        registered.extend(successfully_registered);
+    }
+    set_failures(app, failures);
+    clear_held_shortcuts(app);
+
+    sync_scoped_shortcuts(app, is_main_window_visible(app));
 }
 
-/// Handle app toggle (hide/show) with input focus and app icon management
-fn handle_toggle_window<R: Runtime>(app: &AppHandle<R>) {
-    // Get the main window
-    let Some(window) = app.get_webview_window("main") else {
-        return;
+fn default_scopes() -> HashMap<String, ShortcutScope> {
+    DEFAULT_WINDOW_VISIBLE_ACTIONS
+        .iter()
+        .map(|action_id| (action_id.to_string(), ShortcutScope::WindowVisible))
+        .collect()
+}
+
+/// Parse and register a single shortcut string
+fn register_shortcut_str<R: Runtime>(app: &AppHandle<R>, shortcut_str: &str) -> Result<(), String> {
+    let binding = parse_binding(shortcut_str).map_err(|e| format!("Invalid shortcut: {}", e))?;
+    register_binding(app, &binding)
+}
+
+/// Parse a binding, plugin chords first and side-specific modifiers second
+fn parse_binding(key: &str) -> Result<ParsedBinding, String> {
+    let chord_error = match key.parse::<Shortcut>() {
+        Ok(shortcut) => return Ok(ParsedBinding::Chord(shortcut)),
+        Err(e) => e.to_string(),
     };
+    match key.parse::<HookBinding>() {
+        Ok(hook_binding) => Ok(ParsedBinding::Hook(hook_binding)),
+        Err(_) => Err(chord_error),
+    }
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        let state = app.state::<WindowVisibility>();
-        let mut is_hidden = state.is_hidden.lock().unwrap();
-        *is_hidden = !*is_hidden;
+/// On Wayland chords are only kept in the registry, the portal binds the whole
+/// registry at once after each change
+fn register_binding<R: Runtime>(app: &AppHandle<R>, binding: &ParsedBinding) -> Result<(), String> {
+    match binding {
+        ParsedBinding::Chord(_) if portal_shortcuts::is_enabled(app) => Ok(()),
+        ParsedBinding::Chord(shortcut) => app
+            .global_shortcut()
+            .register(*shortcut)
+            .map_err(|e| e.to_string()),
+        ParsedBinding::Hook(hook_binding) => key_hook::register(app, hook_binding),
+    }
+}
 
-        if let Err(e) = window.emit("toggle-window-visibility", *is_hidden) {
-            eprintln!("Failed to emit toggle-window-visibility event: {}", e);
+/// Portal chords are released by rebinding the live set in sync_portal_shortcuts
+fn unregister_binding<R: Runtime>(app: &AppHandle<R>, binding: &ParsedBinding) -> Result<(), String> {
+    match binding {
+        ParsedBinding::Chord(_) if portal_shortcuts::is_enabled(app) => Ok(()),
+        ParsedBinding::Chord(shortcut) => app
+            .global_shortcut()
+            .unregister(*shortcut)
+            .map_err(|e| e.to_string()),
+        ParsedBinding::Hook(hook_binding) => key_hook::unregister(app, hook_binding),
+    }
+}
+
+fn is_binding_registered<R: Runtime>(app: &AppHandle<R>, binding: &ParsedBinding) -> bool {
+    match binding {
+        ParsedBinding::Chord(_) if portal_shortcuts::is_enabled(app) => true,
+        ParsedBinding::Chord(shortcut) => app.global_shortcut().is_registered(*shortcut),
+        ParsedBinding::Hook(hook_binding) => key_hook::is_registered(app, hook_binding),
+    }
+}
+
+/// Dispatch a key hook binding to the action bound to it
+pub fn handle_hook_event<R: Runtime>(app: &AppHandle<R>, hook_binding: &HookBinding, state: ShortcutState) {
+    let action = {
+        let registered_state = app.state::<RegisteredShortcuts>();
+        let registered = match registered_state.shortcuts.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        registered.iter().find_map(|(action_id, keys)| {
+            keys.iter()
+                .find(|key| matches!(parse_binding(key), Ok(ParsedBinding::Hook(ref b)) if b == hook_binding))
+                .map(|key| (action_id.clone(), key.clone()))
+        })
+    };
+
+    if let Some((action_id, binding)) = action {
+        eprintln!("Key hook {:?}: {} ({})", state, action_id, binding);
+        handle_shortcut_event(app, &action_id, &binding, state);
+    }
+}
+
+fn is_default_binding(action_id: &str, shortcut_str: &str) -> bool {
+    DEFAULT_SHORTCUTS
+        .iter()
+        .any(|(id, key)| *id == action_id && *key == shortcut_str)
+}
+
+fn fallback_shortcuts(action_id: &str) -> &'static [&'static str] {
+    FALLBACK_SHORTCUTS
+        .iter()
+        .find(|(id, _)| *id == action_id)
+        .map(|(_, fallbacks)| *fallbacks)
+        .unwrap_or(&[])
+}
+
+/// Tell the frontend that a shortcut could not be registered
+fn emit_registration_failed<R: Runtime>(
+    app: &AppHandle<R>,
+    action_id: &str,
+    binding: &str,
+    error: &str,
+    fallback: Option<&str>,
+) {
+    if let Some(window) = app.get_webview_window("main") {
+        let payload = json!({
+            "action": action_id,
+            "binding": binding,
+            "error": error,
+            "fallback": fallback,
+        });
+        if let Err(e) = window.emit("shortcut-registration-failed", payload) {
+            eprintln!("Failed to emit shortcut registration failure: {}", e);
         }
-        return;
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    match window.is_visible() {
-        Ok(true) => {
-            // Window is visible, hide it and handle app icon based on user settings
-            if let Err(e) = window.hide() {
-                eprintln!("Failed to hide window: {}", e);
-            }
+/// Replace the stored registration failures
+fn set_failures<R: Runtime>(
+    app: &AppHandle<R>,
+    new_failures: HashMap<String, Vec<ShortcutFailure>>,
+) {
+    let state = app.state::<RegisteredShortcuts>();
+    let mut failures = match state.failures.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in set_failures, recovering...");
+            poisoned.into_inner()
         }
-        Ok(false) => {
-            // Window is hidden, show it and handle app icon based on user settings
-            if let Err(e) = window.show() {
-                eprintln!("Failed to show window: {}", e);
-            }
+    };
+    *failures = new_failures;
+}
 
-            if let Err(e) = window.set_focus() {
-                eprintln!("Failed to focus window: {}", e);
-            }
+/// Replace the registration failures of a single action, an empty list clears them
+fn set_action_failures<R: Runtime>(
+    app: &AppHandle<R>,
+    action_id: &str,
+    action_failures: Vec<ShortcutFailure>,
+) {
+    let state = app.state::<RegisteredShortcuts>();
+    let mut failures = match state.failures.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in set_action_failures, recovering...");
+            poisoned.into_inner()
+        }
+    };
+    if action_failures.is_empty() {
+        failures.remove(action_id);
+    } else {
+        failures.insert(action_id.to_string(), action_failures);
+    }
+}
 
-            // Emit event to focus text input
-            if let Err(e) = window.emit("focus-text-input", json!({})) {
-                eprintln!("Failed to emit focus event: {}", e);
-            }
+// Shortcut persistence in the app config directory
+fn get_shortcuts_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    Ok(config_dir.join(SHORTCUTS_FILE))
+}
+
+/// Read saved bindings, returning an empty map if the file is missing or corrupted
+fn load_saved_shortcuts<R: Runtime>(app: &AppHandle<R>) -> SavedShortcuts {
+    let path = match get_shortcuts_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}", e);
+            return SavedShortcuts::default();
         }
+    };
+
+    if !path.exists() {
+        return SavedShortcuts::default();
+    }
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
         Err(e) => {
-            eprintln!("Failed to check window visibility: {}", e);
+            eprintln!("Failed to read shortcuts file: {}", e);
+            return SavedShortcuts::default();
+        }
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(saved) => saved,
+        Err(e) => {
+            eprintln!("Failed to parse shortcuts file, using defaults: {}", e);
+            SavedShortcuts::default()
         }
     }
 }
 
-/// Handle audio shortcut
-fn handle_audio_shortcut<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        // Ensure window is visible
-        if let Ok(false) = window.is_visible() {
-            if let Err(_e) = window.show() {
+/// Bindings as the user configured them, including ones the OS refused and
+/// without the fallbacks registered in their place
+fn configured_shortcuts<R: Runtime>(app: &AppHandle<R>) -> Bindings {
+    let state = app.state::<RegisteredShortcuts>();
+    let configured = match state.configured.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    configured
+}
+
+fn set_configured_shortcuts<R: Runtime>(app: &AppHandle<R>, bindings: Bindings) {
+    let state = app.state::<RegisteredShortcuts>();
+    match state.configured.lock() {
+        Ok(mut guard) => *guard = bindings,
+        Err(poisoned) => *poisoned.into_inner() = bindings,
+    };
+}
+
+/// Write the configured bindings, scopes and profiles to the shortcuts file. A
+/// binding the OS refused this time is kept so it is tried again next launch.
+fn save_shortcuts<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<RegisteredShortcuts>();
+    let bindings = configured_shortcuts(app)
+        .into_iter()
+        .map(|(action_id, keys)| (action_id, BindingList::Many(keys)))
+        .collect();
+    let scopes = match state.scopes.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    let platform_bindings = match state.platform_bindings.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+
+    let profile_state = app.state::<ShortcutProfiles>();
+    let profiles = match profile_state.profiles.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    let active_profile = match profile_state.active.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+
+    let path = get_shortcuts_path(app)?;
+    let saved = SavedShortcuts {
+        bindings,
+        platform_bindings,
+        scopes,
+        profiles,
+        active_profile,
+    };
+    let content = serde_json::to_string_pretty(&saved)
+        .map_err(|e| format!("Failed to serialize shortcuts: {}", e))?;
+
+    fs::write(&path, content).map_err(|e| format!("Failed to write shortcuts file: {}", e))
+}
+
+/// Tauri command to delete saved shortcuts and re-register the defaults
+#[tauri::command]
+pub fn reset_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<Bindings, String> {
+    let path = get_shortcuts_path(&app)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete shortcuts file: {}", e))?;
+    }
+
+    register_default_shortcuts(&app);
+
+    get_registered_shortcuts(app)
+}
+
+/// Tauri command to repair shortcuts: drop every registration, the saved config and
+/// any stale OS state, then register the defaults again. Safe to call repeatedly.
+#[tauri::command]
+pub fn reset_shortcuts_to_defaults<R: Runtime>(app: AppHandle<R>) -> Result<ShortcutStatusReport, String> {
+    // A broken config must not block the repair
+    match get_shortcuts_path(&app) {
+        Ok(path) if path.exists() => {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("Failed to delete shortcuts file: {}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("{}", e),
+    }
+
+    // "Not registered" errors from the registry are only logged
+    if let Err(e) = unregister_all_shortcuts(&app) {
+        eprintln!("Failed to unregister shortcuts: {}", e);
+    }
+
+    // Leftovers the registry lost track of, skipped while a capture owns the plugin
+    if !is_suspended(&app) {
+        if let Err(e) = app.global_shortcut().unregister_all() {
+            eprintln!("Failed to clear plugin shortcuts: {}", e);
+        }
+        match app.state::<RegisteredShortcuts>().shortcuts.lock() {
+            Ok(mut guard) => guard.clear(),
+            Err(poisoned) => poisoned.into_inner().clear(),
+        }
+    }
+
+    // A release for the old audio binding may never arrive, don't wait for it.
+    // Whatever the frontend is already recording or capturing is left alone.
+    match app.state::<PushToTalkState>().held_since.lock() {
+        Ok(mut guard) => *guard = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    }
+    match app.state::<PushToTalkState>().started.lock() {
+        Ok(mut guard) => *guard = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    };
+    match app.state::<PushToTalkState>().latched.lock() {
+        Ok(mut guard) => *guard = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    };
+
+    register_default_shortcuts(&app);
+
+    Ok(shortcut_status_report(&app))
+}
+
+fn register_default_shortcuts<R: Runtime>(app: &AppHandle<R>) {
+    {
+        let state = app.state::<RegisteredShortcuts>();
+        let mut scopes = match state.scopes.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *scopes = default_scopes();
+    }
+    match app.state::<ShortcutProfiles>().active.lock() {
+        Ok(mut guard) => *guard = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    }
+    register_bindings(app, &default_shortcuts());
+
+    // Profiles survive a reset, only the bindings go back to the defaults
+    let has_profiles = match app.state::<ShortcutProfiles>().profiles.lock() {
+        Ok(guard) => !guard.is_empty(),
+        Err(poisoned) => !poisoned.into_inner().is_empty(),
+    };
+    if has_profiles {
+        if let Err(e) = save_shortcuts(app) {
+            eprintln!("Failed to keep shortcut profiles: {}", e);
+        }
+    }
+}
+
+/// Handle a pressed or released shortcut for the given action_id
+pub fn handle_shortcut_event<R: Runtime>(
+    app: &AppHandle<R>,
+    action_id: &str,
+    binding: &str,
+    state: ShortcutState,
+) {
+    if !accept_transition(app, binding, state) {
+        return;
+    }
+
+    if state == ShortcutState::Pressed && !accept_press(app, action_id) {
+        eprintln!("Debounced shortcut press: {}", action_id);
+        return;
+    }
+
+    // The toggle shortcut is never blocked so the window can always be hidden again
+    if state == ShortcutState::Pressed && action_id != "toggle_window" && is_frontmost_app_blocked(app) {
+        eprintln!("Shortcut ignored in blocklisted app: {}", action_id);
+        return;
+    }
+
+    if state == ShortcutState::Pressed {
+        // Every handler needs the main window, without it the press does nothing
+        let handled = app.get_webview_window("main").is_some();
+        emit_shortcut_triggered(app, action_id, binding, handled);
+    }
+
+    if action_id == "audio_recording" && is_push_to_talk_enabled(app) {
+        handle_push_to_talk(app, state);
+        return;
+    }
+
+    if state == ShortcutState::Pressed {
+        handle_shortcut_action(app, action_id, binding);
+    }
+}
+
+/// Whether the frontmost application is on the shortcut blocklist
+fn is_frontmost_app_blocked<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<ShortcutBlocklist>();
+    let blocklist = match state.apps.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    if blocklist.is_empty() {
+        return false;
+    }
+
+    let Some(frontmost) = active_app::frontmost_app(app) else {
+        return false;
+    };
+    blocklist
+        .iter()
+        .any(|blocked| blocked.eq_ignore_ascii_case(&frontmost.id))
+}
+
+/// Report an accepted shortcut press to the frontend, local only
+fn emit_shortcut_triggered<R: Runtime>(app: &AppHandle<R>, action_id: &str, binding: &str, handled: bool) {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    // Emitted app-wide so it is still delivered when the main window is missing
+    if let Err(e) = app.emit(
+        "shortcut-triggered",
+        json!({
+            "action": action_id,
+            "binding": binding,
+            "timestamp_ms": timestamp_ms,
+            "handled": handled,
+        }),
+    ) {
+        eprintln!("Failed to emit shortcut-triggered event: {}", e);
+    }
+}
+
+/// Filter OS auto-repeat: only the first Pressed of a binding and the Released
+/// that ends it get through, however many repeats arrive in between
+fn accept_transition<R: Runtime>(app: &AppHandle<R>, binding: &str, shortcut_state: ShortcutState) -> bool {
+    let state = app.state::<ShortcutDebounce>();
+    let mut held = match state.held.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in accept_transition, recovering...");
+            poisoned.into_inner()
+        }
+    };
+    is_key_transition(&mut held, binding, shortcut_state)
+}
+
+/// Update the held set for one event and report whether it changed the key state
+fn is_key_transition(held: &mut HashSet<String>, binding: &str, shortcut_state: ShortcutState) -> bool {
+    match shortcut_state {
+        ShortcutState::Pressed => held.insert(binding.to_string()),
+        ShortcutState::Released => held.remove(binding),
+    }
+}
+
+/// Hand the live part of the registry to the portal session if that backend is
+/// in use. The portal binds the whole set at once, so suspending binds nothing and
+/// window-visible actions are left out while the window is hidden.
+fn sync_portal_shortcuts<R: Runtime>(app: &AppHandle<R>, visible: bool) {
+    if !portal_shortcuts::is_enabled(app) {
+        return;
+    }
+    portal_shortcuts::request_bind(app, &live_shortcuts(app, visible));
+}
+
+/// Registered bindings that should fire right now
+fn live_shortcuts<R: Runtime>(app: &AppHandle<R>, visible: bool) -> Bindings {
+    if is_suspended(app) {
+        return Bindings::new();
+    }
+
+    let state = app.state::<RegisteredShortcuts>();
+    let scopes = match state.scopes.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    let mut bindings = get_registered_shortcuts(app.clone()).unwrap_or_default();
+    bindings.retain(|action_id, _| visible || scopes.get(action_id) != Some(&ShortcutScope::WindowVisible));
+    bindings
+}
+
+/// Whether a portal activation for action_id should run. The portal may still
+/// deliver one for a binding that was dropped before its rebind went through.
+pub fn is_portal_action_live<R: Runtime>(app: &AppHandle<R>, action_id: &str) -> bool {
+    live_shortcuts(app, is_main_window_visible(app)).contains_key(action_id)
+}
+
+/// Forget held keys, releases for bindings that were just unregistered never arrive
+fn clear_held_shortcuts<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<ShortcutDebounce>();
+    match state.held.lock() {
+        Ok(mut guard) => guard.clear(),
+        Err(poisoned) => poisoned.into_inner().clear(),
+    };
+}
+
+/// Forget one held key when only its binding is unregistered, the others may
+/// still be down and their releases must get through
+fn forget_held_shortcut<R: Runtime>(app: &AppHandle<R>, binding: &str) {
+    let state = app.state::<ShortcutDebounce>();
+    match state.held.lock() {
+        Ok(mut guard) => guard.remove(binding),
+        Err(poisoned) => poisoned.into_inner().remove(binding),
+    };
+}
+
+/// Record a press of action_id if it is outside the debounce window
+fn accept_press<R: Runtime>(app: &AppHandle<R>, action_id: &str) -> bool {
+    let state = app.state::<ShortcutDebounce>();
+    let mut last_handled = match state.last_handled.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in accept_press, recovering...");
+            poisoned.into_inner()
+        }
+    };
+
+    let now = Instant::now();
+    let window = match action_id {
+        "clear_conversation" => CLEAR_CONVERSATION_DEBOUNCE_WINDOW,
+        _ => DEBOUNCE_WINDOW,
+    };
+    if !is_outside_debounce(last_handled.get(action_id).copied(), now, window) {
+        return false;
+    }
+    last_handled.insert(action_id.to_string(), now);
+    true
+}
+
+/// Whether a press at `now` is far enough from the last accepted one
+fn is_outside_debounce(last: Option<Instant>, now: Instant, window: Duration) -> bool {
+    match last {
+        Some(last) => now.saturating_duration_since(last) >= window,
+        None => true,
+    }
+}
+
+pub fn is_push_to_talk_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<PushToTalkState>();
+    let enabled = match state.enabled.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    enabled
+}
+
+/// Start recording on the first press and stop on release, ignoring auto-repeat presses.
+/// A release quicker than the tap threshold emits toggle-audio-recording
+/// { duration_ms, recording } instead: the first tap leaves the recording
+/// running (recording: true) and the next one stops it (recording: false). A
+/// longer hold emits stop-audio-recording { duration_ms }. Native capture is
+/// left running by a tap too, until the next press.
+fn handle_push_to_talk<R: Runtime>(app: &AppHandle<R>, shortcut_state: ShortcutState) {
+    let state = app.state::<PushToTalkState>();
+    let mut held_since = match state.held_since.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in handle_push_to_talk, recovering...");
+            poisoned.into_inner()
+        }
+    };
+
+    match shortcut_state {
+        ShortcutState::Pressed => {
+            if held_since.is_some() {
+                return;
+            }
+            *held_since = Some(Instant::now());
+            drop(held_since);
+
+            // A recording a tap left running is stopped by this hold's release,
+            // unless the frontend or the duration limit already stopped it
+            let latched = match state.latched.lock() {
+                Ok(guard) => *guard,
+                Err(poisoned) => *poisoned.into_inner(),
+            };
+            if latched.is_some_and(|generation| is_recording_timer_current(app, generation)) {
+                return;
+            }
+            match state.latched.lock() {
+                Ok(mut guard) => *guard = None,
+                Err(poisoned) => *poisoned.into_inner() = None,
+            };
+
+            // Only a recording the frontend actually started can be left running
+            let before = current_recording_generation(app);
+            handle_audio_shortcut(app);
+            let generation = current_recording_generation(app);
+            let started = (generation != before).then_some(generation);
+            match state.started.lock() {
+                Ok(mut guard) => *guard = started,
+                Err(poisoned) => *poisoned.into_inner() = started,
+            };
+        }
+        ShortcutState::Released => {
+            let Some(pressed_at) = held_since.take() else {
+                return;
+            };
+            drop(held_since);
+
+            let duration_ms = pressed_at.elapsed().as_millis() as u64;
+            // Shorter holds count as a tap
+            let threshold_ms = crate::settings::u64(app, TAP_THRESHOLD_SETTING, DEFAULT_TAP_THRESHOLD_MS);
+            let tap = duration_ms < threshold_ms;
+
+            // A tap leaves native capture running, like a toggle press
+            if audio::is_native_enabled(app) {
+                if !tap {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn_blocking(move || audio::stop_session(&app, audio::AudioSource::Mic));
+                }
+                return;
+            }
+
+            let latched = match state.latched.lock() {
+                Ok(mut guard) => guard.take(),
+                Err(poisoned) => poisoned.into_inner().take(),
+            };
+            let started = match state.started.lock() {
+                Ok(mut guard) => guard.take(),
+                Err(poisoned) => poisoned.into_inner().take(),
+            };
+            let stops_latched = latched.is_some_and(|generation| is_recording_timer_current(app, generation));
+            if !stops_latched && started.is_none() {
+                return;
+            }
+
+            // The first tap leaves the recording running until the next release
+            if tap && !stops_latched {
+                match state.latched.lock() {
+                    Ok(mut guard) => *guard = started,
+                    Err(poisoned) => *poisoned.into_inner() = started,
+                };
+            } else {
+                cancel_recording_timer(app);
+            }
+
+            let Some(window) = app.get_webview_window("main") else {
+                return;
+            };
+            let result = if tap {
+                window.emit(
+                    "toggle-audio-recording",
+                    json!({ "duration_ms": duration_ms, "recording": !stops_latched }),
+                )
+            } else {
+                window.emit("stop-audio-recording", json!({ "duration_ms": duration_ms }))
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to emit audio recording release event: {}", e);
+            }
+        }
+    }
+}
+
+/// Handle shortcut action based on action_id
+pub fn handle_shortcut_action<R: Runtime>(app: &AppHandle<R>, action_id: &str, binding: &str) {
+    match action_id {
+        "toggle_window" => handle_toggle_window(app),
+        "audio_recording" => handle_audio_shortcut(app),
+        "screenshot" => handle_screenshot_shortcut(app),
+        "system_audio" => handle_system_audio_shortcut(app),
+        "toggle_always_on_top" => handle_always_on_top_shortcut(app),
+        "toggle_click_through" => handle_click_through_shortcut(app),
+        "cancel_request" => handle_cancel_request_shortcut(app),
+        "rerun_last_query" => handle_rerun_last_query_shortcut(app),
+        "cycle_ai_provider" => handle_cycle_provider_shortcut(app, binding),
+        "screenshot_and_ask" => handle_screenshot_and_ask_shortcut(app),
+        "clear_conversation" => handle_clear_conversation_shortcut(app),
+        "copy_last_response" => handle_copy_last_response_shortcut(app),
+        "scroll_response_up" => handle_scroll_response_shortcut(app, "up"),
+        "scroll_response_down" => handle_scroll_response_shortcut(app, "down"),
+        "capture_recent_audio" => handle_capture_recent_audio_shortcut(app),
+        "toggle_mic_mute" => handle_toggle_mic_mute_shortcut(app),
+        "cycle_opacity" => crate::window::cycle_opacity(app),
+        "toggle_stealth_mode" => crate::window::toggle_stealth(app),
+        "cycle_window_preset" => crate::window::cycle_window_preset(app),
+        "zoom_in" => crate::window::step_zoom(app, Some(1.0)),
+        "zoom_out" => crate::window::step_zoom(app, Some(-1.0)),
+        "zoom_reset" => crate::window::step_zoom(app, None),
+        custom_action => {
+            // Emit custom action event for frontend to handle
+            if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) = window.emit("custom-shortcut-triggered", json!({ "action": custom_action })) {
+                    eprintln!("Failed to emit custom shortcut event: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Handle app toggle (hide/show) with input focus and app icon management
+fn handle_toggle_window<R: Runtime>(app: &AppHandle<R>) {
+    // Get the main window
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        let natively_hidden = matches!(window.is_visible(), Ok(false))
+            || matches!(window.is_minimized(), Ok(true));
+        let focused = matches!(window.is_focused(), Ok(true));
+
+        // Flip the flag and release it before showing, the show handlers read it
+        let was_hidden = {
+            let state = app.state::<WindowState>();
+            let mut is_hidden = match state.is_hidden.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let was_hidden = resolve_hidden(*is_hidden, natively_hidden, focused);
+            *is_hidden = !was_hidden;
+            was_hidden
+        };
+
+        if was_hidden {
+            crate::window::before_show(app, &window);
+            if natively_hidden {
+                if let Err(e) = window.unminimize() {
+                    eprintln!("Failed to unminimize window: {}", e);
+                }
+                if let Err(e) = window.show() {
+                    eprintln!("Failed to show window: {}", e);
+                }
+                after_window_shown(app, &window);
+            } else {
+                // Only the frontend hid it, so nothing above ran the show handlers
+                crate::window::reapply_window_settings(app, &window);
+                sync_scoped_shortcuts(app, true);
+            }
+            active_app::remember_previous_app(app);
+        }
+
+        if let Err(e) = window.emit("toggle-window-visibility", !was_hidden) {
+            eprintln!("Failed to emit toggle-window-visibility event: {}", e);
+        }
+
+        if !was_hidden {
+            after_window_hidden(app);
+            active_app::restore_previous_app(app);
+        }
+        return;
+    }
+
+    // Toggled again mid-slide, head back the other way from where it is
+    #[cfg(not(target_os = "windows"))]
+    if crate::slide::reverse(app, &window) {
+        return;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    match window.is_visible() {
+        Ok(true) => {
+            // Hidden once it is off screen
+            if crate::slide::slide_out(app, &window) {
+                return;
+            }
+            // Window is visible, hide it and handle app icon based on user settings
+            if let Err(e) = window.hide() {
+                eprintln!("Failed to hide window: {}", e);
+            }
+            after_window_hidden(app);
+
+            active_app::restore_previous_app(app);
+        }
+        Ok(false) => {
+            // Window is hidden, show it and handle app icon based on user settings
+            active_app::remember_previous_app(app);
+            // Checked before showing, once shown the overlay is in front
+            let fullscreen = active_app::is_foreground_fullscreen(app);
+
+            crate::window::before_show(app, &window);
+            crate::slide::slide_in(app, &window);
+            if let Err(e) = window.show() {
+                eprintln!("Failed to show window: {}", e);
+            }
+            after_window_shown(app, &window);
+
+            // Taking focus would knock a game or video out of fullscreen
+            if fullscreen {
                 return;
             }
             if let Err(e) = window.set_focus() {
                 eprintln!("Failed to focus window: {}", e);
             }
+
+            // Emit event to focus text input
+            if let Err(e) = window.emit("focus-text-input", json!({})) {
+                eprintln!("Failed to emit focus event: {}", e);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to check window visibility: {}", e);
         }
+    }
+}
+
+/// Handle always on top shortcut
+fn handle_always_on_top_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    let enabled = {
+        let state = app.state::<WindowState>();
+        let enabled = match state.always_on_top.lock() {
+            Ok(guard) => !*guard,
+            Err(poisoned) => !*poisoned.into_inner(),
+        };
+        enabled
+    };
+
+    if let Err(e) = apply_always_on_top(app, enabled) {
+        eprintln!("Failed to toggle always on top: {}", e);
+    }
+}
+
+/// Set always on top on the main window, store it and notify the frontend
+fn apply_always_on_top<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+
+    window
+        .set_always_on_top(enabled)
+        .map_err(|e| format!("Failed to set always on top: {}", e))?;
+
+    let state = app.state::<WindowState>();
+    *state
+        .always_on_top
+        .lock()
+        .map_err(|e| format!("Failed to update always on top state: {}", e))? = enabled;
+
+    // set_always_on_top moves the window to the floating level on macOS
+    if crate::window_settings::get(app).float_over_fullscreen {
+        crate::window::apply_float_over_fullscreen(app, &window, true);
+    }
+
+    if let Err(e) = window.emit("always-on-top-changed", enabled) {
+        eprintln!("Failed to emit always-on-top-changed event: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Handle click-through shortcut, this is the way back out since the window can't be clicked
+fn handle_click_through_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    let enabled = !is_click_through_enabled(app);
+    if let Err(e) = apply_click_through(app, enabled) {
+        eprintln!("Failed to toggle click-through: {}", e);
+    }
+}
+
+fn is_click_through_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<WindowState>();
+    let enabled = match state.click_through.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    enabled
+}
+
+/// Set click-through on the main window, store it, arm the safety timeout and notify the frontend
+fn apply_click_through<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+
+    window
+        .set_ignore_cursor_events(enabled)
+        .map_err(|e| format!("Failed to set click-through: {}", e))?;
+
+    let flags = app.state::<WindowState>();
+    *flags
+        .click_through
+        .lock()
+        .map_err(|e| format!("Failed to update click-through state: {}", e))? = enabled;
+
+    let state = app.state::<ClickThroughState>();
+
+    {
+        let mut timer = state
+            .timer
+            .lock()
+            .map_err(|e| format!("Failed to update click-through timer: {}", e))?;
+        if let Some(task) = timer.take() {
+            task.abort();
+        }
+
+        let timeout_minutes = match state.timeout_minutes.lock() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        if let (true, Some(minutes)) = (enabled, timeout_minutes) {
+            let app_clone = app.clone();
+            *timer = Some(tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+                eprintln!("Click-through timeout reached, disabling");
+                if let Err(e) = apply_click_through(&app_clone, false) {
+                    eprintln!("Failed to disable click-through: {}", e);
+                }
+            }));
+        }
+    }
+
+    if let Err(e) = window.emit("click-through-changed", enabled) {
+        eprintln!("Failed to emit click-through-changed event: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Restore per-show state: window flags some platforms reset on hide/show,
+/// and the shortcuts scoped to a visible window. Every show path calls this,
+/// a show without focus gets no focus event to fall back on.
+pub fn after_window_shown<R: Runtime>(app: &AppHandle<R>, window: &tauri::WebviewWindow<R>) {
+    if is_click_through_enabled(app) {
+        if let Err(e) = window.set_ignore_cursor_events(true) {
+            eprintln!("Failed to re-apply click-through: {}", e);
+        }
+    }
+
+    crate::window::reapply_window_settings(app, window);
+    sync_scoped_shortcuts(app, true);
+    tray::set_window_shown(app, true);
+}
+
+/// Hide the main window without handing focus back, for hides the user didn't
+/// ask for through the toggle. On Windows the frontend hides it and the shared
+/// flag is updated so the next toggle shows it again.
+pub fn hide_main_window<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        let state = app.state::<WindowState>();
+        let mut is_hidden = match state.is_hidden.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if *is_hidden {
+            return;
+        }
+        *is_hidden = true;
+        drop(is_hidden);
+        if let Err(e) = window.emit("toggle-window-visibility", true) {
+            eprintln!("Failed to emit toggle-window-visibility event: {}", e);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    if let Err(e) = window.hide() {
+        eprintln!("Failed to hide window: {}", e);
+        return;
+    }
+
+    after_window_hidden(app);
+}
+
+/// Finish a toggle hide once the window has slid off screen
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub fn hide_after_slide<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if let Err(e) = window.hide() {
+        eprintln!("Failed to hide window: {}", e);
+    }
+    after_window_hidden(app);
+
+    active_app::restore_previous_app(app);
+}
+
+/// Release the shortcuts scoped to a visible window
+pub fn after_window_hidden<R: Runtime>(app: &AppHandle<R>) {
+    sync_scoped_shortcuts(app, false);
+    tray::set_window_shown(app, false);
+}
+
+/// Whether the main window is on screen, Windows hides it from the frontend
+pub fn is_main_window_visible<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(window) = app.get_webview_window("main") else {
+        return false;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        let state = app.state::<WindowState>();
+        let is_hidden = match state.is_hidden.lock() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        return !is_hidden && !matches!(window.is_minimized(), Ok(true));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    matches!(window.is_visible(), Ok(true))
+}
+
+/// Register window-visible scoped shortcuts while visible and release them while hidden
+fn sync_scoped_shortcuts<R: Runtime>(app: &AppHandle<R>, visible: bool) {
+    if is_suspended(app) {
+        return;
+    }
+    sync_portal_shortcuts(app, visible);
+
+    let state = app.state::<RegisteredShortcuts>();
+    let scoped: Vec<String> = match state.scopes.lock() {
+        Ok(guard) => guard
+            .iter()
+            .filter(|(_, scope)| **scope == ShortcutScope::WindowVisible)
+            .map(|(action_id, _)| action_id.clone())
+            .collect(),
+        Err(poisoned) => poisoned
+            .into_inner()
+            .iter()
+            .filter(|(_, scope)| **scope == ShortcutScope::WindowVisible)
+            .map(|(action_id, _)| action_id.clone())
+            .collect(),
+    };
+
+    if scoped.is_empty() {
+        return;
+    }
+
+    let keys: Vec<String> = {
+        let registered = match state.shortcuts.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        scoped
+            .iter()
+            .filter_map(|action_id| registered.get(action_id))
+            .flatten()
+            .cloned()
+            .collect()
+    };
+
+    for key in keys {
+        let Ok(binding) = parse_binding(&key) else {
+            continue;
+        };
+
+        // Check first so rapid toggling never registers twice
+        let is_registered = is_binding_registered(app, &binding);
+        let result = match (visible, is_registered) {
+            (true, false) => register_binding(app, &binding),
+            (false, true) => {
+                forget_held_shortcut(app, &key);
+                unregister_binding(app, &binding)
+            }
+            _ => continue,
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to update scoped shortcut {}: {}", key, e);
+        }
+    }
+}
+
+/// Handle cancel request shortcut, a no-op unless a request is running
+fn handle_cancel_request_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if !api::is_request_active(app) {
+        return;
+    }
+
+    api::cancel_active_request(app);
+
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("cancel-active-request", json!({})) {
+            eprintln!("Failed to emit cancel request event: {}", e);
+        }
+    }
+}
+
+/// Whether the Windows overlay is hidden before a toggle. The flag only tracks
+/// what the shortcut did, so the real window wins when they disagree: minimized
+/// or natively hidden means hidden, and focus means the user has it on screen
+/// (hiding hands focus back to the previous app).
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn resolve_hidden(flag_hidden: bool, natively_hidden: bool, focused: bool) -> bool {
+    if natively_hidden {
+        return true;
+    }
+    if focused {
+        return false;
+    }
+    flag_hidden
+}
+
+/// Reset visibility state when the main window is destroyed so a recreated
+/// window doesn't inherit a stale "hidden" flag
+pub fn handle_window_event<R: Runtime>(window: &tauri::Window<R>, event: &tauri::WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+
+    // A focused window is on screen no matter how it was shown
+    if let tauri::WindowEvent::Focused(true) = event {
+        sync_scoped_shortcuts(window.app_handle(), true);
+    }
+    if let tauri::WindowEvent::Focused(false) = event {
+        crate::window::handle_blur(window.app_handle());
+    }
+
+    // Without the window every shortcut would be dead, so closing it either
+    // hides it or quits properly
+    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        api.prevent_close();
+        if crate::window_settings::get(window.app_handle()).close_hides_to_tray {
+            hide_main_window(window.app_handle());
+        } else {
+            tray::quit(window.app_handle());
+        }
+    }
+
+    if let tauri::WindowEvent::Destroyed = event {
+        let state = window.app_handle().state::<WindowState>();
+        let mut is_hidden = match state.is_hidden.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *is_hidden = false;
+    }
+}
+
+/// Emit permission-missing instead of running an action macOS would silently
+/// break, a permission that was never asked for still lets the OS prompt
+fn ensure_permission<R: Runtime>(app: &AppHandle<R>, kind: PermissionKind) -> bool {
+    if permissions::check(kind) != PermissionStatus::Denied {
+        return true;
+    }
+
+    eprintln!("Missing {:?} permission", kind);
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("permission-missing", json!({ "kind": kind })) {
+            eprintln!("Failed to emit permission-missing event: {}", e);
+        }
+    }
+    false
+}
+
+/// Handle audio shortcut
+fn handle_audio_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if !ensure_permission(app, PermissionKind::Microphone) {
+        return;
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        // Ensure window is visible
+        if let Ok(false) = window.is_visible() {
+            if show_for_capture(app, &window).is_err() {
+                return;
+            }
+        }
+
+        // Native capture streams audio-chunk events itself, pressing again stops it
+        if audio::is_native_enabled(app) {
+            toggle_native_mic(app);
+            return;
+        }
+
+        // Emit event to start audio recording
+        if let Err(e) = window.emit("start-audio-recording", json!({})) {
+            eprintln!("Failed to emit audio recording event: {}", e);
+        }
+        start_recording_timer(app);
+    }
+}
+
+fn cancel_recording_timer<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    let state = app.state::<RecordingTimer>();
+    let mut generation = match state.generation.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *generation += 1;
+    *generation
+}
+
+fn current_recording_generation<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    let state = app.state::<RecordingTimer>();
+    let generation = match state.generation.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    generation
+}
+
+fn is_recording_timer_current<R: Runtime>(app: &AppHandle<R>, generation: u64) -> bool {
+    let state = app.state::<RecordingTimer>();
+    let current = match state.generation.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    current == generation
+}
+
+/// Warn the frontend at 80% of the maximum duration and force a stop at the
+/// limit, unless recording_stopped comes first
+fn start_recording_timer<R: Runtime>(app: &AppHandle<R>) {
+    let generation = cancel_recording_timer(app);
+    let max_duration_ms = audio::max_duration_ms(app);
+    let warning_ms = max_duration_ms * 4 / 5;
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(warning_ms)).await;
+        if !is_recording_timer_current(&app, generation) {
+            return;
+        }
+        if let Some(window) = app.get_webview_window("main") {
+            let payload = json!({ "source": "mic", "elapsed_ms": warning_ms, "max_duration_ms": max_duration_ms });
+            if let Err(e) = window.emit("audio-duration-warning", payload) {
+                eprintln!("Failed to emit audio duration warning: {}", e);
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(max_duration_ms - warning_ms)).await;
+        if !is_recording_timer_current(&app, generation) {
+            return;
+        }
+        cancel_recording_timer(&app);
+        if let Some(window) = app.get_webview_window("main") {
+            let payload = json!({ "reason": "max_duration", "duration_ms": max_duration_ms });
+            if let Err(e) = window.emit("force-stop-audio-recording", payload) {
+                eprintln!("Failed to emit force stop event: {}", e);
+            }
+        }
+    });
+}
+
+/// Start or stop native microphone capture off the shortcut thread
+fn toggle_native_mic<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if audio::stop_session(&app, audio::AudioSource::Mic) {
+            return;
+        }
+        if let Err(e) = audio::start_mic(&app, None, None) {
+            audio::emit_audio_error(&app, audio::AudioSource::Mic, &e);
+        }
+    });
+}
+
+/// Handle rerun shortcut, falls back to focusing the input when there is nothing to rerun
+fn handle_rerun_last_query_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        // Ensure window is visible
+        if let Ok(false) = window.is_visible() {
+            if let Err(_e) = window.show() {
+                return;
+            }
+            after_window_shown(app, &window);
+            if let Err(e) = window.set_focus() {
+                eprintln!("Failed to focus window: {}", e);
+            }
+        }
+
+        let state = app.state::<LastQueryState>();
+        let available = match state.available.lock() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+
+        let event = if available { "rerun-last-query" } else { "focus-text-input" };
+        if let Err(e) = window.emit(event, json!({})) {
+            eprintln!("Failed to emit {} event: {}", event, e);
+        }
+    }
+}
+
+/// Handle provider cycling without showing or focusing the window, a binding
+/// with shift goes to the previous provider
+fn handle_cycle_provider_shortcut<R: Runtime>(app: &AppHandle<R>, binding: &str) {
+    let has_shift = binding
+        .to_lowercase()
+        .split('+')
+        .any(|token| matches!(token.trim(), "shift" | "lshift" | "rshift"));
+    let direction = if has_shift { "previous" } else { "next" };
+
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("cycle-ai-provider", json!({ "direction": direction })) {
+            eprintln!("Failed to emit cycle provider event: {}", e);
+        }
+    }
+}
+
+/// Handle screenshot shortcut
+fn handle_screenshot_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if !ensure_permission(app, PermissionKind::Screen) {
+        return;
+    }
+
+    if capture::is_native_enabled(app) {
+        capture::capture_and_emit(app);
+        return;
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        // Emit event to trigger screenshot - frontend will determine auto/manual mode
+        if let Err(e) = window.emit("trigger-screenshot", json!({})) {
+            eprintln!("Failed to emit screenshot event: {}", e);
+        }
+    }
+}
+
+/// Handle screenshot-and-ask shortcut, captures and submits the prompt in one go
+fn handle_screenshot_and_ask_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        // Ensure window is visible
+        if let Ok(false) = window.is_visible() {
+            if let Err(_e) = window.show() {
+                return;
+            }
+            after_window_shown(app, &window);
+            if let Err(e) = window.set_focus() {
+                eprintln!("Failed to focus window: {}", e);
+            }
+        }
+
+        let prompt_template = screenshot_prompt_template(app);
+
+        if let Err(e) = window.emit(
+            "trigger-screenshot",
+            json!({ "submit_immediately": true, "prompt_template": prompt_template }),
+        ) {
+            eprintln!("Failed to emit screenshot event: {}", e);
+        }
+    }
+}
+
+/// Handle clear conversation shortcut, works silently while the window is hidden.
+/// Conversations are persisted by the frontend through the SQL plugin, so dropping
+/// the active session is left to the event listener.
+fn handle_clear_conversation_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    set_stored_response(app, None);
+
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("clear-conversation", json!({})) {
+            eprintln!("Failed to emit clear conversation event: {}", e);
+        }
+    }
+}
+
+/// Handle copy shortcut, puts the last response on the clipboard without showing the window
+fn handle_copy_last_response_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<LastResponseState>();
+    let text = match state.text.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+
+    let Some(text) = text else {
+        if let Some(window) = app.get_webview_window("main") {
+            if let Err(e) = window.emit("focus-text-input", json!({})) {
+                eprintln!("Failed to emit focus text input event: {}", e);
+            }
+        }
+        return;
+    };
+
+    if let Err(e) = app.clipboard().write_text(text) {
+        eprintln!("Failed to copy response to clipboard: {}", e);
+        return;
+    }
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("Pluely")
+        .body("Response copied")
+        .show()
+    {
+        eprintln!("Failed to show copy notification: {}", e);
+    }
+}
+
+fn set_stored_response<R: Runtime>(app: &AppHandle<R>, text: Option<String>) {
+    let state = app.state::<LastResponseState>();
+    match state.text.lock() {
+        Ok(mut guard) => *guard = text,
+        Err(poisoned) => *poisoned.into_inner() = text,
+    };
+}
+
+/// Handle toggle mic mute shortcut, unbound by default. Recording carries on either way.
+fn handle_toggle_mic_mute_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(e) = audio::toggle_mic_mute(app) {
+        eprintln!("Failed to toggle microphone mute: {}", e);
+    }
+}
+
+/// Handle capture recent audio shortcut, saves the rolling buffer when it is on
+fn handle_capture_recent_audio_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = audio::capture_recent(&app) {
+            audio::emit_audio_error(&app, audio::AudioSource::System, &e);
+        }
+    });
+}
+
+/// Handle scroll shortcuts, inert while the window is hidden so focus is never stolen
+fn handle_scroll_response_shortcut<R: Runtime>(app: &AppHandle<R>, direction: &str) {
+    if !is_main_window_visible(app) {
+        return;
+    }
+
+    let amount: ScrollAmount = crate::settings::get(app, SCROLL_AMOUNT_SETTING)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit(
+            "scroll-response",
+            json!({ "direction": direction, "amount": amount }),
+        ) {
+            eprintln!("Failed to emit scroll response event: {}", e);
+        }
+    }
+}
+
+/// Show the hidden window for a capture shortcut. Over a fullscreen app it
+/// isn't focused, or stays hidden if the user chose so, and capture starts silently.
+fn show_for_capture<R: Runtime>(app: &AppHandle<R>, window: &tauri::WebviewWindow<R>) -> tauri::Result<()> {
+    let fullscreen = active_app::is_foreground_fullscreen(app);
+    if fullscreen
+        && crate::window_settings::get(app).fullscreen_behavior == crate::window_settings::FullscreenBehavior::StayHidden
+    {
+        return Ok(());
+    }
+
+    crate::window::before_show(app, window);
+    window.show()?;
+    after_window_shown(app, window);
+    if !fullscreen {
+        if let Err(e) = window.set_focus() {
+            eprintln!("Failed to focus window: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Handle system audio shortcut
+fn handle_system_audio_shortcut<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        // Ensure window is visible
+        if let Ok(false) = window.is_visible() {
+            if let Err(e) = show_for_capture(app, &window) {
+                eprintln!("Failed to show window: {}", e);
+                return;
+            }
+        }
+
+        // Native capture keeps its running state in Rust, the toggle works without the frontend
+        if audio::is_native_enabled(app) {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if audio::stop_session(&app, audio::AudioSource::System) {
+                    return;
+                }
+                if let Err(e) = audio::start_system(&app) {
+                    audio::emit_audio_error(&app, audio::AudioSource::System, &e.to_string());
+                }
+            });
+            return;
+        }
+
+        // Emit event to toggle system audio capture - frontend will determine current state
+        if let Err(e) = window.emit("toggle-system-audio", json!({})) {
+            eprintln!("Failed to emit system audio event: {}", e);
+        }
+    }
+}
+
+/// Tauri command to get all registered shortcuts
+#[tauri::command]
+pub fn get_registered_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<Bindings, String> {
+    let state = app.state::<RegisteredShortcuts>();
+    let registered = match state.shortcuts.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in get_registered_shortcuts, recovering...");
+            poisoned.into_inner()
+        }
+    };
+    Ok(registered.clone())
+}
+
+/// Tauri command to update shortcuts dynamically. The actions in the config
+/// replace their stored bindings, every other action keeps its own. Nothing is
+/// saved, the frontend sends its copy on every launch.
+#[tauri::command]
+pub fn update_shortcuts<R: Runtime>(
+    app: AppHandle<R>,
+    config: ShortcutsConfig,
+) -> Result<(), String> {
+    eprintln!("Updating shortcuts with {} bindings", config.bindings.len());
+    
+    let mut shortcuts_to_register = configured_shortcuts(&app);
+    
+    for (action_id, binding) in &config.bindings {
+        if binding.enabled && !binding.key.is_empty() {
+            // Validate before adding
+            match parse_binding(&binding.key) {
+                Ok(_) => {
+                    shortcuts_to_register.insert(action_id.clone(), vec![binding.key.clone()]);
+                }
+                Err(e) => {
+                    eprintln!("Invalid shortcut '{}' for action '{}': {}", binding.key, action_id, e);
+                    return Err(format!("Invalid shortcut '{}' for action '{}': {}", binding.key, action_id, e));
+                }
+            }
+        } else {
+            shortcuts_to_register.remove(action_id);
+        }
+    }
+    
+    register_bindings(&app, &shortcuts_to_register);
+    
+    Ok(())
+}
+
+/// Tauri command to set shortcuts from an action_id -> shortcut keys map.
+/// An action whose new bindings all fail keeps its previous bindings active.
+#[tauri::command]
+pub fn set_shortcuts<R: Runtime>(
+    app: AppHandle<R>,
+    shortcuts: HashMap<String, BindingList>,
+) -> Result<HashMap<String, Vec<ShortcutUpdateResult>>, String> {
+    eprintln!("Setting shortcuts for {} actions", shortcuts.len());
+
+    let mut results = HashMap::new();
+
+    for (action_id, keys) in shortcuts {
+        let action_results = bind_action(&app, &action_id, keys.into_vec());
+        results.insert(action_id, action_results);
+    }
+    clear_held_shortcuts(&app);
+    sync_scoped_shortcuts(&app, is_main_window_visible(&app));
+
+    if results.values().flatten().any(|r| r.success) || results.values().any(|r| r.is_empty()) {
+        if let Err(e) = save_shortcuts(&app) {
+            eprintln!("Failed to save shortcuts: {}", e);
+        }
+    }
+
+    Ok(results)
+}
+
+fn update_result(binding: &str, error: Option<String>) -> ShortcutUpdateResult {
+    ShortcutUpdateResult {
+        binding: binding.to_string(),
+        success: error.is_none(),
+        error,
+    }
+}
+
+/// Replace the bindings of a single action. New bindings are registered before the
+/// old ones are dropped, so an action keeps its old bindings if none of the new work.
+/// An empty list removes all bindings of the action.
+fn bind_action<R: Runtime>(
+    app: &AppHandle<R>,
+    action_id: &str,
+    keys: Vec<String>,
+) -> Vec<ShortcutUpdateResult> {
+    if keys.len() > MAX_BINDINGS_PER_ACTION {
+        let error = format!("At most {} bindings per action are allowed", MAX_BINDINGS_PER_ACTION);
+        return keys
+            .iter()
+            .map(|key| update_result(key, Some(error.clone())))
+            .collect();
+    }
+
+    let suspended = is_suspended(app);
+    let mut results = Vec::new();
+    // Valid keys the user asked for, kept even when the OS refuses them
+    let mut wanted: Vec<String> = Vec::new();
+
+    let state = app.state::<RegisteredShortcuts>();
+    let mut registered = match state.shortcuts.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in bind_action, recovering...");
+            poisoned.into_inner()
+        }
+    };
+
+    let old: Vec<ParsedBinding> = registered
+        .get(action_id)
+        .map(|keys| keys.iter().filter_map(|key| parse_binding(key).ok()).collect())
+        .unwrap_or_default();
+
+    let mut active: Vec<(String, ParsedBinding)> = Vec::new();
+
+    for key in &keys {
+        let shortcut = match parse_binding(key) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                results.push(update_result(key, Some(format!("Invalid shortcut '{}': {}", key, e))));
+                continue;
+            }
+        };
+
+        // Listed twice in the same request
+        if active.iter().any(|(_, s)| *s == shortcut) {
+            results.push(update_result(key, None));
+            continue;
+        }
+
+        let conflict = registered.iter().find(|(id, other_keys)| {
+            id.as_str() != action_id
+                && other_keys
+                    .iter()
+                    .any(|other| parse_binding(other).ok().as_ref() == Some(&shortcut))
+        });
+        if let Some((other_action, _)) = conflict {
+            results.push(update_result(
+                key,
+                Some(format!("Shortcut '{}' is already bound to '{}'", key, other_action)),
+            ));
+            continue;
+        }
+
+        wanted.push(key.clone());
+
+        // Already registered for this action, or only stored while suspended
+        if suspended || old.contains(&shortcut) {
+            active.push((key.clone(), shortcut));
+            results.push(update_result(key, None));
+            continue;
+        }
+
+        match register_binding(app, &shortcut) {
+            Ok(_) => {
+                eprintln!("Registered shortcut: {} -> {}", action_id, key);
+                active.push((key.clone(), shortcut));
+                results.push(update_result(key, None));
+            }
+            Err(e) => {
+                results.push(update_result(key, Some(format!("Failed to register '{}': {}", key, e))));
+            }
+        }
+    }
+
+    // Keep the previous bindings so the action stays reachable
+    if active.is_empty() && !keys.is_empty() {
+        eprintln!("No new binding for {} could be set, keeping the old ones", action_id);
+    } else {
+        if !suspended {
+            for shortcut in old.iter().filter(|s| !active.iter().any(|(_, a)| a == *s)) {
+                if let Err(e) = unregister_binding(app, shortcut) {
+                    eprintln!("Failed to unregister old shortcut for {}: {}", action_id, e);
+                }
+            }
+        }
+
+        if active.is_empty() {
+            registered.remove(action_id);
+            eprintln!("Removed shortcuts for {}", action_id);
+        } else {
+            registered.insert(
+                action_id.to_string(),
+                active.into_iter().map(|(key, _)| key).collect(),
+            );
+        }
+    }
+    drop(registered);
+
+    // Invalid or conflicting keys alone leave the configuration as it was
+    if wanted.is_empty() == keys.is_empty() {
+        let state = app.state::<RegisteredShortcuts>();
+        let mut configured = match state.configured.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if wanted.is_empty() {
+            configured.remove(action_id);
+        } else {
+            configured.insert(action_id.to_string(), wanted);
+        }
+    }
+
+    let action_failures = results
+        .iter()
+        .filter_map(|r| {
+            r.error.as_ref().map(|error| ShortcutFailure {
+                binding: r.binding.clone(),
+                error: error.clone(),
+            })
+        })
+        .collect();
+    set_action_failures(app, action_id, action_failures);
+
+    results
+}
+
+/// Unregister all currently registered shortcuts
+pub fn unregister_all_shortcuts<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    // Suspended shortcuts are already unregistered from the OS
+    if is_suspended(app) {
+        return Ok(());
+    }
+
+    let state = app.state::<RegisteredShortcuts>();
+    let registered = match state.shortcuts.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in unregister_all_shortcuts, recovering...");
+            poisoned.into_inner()
+        }
+    };
+    
+    for (action_id, shortcut_str) in registered
+        .iter()
+        .flat_map(|(action_id, keys)| keys.iter().map(move |key| (action_id, key)))
+    {
+        if let Ok(binding) = parse_binding(shortcut_str) {
+            match unregister_binding(app, &binding) {
+                Ok(_) => {
+                    eprintln!("Unregistered shortcut: {} -> {}", action_id, shortcut_str);
+                }
+                Err(e) => {
+                    eprintln!("Failed to unregister shortcut {}: {}", shortcut_str, e);
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Tauri command to check if shortcuts are registered
+#[tauri::command]
+pub fn check_shortcuts_registered<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    let state = app.state::<RegisteredShortcuts>();
+    let registered = match state.shortcuts.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in check_shortcuts_registered, recovering...");
+            poisoned.into_inner()
+        }
+    };
+    Ok(registered.values().any(|keys| !keys.is_empty()))
+}
+
+/// Tauri command to list every binding the app tried to register and whether the OS
+/// still has it. Failed bindings are listed too so partial setup failures show up.
+#[tauri::command]
+pub fn list_registered_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<Vec<RegisteredShortcutInfo>, String> {
+    let state = app.state::<RegisteredShortcuts>();
+    let mut entries: Vec<(String, String)> = Vec::new();
+
+    match state.shortcuts.lock() {
+        Ok(guard) => entries.extend(
+            guard
+                .iter()
+                .flat_map(|(action_id, keys)| keys.iter().map(move |key| (action_id.clone(), key.clone()))),
+        ),
+        Err(poisoned) => entries.extend(
+            poisoned
+                .into_inner()
+                .iter()
+                .flat_map(|(action_id, keys)| keys.iter().map(move |key| (action_id.clone(), key.clone()))),
+        ),
+    }
+    match state.failures.lock() {
+        Ok(guard) => entries.extend(guard.iter().flat_map(|(action_id, failures)| {
+            failures.iter().map(move |f| (action_id.clone(), f.binding.clone()))
+        })),
+        Err(poisoned) => entries.extend(poisoned.into_inner().iter().flat_map(|(action_id, failures)| {
+            failures.iter().map(move |f| (action_id.clone(), f.binding.clone()))
+        })),
+    }
+
+    let mut list: Vec<RegisteredShortcutInfo> = entries
+        .into_iter()
+        .map(|(action, binding)| {
+            let parsed = parse_binding(&binding).ok();
+            let normalized = parsed.as_ref().map(|parsed| match parsed {
+                ParsedBinding::Chord(shortcut) => shortcut.into_string(),
+                ParsedBinding::Hook(_) => binding.trim().to_lowercase(),
+            });
+            let is_registered = parsed
+                .as_ref()
+                .map(|parsed| is_binding_registered(&app, parsed))
+                .unwrap_or(false);
+            RegisteredShortcutInfo {
+                action,
+                binding,
+                normalized,
+                is_registered,
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| a.action.cmp(&b.action).then_with(|| a.binding.cmp(&b.binding)));
+
+    Ok(list)
+}
+
+/// Tauri command to get the registration status of every configured shortcut
+#[tauri::command]
+pub fn get_shortcut_status<R: Runtime>(app: AppHandle<R>) -> Result<ShortcutStatusReport, String> {
+    Ok(shortcut_status_report(&app))
+}
+
+fn shortcut_status_report<R: Runtime>(app: &AppHandle<R>) -> ShortcutStatusReport {
+    let suspended = is_suspended(app);
+    let state = app.state::<RegisteredShortcuts>();
+    let mut status = HashMap::new();
+
+    {
+        let failures = match state.failures.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                eprintln!("Mutex poisoned in get_shortcut_status, recovering...");
+                poisoned.into_inner()
+            }
+        };
+        for (action_id, action_failures) in failures.iter() {
+            let entries: &mut Vec<ShortcutStatus> = status.entry(action_id.clone()).or_default();
+            entries.extend(action_failures.iter().map(|failure| ShortcutStatus {
+                binding: failure.binding.clone(),
+                registered: false,
+                error: Some(failure.error.clone()),
+                approved: None,
+            }));
+        }
+    }
+
+    let registered = match state.shortcuts.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            eprintln!("Mutex poisoned in get_shortcut_status, recovering...");
+            poisoned.into_inner()
+        }
+    };
+    let portal = portal_shortcuts::is_enabled(app);
+    for (action_id, keys) in registered.iter() {
+        let entries = status.entry(action_id.clone()).or_default();
+        entries.extend(keys.iter().map(|key| {
+            let via_portal = portal && matches!(parse_binding(key), Ok(ParsedBinding::Chord(_)));
+            let approved = if via_portal {
+                portal_shortcuts::is_approved(app, action_id)
+            } else {
+                None
+            };
+            ShortcutStatus {
+                binding: key.clone(),
+                registered: !suspended && approved != Some(false),
+                error: None,
+                approved,
+            }
+        }));
+    }
+
+    ShortcutStatusReport {
+        backend: if portal { "portal" } else { "plugin" }.to_string(),
+        suspended,
+        input_permission: key_hook::has_input_permission(),
+        shortcuts: status,
+    }
+}
+
+/// Unregister and register every configured binding again, for hotkeys the OS
+/// dropped silently. Handlers are attached once to the plugin, so this never
+/// duplicates them, and live shortcuts are released before registering again.
+pub fn reregister_all_shortcuts<R: Runtime>(app: &AppHandle<R>) -> ShortcutStatusReport {
+    // Bindings that failed earlier are configured too and get another chance
+    if !is_suspended(app) {
+        register_bindings(app, &configured_shortcuts(app));
+    }
+
+    let report = shortcut_status_report(app);
+    if let Err(e) = app.emit("shortcuts-reregistered", &report) {
+        eprintln!("Failed to emit shortcuts-reregistered event: {}", e);
+    }
+    report
+}
+
+/// Tauri command behind the "Fix shortcuts" button
+#[tauri::command]
+pub fn reregister_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<ShortcutStatusReport, String> {
+    Ok(reregister_all_shortcuts(&app))
+}
+
+/// Watch for system sleep by comparing the wall clock against a fixed tick,
+/// the monotonic clock stops while suspended but the wall clock keeps going.
+/// Unlocking the session re-registers too, a lock doesn't always sleep.
+pub fn start_resume_watcher<R: Runtime>(app: &AppHandle<R>) {
+    let handle = app.clone();
+    session_lock::watch_unlock(move || {
+        eprintln!("Session unlocked, re-registering shortcuts");
+        reregister_all_shortcuts(&handle);
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_tick = SystemTime::now();
+        loop {
+            tokio::time::sleep(RESUME_CHECK_INTERVAL).await;
+            let now = SystemTime::now();
+            let elapsed = now.duration_since(last_tick).unwrap_or_default();
+            last_tick = now;
+
+            if elapsed >= RESUME_CHECK_INTERVAL + RESUME_JUMP_THRESHOLD {
+                eprintln!("System resume detected, re-registering shortcuts");
+                reregister_all_shortcuts(&app);
+            }
+        }
+    });
+}
+
+fn is_suspended<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<RegisteredShortcuts>();
+    let suspended = match state.suspended.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    suspended
+}
+
+/// Unregister every bound shortcut from the OS while keeping the bindings
+pub fn suspend_all_shortcuts<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if is_suspended(app) {
+        return Ok(());
+    }
+
+    unregister_all_shortcuts(app)?;
+    clear_held_shortcuts(app);
+
+    let state = app.state::<RegisteredShortcuts>();
+    *state
+        .suspended
+        .lock()
+        .map_err(|e| format!("Failed to update suspended state: {}", e))? = true;
+    sync_portal_shortcuts(app, false);
+
+    eprintln!("Global shortcuts suspended");
+    Ok(())
+}
+
+/// Re-register the bindings kept while suspended
+pub fn resume_all_shortcuts<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if !is_suspended(app) {
+        return Ok(());
+    }
+
+    let state = app.state::<RegisteredShortcuts>();
+    *state
+        .suspended
+        .lock()
+        .map_err(|e| format!("Failed to update suspended state: {}", e))? = false;
+
+    let bindings = configured_shortcuts(app);
+
+    // Clear stored bindings first so nothing is unregistered twice
+    match state.shortcuts.lock() {
+        Ok(mut guard) => guard.clear(),
+        Err(poisoned) => poisoned.into_inner().clear(),
+    }
+    register_bindings(app, &bindings);
+
+    eprintln!("Global shortcuts resumed");
+    Ok(())
+}
+
+/// Tauri command to set when an action's shortcuts are registered.
+/// The toggle shortcut always stays global so the window can be brought back.
+#[tauri::command]
+pub fn set_shortcut_scope<R: Runtime>(
+    app: AppHandle<R>,
+    action_id: String,
+    scope: ShortcutScope,
+) -> Result<(), String> {
+    if action_id == "toggle_window" && scope != ShortcutScope::Global {
+        return Err("The toggle_window shortcut must stay global".to_string());
+    }
+
+    {
+        let state = app.state::<RegisteredShortcuts>();
+        let mut scopes = state
+            .scopes
+            .lock()
+            .map_err(|e| format!("Failed to update shortcut scope: {}", e))?;
+        // Global is stored explicitly so it overrides a window-visible default
+        scopes.insert(action_id.clone(), scope);
+    }
+
+    // A scope switched back to global must be registered even while hidden
+    if scope == ShortcutScope::Global && !is_suspended(&app) {
+        let keys = match app.state::<RegisteredShortcuts>().shortcuts.lock() {
+            Ok(guard) => guard.get(&action_id).cloned().unwrap_or_default(),
+            Err(poisoned) => poisoned.into_inner().get(&action_id).cloned().unwrap_or_default(),
+        };
+        for key in keys {
+            if let Ok(binding) = parse_binding(&key) {
+                if !is_binding_registered(&app, &binding) {
+                    if let Err(e) = register_binding(&app, &binding) {
+                        eprintln!("Failed to register shortcut {}: {}", key, e);
+                    }
+                }
+            }
+        }
+    }
+    sync_scoped_shortcuts(&app, is_main_window_visible(&app));
+
+    save_shortcuts(&app)
+}
+
+/// Tauri command to list the saved shortcut profiles
+#[tauri::command]
+pub fn list_shortcut_profiles<R: Runtime>(app: AppHandle<R>) -> Result<ShortcutProfileList, String> {
+    let state = app.state::<ShortcutProfiles>();
+    let profiles = state
+        .profiles
+        .lock()
+        .map_err(|e| format!("Failed to read shortcut profiles: {}", e))?
+        .clone();
+    let active = state
+        .active
+        .lock()
+        .map_err(|e| format!("Failed to read shortcut profiles: {}", e))?
+        .clone();
+    Ok(ShortcutProfileList { active, profiles })
+}
+
+/// Tauri command to create or replace a shortcut profile
+#[tauri::command]
+pub fn save_shortcut_profile<R: Runtime>(
+    app: AppHandle<R>,
+    name: String,
+    map: ShortcutProfile,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+
+    for (action_id, entry) in &map {
+        if let Some(invalid) = entry
+            .binding
+            .clone()
+            .into_vec()
+            .iter()
+            .find(|key| parse_binding(key).is_err())
+        {
+            return Err(format!("Invalid shortcut '{}' for action '{}'", invalid, action_id));
+        }
+    }
+
+    {
+        let state = app.state::<ShortcutProfiles>();
+        state
+            .profiles
+            .lock()
+            .map_err(|e| format!("Failed to update shortcut profiles: {}", e))?
+            .insert(name, map);
+    }
+
+    save_shortcuts(&app)
+}
+
+/// Tauri command to switch every binding over to a profile. If any action of the
+/// profile can't be registered the previous bindings are restored.
+#[tauri::command]
+pub fn activate_shortcut_profile<R: Runtime>(app: AppHandle<R>, name: String) -> Result<ShortcutStatusReport, String> {
+    let profile_state = app.state::<ShortcutProfiles>();
+
+    // Held for the whole switch so two activations can't interleave
+    let mut active = profile_state
+        .active
+        .lock()
+        .map_err(|e| format!("Failed to read shortcut profiles: {}", e))?;
+
+    let profile = profile_state
+        .profiles
+        .lock()
+        .map_err(|e| format!("Failed to read shortcut profiles: {}", e))?
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown shortcut profile '{}'", name))?;
+
+    let mut bindings = default_shortcuts();
+    for (action_id, entry) in profile {
+        let keys = entry.binding.into_vec();
+        if entry.enabled && !keys.is_empty() {
+            bindings.insert(action_id, keys);
+        } else {
+            bindings.remove(&action_id);
+        }
+    }
+
+    let previous = configured_shortcuts(&app);
+    if let Err(failed) = register_all_or_nothing(&app, &bindings) {
+        eprintln!("Profile {} failed for {:?}, rolling back", name, failed);
+        register_bindings(&app, &previous);
+        return Err(format!(
+            "Could not register shortcuts for {} in profile '{}', previous shortcuts restored",
+            failed.join(", "),
+            name
+        ));
+    }
+
+    *active = Some(name);
+    drop(active);
+
+    save_shortcuts(&app)?;
+    Ok(shortcut_status_report(&app))
+}
+
+/// Register bindings and report the actions that ended up without any working key.
+/// The caller restores its previous bindings on error.
+fn register_all_or_nothing<R: Runtime>(app: &AppHandle<R>, bindings: &Bindings) -> Result<(), Vec<String>> {
+    register_bindings(app, bindings);
+
+    // Suspended bindings are only stored, so nothing can fail until resume
+    if is_suspended(app) {
+        return Ok(());
+    }
+
+    let registered = get_registered_shortcuts(app.clone()).unwrap_or_default();
+    let mut failed: Vec<String> = bindings
+        .iter()
+        .filter(|(_, keys)| !keys.is_empty())
+        .filter(|(action_id, _)| registered.get(*action_id).is_none_or(|keys| keys.is_empty()))
+        .map(|(action_id, _)| action_id.clone())
+        .collect();
+    failed.sort();
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+/// Tauri command to export bindings, scopes and profiles as a JSON string
+#[tauri::command]
+pub fn export_shortcuts_config<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+    let bindings = configured_shortcuts(&app);
+    let state = app.state::<RegisteredShortcuts>();
+    let scopes = match state.scopes.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    // This machine's own bindings are exact for it, the rest came from earlier imports
+    let mut platform_bindings = match state.platform_bindings.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    platform_bindings.insert(std::env::consts::OS.to_string(), bindings.clone());
+    let profiles = list_shortcut_profiles(app)?;
+
+    let config = ExportedShortcuts {
+        schema_version: SHORTCUTS_SCHEMA_VERSION,
+        platform: std::env::consts::OS.to_string(),
+        bindings,
+        platform_bindings,
+        scopes,
+        profiles: profiles.profiles,
+        active_profile: profiles.active,
+    };
+    serde_json::to_string_pretty(&config).map_err(|e| format!("Failed to serialize shortcuts: {}", e))
+}
+
+/// Tauri command to import a config from export_shortcuts_config. Every binding is
+/// checked first and nothing is applied if any of them is rejected; registration
+/// failures roll back to the previous shortcuts.
+#[tauri::command]
+pub fn import_shortcuts_config<R: Runtime>(app: AppHandle<R>, json: String) -> Result<ShortcutImportReport, String> {
+    let config: ExportedShortcuts =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid shortcuts config: {}", e))?;
+    if config.schema_version > SHORTCUTS_SCHEMA_VERSION {
+        return Err(format!(
+            "Shortcuts config version {} is newer than supported version {}",
+            config.schema_version, SHORTCUTS_SCHEMA_VERSION
+        ));
+    }
+
+    let platform = std::env::consts::OS;
+    let explicit = config.platform_bindings.get(platform);
+    let mut rejected = Vec::new();
+
+    let source_platform = config.platform.clone();
+    let mut check = |action_id: &str, keys: Vec<String>, remap: bool| -> Vec<String> {
+        keys.into_iter()
+            .map(|key| if remap { map_binding_for_platform(&key, &source_platform) } else { key })
+            .inspect(|key| {
+                if let Err(e) = parse_binding(key) {
+                    rejected.push(RejectedShortcut {
+                        action: action_id.to_string(),
+                        binding: key.clone(),
+                        error: e,
+                    });
+                }
+            })
+            .collect()
+    };
+
+    // Explicit bindings for this platform win over mapped ones
+    let mut bindings: Bindings = HashMap::new();
+    for (action_id, keys) in config.bindings {
+        if explicit.is_none_or(|explicit| !explicit.contains_key(&action_id)) {
+            let keys = check(&action_id, keys, true);
+            bindings.insert(action_id, keys);
+        }
+    }
+    for (action_id, keys) in explicit.into_iter().flatten() {
+        let keys = check(action_id, keys.clone(), false);
+        bindings.insert(action_id.clone(), keys);
+    }
+    bindings.retain(|_, keys| !keys.is_empty());
+
+    let mut profiles = HashMap::new();
+    for (name, profile) in config.profiles {
+        let mut mapped = HashMap::new();
+        for (action_id, entry) in profile {
+            let keys = check(&format!("{}:{}", name, action_id), entry.binding.into_vec(), true);
+            mapped.insert(
+                action_id,
+                ProfileBinding {
+                    binding: BindingList::Many(keys),
+                    enabled: entry.enabled,
+                },
+            );
+        }
+        profiles.insert(name, mapped);
+    }
+
+    if !rejected.is_empty() {
+        return Ok(ShortcutImportReport {
+            applied: false,
+            rejected,
+        });
+    }
+
+    let state = app.state::<RegisteredShortcuts>();
+    let previous_bindings = configured_shortcuts(&app);
+    let previous_scopes = match state.scopes.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
 
-        // Emit event to start audio recording
-        if let Err(e) = window.emit("start-audio-recording", json!({})) {
-            eprintln!("Failed to emit audio recording event: {}", e);
-        }
+    let mut scopes = default_scopes();
+    scopes.extend(config.scopes);
+    scopes.remove("toggle_window");
+    match state.scopes.lock() {
+        Ok(mut guard) => *guard = scopes,
+        Err(poisoned) => *poisoned.into_inner() = scopes,
     }
-}
 
-/// Handle screenshot shortcut
-fn handle_screenshot_shortcut<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        // Emit event to trigger screenshot - frontend will determine auto/manual mode
-        if let Err(e) = window.emit("trigger-screenshot", json!({})) {
-            eprintln!("Failed to emit screenshot event: {}", e);
+    if let Err(failed) = register_all_or_nothing(&app, &bindings) {
+        eprintln!("Import failed for {:?}, rolling back", failed);
+        match state.scopes.lock() {
+            Ok(mut guard) => *guard = previous_scopes,
+            Err(poisoned) => *poisoned.into_inner() = previous_scopes,
         }
+        register_bindings(&app, &previous_bindings);
+        return Err(format!(
+            "Could not register shortcuts for {}, previous shortcuts restored",
+            failed.join(", ")
+        ));
     }
-}
 
-/// Handle system audio shortcut
-fn handle_system_audio_shortcut<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        // Ensure window is visible
-        if let Ok(false) = window.is_visible() {
-            if let Err(e) = window.show() {
-                eprintln!("Failed to show window: {}", e);
-                return;
-            }
-            if let Err(e) = window.set_focus() {
-                eprintln!("Failed to focus window: {}", e);
-            }
-        }
+    let profile_state = app.state::<ShortcutProfiles>();
+    match profile_state.profiles.lock() {
+        Ok(mut guard) => *guard = profiles,
+        Err(poisoned) => *poisoned.into_inner() = profiles,
+    }
+    match profile_state.active.lock() {
+        Ok(mut guard) => *guard = config.active_profile,
+        Err(poisoned) => *poisoned.into_inner() = config.active_profile,
+    }
 
-        // Emit event to toggle system audio capture - frontend will determine current state
-        if let Err(e) = window.emit("toggle-system-audio", json!({})) {
-            eprintln!("Failed to emit system audio event: {}", e);
+    // Keep the other platforms' bindings so the next export still carries them
+    let mut platform_bindings = config.platform_bindings;
+    platform_bindings.remove(platform);
+    match state.platform_bindings.lock() {
+        Ok(mut guard) => *guard = platform_bindings,
+        Err(poisoned) => *poisoned.into_inner() = platform_bindings,
+    }
+
+    save_shortcuts(&app)?;
+    Ok(ShortcutImportReport {
+        applied: true,
+        rejected: Vec::new(),
+    })
+}
+
+/// Map cmd to ctrl for configs written on macOS and imported elsewhere. A chord
+/// that already had ctrl keeps it once, so "cmd+ctrl+x" becomes "ctrl+x".
+fn map_binding_for_platform(key: &str, source_platform: &str) -> String {
+    if source_platform != "macos" || cfg!(target_os = "macos") {
+        return key.to_string();
+    }
+    let mut tokens: Vec<String> = Vec::new();
+    for token in key.split('+') {
+        let token = match token.trim().to_lowercase().as_str() {
+            "cmd" | "command" | "control" => "ctrl".to_string(),
+            _ => token.trim().to_string(),
+        };
+        if !tokens.iter().any(|seen| seen.eq_ignore_ascii_case(&token)) {
+            tokens.push(token);
         }
     }
+    tokens.join("+")
 }
 
-/// Tauri command to get all registered shortcuts
+/// Tauri command to temporarily disable all global shortcuts
 #[tauri::command]
-pub fn get_registered_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<HashMap<String, String>, String> {
-    let state = app.state::<RegisteredShortcuts>();
-    let registered = match state.shortcuts.lock() {
+pub fn suspend_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    suspend_all_shortcuts(&app)
+}
+
+/// Tauri command to re-enable global shortcuts after suspend_shortcuts
+#[tauri::command]
+pub fn resume_shortcuts<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    resume_all_shortcuts(&app)
+}
+
+/// Finish an active capture session with the chord the key hook saw
+pub fn handle_captured_chord<R: Runtime>(app: &AppHandle<R>, chord: String) {
+    let state = app.state::<ShortcutCaptureState>();
+    let mut session = match state.session.lock() {
         Ok(guard) => guard,
         Err(poisoned) => {
-            eprintln!("Mutex poisoned in get_registered_shortcuts, recovering...");
+            eprintln!("Mutex poisoned in handle_captured_chord, recovering...");
             poisoned.into_inner()
         }
     };
-    Ok(registered.clone())
+
+    if let Some(sender) = session.as_mut().and_then(|s| s.sender.take()) {
+        let _ = sender.send(Some(chord));
+    }
 }
 
-/// Tauri command to update shortcuts dynamically
+/// Tauri command to capture the next pressed key combination as a shortcut string.
+/// The key hook reads the chord, existing shortcuts are suspended meanwhile so
+/// pressing one doesn't run it. Returns None on timeout or cancel.
 #[tauri::command]
-pub fn update_shortcuts<R: Runtime>(
+pub async fn begin_shortcut_capture<R: Runtime>(
     app: AppHandle<R>,
-    config: ShortcutsConfig,
-) -> Result<(), String> {
-    eprintln!("Updating shortcuts with {} bindings", config.bindings.len());
-    
-    let mut shortcuts_to_register = Vec::new();
-    
-    for (action_id, binding) in &config.bindings {
-        if binding.enabled && !binding.key.is_empty() {
-            // Validate before adding
-            match binding.key.parse::<Shortcut>() {
-                Ok(shortcut) => {
-                    shortcuts_to_register.push((action_id.clone(), binding.key.clone(), shortcut));
-                }
-                Err(e) => {
-                    eprintln!("Invalid shortcut '{}' for action '{}': {}", binding.key, action_id, e);
-                    return Err(format!("Invalid shortcut '{}' for action '{}': {}", binding.key, action_id, e));
-                }
-            }
+    timeout_ms: Option<u64>,
+) -> Result<Option<String>, String> {
+    let (sender, receiver) = oneshot::channel();
+
+    {
+        let state = app.state::<ShortcutCaptureState>();
+        let mut session = state
+            .session
+            .lock()
+            .map_err(|e| format!("Failed to acquire capture lock: {}", e))?;
+        if session.is_some() {
+            return Err("Shortcut capture already in progress".to_string());
         }
+        *session = Some(CaptureSession {
+            sender: Some(sender),
+        });
     }
-    
-    // First, unregister all existing shortcuts
-    unregister_all_shortcuts(&app)?;
-    
-    // Now register all new shortcuts
-    let mut successfully_registered = HashMap::new();
-    
-    for (action_id, shortcut_str, shortcut) in shortcuts_to_register {
-        match app.global_shortcut().register(shortcut.clone()) {
-            Ok(_) => {
-                eprintln!("Registered shortcut: {} -> {}", action_id, shortcut_str);
-                successfully_registered.insert(action_id, shortcut_str);
-            }
-            Err(e) => {
-                eprintln!("Failed to register {} shortcut: {}", action_id, e);
-            }
+
+    let was_suspended = is_suspended(&app);
+    if let Err(e) = suspend_all_shortcuts(&app) {
+        eprintln!("Failed to suspend shortcuts for capture: {}", e);
+    }
+    clear_held_shortcuts(&app);
+
+    if let Err(e) = key_hook::begin_capture(&app) {
+        let state = app.state::<ShortcutCaptureState>();
+        match state.session.lock() {
+            Ok(mut guard) => *guard = None,
+            Err(poisoned) => *poisoned.into_inner() = None,
+        };
+        if !was_suspended {
+            resume_all_shortcuts(&app)?;
         }
+        return Err(e);
     }
-    
-    // Update state with successfully registered shortcuts
+
+    let timeout = Duration::from_millis(
+        timeout_ms
+            .unwrap_or(DEFAULT_CAPTURE_TIMEOUT_MS)
+            .min(MAX_CAPTURE_TIMEOUT_MS),
+    );
+    let captured = match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(captured)) => captured,
+        _ => None,
+    };
+
+    // Stop capturing before anything else sees key presses
+    key_hook::end_capture(&app);
     {
-        let state = app.state::<RegisteredShortcuts>();
-        let mut registered = match state.shortcuts.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                eprintln!("Mutex poisoned in update_shortcuts, recovering...");
-                poisoned.into_inner()
-            }
+        let state = app.state::<ShortcutCaptureState>();
+        match state.session.lock() {
+            Ok(mut guard) => *guard = None,
+            Err(poisoned) => *poisoned.into_inner() = None,
         };
-        
-        registered.clear();
-        registered.extend(successfully_registered);
     }
-    
-    Ok(())
-}
 
-/// Unregister all currently registered shortcuts
-fn unregister_all_shortcuts<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
-    let state = app.state::<RegisteredShortcuts>();
-    let registered = match state.shortcuts.lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => {
-            eprintln!("Mutex poisoned in unregister_all_shortcuts, recovering...");
-            poisoned.into_inner()
-        }
-    };
-    
-    for (action_id, shortcut_str) in registered.iter() {
-        if let Ok(shortcut) = shortcut_str.parse::<Shortcut>() {
-            match app.global_shortcut().unregister(shortcut) {
-                Ok(_) => {
-                    eprintln!("Unregistered shortcut: {} -> {}", action_id, shortcut_str);
-                }
-                Err(e) => {
-                    eprintln!("Failed to unregister shortcut {}: {}", shortcut_str, e);
-                }
-            }
+    if !was_suspended {
+        resume_all_shortcuts(&app)?;
+    }
+
+    let captured = captured.filter(|s| parse_binding(s).is_ok());
+
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("shortcut-captured", json!({ "shortcut": captured })) {
+            eprintln!("Failed to emit shortcut captured event: {}", e);
         }
     }
-    
-    Ok(())
+
+    Ok(captured)
 }
 
-/// Tauri command to check if shortcuts are registered
+/// Tauri command to stop an in-progress shortcut capture
 #[tauri::command]
-pub fn check_shortcuts_registered<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
-    let state = app.state::<RegisteredShortcuts>();
-    let registered = match state.shortcuts.lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => {
-            eprintln!("Mutex poisoned in check_shortcuts_registered, recovering...");
-            poisoned.into_inner()
-        }
-    };
-    Ok(!registered.is_empty())
+pub fn cancel_shortcut_capture<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<ShortcutCaptureState>();
+    let mut session = state
+        .session
+        .lock()
+        .map_err(|e| format!("Failed to acquire capture lock: {}", e))?;
+
+    if let Some(sender) = session.as_mut().and_then(|s| s.sender.take()) {
+        let _ = sender.send(None);
+    }
+
+    Ok(())
 }
 
 /// Tauri command to validate shortcut key
 #[tauri::command]
 pub fn validate_shortcut_key(key: String) -> Result<bool, String> {
-    match key.parse::<Shortcut>() {
+    match parse_binding(&key) {
         Ok(_) => Ok(true),
         Err(e) => {
             eprintln!("Invalid shortcut '{}': {}", key, e);
@@ -300,10 +2978,56 @@ pub fn validate_shortcut_key(key: String) -> Result<bool, String> {
     }
 }
 
-/// Tauri command to set app icon visibility in dock/taskbar
+/// Tauri command to report which layer would handle a shortcut key
+#[tauri::command]
+pub fn describe_shortcut_key(key: String) -> Result<ShortcutKeyInfo, String> {
+    let info = match parse_binding(&key) {
+        Ok(ParsedBinding::Chord(_)) => ShortcutKeyInfo {
+            valid: true,
+            syntax: Some("chord".to_string()),
+            error: None,
+        },
+        Ok(ParsedBinding::Hook(hook_binding)) => {
+            let syntax = match hook_binding {
+                HookBinding::Combo(_) => "key-hook",
+                HookBinding::DoubleTap(_) => "double-tap",
+                HookBinding::Fn(_) => "fn-key",
+            };
+            let error = if !key_hook::is_supported(&hook_binding) {
+                Some("This binding is not supported on this platform".to_string())
+            } else if !key_hook::has_input_permission() {
+                Some("Accessibility permission is required for fn shortcuts".to_string())
+            } else {
+                None
+            };
+            ShortcutKeyInfo {
+                valid: error.is_none(),
+                syntax: Some(syntax.to_string()),
+                error,
+            }
+        }
+        Err(e) => ShortcutKeyInfo {
+            valid: false,
+            syntax: None,
+            error: Some(e),
+        },
+    };
+    Ok(info)
+}
+
+/// Tauri command to set app icon visibility in dock/taskbar, stealth mode keeps
+/// it hidden until it ends
 #[tauri::command]
 pub fn set_app_icon_visibility<R: Runtime>(app: AppHandle<R>, visible: bool) -> Result<(), String> {
+    crate::window_settings::update(&app, |settings| settings.app_icon_visible = visible)?;
+    if crate::window::is_stealth_enabled(&app) {
+        return Ok(());
+    }
+    apply_app_icon_visibility(&app, visible)
+}
 
+/// Show or hide the dock/taskbar icon without remembering the choice
+pub fn apply_app_icon_visibility<R: Runtime>(app: &AppHandle<R>, visible: bool) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         // On macOS, use activation policy to control dock icon
@@ -317,6 +3041,13 @@ pub fn set_app_icon_visibility<R: Runtime>(app: AppHandle<R>, visible: bool) ->
             eprintln!("Failed to set activation policy: {}", e);
             format!("Failed to set activation policy: {}", e)
         })?;
+
+        // Switching policy can drop the window back to its normal level
+        if crate::window_settings::get(app).float_over_fullscreen {
+            if let Some(window) = app.get_webview_window("main") {
+                crate::window::apply_float_over_fullscreen(app, &window, true);
+            }
+        }
     }
 
     #[cfg(target_os = "windows")]
@@ -346,17 +3077,346 @@ pub fn set_app_icon_visibility<R: Runtime>(app: AppHandle<R>, visible: bool) ->
     Ok(())
 }
 
+/// Tauri command to switch push-to-talk mode for the audio shortcut
+#[tauri::command]
+pub fn set_push_to_talk<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let state = app.state::<PushToTalkState>();
+    *state
+        .enabled
+        .lock()
+        .map_err(|e| format!("Failed to update push-to-talk state: {}", e))? = enabled;
+
+    // Drop any in-progress hold or tap so the next press starts fresh
+    *state
+        .held_since
+        .lock()
+        .map_err(|e| format!("Failed to update push-to-talk state: {}", e))? = None;
+    *state
+        .started
+        .lock()
+        .map_err(|e| format!("Failed to update push-to-talk state: {}", e))? = None;
+    *state
+        .latched
+        .lock()
+        .map_err(|e| format!("Failed to update push-to-talk state: {}", e))? = None;
+
+    Ok(())
+}
+
+/// Tauri command for the frontend to report its recording ended, so the maximum
+/// duration timer doesn't force a stop later
+#[tauri::command]
+pub fn recording_stopped<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    cancel_recording_timer(&app);
+    Ok(())
+}
+
+fn check_tap_threshold(threshold_ms: u64) -> Result<(), String> {
+    if threshold_ms > MAX_TAP_THRESHOLD_MS {
+        return Err(format!(
+            "Tap threshold must be at most {} ms",
+            MAX_TAP_THRESHOLD_MS
+        ));
+    }
+    Ok(())
+}
+
+/// Check a value set_setting would store under one of this module's keys the
+/// way the dedicated command checks it
+pub fn validate_setting(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    match key {
+        SCROLL_AMOUNT_SETTING => crate::settings::parse::<ScrollAmount>(key, value).map(drop),
+        SCREENSHOT_PROMPT_SETTING => match crate::settings::parse::<String>(key, value)?.trim() {
+            "" => Err("Prompt template cannot be empty".to_string()),
+            _ => Ok(()),
+        },
+        TAP_THRESHOLD_SETTING => check_tap_threshold(crate::settings::parse(key, value)?),
+        _ => Ok(()),
+    }
+}
+
+/// Tauri command to set how short a push-to-talk hold must be to count as a tap,
+/// kept in the settings store
+#[tauri::command]
+pub fn set_hold_tap_threshold<R: Runtime>(app: AppHandle<R>, threshold_ms: u64) -> Result<(), String> {
+    check_tap_threshold(threshold_ms)?;
+    crate::settings::set(&app, TAP_THRESHOLD_SETTING, json!(threshold_ms))
+}
+
+/// Tauri command to get push-to-talk mode
+#[tauri::command]
+pub fn get_push_to_talk<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    Ok(is_push_to_talk_enabled(&app))
+}
+
+/// Tauri command for the frontend to announce the provider picked by the cycle shortcut
+#[tauri::command]
+pub fn notify_provider_changed<R: Runtime>(app: AppHandle<R>, name: String) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title("Pluely")
+        .body(format!("Switched to {}", name))
+        .show()
+        .map_err(|e| format!("Failed to show provider notification: {}", e))
+}
+
+/// Tauri command to set the prompt submitted by the screenshot-and-ask shortcut
+#[tauri::command]
+pub fn set_screenshot_prompt_template<R: Runtime>(app: AppHandle<R>, template: String) -> Result<(), String> {
+    let template = template.trim();
+    if template.is_empty() {
+        return Err("Prompt template cannot be empty".to_string());
+    }
+
+    crate::settings::set(&app, SCREENSHOT_PROMPT_SETTING, json!(template))
+}
+
+/// Tauri command to get the prompt submitted by the screenshot-and-ask shortcut
+#[tauri::command]
+pub fn get_screenshot_prompt_template<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+    Ok(screenshot_prompt_template(&app))
+}
+
+fn screenshot_prompt_template<R: Runtime>(app: &AppHandle<R>) -> String {
+    crate::settings::string(app, SCREENSHOT_PROMPT_SETTING, DEFAULT_SCREENSHOT_PROMPT)
+}
+
+/// Tauri command for the frontend to mirror the last response, an empty text clears it.
+/// Responses over 1 MB are cut at the last full character below the limit.
+#[tauri::command]
+pub fn set_last_response<R: Runtime>(app: AppHandle<R>, text: String) -> Result<(), String> {
+    if text.is_empty() {
+        set_stored_response(&app, None);
+        return Ok(());
+    }
+
+    let mut text = text;
+    if text.len() > MAX_LAST_RESPONSE_BYTES {
+        let mut end = MAX_LAST_RESPONSE_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    set_stored_response(&app, Some(text));
+    Ok(())
+}
+
+/// Tauri command to set whether the scroll shortcuts move by line or by page
+#[tauri::command]
+pub fn set_scroll_amount<R: Runtime>(app: AppHandle<R>, amount: ScrollAmount) -> Result<(), String> {
+    crate::settings::set(&app, SCROLL_AMOUNT_SETTING, json!(amount))
+}
+
+/// Tauri command for the frontend to report whether there is a query to rerun
+#[tauri::command]
+pub fn set_last_query_available<R: Runtime>(app: AppHandle<R>, available: bool) -> Result<(), String> {
+    let state = app.state::<LastQueryState>();
+    *state
+        .available
+        .lock()
+        .map_err(|e| format!("Failed to update last query state: {}", e))? = available;
+    Ok(())
+}
+
+/// Tauri command to set the time allowed between the taps of a double-tap binding
+#[tauri::command]
+pub fn set_double_tap_window<R: Runtime>(app: AppHandle<R>, window_ms: u64) -> Result<(), String> {
+    if !(100..=1000).contains(&window_ms) {
+        return Err("Double-tap window must be between 100 and 1000 ms".to_string());
+    }
+    key_hook::set_double_tap_window(&app, Duration::from_millis(window_ms));
+    Ok(())
+}
+
+/// Tauri command to set the applications in which shortcuts are ignored
+#[tauri::command]
+pub fn set_shortcut_blocklist<R: Runtime>(app: AppHandle<R>, apps: Vec<String>) -> Result<(), String> {
+    let state = app.state::<ShortcutBlocklist>();
+    *state
+        .apps
+        .lock()
+        .map_err(|e| format!("Failed to update shortcut blocklist: {}", e))? = apps
+        .into_iter()
+        .map(|app_id| app_id.trim().to_string())
+        .filter(|app_id| !app_id.is_empty())
+        .collect();
+    Ok(())
+}
+
+/// Tauri command to get the applications in which shortcuts are ignored
+#[tauri::command]
+pub fn get_shortcut_blocklist<R: Runtime>(app: AppHandle<R>) -> Result<Vec<String>, String> {
+    let state = app.state::<ShortcutBlocklist>();
+    let apps = state
+        .apps
+        .lock()
+        .map_err(|e| format!("Failed to read shortcut blocklist: {}", e))?
+        .clone();
+    Ok(apps)
+}
+
+/// Tauri command for the frontend to report visibility changes it made itself
+#[tauri::command]
+pub fn sync_window_visibility<R: Runtime>(app: AppHandle<R>, hidden: bool) -> Result<(), String> {
+    let state = app.state::<WindowState>();
+    *state
+        .is_hidden
+        .lock()
+        .map_err(|e| format!("Failed to update window visibility: {}", e))? = hidden;
+    tray::set_window_shown(&app, !hidden);
+    Ok(())
+}
+
 /// Tauri command to set always on top state
 #[tauri::command]
 pub fn set_always_on_top<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
-        window.set_always_on_top(enabled).map_err(|e| {
-            format!("Failed to set always on top: {}", e)
-        })?;
+    apply_always_on_top(&app, enabled)
+}
 
-    } else {
-        return Err("Main window not found".to_string());
+/// Tauri command to get always on top state
+#[tauri::command]
+pub fn get_always_on_top<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    let state = app.state::<WindowState>();
+    let enabled = *state
+        .always_on_top
+        .lock()
+        .map_err(|e| format!("Failed to get always on top state: {}", e))?;
+    Ok(enabled)
+}
+
+/// Tauri command to set click-through (ignore cursor events) mode
+#[tauri::command]
+pub fn set_click_through<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    apply_click_through(&app, enabled)
+}
+
+/// Tauri command to get click-through mode
+#[tauri::command]
+pub fn get_click_through<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    Ok(is_click_through_enabled(&app))
+}
+
+/// Tauri command to set the click-through safety timeout, None disables it
+#[tauri::command]
+pub fn set_click_through_timeout<R: Runtime>(
+    app: AppHandle<R>,
+    minutes: Option<u64>,
+) -> Result<(), String> {
+    if minutes == Some(0) {
+        return Err("Invalid timeout: must be at least 1 minute".to_string());
     }
 
+    let state = app.state::<ClickThroughState>();
+    *state
+        .timeout_minutes
+        .lock()
+        .map_err(|e| format!("Failed to update click-through timeout: {}", e))? = minutes;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_transition_passes_the_first_press_only() {
+        let mut held = HashSet::new();
+        assert!(is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+        // OS auto-repeat while the key stays down
+        for _ in 0..20 {
+            assert!(!is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+        }
+        assert!(is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Released));
+        assert!(held.is_empty());
+    }
+
+    #[test]
+    fn key_transition_passes_a_press_after_release() {
+        let mut held = HashSet::new();
+        assert!(is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+        assert!(is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Released));
+        assert!(is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+        assert!(!is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+    }
+
+    #[test]
+    fn key_transition_drops_a_release_without_a_press() {
+        let mut held = HashSet::new();
+        assert!(!is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Released));
+        // Another binding held at the same time keeps its own state
+        assert!(is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+        assert!(is_key_transition(&mut held, "ctrl+backslash", ShortcutState::Pressed));
+        assert!(!is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+        assert!(is_key_transition(&mut held, "ctrl+backslash", ShortcutState::Released));
+        assert!(!is_key_transition(&mut held, "ctrl+shift+a", ShortcutState::Pressed));
+    }
+
+    #[test]
+    fn debounce_accepts_the_first_press() {
+        assert!(is_outside_debounce(None, Instant::now(), DEBOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn debounce_accepts_a_press_exactly_at_the_window() {
+        let last = Instant::now();
+        assert!(is_outside_debounce(Some(last), last + DEBOUNCE_WINDOW, DEBOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn debounce_drops_a_press_just_inside_the_window() {
+        let last = Instant::now();
+        let now = last + DEBOUNCE_WINDOW - Duration::from_millis(1);
+        assert!(!is_outside_debounce(Some(last), now, DEBOUNCE_WINDOW));
+        assert!(!is_outside_debounce(Some(last), last, DEBOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn debounce_accepts_a_press_just_outside_the_window() {
+        let last = Instant::now();
+        let now = last + DEBOUNCE_WINDOW + Duration::from_millis(1);
+        assert!(is_outside_debounce(Some(last), now, DEBOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn debounce_drops_a_press_from_before_the_last_one() {
+        // Events can be handled slightly out of order across threads
+        let now = Instant::now();
+        let last = now + Duration::from_millis(5);
+        assert!(!is_outside_debounce(Some(last), now, DEBOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn toggle_trusts_the_flag_when_the_window_agrees() {
+        assert!(resolve_hidden(true, false, false));
+        assert!(!resolve_hidden(false, false, false));
+    }
+
+    #[test]
+    fn toggle_sees_a_minimized_window_as_hidden() {
+        // Flag says shown, but the user minimized it or the frontend hid it
+        assert!(resolve_hidden(false, true, false));
+        assert!(resolve_hidden(false, true, true));
+    }
+
+    #[test]
+    fn toggle_sees_a_focused_window_as_shown() {
+        // Flag says hidden, but the frontend showed it and the user is in it
+        assert!(!resolve_hidden(true, false, true));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn mac_bindings_map_cmd_to_ctrl() {
+        assert_eq!(map_binding_for_platform("cmd+shift+a", "macos"), "ctrl+shift+a");
+        assert_eq!(map_binding_for_platform("cmd+shift+a", "windows"), "cmd+shift+a");
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn mac_bindings_keep_a_single_ctrl() {
+        assert_eq!(map_binding_for_platform("cmd+ctrl+x", "macos"), "ctrl+x");
+        assert_eq!(map_binding_for_platform("Control+Command+x", "macos"), "ctrl+x");
+        assert!(parse_binding(&map_binding_for_platform("cmd+ctrl+x", "macos")).is_ok());
+    }
+}
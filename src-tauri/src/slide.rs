@@ -0,0 +1,293 @@
+// Quake-style slide of the main window from above the top edge of its monitor
+// when the toggle shows it, and back up before it hides. Only where Rust shows
+// and hides the window itself, on Windows the frontend owns visibility.
+use crate::window_settings;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, Runtime, WebviewWindow};
+
+// Upper bound on position updates per slide
+const MAX_FRAMES: u64 = 60;
+
+// A slide in progress, the toggle cancels it to reverse direction
+struct Slide {
+    showing: bool,
+    x: i32,
+    rest_y: i32,
+    hidden_y: i32,
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+pub struct SlideState {
+    current: Mutex<Option<Slide>>,
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    reduced_motion: OnceLock<Arc<AtomicBool>>, // kept current by a gsettings monitor
+}
+
+#[cfg(target_os = "macos")]
+fn prefers_reduced_motion<R: Runtime>(_app: &AppHandle<R>) -> bool {
+    use objc2::msg_send;
+    use objc2::runtime::{AnyClass, AnyObject};
+
+    let Some(class) = AnyClass::get(c"NSWorkspace") else {
+        return false;
+    };
+    unsafe {
+        let workspace: *mut AnyObject = msg_send![class, sharedWorkspace];
+        if workspace.is_null() {
+            return false;
+        }
+        msg_send![&*workspace, accessibilityDisplayShouldReduceMotion]
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn prefers_reduced_motion<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<SlideState>();
+    state.reduced_motion.get_or_init(watch_reduced_motion).load(Ordering::Acquire)
+}
+
+/// Read GNOME's animation switch once and follow its changes from a background
+/// thread, so toggling never waits on gsettings. Most GTK desktops' reduce-animation
+/// settings set it too.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn watch_reduced_motion() -> Arc<AtomicBool> {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    const SCHEMA: &str = "org.gnome.desktop.interface";
+    const KEY: &str = "enable-animations";
+
+    let reduced = Command::new("gsettings")
+        .args(["get", SCHEMA, KEY])
+        .output()
+        .map(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "false")
+        .unwrap_or(false);
+    let reduced = Arc::new(AtomicBool::new(reduced));
+
+    let flag = reduced.clone();
+    std::thread::spawn(move || {
+        let mut child = match Command::new("gsettings")
+            .args(["monitor", SCHEMA, KEY])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("Failed to watch the reduced motion setting: {}", e);
+                return;
+            }
+        };
+        let Some(stdout) = child.stdout.take() else {
+            return;
+        };
+        // Lines look like "enable-animations: false"
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            flag.store(line.trim_end().ends_with("false"), Ordering::Release);
+        }
+        let _ = child.wait();
+    });
+    reduced
+}
+
+/// Whether the toggle should slide, the OS reduced-motion setting wins
+#[cfg(not(target_os = "windows"))]
+fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    window_settings::get(app).slide_animation && !prefers_reduced_motion(app)
+}
+
+#[cfg(target_os = "windows")]
+fn is_enabled<R: Runtime>(_app: &AppHandle<R>) -> bool {
+    false
+}
+
+fn ease_out(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+fn ease_in(t: f64) -> f64 {
+    t.powi(3)
+}
+
+/// Animate from the window's current y towards the slide's target
+fn start<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>, slide: Slide, from_y: i32) {
+    let state = app.state::<SlideState>();
+    let mut current = match state.current.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(previous) = current.take() {
+        previous.cancel.store(true, Ordering::Release);
+    }
+
+    let to_y = if slide.showing { slide.rest_y } else { slide.hidden_y };
+    let full_distance = (slide.rest_y - slide.hidden_y).abs().max(1) as f64;
+    // A reversed slide only covers what is left, at the same speed
+    let duration_ms = window_settings::get(app).slide_duration_ms as f64 * (to_y - from_y).abs() as f64 / full_distance;
+    let frames = ((duration_ms * 60.0 / 1000.0).round() as u64).clamp(1, MAX_FRAMES);
+    let frame = Duration::from_millis(duration_ms.round() as u64) / frames as u32;
+
+    let (showing, x, rest_y) = (slide.showing, slide.x, slide.rest_y);
+    let cancel = slide.cancel.clone();
+    *current = Some(slide);
+    drop(current);
+
+    let app = app.clone();
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        for step in 1..=frames {
+            tokio::time::sleep(frame).await;
+            if cancel.load(Ordering::Acquire) {
+                return;
+            }
+            let t = step as f64 / frames as f64;
+            let eased = if showing { ease_out(t) } else { ease_in(t) };
+            let y = from_y + ((to_y - from_y) as f64 * eased).round() as i32;
+            if let Err(e) = crate::window::move_window(&window, PhysicalPosition::new(x, y)) {
+                eprintln!("Failed to move window during slide: {}", e);
+                break;
+            }
+        }
+
+        {
+            let state = app.state::<SlideState>();
+            let mut current = match state.current.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            // Reversed meanwhile, the new slide finishes the job
+            if cancel.load(Ordering::Acquire) {
+                return;
+            }
+            *current = None;
+        }
+
+        if !showing {
+            crate::shortcuts::hide_after_slide(&app);
+            // Back where it rests so the saved position and the next show are right
+            if let Err(e) = crate::window::move_window(&window, PhysicalPosition::new(x, rest_y)) {
+                eprintln!("Failed to restore window position after slide: {}", e);
+            }
+        }
+    });
+}
+
+/// Whether the window is moving in or out, it is partly off screen meanwhile
+pub fn is_sliding<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<SlideState>();
+    let sliding = match state.current.lock() {
+        Ok(guard) => guard.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    };
+    sliding
+}
+
+/// Where the window rests and where it sits just above its monitor
+fn slide_bounds<R: Runtime>(window: &WebviewWindow<R>) -> Option<(i32, i32, i32)> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let monitor = window.current_monitor().ok()??;
+    let hidden_y = monitor.position().y - size.height as i32;
+    Some((position.x, position.y, hidden_y))
+}
+
+/// Move the window above the screen and slide it down to where it was, call
+/// right before show(). Does nothing when sliding is off.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub fn slide_in<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    if !is_enabled(app) {
+        return;
+    }
+    let Some((x, rest_y, hidden_y)) = slide_bounds(window) else {
+        return;
+    };
+    if let Err(e) = crate::window::move_window(window, PhysicalPosition::new(x, hidden_y)) {
+        eprintln!("Failed to move window above the screen: {}", e);
+        return;
+    }
+    let slide = Slide {
+        showing: true,
+        x,
+        rest_y,
+        hidden_y,
+        cancel: Arc::new(AtomicBool::new(false)),
+    };
+    start(app, window, slide, hidden_y);
+}
+
+/// Slide the window up past the top edge and hide it there. Returns false when
+/// sliding is off and the caller should hide it right away.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub fn slide_out<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) -> bool {
+    if !is_enabled(app) {
+        return false;
+    }
+    let Some((x, rest_y, hidden_y)) = slide_bounds(window) else {
+        return false;
+    };
+    let slide = Slide {
+        showing: false,
+        x,
+        rest_y,
+        hidden_y,
+        cancel: Arc::new(AtomicBool::new(false)),
+    };
+    start(app, window, slide, rest_y);
+    true
+}
+
+/// Turn a running slide around from where the window is now. Returns false
+/// when nothing is sliding.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub fn reverse<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) -> bool {
+    let running = {
+        let state = app.state::<SlideState>();
+        let mut current = match state.current.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        current.take()
+    };
+    let Some(running) = running else {
+        return false;
+    };
+    running.cancel.store(true, Ordering::Release);
+
+    let from_y = window
+        .outer_position()
+        .map(|position| position.y)
+        .unwrap_or(if running.showing { running.hidden_y } else { running.rest_y });
+    let slide = Slide {
+        showing: !running.showing,
+        cancel: Arc::new(AtomicBool::new(false)),
+        ..running
+    };
+    start(app, window, slide, from_y);
+    true
+}
+
+/// Tauri command to slide the window in and out on toggle, duration_ms from
+/// 50 to 1000 (150 by default). Skipped while the OS asks for reduced motion.
+/// Not supported on Windows, where the frontend shows and hides the window, so
+/// enabling it there is an error.
+#[tauri::command]
+pub fn set_slide_animation<R: Runtime>(app: AppHandle<R>, enabled: bool, duration_ms: Option<u64>) -> Result<(), String> {
+    if cfg!(target_os = "windows") && enabled {
+        return Err("The slide animation is not supported on Windows".to_string());
+    }
+    if let Some(duration_ms) = duration_ms.filter(|duration_ms| !(50..=1000).contains(duration_ms)) {
+        return Err(format!("Slide duration must be between 50 and 1000 ms, got {}", duration_ms));
+    }
+    window_settings::update(&app, |settings| {
+        settings.slide_animation = enabled;
+        if let Some(duration_ms) = duration_ms {
+            settings.slide_duration_ms = duration_ms;
+        }
+    })?;
+    Ok(())
+}
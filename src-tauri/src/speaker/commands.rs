@@ -44,14 +44,25 @@ impl Default for VadConfig {
     }
 }
 
+// What start_system_audio_capture ended up capturing
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemCaptureStarted {
+    pub pid: Option<u32>, // None when capturing all system audio
+    pub warning: Option<String>,
+}
 
 #[tauri::command]
 pub async fn start_system_audio_capture(
     app: AppHandle,
     vad_config: Option<VadConfig>,
-) -> Result<(), String> {
+    pid: Option<u32>,
+) -> Result<SystemCaptureStarted, String> {
 
     let state = app.state::<crate::AudioState>();
+
+    if crate::audio::is_capturing(&app, crate::audio::AudioSource::System) {
+        return Err("System audio is already being streamed natively".to_string());
+    }
     
     // Check if already capturing (atomic check)
     {
@@ -71,7 +82,16 @@ pub async fn start_system_audio_capture(
         *vad_cfg = config;
     }
 
-    let input = SpeakerInput::new().map_err(|e| {
+    // Too old for process loopback, capture everything rather than nothing
+    let (input, pid, warning) = match pid.map(|pid| (pid, crate::speaker::process_loopback_support())) {
+        Some((pid, Ok(()))) => (SpeakerInput::for_process(pid), Some(pid), None),
+        Some((_, Err(reason))) => {
+            warn!("{}, capturing all system audio", reason);
+            (SpeakerInput::new(), None, Some(format!("{}, capturing all system audio instead", reason)))
+        }
+        None => (SpeakerInput::new(), None, None),
+    };
+    let input = input.map_err(|e| {
         error!("Failed to create speaker input: {}", e);
         format!("Failed to access system audio: {}", e)
     })?;
@@ -96,6 +116,7 @@ pub async fn start_system_audio_capture(
     
     // Emit capture started event
     let _ = app_clone.emit("capture-started", sr);
+    crate::tray::set_recording_state(&app, crate::tray::RecordingKind::System);
     
     let state_clone = app.state::<crate::AudioState>();
     let task = tokio::spawn(async move {
@@ -111,12 +132,15 @@ pub async fn start_system_audio_capture(
                 *guard = None;
             };
         }
+        crate::tray::clear_recording_state(&app_clone, crate::tray::RecordingKind::System);
+        crate::audio::update_sleep_inhibit(&app_clone);
     });
 
     *state_clone.stream_task.lock()
         .map_err(|e| format!("Failed to store task: {}", e))? = Some(task);
+    crate::audio::update_sleep_inhibit(&app);
     
-    Ok(())
+    Ok(SystemCaptureStarted { pid, warning })
 }
 
 // VAD-enabled capture - OPTIMIZED for real-time speech detection
@@ -420,6 +444,7 @@ pub async fn stop_system_audio_capture(app: AppHandle) -> Result<(), String> {
             task.abort();
         }
     }
+    crate::audio::update_sleep_inhibit(&app);
     
     // LONGER delay for proper cleanup (300ms instead of 150ms)
     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
@@ -433,6 +458,7 @@ pub async fn stop_system_audio_capture(app: AppHandle) -> Result<(), String> {
     
     // Emit stopped event
     let _ = app.emit("capture-stopped", ());
+    crate::tray::clear_recording_state(&app, crate::tray::RecordingKind::System);
     Ok(())
 }
 
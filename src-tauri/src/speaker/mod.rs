@@ -19,10 +19,12 @@ use linux::{SpeakerInput as PlatformSpeakerInput, SpeakerStream as PlatformSpeak
 
 mod commands;
 mod devices;
+mod sessions;
 
 // Re-export commands for tauri handler
 pub use commands::*;
 pub use devices::*;
+pub use sessions::*;
 
 // Pluely speaker input and stream
 pub struct SpeakerInput {
@@ -45,6 +47,20 @@ impl SpeakerInput {
         ))
     }
 
+    // Captures only the audio of one process and its children. Windows 10 2004+ only.
+    #[cfg(target_os = "windows")]
+    pub fn for_process(pid: u32) -> Result<Self> {
+        let inner = PlatformSpeakerInput::for_process(pid)?;
+        Ok(Self { inner })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn for_process(_pid: u32) -> Result<Self> {
+        Err(anyhow::anyhow!(
+            "SpeakerInput::for_process is only supported on Windows"
+        ))
+    }
+
     // Starts the audio stream.
     #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
     pub fn stream(self) -> SpeakerStream {
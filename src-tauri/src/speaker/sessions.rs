@@ -0,0 +1,131 @@
+// Apps with an audio session on an output device, so one of them (a meeting app)
+// can be captured without the rest of system audio. Windows only.
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioSession {
+    pub pid: u32,
+    pub name: String,
+    // Playing right now, meeting apps often only start rendering once a call begins
+    pub active: bool,
+}
+
+// Process loopback arrived with Windows 10 2004
+#[cfg(target_os = "windows")]
+const PROCESS_LOOPBACK_MIN_BUILD: u32 = 19041;
+
+#[cfg(target_os = "windows")]
+fn windows_build() -> Option<u32> {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+
+    let key: Vec<u16> = "SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion"
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let name: Vec<u16> = "CurrentBuildNumber".encode_utf16().chain(std::iter::once(0)).collect();
+    let mut data = [0u16; 32];
+    let mut size = (data.len() * 2) as u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(name.as_ptr()),
+            RRF_RT_REG_SZ,
+            None,
+            Some(data.as_mut_ptr() as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+    let len = data.iter().position(|unit| *unit == 0).unwrap_or(data.len());
+    String::from_utf16_lossy(&data[..len]).trim().parse().ok()
+}
+
+/// Whether a single process can be captured, the reason when it can't
+pub fn process_loopback_support() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    return match windows_build() {
+        Some(build) if build >= PROCESS_LOOPBACK_MIN_BUILD => Ok(()),
+        _ => Err("Capturing a single application needs Windows 10 version 2004 or later".to_string()),
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    Err("Capturing a single application is only supported on Windows".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn list_sessions() -> Result<Vec<AudioSession>, String> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::{
+        eRender, AudioSessionStateActive, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+        MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    let own_pid = std::process::id();
+    let mut sessions: Vec<AudioSession> = Vec::new();
+    unsafe {
+        // Already initialized on this thread is fine, the apartment doesn't matter here
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+            .map_err(|e| format!("Failed to create device enumerator: {}", e))?;
+        let devices = enumerator
+            .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+            .map_err(|e| format!("Failed to list output devices: {}", e))?;
+        let device_count = devices.GetCount().map_err(|e| format!("Failed to list output devices: {}", e))?;
+
+        for device_index in 0..device_count {
+            let Ok(device) = devices.Item(device_index) else {
+                continue;
+            };
+            let Ok(manager) = device.Activate::<IAudioSessionManager2>(CLSCTX_ALL, None) else {
+                continue;
+            };
+            let Ok(list) = manager.GetSessionEnumerator() else {
+                continue;
+            };
+            let session_count = list.GetCount().unwrap_or(0);
+            for session_index in 0..session_count {
+                let Ok(control) = list.GetSession(session_index) else {
+                    continue;
+                };
+                let Ok(control) = control.cast::<IAudioSessionControl2>() else {
+                    continue;
+                };
+                let Ok(pid) = control.GetProcessId() else {
+                    continue;
+                };
+                // pid 0 is the system sounds session
+                if pid == 0 || pid == own_pid {
+                    continue;
+                }
+                let active = control.GetState().is_ok_and(|state| state == AudioSessionStateActive);
+                if let Some(existing) = sessions.iter_mut().find(|session| session.pid == pid) {
+                    existing.active |= active;
+                    continue;
+                }
+                sessions.push(AudioSession {
+                    pid,
+                    name: crate::active_app::process_exe_name(pid).unwrap_or_else(|| format!("Process {}", pid)),
+                    active,
+                });
+            }
+        }
+    }
+
+    sessions.sort_by(|a, b| b.active.cmp(&a.active).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    Ok(sessions)
+}
+
+/// Tauri command to list processes with audio sessions, playing ones first. Read
+/// fresh on each call. Empty outside Windows.
+#[tauri::command]
+pub fn list_audio_sessions() -> Result<Vec<AudioSession>, String> {
+    #[cfg(target_os = "windows")]
+    return list_sessions();
+
+    #[cfg(not(target_os = "windows"))]
+    Ok(Vec::new())
+}
@@ -5,15 +5,27 @@ use std::collections::VecDeque;
 use std::sync::{mpsc, Arc, Mutex};
 use std::task::{Poll, Waker};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::error;
 use wasapi::{get_default_device, Direction, SampleType, StreamMode, WaveFormat};
 
-pub struct SpeakerInput {}
+// How often the default output device is compared with the captured one
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Wait per loop for the capture event, short so device checks and shutdown stay responsive
+const EVENT_TIMEOUT_MS: u32 = 200;
+
+pub struct SpeakerInput {
+    process: Option<u32>, // process loopback instead of the whole output device
+}
 
 impl SpeakerInput {
     pub fn new() -> Result<Self> {
-        Ok(Self {})
+        Ok(Self { process: None })
+    }
+
+    pub fn for_process(pid: u32) -> Result<Self> {
+        Ok(Self { process: Some(pid) })
     }
 
     // Starts the audio stream
@@ -28,9 +40,10 @@ impl SpeakerInput {
 
         let queue_clone = sample_queue.clone();
         let waker_clone = waker_state.clone();
+        let process = self.process;
 
         let capture_thread = thread::spawn(move || {
-            if let Err(e) = SpeakerStream::capture_audio_loop(queue_clone, waker_clone, init_tx) {
+            if let Err(e) = SpeakerStream::capture_audio_loop(queue_clone, waker_clone, init_tx, process) {
                 error!("Pluely Audio capture loop failed: {}", e);
             }
         });
@@ -64,118 +77,199 @@ impl SpeakerStream {
         44100
     }
 
-    fn capture_audio_loop(
-        sample_queue: Arc<Mutex<VecDeque<f32>>>,
-        waker_state: Arc<Mutex<WakerState>>,
-        init_tx: mpsc::Sender<Result<()>>,
-    ) -> Result<()> {
-        let init_result = (|| -> Result<_> {
-            let device = get_default_device(&Direction::Render)?;
-            let mut audio_client = device.get_iaudioclient()?;
+    /// Open loopback capture on the current default output device. Shared mode
+    /// with autoconvert keeps the format at 44.1 kHz mono float whatever the
+    /// device runs at, so an output switch doesn't change the sample rate.
+    fn open_loopback() -> Result<(String, wasapi::Handle, wasapi::AudioCaptureClient, wasapi::AudioClient)> {
+        let device = get_default_device(&Direction::Render)?;
+        let device_id = device.get_id()?;
+        let mut audio_client = device.get_iaudioclient()?;
 
-            let desired_format = WaveFormat::new(32, 32, &SampleType::Float, 44100, 1, None);
+        let desired_format = WaveFormat::new(32, 32, &SampleType::Float, 44100, 1, None);
 
-            let (_def_time, min_time) = audio_client.get_device_period()?;
+        let (_def_time, min_time) = audio_client.get_device_period()?;
 
-            let mode = StreamMode::EventsShared {
-                autoconvert: true,
-                buffer_duration_hns: min_time,
-            };
+        let mode = StreamMode::EventsShared {
+            autoconvert: true,
+            buffer_duration_hns: min_time,
+        };
 
-            audio_client.initialize_client(&desired_format, &Direction::Capture, &mode)?;
+        // AUDCLNT_E_DEVICE_IN_USE when another app holds the device in exclusive mode
+        audio_client
+            .initialize_client(&desired_format, &Direction::Capture, &mode)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to open loopback capture, the output device may be in exclusive use by another application: {}",
+                    e
+                )
+            })?;
 
-            let h_event = audio_client.set_get_eventhandle()?;
-            let render_client = audio_client.get_audiocaptureclient()?;
+        let h_event = audio_client.set_get_eventhandle()?;
+        let render_client = audio_client.get_audiocaptureclient()?;
 
-            audio_client.start_stream()?;
+        audio_client.start_stream()?;
 
-            Ok((h_event, render_client))
-        })();
+        Ok((device_id, h_event, render_client, audio_client))
+    }
 
-        match init_result {
-            Ok((h_event, render_client)) => {
-                let _ = init_tx.send(Ok(()));
+    /// Loopback of one process tree, which keeps working whatever output it plays
+    /// to. Process loopback clients have no device period, the buffer is left to WASAPI.
+    fn open_process_loopback(
+        pid: u32,
+    ) -> Result<(String, wasapi::Handle, wasapi::AudioCaptureClient, wasapi::AudioClient)> {
+        let mut audio_client = wasapi::AudioClient::new_application_loopback_client(pid, true)
+            .map_err(|e| anyhow::anyhow!("Failed to capture process {}: {}", pid, e))?;
+
+        let desired_format = WaveFormat::new(32, 32, &SampleType::Float, 44100, 1, None);
+        let mode = StreamMode::EventsShared {
+            autoconvert: true,
+            buffer_duration_hns: 0,
+        };
+        audio_client
+            .initialize_client(&desired_format, &Direction::Capture, &mode)
+            .map_err(|e| anyhow::anyhow!("Failed to open loopback capture for process {}: {}", pid, e))?;
+
+        let h_event = audio_client.set_get_eventhandle()?;
+        let render_client = audio_client.get_audiocaptureclient()?;
+
+        audio_client.start_stream()?;
+
+        Ok((format!("process:{}", pid), h_event, render_client, audio_client))
+    }
 
-                loop {
-                    {
-                        let state = waker_state.lock().unwrap();
-                        if state.shutdown {
-                            break;
-                        }
-                    }
+    fn open(process: Option<u32>) -> Result<(String, wasapi::Handle, wasapi::AudioCaptureClient, wasapi::AudioClient)> {
+        match process {
+            Some(pid) => Self::open_process_loopback(pid),
+            None => Self::open_loopback(),
+        }
+    }
 
-                    if h_event.wait_for_event(3000).is_err() {
-                        error!("Pluely timeout error, stopping capture");
-                        break;
-                    }
+    fn default_device_changed(current_id: &str) -> bool {
+        get_default_device(&Direction::Render)
+            .and_then(|device| device.get_id())
+            .is_ok_and(|id| id != current_id)
+    }
 
-                    let mut temp_queue = VecDeque::new();
-                    if let Err(e) = render_client.read_from_device_to_deque(&mut temp_queue) {
-                        error!("Pluely Failed to read audio data: {}", e);
-                        continue;
+    fn capture_audio_loop(
+        sample_queue: Arc<Mutex<VecDeque<f32>>>,
+        waker_state: Arc<Mutex<WakerState>>,
+        init_tx: mpsc::Sender<Result<()>>,
+        process: Option<u32>,
+    ) -> Result<()> {
+        let (mut device_id, mut h_event, mut render_client, mut audio_client) = match Self::open(process) {
+            Ok(opened) => {
+                let _ = init_tx.send(Ok(()));
+                opened
+            }
+            Err(e) => {
+                let _ = init_tx.send(Err(e));
+                // End the stream so consumers don't wait forever
+                if let Ok(mut state) = waker_state.lock() {
+                    state.shutdown = true;
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
                     }
+                }
+                return Ok(());
+            }
+        };
+        let mut last_device_check = Instant::now();
+
+        loop {
+            {
+                let state = waker_state.lock().unwrap();
+                if state.shutdown {
+                    break;
+                }
+            }
+
+            // Follow the user switching outputs, and recover from an invalidated device
+            let mut reopen = false;
+            if process.is_none() && last_device_check.elapsed() >= DEVICE_CHECK_INTERVAL {
+                last_device_check = Instant::now();
+                reopen = Self::default_device_changed(&device_id);
+            }
+
+            // Loopback only signals while something is playing, silence is not an error
+            if !reopen && h_event.wait_for_event(EVENT_TIMEOUT_MS).is_err() {
+                continue;
+            }
+
+            let mut temp_queue = VecDeque::new();
+            if !reopen {
+                if let Err(e) = render_client.read_from_device_to_deque(&mut temp_queue) {
+                    error!("Pluely Failed to read audio data: {}", e);
+                    reopen = true;
+                }
+            }
 
-                    if temp_queue.is_empty() {
-                        continue;
+            if reopen {
+                let _ = audio_client.stop_stream();
+                match Self::open(process) {
+                    Ok(opened) => {
+                        (device_id, h_event, render_client, audio_client) = opened;
                     }
+                    Err(e) => {
+                        error!("Pluely Failed to reopen loopback capture: {}", e);
+                        thread::sleep(DEVICE_CHECK_INTERVAL);
+                    }
+                }
+                continue;
+            }
+
+            if temp_queue.is_empty() {
+                continue;
+            }
+
+            let mut samples = Vec::new();
+            while temp_queue.len() >= 4 {
+                let bytes = [
+                    temp_queue.pop_front().unwrap(),
+                    temp_queue.pop_front().unwrap(),
+                    temp_queue.pop_front().unwrap(),
+                    temp_queue.pop_front().unwrap(),
+                ];
+                let sample = f32::from_le_bytes(bytes);
+                samples.push(sample);
+            }
 
-                    let mut samples = Vec::new();
-                    while temp_queue.len() >= 4 {
-                        let bytes = [
-                            temp_queue.pop_front().unwrap(),
-                            temp_queue.pop_front().unwrap(),
-                            temp_queue.pop_front().unwrap(),
-                            temp_queue.pop_front().unwrap(),
-                        ];
-                        let sample = f32::from_le_bytes(bytes);
-                        samples.push(sample);
+            if !samples.is_empty() {
+                // Consistent buffer overflow handling
+                let dropped = {
+                    let mut queue = sample_queue.lock().unwrap();
+                    let max_buffer_size = 131072; // 128KB buffer (matching macOS)
+
+                    queue.extend(samples.iter());
+
+                    // If buffer exceeds maximum, drop oldest samples
+                    if queue.len() > max_buffer_size {
+                        let to_drop = queue.len() - max_buffer_size;
+                        queue.drain(0..to_drop);
+                        to_drop
+                    } else {
+                        0
                     }
+                };
 
-                    if !samples.is_empty() {
-                        // Consistent buffer overflow handling
-                        let dropped = {
-                            let mut queue = sample_queue.lock().unwrap();
-                            let current_len = queue.len();
-                            let max_buffer_size = 131072; // 128KB buffer (matching macOS)
-                            
-                            queue.extend(samples.iter());
-                            
-                            // If buffer exceeds maximum, drop oldest samples
-                            let dropped_count = if queue.len() > max_buffer_size {
-                                let to_drop = queue.len() - max_buffer_size;
-                                queue.drain(0..to_drop);
-                                to_drop
-                            } else {
-                                0
-                            };
-                            
-                            dropped_count
-                        };
-                        
-                        if dropped > 0 {
-                            error!("Windows buffer overflow - dropped {} samples", dropped);
-                        }
+                if dropped > 0 {
+                    error!("Windows buffer overflow - dropped {} samples", dropped);
+                }
 
-                        // Wake up consumer
-                        {
-                            let mut state = waker_state.lock().unwrap();
-                            if !state.has_data {
-                                state.has_data = true;
-                                if let Some(waker) = state.waker.take() {
-                                    drop(state);
-                                    waker.wake();
-                                }
-                            }
+                // Wake up consumer
+                {
+                    let mut state = waker_state.lock().unwrap();
+                    if !state.has_data {
+                        state.has_data = true;
+                        if let Some(waker) = state.waker.take() {
+                            drop(state);
+                            waker.wake();
                         }
                     }
                 }
             }
-            Err(e) => {
-                let _ = init_tx.send(Err(e));
-                return Ok(());
-            }
         }
 
+        let _ = audio_client.stop_stream();
         Ok(())
     }
 }
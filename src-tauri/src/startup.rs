@@ -0,0 +1,52 @@
+// A cold launch runs setup before the webview has loaded, so launch actions that
+// emit to the frontend wait until it calls frontend_ready
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+
+type StartupAction = Box<dyn FnOnce() + Send>;
+
+// Actions queued during startup, None once the frontend is listening
+pub struct StartupQueue {
+    pending: Mutex<Option<Vec<StartupAction>>>,
+}
+
+impl Default for StartupQueue {
+    fn default() -> Self {
+        StartupQueue {
+            pending: Mutex::new(Some(Vec::new())),
+        }
+    }
+}
+
+/// Run action now if the frontend is listening, otherwise once it reports ready
+pub fn run_when_ready<R: Runtime, F: FnOnce() + Send + 'static>(app: &AppHandle<R>, action: F) {
+    let state = app.state::<StartupQueue>();
+    let mut pending = match state.pending.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match pending.as_mut() {
+        Some(queue) => queue.push(Box::new(action)),
+        None => {
+            drop(pending);
+            action();
+        }
+    }
+}
+
+/// Tauri command the frontend calls once its event listeners are set up. Later
+/// calls, like after a reload, find the queue already drained.
+#[tauri::command]
+pub fn frontend_ready<R: Runtime>(app: AppHandle<R>) {
+    let queued = {
+        let state = app.state::<StartupQueue>();
+        let mut pending = match state.pending.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        pending.take().unwrap_or_default()
+    };
+    for action in queued {
+        action();
+    }
+}
@@ -0,0 +1,128 @@
+// One-line strip with the latest response at the edge of the screen, for
+// meetings where the full overlay is too much. The frontend emits
+// ticker-update and it is passed on to the strip, which can't be clicked or
+// focused, is left alone by the toggle and goes away in stealth mode.
+use crate::window_settings::{self, WindowAnchor};
+use serde::Deserialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, EventTarget, Listener, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+pub const TICKER_WINDOW_LABEL: &str = "ticker";
+
+// Logical size, one line of text
+const TICKER_WIDTH: f64 = 900.0;
+const TICKER_HEIGHT: f64 = 36.0;
+
+#[derive(Deserialize)]
+struct TickerUpdate {
+    text: String,
+}
+
+/// Pass ticker-update events from the frontend on to the strip, call during setup
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let handle = app.clone();
+    app.listen("ticker-update", move |event| {
+        if handle.get_webview_window(TICKER_WINDOW_LABEL).is_none() {
+            return;
+        }
+        let update: TickerUpdate = match serde_json::from_str(event.payload()) {
+            Ok(update) => update,
+            Err(e) => {
+                eprintln!("Invalid ticker-update payload: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = handle.emit_to(
+            EventTarget::webview_window(TICKER_WINDOW_LABEL),
+            "ticker-update",
+            json!({ "text": update.text }),
+        ) {
+            eprintln!("Failed to emit ticker-update event: {}", e);
+        }
+    });
+}
+
+/// Put the strip at its anchor on the monitor the overlay is on
+fn position<R: Runtime>(app: &AppHandle<R>, window: &tauri::WebviewWindow<R>) -> Result<(), String> {
+    let monitor = app
+        .get_webview_window("main")
+        .and_then(|main| main.current_monitor().ok().flatten())
+        .or_else(|| window.primary_monitor().ok().flatten())
+        .ok_or("No monitor found".to_string())?;
+    let settings = window_settings::get(app);
+    crate::window::place_at_anchor(window, &monitor, settings.ticker_anchor, settings.anchor_margin)
+}
+
+/// Hide the strip, for stealth mode
+pub fn hide<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(TICKER_WINDOW_LABEL) {
+        if let Err(e) = window.hide() {
+            eprintln!("Failed to hide response ticker: {}", e);
+        }
+    }
+}
+
+/// Give the strip the overlay's content protection
+pub fn sync_content_protection<R: Runtime>(app: &AppHandle<R>, protected: bool) {
+    if let Some(window) = app.get_webview_window(TICKER_WINDOW_LABEL) {
+        if let Err(e) = window.set_content_protected(protected) {
+            eprintln!("Failed to set content protection on response ticker: {}", e);
+        }
+    }
+}
+
+/// Tauri command to show the response ticker, created on first use. anchor
+/// moves it and is kept across restarts, top center by default. Refused in
+/// stealth mode. Async because creating a window from a sync command
+/// deadlocks on Windows.
+#[tauri::command]
+pub async fn show_response_overlay<R: Runtime>(app: AppHandle<R>, anchor: Option<WindowAnchor>) -> Result<(), String> {
+    if crate::window::is_stealth_enabled(&app) {
+        return Err("The response ticker is hidden in stealth mode".to_string());
+    }
+    if let Some(anchor) = anchor {
+        window_settings::update(&app, |settings| settings.ticker_anchor = anchor)?;
+    }
+
+    let window = match app.get_webview_window(TICKER_WINDOW_LABEL) {
+        Some(window) => window,
+        None => {
+            let window = WebviewWindowBuilder::new(&app, TICKER_WINDOW_LABEL, WebviewUrl::App("ticker".into()))
+                .title("Pluely Ticker")
+                .inner_size(TICKER_WIDTH, TICKER_HEIGHT)
+                .decorations(false)
+                .transparent(true)
+                .always_on_top(true)
+                .visible_on_all_workspaces(true)
+                .skip_taskbar(true)
+                .resizable(false)
+                .shadow(false)
+                .focused(false)
+                .visible(false)
+                .build()
+                .map_err(|e| format!("Failed to open response ticker: {}", e))?;
+            window
+                .set_ignore_cursor_events(true)
+                .map_err(|e| format!("Failed to make response ticker click-through: {}", e))?;
+            window
+        }
+    };
+
+    let protected = *app
+        .state::<crate::window::WindowState>()
+        .content_protected
+        .lock()
+        .map_err(|e| format!("Failed to read content protection: {}", e))?;
+    sync_content_protection(&app, protected);
+    position(&app, &window)?;
+    window
+        .show()
+        .map_err(|e| format!("Failed to show response ticker: {}", e))
+}
+
+/// Tauri command to hide the response ticker
+#[tauri::command]
+pub fn hide_response_overlay<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    hide(&app);
+    Ok(())
+}
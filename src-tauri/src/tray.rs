@@ -0,0 +1,321 @@
+// Tray icon and menu, the way back into the app when the window is hidden.
+// Menu items run the same handlers as the matching shortcuts.
+use crate::shortcuts;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+// Id of the single tray icon
+pub const TRAY_ID: &str = "main";
+
+// Red-dot variant shown while audio is being captured
+const RECORDING_ICON: Image<'static> = tauri::include_image!("./icons/tray-recording.png");
+
+// What the app is currently listening to, reflected by the tray icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingKind {
+    Mic,
+    System,
+    // Rolling buffer of recent system audio, nothing is sent anywhere yet
+    Listening,
+    None,
+}
+
+// Captures running right now, mic and system audio start and stop independently
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Recording {
+    mic: bool,
+    system: bool,
+}
+
+impl Recording {
+    fn set(&mut self, kind: RecordingKind, on: bool) {
+        match kind {
+            RecordingKind::Mic => self.mic = on,
+            RecordingKind::System => self.system = on,
+            RecordingKind::Listening => {}
+            // Forget every capture, nothing can still be running
+            RecordingKind::None => *self = Recording::default(),
+        }
+    }
+
+    fn with(self, other: Recording) -> Recording {
+        Recording {
+            mic: self.mic || other.mic,
+            system: self.system || other.system,
+        }
+    }
+
+    // Tooltip for the running captures, None when idle
+    fn tooltip(&self) -> Option<&'static str> {
+        match (self.mic, self.system) {
+            (true, true) => Some("Pluely — recording microphone and system audio"),
+            (true, false) => Some("Pluely — recording microphone"),
+            (false, true) => Some("Pluely — recording system audio"),
+            (false, false) => None,
+        }
+    }
+}
+
+// Tray state, only managed once the tray icon exists
+pub struct TrayMenu<R: Runtime> {
+    show_hide: MenuItem<R>, // label follows the window visibility
+    recording: Mutex<Recording>,          // captures Rust runs
+    frontend_recording: Mutex<Recording>, // captures the frontend reported
+    shown_tooltip: Mutex<&'static str>, // skips redundant icon updates
+}
+
+/// Create the tray icon and its menu, call during setup
+pub fn setup_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let item = |id: &str, label: &str| {
+        MenuItem::with_id(app, id, label, true, None::<&str>)
+            .map_err(|e| format!("Failed to create tray menu item {}: {}", id, e))
+    };
+
+    let show_hide = item("show_hide", "Show Pluely")?;
+    let separator = || {
+        PredefinedMenuItem::separator(app).map_err(|e| format!("Failed to create tray separator: {}", e))
+    };
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_hide,
+            &separator()?,
+            &item("audio_recording", "Start voice input")?,
+            &item("screenshot", "Capture screenshot")?,
+            &item("system_audio", "Toggle system audio")?,
+            &separator()?,
+            &item("settings", "Settings")?,
+            &item("quit", "Quit Pluely")?,
+        ],
+    )
+    .map_err(|e| format!("Failed to create tray menu: {}", e))?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Pluely")
+        .menu(&menu)
+        // macOS convention is a menu on any click, elsewhere left click toggles the window
+        .show_menu_on_left_click(cfg!(target_os = "macos"))
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if cfg!(target_os = "macos") {
+                return;
+            }
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                shortcuts::handle_shortcut_action(tray.app_handle(), "toggle_window", "");
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder
+        .build(app)
+        .map_err(|e| format!("Failed to create tray icon: {}", e))?;
+
+    app.manage(TrayMenu {
+        show_hide,
+        recording: Mutex::new(Recording::default()),
+        frontend_recording: Mutex::new(Recording::default()),
+        shown_tooltip: Mutex::new("Pluely"),
+    });
+    set_window_shown(app, shortcuts::is_main_window_visible(app));
+    Ok(())
+}
+
+/// Update the Show/Hide label, called whenever the window is shown or hidden
+pub fn set_window_shown<R: Runtime>(app: &AppHandle<R>, shown: bool) {
+    let Some(menu) = app.try_state::<TrayMenu<R>>() else {
+        return;
+    };
+    let label = if shown { "Hide Pluely" } else { "Show Pluely" };
+    if let Err(e) = menu.show_hide.set_text(label) {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
+}
+
+/// Mark a capture as running and update the tray icon and tooltip, no-op without a tray
+pub fn set_recording_state<R: Runtime>(app: &AppHandle<R>, kind: RecordingKind) {
+    update_recording(app, kind, true);
+}
+
+/// Mark a capture as stopped, the icon keeps showing any other that is still running
+pub fn clear_recording_state<R: Runtime>(app: &AppHandle<R>, kind: RecordingKind) {
+    update_recording(app, kind, false);
+}
+
+/// Whether the microphone or system audio is being recorded, not counting the rolling buffer
+pub fn is_recording<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let current = app.try_state::<TrayMenu<R>>().map(|menu| current_recording(&menu));
+    current.is_some_and(|recording| recording.mic || recording.system)
+}
+
+// Everything recording right now, from Rust or the frontend
+fn current_recording<R: Runtime>(menu: &TrayMenu<R>) -> Recording {
+    let native = match menu.recording.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let frontend = match menu.frontend_recording.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    native.with(frontend)
+}
+
+/// Re-apply the idle state after the rolling buffer starts or stops, leaving
+/// a running recording alone
+pub fn refresh_idle_state<R: Runtime>(app: &AppHandle<R>) {
+    refresh_icon(app);
+}
+
+fn update_recording<R: Runtime>(app: &AppHandle<R>, kind: RecordingKind, on: bool) {
+    let Some(menu) = app.try_state::<TrayMenu<R>>() else {
+        return;
+    };
+    match menu.recording.lock() {
+        Ok(mut guard) => guard.set(kind, on),
+        Err(poisoned) => poisoned.into_inner().set(kind, on),
+    };
+    refresh_icon(app);
+}
+
+// Swap the tray icon and tooltip for what is running, the rolling buffer
+// only shows while nothing records
+fn refresh_icon<R: Runtime>(app: &AppHandle<R>) {
+    let Some(menu) = app.try_state::<TrayMenu<R>>() else {
+        return;
+    };
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let tooltip = match current_recording(&menu).tooltip() {
+        Some(tooltip) => tooltip,
+        None if crate::audio::is_rolling_buffer_running(app) => "Pluely — keeping recent system audio",
+        None => "Pluely",
+    };
+
+    let mut shown = match menu.shown_tooltip.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if *shown == tooltip {
+        return;
+    }
+    *shown = tooltip;
+
+    let icon = if tooltip == "Pluely" {
+        app.default_window_icon().cloned()
+    } else {
+        Some(RECORDING_ICON)
+    };
+    if let Err(e) = tray.set_icon(icon) {
+        eprintln!("Failed to update tray icon: {}", e);
+    }
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        eprintln!("Failed to update tray tooltip: {}", e);
+    }
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    match id {
+        "show_hide" => shortcuts::handle_shortcut_action(app, "toggle_window", ""),
+        "audio_recording" | "screenshot" | "system_audio" => {
+            shortcuts::handle_shortcut_action(app, id, "")
+        }
+        "settings" => open_settings(app),
+        "quit" => quit(app),
+        _ => {}
+    }
+}
+
+/// Release the global shortcuts and exit, the window state is saved on the
+/// way out
+pub fn quit<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(e) = shortcuts::unregister_all_shortcuts(app) {
+        eprintln!("Failed to unregister shortcuts on quit: {}", e);
+    }
+    app.exit(0);
+}
+
+/// Show the window and ask the frontend to open its settings view
+fn open_settings<R: Runtime>(app: &AppHandle<R>) {
+    if !shortcuts::is_main_window_visible(app) {
+        shortcuts::handle_shortcut_action(app, "toggle_window", "");
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("open-settings", json!({})) {
+            eprintln!("Failed to emit open-settings event: {}", e);
+        }
+    }
+}
+
+/// Tauri command to reflect a microphone or system audio capture the frontend
+/// runs in the tray icon, active false once it stops. "none" clears every
+/// capture the frontend reported, the ones Rust runs keep showing.
+#[tauri::command]
+pub fn set_tray_recording_state<R: Runtime>(
+    app: AppHandle<R>,
+    kind: RecordingKind,
+    active: bool,
+) -> Result<(), String> {
+    if kind == RecordingKind::Listening {
+        return Err("The listening state follows the rolling buffer and can't be set".to_string());
+    }
+    let Some(menu) = app.try_state::<TrayMenu<R>>() else {
+        return Ok(());
+    };
+    match menu.frontend_recording.lock() {
+        Ok(mut guard) => guard.set(kind, active),
+        Err(poisoned) => poisoned.into_inner().set(kind, active),
+    };
+    refresh_icon(&app);
+    Ok(())
+}
+
+/// Tauri command to quit the app for real, like the tray's Quit item
+#[tauri::command]
+pub fn quit_app<R: Runtime>(app: AppHandle<R>) {
+    quit(&app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopping_mic_keeps_system_recording() {
+        let mut recording = Recording::default();
+        recording.set(RecordingKind::System, true);
+        recording.set(RecordingKind::Mic, true);
+        assert_eq!(recording.tooltip(), Some("Pluely — recording microphone and system audio"));
+
+        recording.set(RecordingKind::Mic, false);
+        assert_eq!(recording.tooltip(), Some("Pluely — recording system audio"));
+
+        recording.set(RecordingKind::System, false);
+        assert_eq!(recording.tooltip(), None);
+    }
+
+    #[test]
+    fn clearing_frontend_captures_keeps_native_ones() {
+        let mut native = Recording::default();
+        native.set(RecordingKind::System, true);
+        let mut frontend = Recording::default();
+        frontend.set(RecordingKind::Mic, true);
+        assert_eq!(native.with(frontend).tooltip(), Some("Pluely — recording microphone and system audio"));
+
+        frontend.set(RecordingKind::None, true);
+        assert_eq!(native.with(frontend).tooltip(), Some("Pluely — recording system audio"));
+    }
+}
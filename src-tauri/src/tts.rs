@@ -0,0 +1,293 @@
+// Reads responses aloud with the platform voice (AVFoundation, WinRT/SAPI or
+// speech-dispatcher). One thread owns the synthesizer and plays a queue of
+// utterances, holding it while the microphone captures so the answer isn't
+// transcribed back into the conversation.
+use crate::audio::AudioSource;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tts::{Gender, Tts, Voice};
+
+// How often the thread checks playback and the microphone
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// An utterance that never reports speaking is given up after this long
+const START_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsVoice {
+    pub id: String,
+    pub name: String,
+    pub language: String,
+    pub gender: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum SpeakingState {
+    Idle,
+    Speaking,
+    // Waiting for the microphone capture to end
+    Held,
+}
+
+struct Utterance {
+    text: String,
+    voice: Option<String>,
+    rate: f32, // multiple of the voice's normal rate
+}
+
+enum Command {
+    Speak { utterance: Utterance, queue: bool },
+    Stop,
+    ListVoices(Sender<Result<Vec<TtsVoice>, String>>),
+}
+
+// Started on first use, the synthesizer is slow to create on some platforms
+#[derive(Default)]
+pub struct TtsState {
+    sender: Mutex<Option<Sender<Command>>>,
+}
+
+// The front utterance is being played, it leaves the queue once it finished
+struct Playing {
+    since: Instant,
+    seen_speaking: bool,
+}
+
+fn to_tts_voice(voice: &Voice) -> TtsVoice {
+    TtsVoice {
+        id: voice.id(),
+        name: voice.name(),
+        language: voice.language().to_string(),
+        gender: voice.gender().map(|gender| match gender {
+            Gender::Male => "male".to_string(),
+            Gender::Female => "female".to_string(),
+        }),
+    }
+}
+
+fn list_voices(tts: &Tts) -> Result<Vec<TtsVoice>, String> {
+    let voices = tts.voices().map_err(|e| format!("Failed to list voices: {}", e))?;
+    Ok(voices.iter().map(to_tts_voice).collect())
+}
+
+// Backends use different rate scales, 0.5 and 2 map to their minimum and maximum
+fn backend_rate(tts: &Tts, rate: f32) -> f32 {
+    let normal = tts.normal_rate();
+    if rate >= 1.0 {
+        normal + (tts.max_rate() - normal) * (rate - 1.0)
+    } else {
+        normal - (normal - tts.min_rate()) * (1.0 - rate) * 2.0
+    }
+}
+
+fn start(tts: &mut Tts, default_voice: Option<&Voice>, utterance: &Utterance) -> Result<(), String> {
+    let features = tts.supported_features();
+    if features.voice {
+        let voice = match &utterance.voice {
+            Some(id) => {
+                let voices = tts.voices().map_err(|e| format!("Failed to list voices: {}", e))?;
+                let found = voices.into_iter().find(|voice| &voice.id() == id);
+                if found.is_none() {
+                    eprintln!("Voice {} not found, using the default", id);
+                }
+                found.or_else(|| default_voice.cloned())
+            }
+            None => default_voice.cloned(),
+        };
+        if let Some(voice) = voice {
+            tts.set_voice(&voice).map_err(|e| format!("Failed to set voice: {}", e))?;
+        }
+    }
+    if features.rate {
+        let rate = backend_rate(tts, utterance.rate);
+        tts.set_rate(rate).map_err(|e| format!("Failed to set speaking rate: {}", e))?;
+    }
+    tts.speak(utterance.text.clone(), true)
+        .map_err(|e| format!("Failed to speak: {}", e))?;
+    Ok(())
+}
+
+fn run<R: Runtime>(app: AppHandle<R>, mut tts: Tts, receiver: mpsc::Receiver<Command>) {
+    let default_voice = tts.voice().ok().flatten();
+    let mut queue: VecDeque<Utterance> = VecDeque::new();
+    let mut playing: Option<Playing> = None;
+    let mut reported = SpeakingState::Idle;
+
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(Command::Speak { utterance, queue: append }) => {
+                if !append {
+                    queue.clear();
+                    if playing.take().is_some() {
+                        let _ = tts.stop();
+                    }
+                }
+                queue.push_back(utterance);
+            }
+            Ok(Command::Stop) => {
+                queue.clear();
+                playing = None;
+                let _ = tts.stop();
+            }
+            Ok(Command::ListVoices(reply)) => {
+                let _ = reply.send(list_voices(&tts));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        // A held utterance plays again from its start once the capture ends
+        let held = crate::audio::is_capturing(&app, AudioSource::Mic);
+        if held && playing.take().is_some() {
+            let _ = tts.stop();
+        }
+        if let Some(current) = playing.as_mut() {
+            let speaking = tts.is_speaking().unwrap_or(false);
+            current.seen_speaking |= speaking;
+            let finished = if current.seen_speaking {
+                !speaking
+            } else {
+                current.since.elapsed() >= START_TIMEOUT
+            };
+            if finished {
+                playing = None;
+                queue.pop_front();
+            }
+        }
+        if !held && playing.is_none() {
+            while let Some(utterance) = queue.front() {
+                match start(&mut tts, default_voice.as_ref(), utterance) {
+                    Ok(()) => {
+                        playing = Some(Playing {
+                            since: Instant::now(),
+                            seen_speaking: false,
+                        });
+                        break;
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        queue.pop_front();
+                    }
+                }
+            }
+        }
+
+        let state = if playing.is_some() {
+            SpeakingState::Speaking
+        } else if !queue.is_empty() {
+            SpeakingState::Held
+        } else {
+            SpeakingState::Idle
+        };
+        if state != reported {
+            reported = state;
+            let _ = app.emit(
+                "speaking-state-changed",
+                json!({ "state": state, "queued": queue.len() }),
+            );
+        }
+    }
+}
+
+/// Send a command to the speech thread, starting it on first use
+fn send<R: Runtime>(app: &AppHandle<R>, command: Command) -> Result<(), String> {
+    let state = app.state::<TtsState>();
+    let mut sender = state
+        .sender
+        .lock()
+        .map_err(|e| format!("Failed to access speech state: {}", e))?;
+
+    if sender.is_none() {
+        let (command_sender, receiver) = mpsc::channel();
+        let (ready_sender, ready) = mpsc::sync_channel(1);
+        let handle = app.clone();
+        // Some backends aren't Send, so the synthesizer is created on its thread
+        std::thread::Builder::new()
+            .name("pluely-tts".to_string())
+            .spawn(move || match Tts::default() {
+                Ok(tts) => {
+                    let _ = ready_sender.send(Ok(()));
+                    run(handle, tts, receiver);
+                }
+                Err(e) => {
+                    let _ = ready_sender.send(Err(format!("Text to speech is unavailable: {}", e)));
+                }
+            })
+            .map_err(|e| format!("Failed to start text to speech: {}", e))?;
+        ready
+            .recv()
+            .map_err(|_| "Text to speech thread exited".to_string())??;
+        *sender = Some(command_sender);
+    }
+
+    let Some(active) = sender.as_ref() else {
+        return Err("Text to speech is unavailable".to_string());
+    };
+    if active.send(command).is_err() {
+        // Let the next call start a fresh thread
+        *sender = None;
+        return Err("Text to speech thread exited".to_string());
+    }
+    Ok(())
+}
+
+/// Tauri command to read text aloud. Cancels what is playing unless queue is set.
+/// voice is an id from list_tts_voices, rate a multiple of the normal rate (0.5 to 2).
+#[tauri::command]
+pub async fn speak_text<R: Runtime>(
+    app: AppHandle<R>,
+    text: String,
+    voice: Option<String>,
+    rate: Option<f32>,
+    queue: Option<bool>,
+) -> Result<(), String> {
+    let rate = rate.unwrap_or(1.0);
+    if !(0.5..=2.0).contains(&rate) {
+        return Err(format!("Speaking rate must be between 0.5 and 2, got {}", rate));
+    }
+    if text.trim().is_empty() {
+        return Err("Nothing to speak".to_string());
+    }
+    let command = Command::Speak {
+        utterance: Utterance { text, voice, rate },
+        queue: queue.unwrap_or(false),
+    };
+    tauri::async_runtime::spawn_blocking(move || send(&app, command))
+        .await
+        .map_err(|e| format!("Failed to speak: {}", e))?
+}
+
+/// Tauri command to stop speaking and drop queued utterances
+#[tauri::command]
+pub fn stop_speaking<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<TtsState>();
+    let sender = state
+        .sender
+        .lock()
+        .map_err(|e| format!("Failed to access speech state: {}", e))?;
+    // Nothing was ever spoken
+    if let Some(sender) = sender.as_ref() {
+        let _ = sender.send(Command::Stop);
+    }
+    Ok(())
+}
+
+/// Tauri command to list the voices speak_text accepts
+#[tauri::command]
+pub async fn list_tts_voices<R: Runtime>(app: AppHandle<R>) -> Result<Vec<TtsVoice>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (reply, voices) = mpsc::channel();
+        send(&app, Command::ListVoices(reply))?;
+        voices
+            .recv_timeout(Duration::from_secs(5))
+            .map_err(|_| "Timed out listing voices".to_string())?
+    })
+    .await
+    .map_err(|e| format!("Failed to list voices: {}", e))?
+}
@@ -1,7 +1,140 @@
-use tauri::{App, Manager, WebviewWindow};
+use crate::window_settings::{self, FullscreenBehavior, PresetAnchor, WindowAnchor, WindowPreset, WindowPresetName};
+use crate::window_state::WindowRect;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{App, AppHandle, Emitter, Manager, Runtime, WebviewWindow};
 
 // The offset from the top of the screen to the window
-const TOP_OFFSET: i32 = 54;
+pub const TOP_OFFSET: i32 = 54;
+
+// Regular window for the settings UI, separate from the overlay
+pub const SETTINGS_WINDOW_LABEL: &str = "settings";
+
+// Time the window may stay unfocused before hide_on_blur hides it, so focus
+// bouncing through a menu or dialog doesn't close it
+const BLUR_GRACE: Duration = Duration::from_millis(250);
+
+// Levels the opacity shortcut cycles through
+const OPACITY_PRESETS: &[f64] = &[1.0, 0.75, 0.5];
+
+// Change per zoom shortcut press
+const ZOOM_STEP: f64 = 0.1;
+
+// Window failures the frontend tells apart, serialized as { kind, message }
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum WindowError {
+    Unsupported { message: String },
+    Failed { message: String },
+}
+
+impl WindowError {
+    fn failed(message: String) -> Self {
+        WindowError::Failed { message }
+    }
+}
+
+impl std::fmt::Display for WindowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowError::Unsupported { message } | WindowError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+// Flags of the main window the platforms have no getter for, kept by every
+// setter. On Windows the frontend hides the window, is_hidden tracks that.
+pub struct WindowState {
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub is_hidden: Mutex<bool>,
+    pub always_on_top: Mutex<bool>,
+    // Re-applied whenever the window is shown
+    pub click_through: Mutex<bool>,
+    pub content_protected: Mutex<bool>,
+    pub workspace_pinned: Mutex<bool>,
+    pub skip_switcher: Mutex<bool>,
+    // Where the app last put the window, a locked window is snapped back here
+    pub position: Mutex<Option<tauri::PhysicalPosition<i32>>>,
+    // Stealth mode forces content protection and hides the app icon until toggled off
+    pub stealth: Mutex<bool>,
+}
+
+impl Default for WindowState {
+    // As tauri.conf.json creates the window
+    fn default() -> Self {
+        WindowState {
+            is_hidden: Mutex::new(false),
+            always_on_top: Mutex::new(false),
+            click_through: Mutex::new(false),
+            content_protected: Mutex::new(true),
+            workspace_pinned: Mutex::new(true),
+            skip_switcher: Mutex::new(false),
+            position: Mutex::new(None),
+            stealth: Mutex::new(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowStateSnapshot {
+    pub visible: bool,
+    pub focused: bool,
+    pub position: Option<tauri::PhysicalPosition<i32>>,
+    pub size: Option<tauri::PhysicalSize<u32>>,
+    pub monitor_id: Option<String>,
+    pub always_on_top: bool,
+    pub click_through: bool,
+    pub content_protected: bool,
+    pub workspace_pinned: bool,
+    pub skip_switcher: bool,
+    pub position_locked: bool,
+    pub anchor: Option<WindowAnchor>,
+}
+
+fn read_flag(flag: &Mutex<bool>) -> bool {
+    match flag.lock() {
+        Ok(guard) => *guard,
+        Err(poisoned) => *poisoned.into_inner(),
+    }
+}
+
+fn write_flag(flag: &Mutex<bool>, value: bool) {
+    match flag.lock() {
+        Ok(mut guard) => *guard = value,
+        Err(poisoned) => *poisoned.into_inner() = value,
+    }
+}
+
+/// Set workspace pinning on the main window and record it
+fn apply_workspace_pinning<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>, enabled: bool) -> tauri::Result<()> {
+    window.set_visible_on_all_workspaces(enabled)?;
+    write_flag(&app.state::<WindowState>().workspace_pinned, enabled);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentProtectionSupport {
+    pub supported: bool,
+    pub platform: &'static str,
+    pub detail: &'static str,
+}
+
+// Whether showing the window brings it to the monitor under the cursor, and
+// its distance from that monitor's top edge in logical pixels
+pub struct SummonSettings {
+    pub summon_to_cursor_monitor: Mutex<bool>,
+    pub top_offset: Mutex<i32>,
+}
+
+impl Default for SummonSettings {
+    fn default() -> Self {
+        SummonSettings {
+            summon_to_cursor_monitor: Mutex::new(false),
+            top_offset: Mutex::new(TOP_OFFSET),
+        }
+    }
+}
 
 /// Sets up the main window with custom positioning
 pub fn setup_main_window(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
@@ -21,8 +154,8 @@ pub fn setup_main_window(app: &mut App) -> Result<(), Box<dyn std::error::Error>
 }
 
 /// Positions a window at the top center of the screen with a specified Y offset
-pub fn position_window_top_center(
-    window: &WebviewWindow,
+pub fn position_window_top_center<R: Runtime>(
+    window: &WebviewWindow<R>,
     y_offset: i32,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Get the primary monitor
@@ -34,15 +167,101 @@ pub fn position_window_top_center(
         let center_x = (monitor_size.width as i32 - window_size.width as i32) / 2;
 
         // Set the window position
-        window.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
-            x: center_x,
-            y: y_offset,
-        }))?;
+        move_window(window, tauri::PhysicalPosition { x: center_x, y: y_offset })?;
     }
 
     Ok(())
 }
 
+/// Move a window, recording the spot for the main window so the position lock
+/// lets the move through. Every move the app makes goes through here.
+pub fn move_window<R: Runtime>(window: &WebviewWindow<R>, position: tauri::PhysicalPosition<i32>) -> tauri::Result<()> {
+    if window.label() == "main" {
+        let state = window.state::<WindowState>();
+        match state.position.lock() {
+            Ok(mut guard) => *guard = Some(position),
+            Err(poisoned) => *poisoned.into_inner() = Some(position),
+        };
+    }
+    window.set_position(tauri::Position::Physical(position))
+}
+
+/// Get the window ready to be shown: on the current workspace, on the monitor
+/// under the cursor when summon_to_cursor_monitor is on, and at its anchor when
+/// one is set. Call before show().
+pub fn before_show<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    // Joining every workspace for the show makes it appear where the user is
+    // instead of switching to the one it was last on, the saved value is
+    // reapplied once it is shown
+    if let Err(e) = window.set_visible_on_all_workspaces(true) {
+        eprintln!("Failed to bring window to the current workspace: {}", e);
+    }
+    summon_to_cursor_monitor(app, window);
+    apply_anchor(app, window);
+}
+
+/// Move the window to the top center of the monitor under the cursor when
+/// summon_to_cursor_monitor is on, call before show(). The window keeps its
+/// logical size so it isn't tiny or huge on a monitor with another scale factor.
+pub fn summon_to_cursor_monitor<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    let (enabled, top_offset) = {
+        let settings = app.state::<SummonSettings>();
+        let enabled = match settings.summon_to_cursor_monitor.lock() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        let top_offset = match settings.top_offset.lock() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        (enabled, top_offset)
+    };
+    if !enabled {
+        return;
+    }
+    if let Err(e) = move_to_cursor_monitor(window, top_offset) {
+        eprintln!("Failed to move window to the cursor monitor: {}", e);
+    }
+}
+
+fn move_to_cursor_monitor<R: Runtime>(window: &WebviewWindow<R>, top_offset: i32) -> Result<(), String> {
+    use tauri::{LogicalSize, PhysicalPosition, Size};
+
+    let cursor = window
+        .cursor_position()
+        .map_err(|e| format!("Failed to get cursor position: {}", e))?;
+    let (x, y) = (cursor.x as i32, cursor.y as i32);
+    let monitor = window
+        .available_monitors()
+        .map_err(|e| format!("Failed to get monitors: {}", e))?
+        .into_iter()
+        .find(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            x >= position.x
+                && x < position.x + size.width as i32
+                && y >= position.y
+                && y < position.y + size.height as i32
+        })
+        .ok_or("No monitor under the cursor".to_string())?;
+
+    let logical_size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to get window size: {}", e))?
+        .to_logical::<f64>(window.scale_factor().map_err(|e| format!("Failed to get scale factor: {}", e))?);
+    let scale = monitor.scale_factor();
+    let width = (logical_size.width * scale).round() as i32;
+    let position = PhysicalPosition {
+        x: monitor.position().x + (monitor.size().width as i32 - width) / 2,
+        y: monitor.position().y + (top_offset as f64 * scale).round() as i32,
+    };
+
+    // Move first so the size is applied at the new monitor's scale factor
+    move_window(window, position).map_err(|e| format!("Failed to reposition window: {}", e))?;
+    window
+        .set_size(Size::Logical(LogicalSize::new(logical_size.width, logical_size.height)))
+        .map_err(|e| format!("Failed to resize window: {}", e))
+}
+
 /// Future function for centering window completely (both X and Y)
 #[allow(dead_code)]
 pub fn center_window_completely(window: &WebviewWindow) -> Result<(), Box<dyn std::error::Error>> {
@@ -107,12 +326,947 @@ pub fn set_window_height(window: tauri::WebviewWindow, height: u32) -> Result<()
         .map_err(|e| format!("Failed to resize window: {}", e))?;
 
     // Reposition
-    window
-        .set_position(tauri::Position::Physical(PhysicalPosition {
-            x: new_x,
-            y: new_y,
-        }))
+    move_window(&window, PhysicalPosition { x: new_x, y: new_y })
         .map_err(|e| format!("Failed to reposition window: {}", e))?;
 
     Ok(())
 }
+
+/// Tauri command to show the window on the monitor under the cursor whenever a
+/// shortcut summons it, top_offset is in logical pixels from that monitor's top
+#[tauri::command]
+pub fn set_summon_to_cursor_monitor<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    top_offset: Option<i32>,
+) -> Result<(), String> {
+    let settings = app.state::<SummonSettings>();
+    *settings
+        .summon_to_cursor_monitor
+        .lock()
+        .map_err(|e| format!("Failed to update window setting: {}", e))? = enabled;
+    if let Some(top_offset) = top_offset {
+        if !(0..=2000).contains(&top_offset) {
+            return Err(format!("Top offset must be between 0 and 2000, got {}", top_offset));
+        }
+        *settings
+            .top_offset
+            .lock()
+            .map_err(|e| format!("Failed to update window setting: {}", e))? = top_offset;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn set_native_opacity<R: Runtime>(window: &WebviewWindow<R>, opacity: f64) -> Result<(), WindowError> {
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED,
+    };
+
+    let hwnd = window
+        .hwnd()
+        .map_err(|e| WindowError::failed(format!("Failed to get window handle: {}", e)))?;
+    let hwnd = HWND(hwnd.0 as _);
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+        SetLayeredWindowAttributes(hwnd, COLORREF(0), (opacity * 255.0).round() as u8, LWA_ALPHA)
+            .map_err(|e| WindowError::failed(format!("Failed to set window opacity: {}", e)))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_native_opacity<R: Runtime>(window: &WebviewWindow<R>, opacity: f64) -> Result<(), WindowError> {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+
+    let ns_window = window
+        .ns_window()
+        .map_err(|e| WindowError::failed(format!("Failed to get window handle: {}", e)))?;
+    unsafe {
+        let ns_window = &*(ns_window as *const AnyObject);
+        let _: () = msg_send![ns_window, setAlphaValue: opacity];
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn set_native_opacity<R: Runtime>(window: &WebviewWindow<R>, opacity: f64) -> Result<(), WindowError> {
+    use gtk::prelude::WidgetExt;
+
+    let gtk_window = window
+        .gtk_window()
+        .map_err(|e| WindowError::failed(format!("Failed to get window handle: {}", e)))?;
+    // Without a compositor GTK accepts the value and draws the window opaque anyway
+    if !gtk_window.screen().is_some_and(|screen| screen.is_composited()) {
+        return Err(WindowError::Unsupported {
+            message: "Window opacity needs a compositing window manager".to_string(),
+        });
+    }
+    gtk_window.set_opacity(opacity);
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn set_native_opacity<R: Runtime>(_window: &WebviewWindow<R>, _opacity: f64) -> Result<(), WindowError> {
+    Err(WindowError::Unsupported {
+        message: "Window opacity is not supported on this platform".to_string(),
+    })
+}
+
+/// Set the window alpha on the main thread, which all three toolkits require,
+/// and report the result to done
+fn apply_opacity<R: Runtime>(
+    window: &WebviewWindow<R>,
+    opacity: f64,
+    done: impl FnOnce(Result<(), WindowError>) + Send + 'static,
+) {
+    let target = window.clone();
+    let done = std::sync::Arc::new(Mutex::new(Some(done)));
+    let on_main = done.clone();
+    let scheduled = window.run_on_main_thread(move || {
+        let result = set_native_opacity(&target, opacity);
+        if let Some(done) = on_main.lock().ok().and_then(|mut done| done.take()) {
+            done(result);
+        }
+    });
+    if let Err(e) = scheduled {
+        if let Some(done) = done.lock().ok().and_then(|mut done| done.take()) {
+            done(Err(WindowError::failed(format!("Failed to reach the main thread: {}", e))));
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_native_skip_switcher<R: Runtime>(window: &WebviewWindow<R>, skip: bool) -> Result<(), String> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowLongPtrW, SetWindowPos, GWL_EXSTYLE, SWP_FRAMECHANGED, SWP_NOACTIVATE,
+        SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WS_EX_APPWINDOW, WS_EX_TOOLWINDOW,
+    };
+
+    let hwnd = window
+        .hwnd()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+    let hwnd = HWND(hwnd.0 as _);
+    unsafe {
+        // Tool windows stay out of Alt-Tab and Task View but still take focus
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        let style = if skip {
+            (style | WS_EX_TOOLWINDOW.0 as isize) & !(WS_EX_APPWINDOW.0 as isize)
+        } else {
+            style & !(WS_EX_TOOLWINDOW.0 as isize)
+        };
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style);
+        SetWindowPos(
+            hwnd,
+            HWND::default(),
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+        )
+        .map_err(|e| format!("Failed to update window style: {}", e))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_native_skip_switcher<R: Runtime>(window: &WebviewWindow<R>, skip: bool) -> Result<(), String> {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+
+    // NSWindowCollectionBehaviorTransient keeps it out of Mission Control,
+    // IgnoresCycle out of the Cmd-` window cycle. Cmd-Tab lists apps, which the
+    // Accessory policy of a hidden app icon takes care of.
+    const TRANSIENT: usize = 1 << 3;
+    const IGNORES_CYCLE: usize = 1 << 6;
+
+    let ns_window = window
+        .ns_window()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+    unsafe {
+        let ns_window = &*(ns_window as *const AnyObject);
+        let behavior: usize = msg_send![ns_window, collectionBehavior];
+        let behavior = if skip {
+            behavior | TRANSIENT | IGNORES_CYCLE
+        } else {
+            behavior & !(TRANSIENT | IGNORES_CYCLE)
+        };
+        let _: () = msg_send![ns_window, setCollectionBehavior: behavior];
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn set_native_skip_switcher<R: Runtime>(window: &WebviewWindow<R>, skip: bool) -> Result<(), String> {
+    use gtk::prelude::GtkWindowExt;
+
+    let gtk_window = window
+        .gtk_window()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+    // _NET_WM_STATE_SKIP_PAGER, and SKIP_TASKBAR which Alt-Tab switchers go by.
+    // The taskbar hint is left to the app icon setting when not skipping.
+    gtk_window.set_skip_pager_hint(skip);
+    if skip {
+        gtk_window.set_skip_taskbar_hint(true);
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn set_native_skip_switcher<R: Runtime>(_window: &WebviewWindow<R>, _skip: bool) -> Result<(), String> {
+    Err("Hiding from the window switcher is not supported on this platform".to_string())
+}
+
+/// Keep the window out of the window switchers or put it back, on the main
+/// thread which the toolkits require
+fn apply_skip_switcher<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>, skip: bool) {
+    let app_clone = app.clone();
+    let target = window.clone();
+    let scheduled = window.run_on_main_thread(move || match set_native_skip_switcher(&target, skip) {
+        Ok(()) => write_flag(&app_clone.state::<WindowState>().skip_switcher, skip),
+        Err(e) => eprintln!("Failed to set window switcher visibility: {}", e),
+    });
+    if let Err(e) = scheduled {
+        eprintln!("Failed to reach the main thread: {}", e);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_native_float_over_fullscreen<R: Runtime>(
+    window: &WebviewWindow<R>,
+    float: bool,
+    always_on_top: bool,
+) -> Result<(), WindowError> {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+
+    // NSStatusWindowLevel is above fullscreen apps, NSFloatingWindowLevel is
+    // what always on top uses
+    const NORMAL_LEVEL: isize = 0;
+    const FLOATING_LEVEL: isize = 3;
+    const STATUS_LEVEL: isize = 25;
+    const CAN_JOIN_ALL_SPACES: usize = 1 << 0;
+    const FULL_SCREEN_AUXILIARY: usize = 1 << 8;
+
+    let ns_window = window
+        .ns_window()
+        .map_err(|e| WindowError::failed(format!("Failed to get window handle: {}", e)))?;
+    unsafe {
+        let ns_window = &*(ns_window as *const AnyObject);
+        let behavior: usize = msg_send![ns_window, collectionBehavior];
+        let (level, behavior) = if float {
+            (STATUS_LEVEL, behavior | CAN_JOIN_ALL_SPACES | FULL_SCREEN_AUXILIARY)
+        } else {
+            let level = if always_on_top { FLOATING_LEVEL } else { NORMAL_LEVEL };
+            (level, behavior & !FULL_SCREEN_AUXILIARY)
+        };
+        let _: () = msg_send![ns_window, setCollectionBehavior: behavior];
+        let _: () = msg_send![ns_window, setLevel: level];
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_native_float_over_fullscreen<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _float: bool,
+    _always_on_top: bool,
+) -> Result<(), WindowError> {
+    Err(WindowError::Unsupported {
+        message: "Floating over fullscreen apps is only needed and supported on macOS".to_string(),
+    })
+}
+
+/// Raise the window above fullscreen apps on macOS or drop it back to its
+/// always on top level, on the main thread. Fullscreen apps live on their own
+/// Space, which only shows the windows of an Accessory app, so it works best
+/// with the app icon hidden.
+pub fn apply_float_over_fullscreen<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>, float: bool) {
+    if !cfg!(target_os = "macos") {
+        return;
+    }
+    let always_on_top = read_flag(&app.state::<WindowState>().always_on_top);
+    let target = window.clone();
+    let scheduled = window.run_on_main_thread(move || {
+        if let Err(e) = set_native_float_over_fullscreen(&target, float, always_on_top) {
+            eprintln!("Failed to set floating over fullscreen apps: {}", e);
+        }
+    });
+    if let Err(e) = scheduled {
+        eprintln!("Failed to reach the main thread: {}", e);
+    }
+}
+
+/// Reapply the saved appearance, call whenever the window is shown
+pub fn reapply_window_settings<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    let settings = window_settings::get(app);
+    // macOS resets the collection behavior on some show/hide cycles
+    if let Err(e) = apply_workspace_pinning(app, window, settings.visible_on_all_workspaces) {
+        eprintln!("Failed to reapply workspace pinning: {}", e);
+    }
+    let protected = settings.content_protection || is_stealth_enabled(app);
+    if let Err(e) = apply_content_protection(app, protected) {
+        eprintln!("Failed to reapply content protection: {}", e);
+    }
+    // Skipped by default, only a window that was changed needs the call
+    if settings.skip_switcher || read_flag(&app.state::<WindowState>().skip_switcher) {
+        apply_skip_switcher(app, window, settings.skip_switcher);
+    }
+    // Last, showing and the calls above can reset the level and behavior
+    if settings.float_over_fullscreen {
+        apply_float_over_fullscreen(app, window, true);
+    }
+    if settings.opacity < 1.0 {
+        apply_opacity(window, settings.opacity, |result| {
+            if let Err(e) = result {
+                eprintln!("Failed to reapply window opacity: {}", e);
+            }
+        });
+    }
+}
+
+/// Apply, save and announce an opacity, clamped to 0.2 to 1
+async fn change_opacity<R: Runtime>(app: &AppHandle<R>, opacity: f64) -> Result<f64, WindowError> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or(WindowError::failed("Main window not found".to_string()))?;
+    let opacity = opacity.clamp(0.2, 1.0);
+
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    apply_opacity(&window, opacity, move |result| {
+        let _ = sender.send(result);
+    });
+    receiver
+        .await
+        .map_err(|_| WindowError::failed("Window opacity was never applied".to_string()))??;
+
+    window_settings::update(app, |settings| settings.opacity = opacity).map_err(WindowError::failed)?;
+    if let Err(e) = app.emit("opacity-changed", opacity) {
+        eprintln!("Failed to emit opacity-changed event: {}", e);
+    }
+    Ok(opacity)
+}
+
+/// Step to the next opacity preset, for the cycle_opacity shortcut
+pub fn cycle_opacity<R: Runtime>(app: &AppHandle<R>) {
+    let current = window_settings::get(app).opacity;
+    let index = OPACITY_PRESETS
+        .iter()
+        .position(|preset| (preset - current).abs() < 0.01)
+        .map_or(0, |index| (index + 1) % OPACITY_PRESETS.len());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = change_opacity(&app, OPACITY_PRESETS[index]).await {
+            eprintln!("Failed to cycle window opacity: {}", e);
+        }
+    });
+}
+
+/// Tauri command to make the overlay see-through, value is clamped to 0.2 to 1
+/// and kept across restarts. Returns the opacity applied.
+#[tauri::command]
+pub async fn set_window_opacity<R: Runtime>(app: AppHandle<R>, value: f64) -> Result<f64, WindowError> {
+    if !value.is_finite() {
+        return Err(WindowError::failed(format!("Invalid opacity {}", value)));
+    }
+    change_opacity(&app, value).await
+}
+
+pub fn is_stealth_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    read_flag(&app.state::<WindowState>().stealth)
+}
+
+fn apply_content_protection<R: Runtime>(app: &AppHandle<R>, protected: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    window
+        .set_content_protected(protected)
+        .map_err(|e| format!("Failed to set content protection: {}", e))?;
+    write_flag(&app.state::<WindowState>().content_protected, protected);
+    crate::ticker::sync_content_protection(app, protected);
+    Ok(())
+}
+
+/// Turn stealth mode on or off: content protection plus a hidden app icon.
+/// Ending it goes back to the saved content protection and icon choice.
+pub fn set_stealth<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let settings = window_settings::get(app);
+    apply_content_protection(app, enabled || settings.content_protection)?;
+    crate::shortcuts::apply_app_icon_visibility(app, !enabled && settings.app_icon_visible)?;
+    if enabled {
+        crate::ticker::hide(app);
+    }
+
+    let state = app.state::<WindowState>();
+    *state
+        .stealth
+        .lock()
+        .map_err(|e| format!("Failed to update stealth state: {}", e))? = enabled;
+
+    if let Err(e) = app.emit("stealth-changed", enabled) {
+        eprintln!("Failed to emit stealth-changed event: {}", e);
+    }
+    Ok(())
+}
+
+/// Flip stealth mode, for the toggle_stealth_mode shortcut
+pub fn toggle_stealth<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(e) = set_stealth(app, !is_stealth_enabled(app)) {
+        eprintln!("Failed to toggle stealth mode: {}", e);
+    }
+}
+
+/// Tauri command to hide the overlay from screen sharing and other apps'
+/// screenshots, kept across restarts. Stealth mode keeps it on regardless.
+#[tauri::command]
+pub fn set_content_protection<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    apply_content_protection(&app, enabled || is_stealth_enabled(&app))?;
+    window_settings::update(&app, |settings| settings.content_protection = enabled)?;
+    Ok(())
+}
+
+/// Tauri command to tell whether content protection works on this platform
+#[tauri::command]
+pub fn get_content_protection_support() -> ContentProtectionSupport {
+    #[cfg(target_os = "windows")]
+    return ContentProtectionSupport {
+        supported: true,
+        platform: "windows",
+        detail: "Excluded from capture on Windows 10 2004 and later, shown as a black box before that",
+    };
+
+    #[cfg(target_os = "macos")]
+    return ContentProtectionSupport {
+        supported: true,
+        platform: "macos",
+        detail: "Hidden from capture through the window sharing type",
+    };
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    ContentProtectionSupport {
+        supported: false,
+        platform: std::env::consts::OS,
+        detail: "The window system offers no way to keep a window out of screen captures",
+    }
+}
+
+/// Hide the window after the grace period when hide_on_blur is on and nothing
+/// needs it to stay: a screenshot countdown, a region selection or a recording
+pub fn handle_blur<R: Runtime>(app: &AppHandle<R>) {
+    if !window_settings::get(app).hide_on_blur {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(BLUR_GRACE).await;
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        if matches!(window.is_focused(), Ok(true)) || !crate::shortcuts::is_main_window_visible(&app) {
+            return;
+        }
+        if crate::capture::is_screenshot_pending(&app) || crate::tray::is_recording(&app) {
+            return;
+        }
+        crate::shortcuts::hide_main_window(&app);
+    });
+}
+
+/// Tauri command to hide the window whenever it loses focus, kept across restarts
+#[tauri::command]
+pub fn set_hide_on_blur<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    window_settings::update(&app, |settings| settings.hide_on_blur = enabled)?;
+    Ok(())
+}
+
+/// Tauri command to choose whether closing the window hides it to the tray
+/// (the default) or quits the app, kept across restarts
+#[tauri::command]
+pub fn set_close_hides_to_tray<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    window_settings::update(&app, |settings| settings.close_hides_to_tray = enabled)?;
+    Ok(())
+}
+
+/// Resize to a preset keeping its anchor on the top edge where it is, so the
+/// window grows from there instead of from the top-left corner
+fn apply_preset<R: Runtime>(window: &WebviewWindow<R>, preset: WindowPreset) -> Result<(), String> {
+    use tauri::{PhysicalPosition, PhysicalSize};
+
+    let scale = window
+        .scale_factor()
+        .map_err(|e| format!("Failed to get scale factor: {}", e))?;
+    let position = window
+        .outer_position()
+        .map_err(|e| format!("Failed to get window position: {}", e))?;
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to get window size: {}", e))?;
+
+    let width = (preset.width * scale).round() as u32;
+    let height = (preset.height * scale).round() as u32;
+    let x = match preset.anchor {
+        PresetAnchor::Left => position.x,
+        PresetAnchor::Center => position.x + (size.width as i32 - width as i32) / 2,
+        PresetAnchor::Right => position.x + size.width as i32 - width as i32,
+    };
+
+    window
+        .set_size(PhysicalSize::new(width, height))
+        .map_err(|e| format!("Failed to resize window: {}", e))?;
+    move_window(window, PhysicalPosition::new(x, position.y)).map_err(|e| format!("Failed to reposition window: {}", e))
+}
+
+fn switch_preset<R: Runtime>(app: &AppHandle<R>, name: WindowPresetName) -> Result<WindowPreset, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    let preset = window_settings::get(app).preset(name);
+    apply_preset(&window, preset)?;
+    window_settings::update(app, |settings| settings.window_preset = name)?;
+
+    if let Err(e) = app.emit(
+        "window-preset-changed",
+        serde_json::json!({ "name": name, "width": preset.width, "height": preset.height }),
+    ) {
+        eprintln!("Failed to emit window-preset-changed event: {}", e);
+    }
+    Ok(preset)
+}
+
+/// Step to the next size preset, for the cycle_window_preset shortcut
+pub fn cycle_window_preset<R: Runtime>(app: &AppHandle<R>) {
+    let next = window_settings::get(app).window_preset.next();
+    if let Err(e) = switch_preset(app, next) {
+        eprintln!("Failed to cycle window preset: {}", e);
+    }
+}
+
+/// Tauri command to resize the window to a preset. width, height (logical
+/// pixels) and anchor, when given, are stored in the preset first.
+#[tauri::command]
+pub fn set_window_preset<R: Runtime>(
+    app: AppHandle<R>,
+    name: WindowPresetName,
+    width: Option<f64>,
+    height: Option<f64>,
+    anchor: Option<PresetAnchor>,
+) -> Result<WindowPreset, String> {
+    if let Some(width) = width.filter(|width| !(200.0..=4000.0).contains(width)) {
+        return Err(format!("Preset width must be between 200 and 4000, got {}", width));
+    }
+    if let Some(height) = height.filter(|height| !(40.0..=3000.0).contains(height)) {
+        return Err(format!("Preset height must be between 40 and 3000, got {}", height));
+    }
+    if width.is_some() || height.is_some() || anchor.is_some() {
+        window_settings::update(&app, |settings| {
+            let preset = settings.preset_mut(name);
+            preset.width = width.unwrap_or(preset.width);
+            preset.height = height.unwrap_or(preset.height);
+            preset.anchor = anchor.unwrap_or(preset.anchor);
+        })?;
+    }
+    switch_preset(&app, name)
+}
+
+/// Tauri command to keep the window on every Space or virtual desktop, on by
+/// default and kept across restarts
+#[tauri::command]
+pub fn set_visible_on_all_workspaces<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    apply_workspace_pinning(&app, &window, enabled).map_err(|e| format!("Failed to set workspace pinning: {}", e))?;
+    window_settings::update(&app, |settings| settings.visible_on_all_workspaces = enabled)?;
+    Ok(())
+}
+
+/// Tauri command to choose whether the capture shortcuts show the window
+/// (unfocused) or leave it hidden while a fullscreen app is in front
+#[tauri::command]
+pub fn set_fullscreen_behavior<R: Runtime>(app: AppHandle<R>, behavior: FullscreenBehavior) -> Result<(), String> {
+    window_settings::update(&app, |settings| settings.fullscreen_behavior = behavior)?;
+    Ok(())
+}
+
+// Docks and panels reserve screen edges through struts, which GDK only reports
+// per monitor on some window managers, so they are read from the X server
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn x11_work_area(monitor: &tauri::Monitor, monitors: &[tauri::Monitor]) -> Option<WindowRect> {
+    use crate::active_app::run_x11_tool;
+
+    let root_width = monitors.iter().map(|m| m.position().x + m.size().width as i32).max()?;
+    let root_height = monitors.iter().map(|m| m.position().y + m.size().height as i32).max()?;
+    let (mx, my) = (monitor.position().x, monitor.position().y);
+    let (mw, mh) = (monitor.size().width as i32, monitor.size().height as i32);
+    let (mut left, mut top, mut right, mut bottom) = (mx, my, mx + mw, my + mh);
+
+    // "_NET_CLIENT_LIST(WINDOW): window id # 0x1a00003, 0x3a00007"
+    let clients = run_x11_tool("xprop", &["-root", "_NET_CLIENT_LIST"])?;
+    let (_, ids) = clients.split_once('#')?;
+    for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        // left, right, top, bottom, then the start and end of each along its edge
+        let values: Vec<i32> = ["_NET_WM_STRUT_PARTIAL", "_NET_WM_STRUT"]
+            .iter()
+            .find_map(|atom| {
+                let out = run_x11_tool("xprop", &["-id", id, atom])?;
+                let (_, values) = out.split_once('=')?;
+                Some(values.split(',').filter_map(|value| value.trim().parse().ok()).collect::<Vec<i32>>())
+            })
+            .unwrap_or_default();
+        let strut = match values.as_slice() {
+            // _NET_WM_STRUT spans the whole edge
+            [l, r, t, b] => [*l, *r, *t, *b, 0, root_height, 0, root_height, 0, root_width, 0, root_width],
+            partial => match <[i32; 12]>::try_from(partial) {
+                Ok(strut) => strut,
+                Err(_) => continue,
+            },
+        };
+        let [l, r, t, b, l_start, l_end, r_start, r_end, t_start, t_end, b_start, b_end] = strut;
+        let spans_y = |start: i32, end: i32| start < my + mh && end >= my;
+        let spans_x = |start: i32, end: i32| start < mx + mw && end >= mx;
+        if l > 0 && spans_y(l_start, l_end) && l > mx {
+            left = left.max(l);
+        }
+        if r > 0 && spans_y(r_start, r_end) && root_width - r < mx + mw {
+            right = right.min(root_width - r);
+        }
+        if t > 0 && spans_x(t_start, t_end) && t > my {
+            top = top.max(t);
+        }
+        if b > 0 && spans_x(b_start, b_end) && root_height - b < my + mh {
+            bottom = bottom.min(root_height - b);
+        }
+    }
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(WindowRect {
+        x: left,
+        y: top,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    })
+}
+
+/// The monitor minus the taskbar, dock and panels. Wayland doesn't expose
+/// panels to clients, there it is usually the whole monitor.
+fn work_area<R: Runtime>(window: &WebviewWindow<R>, monitor: &tauri::Monitor) -> WindowRect {
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        if let Some(area) = window
+            .available_monitors()
+            .ok()
+            .and_then(|monitors| x11_work_area(monitor, &monitors))
+        {
+            return area;
+        }
+    }
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    let _ = window;
+
+    let area = monitor.work_area();
+    WindowRect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    }
+}
+
+/// Where the anchor puts a window of the given size inside the area
+fn anchored_position(anchor: WindowAnchor, area: &WindowRect, width: u32, height: u32, margin: i32) -> (i32, i32) {
+    let left = area.x + margin;
+    let center_x = area.x + (area.width as i32 - width as i32) / 2;
+    let right = area.x + area.width as i32 - width as i32 - margin;
+    let top = area.y + margin;
+    let center_y = area.y + (area.height as i32 - height as i32) / 2;
+    let bottom = area.y + area.height as i32 - height as i32 - margin;
+    match anchor {
+        WindowAnchor::TopLeft => (left, top),
+        WindowAnchor::TopCenter => (center_x, top),
+        WindowAnchor::TopRight => (right, top),
+        WindowAnchor::CenterLeft => (left, center_y),
+        WindowAnchor::Center => (center_x, center_y),
+        WindowAnchor::CenterRight => (right, center_y),
+        WindowAnchor::BottomLeft => (left, bottom),
+        WindowAnchor::BottomCenter => (center_x, bottom),
+        WindowAnchor::BottomRight => (right, bottom),
+    }
+}
+
+fn primary_monitor<R: Runtime>(window: &WebviewWindow<R>) -> Result<tauri::Monitor, String> {
+    window
+        .primary_monitor()
+        .map_err(|e| format!("Failed to get monitor: {}", e))?
+        .ok_or("No monitor found".to_string())
+}
+
+fn move_to_anchor<R: Runtime>(window: &WebviewWindow<R>, anchor: WindowAnchor, margin: u32) -> Result<(), String> {
+    // The monitor it is on, which summon_to_cursor_monitor has already picked
+    let monitor = match window.current_monitor() {
+        Ok(Some(monitor)) => monitor,
+        _ => primary_monitor(window)?,
+    };
+    place_at_anchor(window, &monitor, anchor, margin)
+}
+
+/// Move a window to the anchor inside the given monitor's work area
+pub fn place_at_anchor<R: Runtime>(
+    window: &WebviewWindow<R>,
+    monitor: &tauri::Monitor,
+    anchor: WindowAnchor,
+    margin: u32,
+) -> Result<(), String> {
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to get window size: {}", e))?;
+    let area = work_area(window, monitor);
+    let margin = (margin as f64 * monitor.scale_factor()).round() as i32;
+    let (x, y) = anchored_position(anchor, &area, size.width, size.height, margin);
+    move_window(window, tauri::PhysicalPosition::new(x, y)).map_err(|e| format!("Failed to reposition window: {}", e))
+}
+
+/// Bring a stranded window back to the primary monitor: at its anchor when one
+/// is set, otherwise at the default top center spot
+pub fn move_to_primary_monitor<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) -> Result<(), String> {
+    let monitor = primary_monitor(window)?;
+    let settings = window_settings::get(app);
+    match settings.window_anchor {
+        Some(anchor) => place_at_anchor(window, &monitor, anchor, settings.anchor_margin),
+        None => place_at_anchor(window, &monitor, WindowAnchor::TopCenter, TOP_OFFSET as u32),
+    }
+}
+
+/// Snap the window to its anchor when one is set, call before show()
+pub fn apply_anchor<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    let settings = window_settings::get(app);
+    let Some(anchor) = settings.window_anchor else {
+        return;
+    };
+    if let Err(e) = move_to_anchor(window, anchor, settings.anchor_margin) {
+        eprintln!("Failed to move window to its anchor: {}", e);
+    }
+}
+
+/// Tauri command to pin the window to an edge or corner of the work area of
+/// its monitor with margin logical pixels around it, applied on every show.
+/// A null anchor goes back to leaving the window where it was dragged.
+#[tauri::command]
+pub fn set_window_anchor<R: Runtime>(app: AppHandle<R>, anchor: Option<WindowAnchor>, margin: u32) -> Result<(), String> {
+    if margin > 500 {
+        return Err(format!("Anchor margin must be at most 500, got {}", margin));
+    }
+    window_settings::update(&app, |settings| {
+        settings.window_anchor = anchor;
+        settings.anchor_margin = margin;
+    })?;
+    if let Some(window) = app.get_webview_window("main") {
+        apply_anchor(&app, &window);
+    }
+    Ok(())
+}
+
+/// Tauri command to open the settings UI in its own decorated window at the
+/// /settings route, or bring it to the front when it is already open. It is
+/// left alone by the overlay's toggle, and events the backend emits reach it
+/// as well since emit broadcasts to every webview. Async because creating a
+/// window from a sync command deadlocks on Windows.
+#[tauri::command]
+pub async fn open_settings_window<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    use tauri::{WebviewUrl, WebviewWindowBuilder};
+
+    if let Some(window) = app.get_webview_window(SETTINGS_WINDOW_LABEL) {
+        if let Err(e) = window.unminimize() {
+            eprintln!("Failed to unminimize settings window: {}", e);
+        }
+        return window
+            .show()
+            .and_then(|_| window.set_focus())
+            .map_err(|e| format!("Failed to focus settings window: {}", e));
+    }
+
+    let window = WebviewWindowBuilder::new(&app, SETTINGS_WINDOW_LABEL, WebviewUrl::App("settings".into()))
+        .title("Pluely Settings")
+        .inner_size(820.0, 640.0)
+        .min_inner_size(560.0, 420.0)
+        .decorations(true)
+        .always_on_top(false)
+        .resizable(true)
+        .center()
+        .focused(true)
+        .build()
+        .map_err(|e| format!("Failed to open settings window: {}", e))?;
+    let zoom = window_settings::get(&app).zoom;
+    if let Err(e) = window.set_zoom(zoom) {
+        eprintln!("Failed to apply zoom to settings window: {}", e);
+    }
+    Ok(())
+}
+
+/// Tauri command to report the main window as it is: visibility, focus and
+/// bounds from the window itself, the flags without a getter from WindowState.
+/// Position and size are in physical pixels.
+#[tauri::command]
+pub fn get_window_state<R: Runtime>(app: AppHandle<R>) -> Result<WindowStateSnapshot, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    let state = app.state::<WindowState>();
+    let settings = window_settings::get(&app);
+    Ok(WindowStateSnapshot {
+        visible: crate::shortcuts::is_main_window_visible(&app),
+        focused: window.is_focused().unwrap_or(false),
+        position: window.outer_position().ok(),
+        size: window.outer_size().ok(),
+        monitor_id: window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned()),
+        always_on_top: read_flag(&state.always_on_top),
+        click_through: read_flag(&state.click_through),
+        content_protected: read_flag(&state.content_protected),
+        workspace_pinned: read_flag(&state.workspace_pinned),
+        skip_switcher: read_flag(&state.skip_switcher),
+        position_locked: settings.position_locked,
+        anchor: settings.window_anchor,
+    })
+}
+
+/// Tauri command to keep the overlay out of Alt-Tab, Task View, Mission
+/// Control and pagers, kept across restarts. The window still takes keyboard
+/// focus when the toggle shows it.
+#[tauri::command]
+pub fn set_skip_switcher<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    window_settings::update(&app, |settings| settings.skip_switcher = enabled)?;
+    apply_skip_switcher(&app, &window, enabled);
+    Ok(())
+}
+
+/// Tauri command to show the overlay over other apps' fullscreen windows on
+/// macOS, kept across restarts and reapplied on every show. Unsupported
+/// elsewhere, where always on top already covers fullscreen apps.
+#[tauri::command]
+pub fn set_float_over_fullscreen<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), WindowError> {
+    if !cfg!(target_os = "macos") {
+        return Err(WindowError::Unsupported {
+            message: "Floating over fullscreen apps is only needed and supported on macOS".to_string(),
+        });
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or(WindowError::failed("Main window not found".to_string()))?;
+    window_settings::update(&app, |settings| settings.float_over_fullscreen = enabled).map_err(WindowError::failed)?;
+    apply_float_over_fullscreen(&app, &window, enabled);
+    Ok(())
+}
+
+/// Apply the saved zoom to a newly created main window
+pub fn apply_saved_zoom<R: Runtime>(app: &AppHandle<R>, window: &WebviewWindow<R>) {
+    let zoom = window_settings::get(app).zoom;
+    if (zoom - 1.0).abs() < f64::EPSILON {
+        return;
+    }
+    if let Err(e) = window.set_zoom(zoom) {
+        eprintln!("Failed to apply zoom: {}", e);
+    }
+}
+
+/// Zoom the main and settings windows, save and announce it. Tauri maps the
+/// factor to WebView2's ZoomFactor, WKWebView's pageZoom and WebKitGTK's zoom
+/// level. Clamped to 0.5 to 2, returns the factor applied.
+fn change_zoom<R: Runtime>(app: &AppHandle<R>, factor: f64) -> Result<f64, String> {
+    // Rounded so repeated steps don't drift
+    let factor = (factor.clamp(0.5, 2.0) * 100.0).round() / 100.0;
+    for label in ["main", SETTINGS_WINDOW_LABEL] {
+        if let Some(window) = app.get_webview_window(label) {
+            window
+                .set_zoom(factor)
+                .map_err(|e| format!("Failed to zoom {} window: {}", label, e))?;
+        }
+    }
+    window_settings::update(app, |settings| settings.zoom = factor)?;
+    if let Err(e) = app.emit("zoom-changed", serde_json::json!({ "factor": factor })) {
+        eprintln!("Failed to emit zoom-changed event: {}", e);
+    }
+    Ok(factor)
+}
+
+/// Zoom in or out by a step, or back to 100% without a step, for the zoom shortcuts
+pub fn step_zoom<R: Runtime>(app: &AppHandle<R>, step: Option<f64>) {
+    let factor = match step {
+        Some(step) => window_settings::get(app).zoom + step * ZOOM_STEP,
+        None => 1.0,
+    };
+    if let Err(e) = change_zoom(app, factor) {
+        eprintln!("Failed to change zoom: {}", e);
+    }
+}
+
+/// Tauri command to scale the overlay and settings content, factor is clamped
+/// to 0.5 to 2 and kept across restarts. Returns the factor applied.
+#[tauri::command]
+pub fn set_webview_zoom<R: Runtime>(app: AppHandle<R>, factor: f64) -> Result<f64, String> {
+    if !factor.is_finite() {
+        return Err(format!("Invalid zoom factor {}", factor));
+    }
+    change_zoom(&app, factor)
+}
+
+/// Snap the main window back when it is dragged while its position is locked.
+/// Moves the app makes went through move_window and match the recorded spot.
+pub fn handle_window_event<R: Runtime>(window: &tauri::Window<R>, event: &tauri::WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    let tauri::WindowEvent::Moved(position) = event else {
+        return;
+    };
+    let app = window.app_handle();
+    let state = app.state::<WindowState>();
+    let mut recorded = match state.position.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if !window_settings::get(app).position_locked {
+        *recorded = Some(*position);
+        return;
+    }
+    // A pixel or two off from rounding isn't a drag
+    let Some(target) = *recorded else {
+        return;
+    };
+    if (target.x - position.x).abs() <= 2 && (target.y - position.y).abs() <= 2 {
+        return;
+    }
+    drop(recorded);
+    if let Err(e) = window.set_position(tauri::Position::Physical(target)) {
+        eprintln!("Failed to snap locked window back: {}", e);
+    }
+}
+
+/// Tauri command to stop the window from being dragged, kept across restarts.
+/// The app's own moves (anchor, presets, summon) still go through.
+#[tauri::command]
+pub fn set_position_locked<R: Runtime>(app: AppHandle<R>, locked: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    let position = window
+        .outer_position()
+        .map_err(|e| format!("Failed to get window position: {}", e))?;
+    let state = app.state::<WindowState>();
+    *state
+        .position
+        .lock()
+        .map_err(|e| format!("Failed to update window position: {}", e))? = Some(position);
+    window_settings::update(&app, |settings| settings.position_locked = locked)?;
+    Ok(())
+}
@@ -0,0 +1,303 @@
+// Appearance settings of the main window, kept in the settings store with one
+// key per field, so settings::bool(app, "hide_on_blur", ..) reads the same value.
+// Some platforms drop these flags when the window is hidden, so they are
+// reapplied every time it is shown.
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use tauri::{AppHandle, Manager, Runtime};
+
+// Separate file of earlier versions, moved into the settings store once
+const LEGACY_WINDOW_SETTINGS_FILE: &str = "window-settings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowPresetName {
+    Compact,
+    Expanded,
+    Custom,
+}
+
+impl WindowPresetName {
+    pub fn next(self) -> Self {
+        match self {
+            WindowPresetName::Compact => WindowPresetName::Expanded,
+            WindowPresetName::Expanded => WindowPresetName::Custom,
+            WindowPresetName::Custom => WindowPresetName::Compact,
+        }
+    }
+}
+
+// What the capture shortcuts do with a hidden window over a fullscreen app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FullscreenBehavior {
+    // Shown without taking focus, which would end the app's fullscreen
+    ShowUnfocused,
+    // Left hidden, capture still starts
+    StayHidden,
+}
+
+// Point on the top edge that stays put when a preset resizes the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetAnchor {
+    Left,
+    Center,
+    Right,
+}
+
+// Edge or corner of the monitor's work area the window snaps to on show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+// Minutes after local midnight, end before start spans midnight
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: u16,
+    pub end: u16,
+}
+
+// Size in logical pixels, so it scales with the monitor
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowPreset {
+    pub width: f64,
+    pub height: f64,
+    pub anchor: PresetAnchor,
+}
+
+impl WindowPreset {
+    const fn new(width: f64, height: f64) -> Self {
+        WindowPreset {
+            width,
+            height,
+            anchor: PresetAnchor::Center,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedWindowSettings {
+    pub opacity: f64,
+    // Page zoom of the main and settings windows
+    pub zoom: f64,
+    // Hidden from screen sharing and screenshots of other apps
+    pub content_protection: bool,
+    // Last choice of set_app_icon_visibility, restored when stealth mode ends
+    pub app_icon_visible: bool,
+    // Hide the window shortly after it loses focus
+    pub hide_on_blur: bool,
+    // The window manager's close button hides instead of quitting
+    pub close_hides_to_tray: bool,
+    // request_attention also posts a notification
+    pub attention_notifications: bool,
+    // request_attention does nothing in this window
+    pub quiet_hours: Option<QuietHours>,
+    // Follow the user across Spaces and virtual desktops
+    pub visible_on_all_workspaces: bool,
+    // Left out of Alt-Tab, Mission Control and pagers
+    pub skip_switcher: bool,
+    // macOS only, shown over other apps' fullscreen Spaces
+    pub float_over_fullscreen: bool,
+    pub fullscreen_behavior: FullscreenBehavior,
+    // Slide in from the top edge on toggle, see slide.rs
+    pub slide_animation: bool,
+    pub slide_duration_ms: u64,
+    // Dragging the window is undone, the app can still move it
+    pub position_locked: bool,
+    // None leaves the window where it was dragged
+    pub window_anchor: Option<WindowAnchor>,
+    // Logical pixels between the window and the anchored edges
+    pub anchor_margin: u32,
+    // Where the response ticker strip sits
+    pub ticker_anchor: WindowAnchor,
+    pub window_preset: WindowPresetName,
+    pub compact: WindowPreset,
+    pub expanded: WindowPreset,
+    pub custom: WindowPreset,
+}
+
+impl SavedWindowSettings {
+    pub fn preset(&self, name: WindowPresetName) -> WindowPreset {
+        match name {
+            WindowPresetName::Compact => self.compact,
+            WindowPresetName::Expanded => self.expanded,
+            WindowPresetName::Custom => self.custom,
+        }
+    }
+
+    pub fn preset_mut(&mut self, name: WindowPresetName) -> &mut WindowPreset {
+        match name {
+            WindowPresetName::Compact => &mut self.compact,
+            WindowPresetName::Expanded => &mut self.expanded,
+            WindowPresetName::Custom => &mut self.custom,
+        }
+    }
+}
+
+impl Default for SavedWindowSettings {
+    fn default() -> Self {
+        SavedWindowSettings {
+            opacity: 1.0,
+            zoom: 1.0,
+            content_protection: true,
+            app_icon_visible: true,
+            hide_on_blur: false,
+            close_hides_to_tray: true,
+            attention_notifications: false,
+            quiet_hours: None,
+            visible_on_all_workspaces: true,
+            skip_switcher: false,
+            float_over_fullscreen: false,
+            fullscreen_behavior: FullscreenBehavior::ShowUnfocused,
+            slide_animation: false,
+            slide_duration_ms: 150,
+            position_locked: false,
+            window_anchor: None,
+            anchor_margin: 24,
+            ticker_anchor: WindowAnchor::TopCenter,
+            window_preset: WindowPresetName::Compact,
+            compact: WindowPreset::new(700.0, 54.0),
+            expanded: WindowPreset::new(900.0, 640.0),
+            custom: WindowPreset::new(700.0, 400.0),
+        }
+    }
+}
+
+fn to_values(settings: &SavedWindowSettings) -> Map<String, Value> {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(values)) => values,
+        _ => Map::new(),
+    }
+}
+
+/// Settings from stored values by field name, a missing or malformed value
+/// keeps the default of that field only
+fn from_values(stored: impl Fn(&str) -> Option<Value>) -> SavedWindowSettings {
+    let mut values = to_values(&SavedWindowSettings::default());
+    let keys: Vec<String> = values.keys().cloned().collect();
+    for key in keys {
+        let Some(value) = stored(&key) else {
+            continue;
+        };
+        let default = values.insert(key.clone(), value);
+        if serde_json::from_value::<SavedWindowSettings>(Value::Object(values.clone())).is_err() {
+            eprintln!("Ignoring malformed window setting {}", key);
+            if let Some(default) = default {
+                values.insert(key, default);
+            }
+        }
+    }
+    serde_json::from_value(Value::Object(values)).unwrap_or_default()
+}
+
+/// Check a value set_setting would store under one of the window keys
+pub fn validate_setting(key: &str, value: &Value) -> Result<(), String> {
+    let mut values = to_values(&SavedWindowSettings::default());
+    if !values.contains_key(key) {
+        return Ok(());
+    }
+    values.insert(key.to_string(), value.clone());
+    serde_json::from_value::<SavedWindowSettings>(Value::Object(values))
+        .map(drop)
+        .map_err(|e| format!("Invalid value for setting {}: {}", key, e))
+}
+
+/// Move window-settings.json of earlier versions into the settings store, call
+/// during setup after settings::init. Keys already in the store win.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let path = match app.path().app_config_dir() {
+        Ok(dir) => dir.join(LEGACY_WINDOW_SETTINGS_FILE),
+        Err(e) => {
+            eprintln!("Failed to get app config directory: {}", e);
+            return;
+        }
+    };
+    if !path.exists() {
+        return;
+    }
+
+    let saved = match fs::read_to_string(&path).map(|content| serde_json::from_str::<Map<String, Value>>(&content)) {
+        Ok(Ok(saved)) => saved,
+        Ok(Err(e)) => {
+            eprintln!("Failed to parse window settings file: {}", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("Failed to read window settings file: {}", e);
+            return;
+        }
+    };
+
+    for (key, value) in saved {
+        if value.is_null() || crate::settings::get(app, &key).is_some() || validate_setting(&key, &value).is_err() {
+            continue;
+        }
+        if let Err(e) = crate::settings::set(app, &key, value) {
+            eprintln!("Failed to move window setting {}: {}", key, e);
+            return;
+        }
+    }
+    if let Err(e) = fs::remove_file(&path) {
+        eprintln!("Failed to remove window settings file: {}", e);
+    }
+}
+
+pub fn get<R: Runtime>(app: &AppHandle<R>) -> SavedWindowSettings {
+    from_values(|key| crate::settings::get(app, key))
+}
+
+/// Change the settings and store the fields that changed
+pub fn update<R: Runtime>(
+    app: &AppHandle<R>,
+    change: impl FnOnce(&mut SavedWindowSettings),
+) -> Result<SavedWindowSettings, String> {
+    let current = get(app);
+    let mut updated = current.clone();
+    change(&mut updated);
+
+    let before = to_values(&current);
+    for (key, value) in to_values(&updated) {
+        if before.get(&key) != Some(&value) {
+            crate::settings::set(app, &key, value)?;
+        }
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stored_values_override_their_field_only() {
+        let stored = json!({ "hide_on_blur": true, "opacity": "half", "anchor_margin": 8 });
+        let settings = from_values(|key| stored.get(key).cloned());
+        assert!(settings.hide_on_blur);
+        assert_eq!(settings.anchor_margin, 8);
+        // The malformed opacity keeps its default
+        assert_eq!(settings.opacity, 1.0);
+    }
+
+    #[test]
+    fn set_setting_refuses_a_value_of_the_wrong_type() {
+        assert!(validate_setting("hide_on_blur", &json!(true)).is_ok());
+        assert!(validate_setting("hide_on_blur", &json!("yes")).is_err());
+        assert!(validate_setting("window_anchor", &json!("top-left")).is_ok());
+        assert!(validate_setting("window_anchor", &json!("middle")).is_err());
+        assert!(validate_setting("some_other_key", &json!("anything")).is_ok());
+    }
+}
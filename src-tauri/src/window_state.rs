@@ -0,0 +1,332 @@
+// Position and size of the main window, saved to the app config dir after it
+// moves or resizes and restored before it is first shown. Everything is kept in
+// physical pixels together with the monitor it was on. A window left off every
+// monitor, after a display is unplugged, is moved back before it is saved.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow};
+
+const WINDOW_STATE_FILE: &str = "window-state.json";
+
+// Moves come in bursts while dragging, save once it settles
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+// Not every platform moves or rescales the window when its display goes away
+const MONITOR_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedWindowState {
+    #[serde(flatten)]
+    rect: WindowRect,
+    monitor: Option<String>,
+}
+
+// The pending debounced save
+#[derive(Default)]
+pub struct WindowStateStore {
+    save_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+fn get_window_state_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    Ok(config_dir.join(WINDOW_STATE_FILE))
+}
+
+fn load_saved_state<R: Runtime>(app: &AppHandle<R>) -> Option<SavedWindowState> {
+    let path = get_window_state_path(app).map_err(|e| eprintln!("{}", e)).ok()?;
+    if !path.exists() {
+        return None;
+    }
+    match fs::read_to_string(&path).map(|content| serde_json::from_str(&content)) {
+        Ok(Ok(saved)) => Some(saved),
+        Ok(Err(e)) => {
+            eprintln!("Failed to parse window state file: {}", e);
+            None
+        }
+        Err(e) => {
+            eprintln!("Failed to read window state file: {}", e);
+            None
+        }
+    }
+}
+
+fn monitor_rect(monitor: &Monitor) -> WindowRect {
+    WindowRect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    }
+}
+
+fn intersects(a: &WindowRect, b: &WindowRect) -> bool {
+    a.x < b.x + b.width as i32
+        && b.x < a.x + a.width as i32
+        && a.y < b.y + b.height as i32
+        && b.y < a.y + a.height as i32
+}
+
+/// Move a rect fully inside the area, shrinking it when it doesn't fit
+fn clamp_into(rect: WindowRect, area: &WindowRect) -> WindowRect {
+    let width = rect.width.min(area.width);
+    let height = rect.height.min(area.height);
+    WindowRect {
+        x: rect.x.clamp(area.x, area.x + (area.width - width) as i32),
+        y: rect.y.clamp(area.y, area.y + (area.height - height) as i32),
+        width,
+        height,
+    }
+}
+
+/// The monitor area closest to the rect's center
+fn nearest_area(rect: &WindowRect, areas: &[WindowRect]) -> Option<WindowRect> {
+    let center_x = rect.x as i64 + rect.width as i64 / 2;
+    let center_y = rect.y as i64 + rect.height as i64 / 2;
+    areas.iter().copied().min_by_key(|area| {
+        let dx = (center_x - center_x.clamp(area.x as i64, area.x as i64 + area.width as i64)).abs();
+        let dy = (center_y - center_y.clamp(area.y as i64, area.y as i64 + area.height as i64)).abs();
+        dx * dx + dy * dy
+    })
+}
+
+/// Where the saved rect goes given the monitors now connected: as saved while
+/// its monitor is there and it is at least partly visible, otherwise clamped
+/// into the nearest monitor
+fn resolve_rect(saved: &SavedWindowState, monitors: &[Monitor]) -> Option<WindowRect> {
+    let areas: Vec<WindowRect> = monitors.iter().map(monitor_rect).collect();
+    let monitor_present = saved.monitor.as_ref().is_none_or(|name| {
+        monitors.iter().any(|monitor| monitor.name() == Some(name))
+    });
+    if monitor_present && areas.iter().any(|area| intersects(&saved.rect, area)) {
+        return Some(saved.rect);
+    }
+    nearest_area(&saved.rect, &areas).map(|area| clamp_into(saved.rect, &area))
+}
+
+fn apply_rect<R: Runtime>(window: &WebviewWindow<R>, rect: WindowRect) -> Result<(), String> {
+    window
+        .set_size(PhysicalSize::new(rect.width, rect.height))
+        .map_err(|e| format!("Failed to resize window: {}", e))?;
+    crate::window::move_window(window, PhysicalPosition::new(rect.x, rect.y))
+        .map_err(|e| format!("Failed to reposition window: {}", e))
+}
+
+/// Put the main window where it was last time, call during setup before it is
+/// shown. Returns false when there was nothing to restore.
+pub fn restore<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(window) = app.get_webview_window("main") else {
+        return false;
+    };
+    let Some(saved) = load_saved_state(app) else {
+        return false;
+    };
+    let monitors = match window.available_monitors() {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            eprintln!("Failed to list monitors: {}", e);
+            return false;
+        }
+    };
+    let Some(rect) = resolve_rect(&saved, &monitors) else {
+        return false;
+    };
+    match apply_rect(&window, rect) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Failed to restore window state: {}", e);
+            false
+        }
+    }
+}
+
+/// Write the main window's current position, size and monitor
+pub fn save_now<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    // A minimized window reports a bogus position on Windows
+    if matches!(window.is_minimized(), Ok(true)) {
+        return Ok(());
+    }
+    let position = window
+        .outer_position()
+        .map_err(|e| format!("Failed to get window position: {}", e))?;
+    let size = window
+        .inner_size()
+        .map_err(|e| format!("Failed to get window size: {}", e))?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+
+    let saved = SavedWindowState {
+        rect: WindowRect {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        },
+        monitor,
+    };
+    let content = serde_json::to_string_pretty(&saved)
+        .map_err(|e| format!("Failed to serialize window state: {}", e))?;
+    fs::write(get_window_state_path(app)?, content).map_err(|e| format!("Failed to write window state file: {}", e))
+}
+
+/// Move the main window to the primary monitor when its frame is outside the
+/// work area of every connected monitor, emitting window-repositioned.
+/// Returns true when it was moved.
+pub fn ensure_on_screen<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(window) = app.get_webview_window("main") else {
+        return false;
+    };
+    // Above the screen on purpose, or parked off screen while minimized
+    if crate::slide::is_sliding(app) || matches!(window.is_minimized(), Ok(true)) {
+        return false;
+    }
+    let (Ok(position), Ok(size), Ok(monitors)) = (window.outer_position(), window.outer_size(), window.available_monitors())
+    else {
+        return false;
+    };
+    // Monitors are being reconfigured, check again next time
+    if monitors.is_empty() {
+        return false;
+    }
+    let frame = WindowRect {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    // The platform's work area, cheap enough to poll unlike the X11 strut scan
+    // anchoring uses
+    let on_screen = monitors.iter().any(|monitor| {
+        let area = monitor.work_area();
+        let area = WindowRect {
+            x: area.position.x,
+            y: area.position.y,
+            width: area.size.width,
+            height: area.size.height,
+        };
+        intersects(&frame, &area)
+    });
+    if on_screen {
+        return false;
+    }
+
+    if let Err(e) = crate::window::move_to_primary_monitor(app, &window) {
+        eprintln!("Failed to move window back on screen: {}", e);
+        return false;
+    }
+    if let Err(e) = app.emit("window-repositioned", serde_json::json!({ "reason": "monitor_lost" })) {
+        eprintln!("Failed to emit window-repositioned event: {}", e);
+    }
+    true
+}
+
+/// Check the window against the connected monitors every few seconds, call
+/// once during setup
+pub fn watch_monitors<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(MONITOR_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            // The move schedules its own save
+            ensure_on_screen(&app);
+        }
+    });
+}
+
+/// Save after the pending debounce, replacing an earlier pending save
+fn schedule_save<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<WindowStateStore>();
+    let mut task = match state.save_task.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(pending) = task.take() {
+        pending.abort();
+    }
+    let app_clone = app.clone();
+    *task = Some(tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        // Never persist a position the window can't be seen at
+        if ensure_on_screen(&app_clone) {
+            return;
+        }
+        if let Err(e) = save_now(&app_clone) {
+            eprintln!("Failed to save window state: {}", e);
+        }
+    }));
+}
+
+/// Save right away instead of waiting for the debounce, call on exit
+pub fn flush<R: Runtime>(app: &AppHandle<R>) {
+    let pending = {
+        let state = app.state::<WindowStateStore>();
+        let pending = match state.save_task.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        pending
+    };
+    if let Some(pending) = pending {
+        pending.abort();
+    }
+    if let Err(e) = save_now(app) {
+        eprintln!("Failed to save window state: {}", e);
+    }
+}
+
+/// Save the main window's bounds after it moves or resizes
+pub fn handle_window_event<R: Runtime>(window: &tauri::Window<R>, event: &tauri::WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    match event {
+        tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => schedule_save(window.app_handle()),
+        // Sent when the window lands on another display, as one is unplugged
+        tauri::WindowEvent::ScaleFactorChanged { .. } => {
+            ensure_on_screen(window.app_handle());
+        }
+        tauri::WindowEvent::CloseRequested { .. } => flush(window.app_handle()),
+        _ => {}
+    }
+}
+
+/// Tauri command to forget the saved position and put the window back at the
+/// top center of the primary monitor
+#[tauri::command]
+pub fn reset_window_position<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found".to_string())?;
+    let path = get_window_state_path(&app)?;
+    match fs::remove_file(&path) {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove window state file: {}", e)),
+    }
+    crate::window::position_window_top_center(&window, crate::window::TOP_OFFSET)
+        .map_err(|e| format!("Failed to reposition window: {}", e))
+}
@@ -32,12 +32,13 @@ export const useGlobalShortcuts = () => {
     }
   }, []);
 
+  // Every registered binding of each action, an action can have several
   const getShortcuts = useCallback(async (): Promise<Record<
     string,
-    string
+    string[]
   > | null> => {
     try {
-      const shortcuts = await invoke<Record<string, string>>(
+      const shortcuts = await invoke<Record<string, string[]>>(
         "get_registered_shortcuts"
       );
       return shortcuts;
@@ -192,6 +193,9 @@ export const useGlobalShortcuts = () => {
           }
         );
         globalEventListeners.customShortcut = unlistenCustomShortcut;
+
+        // Launch links and flags wait in the backend until we can hear them
+        await invoke("frontend_ready");
       } catch (error) {
         console.error("Failed to setup event listeners:", error);
       }
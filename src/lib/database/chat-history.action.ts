@@ -1,3 +1,4 @@
+import { invoke } from "@tauri-apps/api/core";
 import { getDatabase } from "./config";
 import { ChatConversation } from "@/types";
 import { safeLocalStorage, CONVERSATION_TITLE_WORD_LIMIT } from "@/lib";
@@ -25,6 +26,7 @@ interface DbMessage {
   content: string;
   timestamp: number;
   attached_files: string | null; // JSON string
+  model: string | null;
 }
 
 /**
@@ -121,7 +123,7 @@ export async function createConversation(
         : null;
 
       await db.execute(
-        "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files) VALUES (?, ?, ?, ?, ?, ?)",
+        "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files, model) VALUES (?, ?, ?, ?, ?, ?, ?)",
         [
           message.id,
           conversation.id,
@@ -129,6 +131,7 @@ export async function createConversation(
           message.content,
           message.timestamp,
           attachedFilesJson,
+          message.model ?? null,
         ]
       );
     }
@@ -190,6 +193,7 @@ export async function getAllConversations(): Promise<ChatConversation[]> {
           content: msg.content,
           timestamp: msg.timestamp,
           attachedFiles: safeJsonParse(msg.attached_files, undefined),
+          model: msg.model ?? undefined,
         })) || [],
     }));
   } catch (error) {
@@ -241,6 +245,7 @@ export async function getConversationById(
         content: msg.content,
         timestamp: msg.timestamp,
         attachedFiles: safeJsonParse(msg.attached_files, undefined),
+        model: msg.model ?? undefined,
       })),
     };
   } catch (error) {
@@ -296,7 +301,7 @@ export async function updateConversation(
           : null;
 
         await db.execute(
-          "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files) VALUES (?, ?, ?, ?, ?, ?)",
+          "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files, model) VALUES (?, ?, ?, ?, ?, ?, ?)",
           [
             message.id,
             conversation.id,
@@ -304,6 +309,7 @@ export async function updateConversation(
             message.content,
             message.timestamp,
             attachedFilesJson,
+            message.model ?? null,
           ]
         );
       }
@@ -316,7 +322,7 @@ export async function updateConversation(
       for (const msg of existingMessages) {
         await db
           .execute(
-            "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files) VALUES (?, ?, ?, ?, ?, ?)",
+            "INSERT INTO messages (id, conversation_id, role, content, timestamp, attached_files, model) VALUES (?, ?, ?, ?, ?, ?, ?)",
             [
               msg.id,
               msg.conversation_id,
@@ -324,6 +330,7 @@ export async function updateConversation(
               msg.content,
               msg.timestamp,
               msg.attached_files,
+              msg.model,
             ]
           )
           .catch(() => {});
@@ -363,7 +370,7 @@ export async function saveConversation(
 }
 
 /**
- * Delete a conversation and all its messages
+ * Delete a conversation and all its messages through the Rust history queue
  */
 export async function deleteConversation(id: string): Promise<boolean> {
   if (!id || typeof id !== "string") {
@@ -371,14 +378,10 @@ export async function deleteConversation(id: string): Promise<boolean> {
     return false;
   }
 
-  const db = await getDatabase();
-
   try {
-    const result = await db.execute("DELETE FROM conversations WHERE id = ?", [
-      id,
-    ]);
+    await invoke("delete_session", { id });
 
-    return result.rowsAffected > 0;
+    return true;
   } catch (error) {
     console.error(`Failed to delete conversation ${id}:`, error);
     throw error;
@@ -386,15 +389,11 @@ export async function deleteConversation(id: string): Promise<boolean> {
 }
 
 /**
- * Delete all conversations and messages
+ * Delete all conversations and messages through the Rust history queue
  */
 export async function deleteAllConversations(): Promise<void> {
-  const db = await getDatabase();
-
   try {
-    // Delete in correct order (messages first due to foreign key)
-    await db.execute("DELETE FROM messages");
-    await db.execute("DELETE FROM conversations");
+    await invoke("clear_history");
   } catch (error) {
     console.error("Failed to delete all conversations:", error);
     throw error;
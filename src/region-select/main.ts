@@ -0,0 +1,64 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Selection overlay opened by the capture_region command. The rectangle is sent
+// back as fractions of the window so the Rust side can map it to image pixels.
+const selection = document.getElementById("selection") as HTMLDivElement;
+let start: { x: number; y: number } | null = null;
+let finished = false;
+
+const finish = (rect: {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+} | null) => {
+  if (finished) return;
+  finished = true;
+  invoke("finish_region_selection", { rect }).catch((error) =>
+    console.error("Failed to finish region selection:", error)
+  );
+};
+
+const draw = (x: number, y: number) => {
+  if (!start) return;
+  selection.style.display = "block";
+  selection.style.left = `${Math.min(start.x, x)}px`;
+  selection.style.top = `${Math.min(start.y, y)}px`;
+  selection.style.width = `${Math.abs(x - start.x)}px`;
+  selection.style.height = `${Math.abs(y - start.y)}px`;
+};
+
+window.addEventListener("mousedown", (event) => {
+  if (event.button !== 0) return;
+  start = { x: event.clientX, y: event.clientY };
+  draw(event.clientX, event.clientY);
+});
+
+window.addEventListener("mousemove", (event) => draw(event.clientX, event.clientY));
+
+window.addEventListener("mouseup", (event) => {
+  if (!start) return;
+  const width = Math.abs(event.clientX - start.x);
+  const height = Math.abs(event.clientY - start.y);
+  // A click without dragging starts over instead of capturing a single pixel
+  if (width < 4 || height < 4) {
+    start = null;
+    selection.style.display = "none";
+    return;
+  }
+  finish({
+    x: Math.min(start.x, event.clientX) / window.innerWidth,
+    y: Math.min(start.y, event.clientY) / window.innerHeight,
+    width: width / window.innerWidth,
+    height: height / window.innerHeight,
+  });
+});
+
+window.addEventListener("keydown", (event) => {
+  if (event.key === "Escape") finish(null);
+});
+
+window.addEventListener("contextmenu", (event) => {
+  event.preventDefault();
+  finish(null);
+});
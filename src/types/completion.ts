@@ -13,6 +13,7 @@ export interface ChatMessage {
   content: string;
   timestamp: number;
   attachedFiles?: AttachedFile[];
+  model?: string;
 }
 
 export interface ChatConversation {